@@ -0,0 +1,27 @@
+// This is free and unencumbered software released into the public domain.
+
+use std::{env, process::Command};
+
+fn main() {
+    // Rust has no stable ABI, so block plugins must be built with the same
+    // compiler as the host:
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=PROTOFLOW_RUSTC_VERSION={}", version.trim());
+    println!("cargo:rerun-if-env-changed=RUSTC");
+
+    // The features that change the layout of the types shared with block
+    // plugins, such as `BlockFactory`, must match as well:
+    let features = ["serde", "std", "sysml", "tokio", "tracing", "unstable"]
+        .into_iter()
+        .filter(|feature| {
+            env::var_os(format!("CARGO_FEATURE_{}", feature.to_uppercase())).is_some()
+        })
+        .collect::<Vec<_>>();
+    println!("cargo:rustc-env=PROTOFLOW_FEATURES={}", features.join(","));
+}
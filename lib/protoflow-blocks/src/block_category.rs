@@ -0,0 +1,43 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::prelude::{fmt, Vec};
+use enum_iterator::Sequence;
+
+/// A category of built-in blocks, corresponding to a block trait such as
+/// [`CoreBlocks`](crate::CoreBlocks).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Sequence)]
+pub enum BlockCategory {
+    Core,
+    Flow,
+    Hash,
+    Io,
+    Math,
+    Sys,
+    Text,
+}
+
+impl BlockCategory {
+    pub fn all() -> Vec<Self> {
+        enum_iterator::all::<Self>().collect()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        use BlockCategory::*;
+        match self {
+            Core => "Core",
+            Flow => "Flow",
+            Hash => "Hash",
+            Io => "IO",
+            Math => "Math",
+            Sys => "Sys",
+            Text => "Text",
+        }
+    }
+}
+
+impl fmt::Display for BlockCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -1,9 +1,9 @@
 // This is free and unencumbered software released into the public domain.
 
-use super::prelude::{Box, Cow, Named, String, Vec};
+use super::prelude::{fmt, Arc, Box, Cow, Named, String, Vec};
 use crate::{
-    BlockConnections, BlockInstantiation, CoreBlockConfig, FlowBlockConfig, IoBlockConfig,
-    MathBlockConfig, SysBlockConfig, System, TextBlockConfig,
+    BlockConfigError, BlockConnections, BlockInstantiation, CoreBlockConfig, FlowBlockConfig,
+    IoBlockConfig, MathBlockConfig, SysBlockConfig, System, TextBlockConfig,
 };
 use protoflow_core::Block;
 
@@ -36,6 +36,21 @@ pub enum BlockConfig {
     #[cfg(feature = "std")]
     Sys(SysBlockConfig),
     Text(TextBlockConfig),
+    /// The configuration of a block registered in the [`BlockRegistry`](crate::BlockRegistry) by
+    /// another crate. This can't be serialized.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    Custom(Arc<dyn CustomBlockConfig>),
+}
+
+/// The configuration of a block that isn't built in.
+pub trait CustomBlockConfig:
+    BlockConnections + BlockInstantiation + Named + fmt::Debug + Send + Sync
+{
+}
+
+impl<T: BlockConnections + BlockInstantiation + Named + fmt::Debug + Send + Sync> CustomBlockConfig
+    for T
+{
 }
 
 #[cfg(feature = "serde")]
@@ -44,57 +59,24 @@ impl<'de> serde::Deserialize<'de> for BlockConfig {
     where
         D: serde::Deserializer<'de>,
     {
-        use serde_yml::{value::TaggedValue, Value};
-        let value = TaggedValue::deserialize(deserializer)?;
-        match &value {
-            TaggedValue {
-                tag,
-                value: Value::Mapping(_mapping),
-            } => Ok(match tag.string.as_str() {
-                "Buffer" | "Const" | "Count" | "Delay" | "Drop" | "Random" => {
-                    CoreBlockConfig::deserialize(value.clone())
-                        .map(BlockConfig::Core)
-                        .unwrap()
-                }
-
-                #[cfg(any(
-                    feature = "hash-blake3",
-                    feature = "hash-md5",
-                    feature = "hash-sha1",
-                    feature = "hash-sha2"
-                ))]
-                "Hash" => HashBlockConfig::deserialize(value.clone())
-                    .map(BlockConfig::Hash)
-                    .unwrap(),
-
-                "Decode" | "DecodeHex" | "DecodeJSON" | "Encode" | "EncodeHex" | "EncodeJSON" => {
-                    IoBlockConfig::deserialize(value.clone())
-                        .map(BlockConfig::Io)
-                        .unwrap()
-                }
-
-                #[cfg(feature = "std")]
-                "ReadDir" | "ReadEnv" | "ReadFile" | "ReadSocket" | "ReadStdin" | "WriteFile"
-                | "WriteSocket" | "WriteStderr" | "WriteStdout" => {
-                    SysBlockConfig::deserialize(value.clone())
-                        .map(BlockConfig::Sys)
-                        .unwrap()
-                }
-
-                "ConcatStrings" | "DecodeCSV" | "EncodeCSV" | "SplitString" => {
-                    TextBlockConfig::deserialize(value.clone())
-                        .map(BlockConfig::Text)
-                        .unwrap()
-                }
+        let value = serde_yml::Value::deserialize(deserializer)?;
+        Self::try_from(value).map_err(serde::de::Error::custom)
+    }
+}
 
-                _ => return Err(serde::de::Error::custom("unknown Protoflow block type")),
-            }),
+#[cfg(feature = "serde")]
+impl TryFrom<serde_yml::Value> for BlockConfig {
+    type Error = Box<BlockConfigError>;
 
-            _ => {
-                return Err(serde::de::Error::custom(
-                    "unexpected YAML element, expected a tagged mapping",
-                ))
+    fn try_from(value: serde_yml::Value) -> Result<Self, Self::Error> {
+        use serde_yml::Value;
+        match value {
+            Value::Tagged(value) if value.value.is_mapping() => {
+                crate::BlockRegistry::global().deserialize_config(*value)
             }
+            _ => Err(Box::new(BlockConfigError::new(
+                "unexpected YAML element, expected a tagged mapping",
+            ))),
         }
     }
 }
@@ -117,11 +99,33 @@ impl Named for BlockConfig {
             #[cfg(feature = "std")]
             Sys(config) => config.name(),
             Text(config) => config.name(),
+            Custom(config) => config.name(),
         }
     }
 }
 
 impl BlockConnections for BlockConfig {
+    fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
+        use BlockConfig::*;
+        match self {
+            Core(config) => config.input_connections(),
+            Flow(config) => config.input_connections(),
+            #[cfg(any(
+                feature = "hash-blake3",
+                feature = "hash-md5",
+                feature = "hash-sha1",
+                feature = "hash-sha2"
+            ))]
+            Hash(config) => config.input_connections(),
+            Io(config) => config.input_connections(),
+            Math(config) => config.input_connections(),
+            #[cfg(feature = "std")]
+            Sys(config) => config.input_connections(),
+            Text(config) => config.input_connections(),
+            Custom(config) => config.input_connections(),
+        }
+    }
+
     fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
         use BlockConfig::*;
         match self {
@@ -139,12 +143,13 @@ impl BlockConnections for BlockConfig {
             #[cfg(feature = "std")]
             Sys(config) => config.output_connections(),
             Text(config) => config.output_connections(),
+            Custom(config) => config.output_connections(),
         }
     }
 }
 
 impl BlockInstantiation for BlockConfig {
-    fn instantiate(&self, system: &mut System) -> Box<dyn Block> {
+    fn instantiate(&self, system: &mut System) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
         use BlockConfig::*;
         match self {
             Core(config) => config.instantiate(system),
@@ -161,6 +166,7 @@ impl BlockInstantiation for BlockConfig {
             #[cfg(feature = "std")]
             Sys(config) => config.instantiate(system),
             Text(config) => config.instantiate(system),
+            Custom(config) => config.instantiate(system),
         }
     }
 }
@@ -0,0 +1,205 @@
+// This is free and unencumbered software released into the public domain.
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use crate::prelude::{fmt, format, Box, String, ToString, Vec};
+
+/// An error in the configuration of a block, such as in a YAML system
+/// definition.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BlockConfigError {
+    /// The name of the block in the system definition, if known.
+    pub block: Option<String>,
+
+    /// The type of the block, given by its YAML tag, if known.
+    pub tag: Option<String>,
+
+    /// The configuration field that is invalid, if any.
+    pub field: Option<String>,
+
+    /// The values allowed in place of an unknown field or value.
+    pub allowed: Vec<String>,
+
+    /// The known names closest to an unknown block type, field, or value.
+    pub suggestions: Vec<String>,
+
+    /// The line and column of the error in the system definition, both
+    /// starting from 1.
+    pub location: Option<(usize, usize)>,
+
+    pub message: String,
+}
+
+impl BlockConfigError {
+    pub fn new(message: impl ToString) -> Self {
+        Self {
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns an error for an unknown block type, suggesting the closest
+    /// of the known block types.
+    pub fn unknown_tag<'a>(tag: &str, known_tags: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            tag: Some(tag.to_string()),
+            suggestions: suggest(tag, known_tags),
+            ..Self::new("unknown block type")
+        }
+    }
+
+    /// Returns an error for an unknown value of a field, suggesting the
+    /// closest of the allowed values.
+    pub fn unknown_value<'a>(
+        field: &str,
+        value: &str,
+        allowed: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let allowed: Vec<&str> = allowed.into_iter().collect();
+        Self {
+            field: Some(field.to_string()),
+            allowed: allowed.iter().map(ToString::to_string).collect(),
+            suggestions: suggest(value, allowed),
+            ..Self::new(format!("unknown value `{}`", value))
+        }
+    }
+
+    /// Parses the standard messages of Serde's derived implementations,
+    /// such as "unknown field `x`, expected one of `y`, `z`".
+    fn from_message(message: String) -> Self {
+        let quoted: Vec<&str> = message.split('`').skip(1).step_by(2).collect();
+        let (field, allowed) = match quoted.split_first() {
+            Some((field, allowed))
+                if message.starts_with("unknown field")
+                    || message.starts_with("missing field")
+                    || message.starts_with("duplicate field") =>
+            {
+                (Some(field.to_string()), allowed)
+            }
+            Some((_, allowed)) if message.starts_with("unknown variant") => (None, allowed),
+            _ => (None, &[][..]),
+        };
+        let suggestions = match quoted.first() {
+            Some(given) if message.starts_with("unknown") => {
+                suggest(given, allowed.iter().copied())
+            }
+            _ => Vec::new(),
+        };
+        Self {
+            field,
+            allowed: allowed.iter().map(ToString::to_string).collect(),
+            suggestions,
+            ..Self::new(message)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_yml::Error> for BlockConfigError {
+    fn from(error: serde_yml::Error) -> Self {
+        Self::from_message(error.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_path_to_error::Error<serde_yml::Error>> for BlockConfigError {
+    fn from(error: serde_path_to_error::Error<serde_yml::Error>) -> Self {
+        // The path starts with the enum variant for the block type:
+        let path = error.path().to_string();
+        let error = Self::from_message(error.into_inner().to_string());
+        Self {
+            field: error
+                .field
+                .clone()
+                .or_else(|| path.split_once('.').map(|(_, field)| field.to_string())),
+            ..error
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_yml::Error> for Box<BlockConfigError> {
+    fn from(error: serde_yml::Error) -> Self {
+        Box::new(error.into())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_path_to_error::Error<serde_yml::Error>> for Box<BlockConfigError> {
+    fn from(error: serde_path_to_error::Error<serde_yml::Error>) -> Self {
+        Box::new(error.into())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlockConfigError {}
+
+impl fmt::Display for BlockConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((line, column)) = self.location {
+            write!(f, "{}:{}: ", line, column)?;
+        }
+        match (&self.block, &self.tag) {
+            (Some(block), Some(tag)) => write!(f, "{} (!{}): ", block, tag)?,
+            (Some(block), None) => write!(f, "{}: ", block)?,
+            (None, Some(tag)) => write!(f, "!{}: ", tag)?,
+            (None, None) => {}
+        }
+        if let Some(field) = &self.field {
+            if !self.message.contains(&format!("`{}`", field)) {
+                write!(f, "field `{}`: ", field)?;
+            }
+        }
+        write!(f, "{}", self.message)?;
+        match self.suggestions.as_slice() {
+            [] => Ok(()),
+            [suggestion] => write!(f, " (did you mean `{}`?)", suggestion),
+            suggestions => write!(f, " (did you mean one of `{}`?)", suggestions.join("`, `")),
+        }
+    }
+}
+
+/// Returns the candidates closest to the given name, by edit distance,
+/// ignoring case.
+fn suggest<'a>(given: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let given = given.to_lowercase();
+    let max_distance = (given.chars().count() / 3).max(1);
+    let mut suggestions: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&given, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    suggestions.sort();
+    suggestions
+        .into_iter()
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Returns the edit distance between two strings, counting insertions,
+/// deletions, substitutions, and transpositions of adjacent characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = [
+        Vec::new(),
+        Vec::new(),
+        (0..=b.len()).collect::<Vec<usize>>(),
+    ];
+    for i in 1..=a.len() {
+        rows.rotate_left(1);
+        let [before, previous, current] = &mut rows;
+        current.clear();
+        current.push(i);
+        for j in 1..=b.len() {
+            let mut distance = (previous[j - 1] + usize::from(a[i - 1] != b[j - 1]))
+                .min(previous[j] + 1)
+                .min(current[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(before[j - 2] + 1);
+            }
+            current.push(distance);
+        }
+    }
+    rows[2][b.len()]
+}
@@ -3,20 +3,15 @@
 use super::{prelude::Vec, InputPortName, OutputPortName};
 
 /// A trait for defining the connections of a block instance.
+///
+/// Each connection pairs the name of one of the block's ports with the port
+/// of another block that it's connected to, given as `block.port`, if any.
 pub trait BlockConnections {
-    fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
-        if cfg!(debug_assertions) {
-            unimplemented!("BlockConnections::input_connections") // for debug builds only
-        } else {
-            Vec::new()
-        }
-    }
+    /// Returns the output ports that each of the block's input ports
+    /// receives messages from.
+    fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)>;
 
-    fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
-        if cfg!(debug_assertions) {
-            unimplemented!("BlockConnections::output_connections") // for debug builds only
-        } else {
-            Vec::new()
-        }
-    }
+    /// Returns the input ports that each of the block's output ports sends
+    /// messages to.
+    fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)>;
 }
@@ -1,11 +1,68 @@
 // This is free and unencumbered software released into the public domain.
 
-use super::{prelude::Box, System};
-use protoflow_core::Block;
+use super::{
+    prelude::{fmt, BTreeMap, Box, String, Vec},
+    BlockConfigError, System,
+};
+use protoflow_core::{Block, PortDescriptor};
 
 /// A trait for instantiating a block in a given system.
 pub trait BlockInstantiation {
-    fn instantiate(&self, _system: &mut System) -> Box<dyn Block> {
-        unimplemented!()
+    /// Instantiates the block, failing if its configuration is invalid,
+    /// such as when it names an unknown message type.
+    fn instantiate(&self, system: &mut System) -> Result<Box<dyn Block>, Box<BlockConfigError>>;
+
+    /// Instantiates the block, along with the ports it created, keyed by
+    /// name, for connecting it to other blocks.
+    fn instantiate_with_ports(
+        &self,
+        system: &mut System,
+    ) -> Result<BlockInstance, Box<BlockConfigError>> {
+        Ok(BlockInstance::new(self.instantiate(system)?))
+    }
+}
+
+/// A block instantiated from its configuration, along with its ports.
+pub struct BlockInstance {
+    pub block: Box<dyn Block>,
+
+    /// The block's input ports, keyed by name.
+    pub inputs: BTreeMap<String, PortDescriptor>,
+
+    /// The block's output ports, keyed by name.
+    pub outputs: BTreeMap<String, PortDescriptor>,
+}
+
+impl BlockInstance {
+    pub fn new(block: Box<dyn Block>) -> Self {
+        let by_name = |ports: Vec<PortDescriptor>| {
+            ports
+                .into_iter()
+                .filter_map(|port| Some((port.name.clone()?, port)))
+                .collect()
+        };
+        Self {
+            inputs: by_name(block.inputs()),
+            outputs: by_name(block.outputs()),
+            block,
+        }
+    }
+
+    pub fn input(&self, name: &str) -> Option<&PortDescriptor> {
+        self.inputs.get(name)
+    }
+
+    pub fn output(&self, name: &str) -> Option<&PortDescriptor> {
+        self.outputs.get(name)
+    }
+}
+
+impl fmt::Debug for BlockInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlockInstance")
+            .field("block", &self.block.name())
+            .field("inputs", &self.inputs)
+            .field("outputs", &self.outputs)
+            .finish()
     }
 }
@@ -0,0 +1,218 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::BlockRegistry;
+
+/// The version of the plugin ABI, incremented whenever the layout of
+/// [`PluginDeclaration`] changes.
+pub const PLUGIN_ABI_VERSION: u32 = 2;
+
+/// The declaration that a block plugin exports, using [`export_plugin!`].
+///
+/// Block plugins are shared libraries (`cdylib` crates) that register
+/// blocks in a [`BlockRegistry`] when loaded. Since Rust has no stable ABI,
+/// a plugin is loaded only if it was built against the same versions of
+/// `protoflow-core` and `protoflow-blocks`, with the same features, and with
+/// the same compiler as the host.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PluginDeclaration {
+    pub abi_version: u32,
+    pub core_version: &'static str,
+    pub blocks_version: &'static str,
+    pub features: &'static str,
+    pub rustc_version: &'static str,
+    pub register: fn(&mut BlockRegistry),
+}
+
+impl PluginDeclaration {
+    pub const fn new(register: fn(&mut BlockRegistry)) -> Self {
+        Self {
+            abi_version: PLUGIN_ABI_VERSION,
+            core_version: protoflow_core::VERSION,
+            blocks_version: env!("CARGO_PKG_VERSION"),
+            features: env!("PROTOFLOW_FEATURES"),
+            rustc_version: env!("PROTOFLOW_RUSTC_VERSION"),
+            register,
+        }
+    }
+
+    /// Checks whether a plugin with this declaration can be loaded.
+    pub fn is_compatible(&self) -> bool {
+        let host = Self::new(|_| {});
+        self.core_version == host.core_version
+            && self.blocks_version == host.blocks_version
+            && self.features == host.features
+            && self.rustc_version == host.rustc_version
+    }
+}
+
+/// Exports the declaration of a block plugin, given a function that
+/// registers the plugin's blocks.
+///
+/// ```rust
+/// # use protoflow_blocks::*;
+/// fn register(registry: &mut BlockRegistry) {
+///     registry.register(BlockFactory::new("Tally", |system| {
+///         Box::new(Count::<u64>::with_system(system))
+///     }));
+/// }
+///
+/// protoflow_blocks::export_plugin!(register);
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($register:expr) => {
+        #[no_mangle]
+        #[allow(unsafe_code)]
+        pub static PROTOFLOW_PLUGIN: $crate::PluginDeclaration =
+            $crate::PluginDeclaration::new($register);
+    };
+}
+
+#[cfg(feature = "plugins")]
+mod loader {
+    extern crate std;
+
+    use super::{PluginDeclaration, PLUGIN_ABI_VERSION};
+    use crate::{prelude::Vec, BlockRegistry};
+    use std::{
+        ffi::OsStr,
+        fmt, fs, io,
+        path::{Path, PathBuf},
+        string::{String, ToString},
+    };
+
+    #[derive(Debug)]
+    pub enum PluginError {
+        Io(PathBuf, io::Error),
+        Load(PathBuf, String),
+        MissingDeclaration(PathBuf),
+        IncompatibleAbi(PathBuf, u32),
+        IncompatibleVersion(PathBuf, String),
+        IncompatibleFeatures(PathBuf, String),
+    }
+
+    impl std::error::Error for PluginError {}
+
+    impl fmt::Display for PluginError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            use PluginError::*;
+            match self {
+                Io(path, error) => {
+                    write!(f, "failed to read {}: {}", path.display(), error)
+                }
+                Load(path, error) => {
+                    write!(f, "failed to load plugin {}: {}", path.display(), error)
+                }
+                MissingDeclaration(path) => {
+                    write!(f, "not a Protoflow plugin: {}", path.display())
+                }
+                IncompatibleAbi(path, version) => write!(
+                    f,
+                    "incompatible plugin ABI version {} (expected {}): {}",
+                    version,
+                    PLUGIN_ABI_VERSION,
+                    path.display()
+                ),
+                IncompatibleVersion(path, version) => write!(
+                    f,
+                    "plugin built against incompatible protoflow {}: {}",
+                    version,
+                    path.display()
+                ),
+                IncompatibleFeatures(path, features) => write!(
+                    f,
+                    "plugin built with incompatible features [{}] (expected [{}]): {}",
+                    features,
+                    env!("PROTOFLOW_FEATURES"),
+                    path.display()
+                ),
+            }
+        }
+    }
+
+    impl BlockRegistry {
+        /// Loads a block plugin from a shared library, registering its
+        /// blocks.
+        ///
+        /// Loaded plugins are never unloaded, since the registry and any
+        /// blocks instantiated from it retain code from them.
+        #[allow(unsafe_code)]
+        pub fn load_plugin(&mut self, path: impl AsRef<Path>) -> Result<(), PluginError> {
+            // Resolve the path, since bare file names would otherwise be
+            // looked up in the system's library search path:
+            let path = path.as_ref();
+            let path = &fs::canonicalize(path)
+                .map_err(|error| PluginError::Io(path.to_path_buf(), error))?;
+
+            // SAFETY: loading a library runs its initialization code, which
+            // we must trust as we would any other block implementation.
+            let library = unsafe { libloading::Library::new(path) }
+                .map_err(|error| PluginError::Load(path.to_path_buf(), error.to_string()))?;
+
+            // SAFETY: the symbol is exported by `export_plugin!`, and the
+            // declaration's ABI version is checked before its other fields
+            // are used.
+            let declaration = unsafe {
+                let symbol = library
+                    .get::<*const PluginDeclaration>(b"PROTOFLOW_PLUGIN\0")
+                    .map_err(|_| PluginError::MissingDeclaration(path.to_path_buf()))?;
+                let abi_version = (*symbol).cast::<u32>().read();
+                if abi_version != PLUGIN_ABI_VERSION {
+                    return Err(PluginError::IncompatibleAbi(
+                        path.to_path_buf(),
+                        abi_version,
+                    ));
+                }
+                **symbol
+            };
+
+            if !declaration.is_compatible() {
+                let host = PluginDeclaration::new(|_| {});
+                return Err(if declaration.features != host.features {
+                    PluginError::IncompatibleFeatures(
+                        path.to_path_buf(),
+                        declaration.features.to_string(),
+                    )
+                } else {
+                    PluginError::IncompatibleVersion(
+                        path.to_path_buf(),
+                        std::format!(
+                            "{} (core {}, {})",
+                            declaration.blocks_version,
+                            declaration.core_version,
+                            declaration.rustc_version
+                        ),
+                    )
+                });
+            }
+
+            (declaration.register)(self);
+            std::mem::forget(library);
+            Ok(())
+        }
+
+        /// Loads every block plugin in a directory, returning the paths of
+        /// the plugins loaded, in order.
+        pub fn load_plugins(&mut self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, PluginError> {
+            let dir = dir.as_ref();
+            let entries =
+                fs::read_dir(dir).map_err(|error| PluginError::Io(dir.to_path_buf(), error))?;
+            let mut paths = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.is_file()
+                        && path.extension() == Some(OsStr::new(std::env::consts::DLL_EXTENSION))
+                })
+                .collect::<Vec<_>>();
+            paths.sort();
+            for path in &paths {
+                self.load_plugin(path)?;
+            }
+            Ok(paths)
+        }
+    }
+}
+
+#[cfg(feature = "plugins")]
+pub use loader::*;
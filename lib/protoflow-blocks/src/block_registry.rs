@@ -0,0 +1,350 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{fmt, Arc, BTreeMap, Box, String, ToString},
+    BlockTag, System,
+};
+use parking_lot::{Once, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use protoflow_core::{Block, BlockDescription};
+
+#[cfg(feature = "serde")]
+use crate::{BlockConfig, BlockConfigError};
+
+#[cfg(feature = "std")]
+use crate::{StdioConfig, StdioError};
+
+/// Instantiates a block in a system, with default parameters.
+pub type BlockInstantiator = Arc<dyn Fn(&mut System) -> Box<dyn Block> + Send + Sync>;
+
+/// Deserializes the configuration of a block from a tagged YAML mapping.
+#[cfg(feature = "serde")]
+pub type BlockConfigDeserializer = Arc<
+    dyn Fn(serde_yml::value::TaggedValue) -> Result<BlockConfig, Box<BlockConfigError>>
+        + Send
+        + Sync,
+>;
+
+/// Builds a system that wires a block to standard input and output.
+#[cfg(feature = "std")]
+pub type StdioSystemBuilder = Arc<dyn Fn(StdioConfig) -> Result<System, StdioError> + Send + Sync>;
+
+/// A factory for a kind of block, registered by name in a [`BlockRegistry`].
+#[derive(Clone)]
+pub struct BlockFactory {
+    /// The name of the block in YAML and SysML system definitions and on the
+    /// command line.
+    pub name: String,
+
+    /// Instantiates the block, with default parameters.
+    pub instantiate: BlockInstantiator,
+
+    /// Deserializes the block's configuration in YAML system definitions,
+    /// if the block can be configured that way.
+    #[cfg(feature = "serde")]
+    pub deserialize_config: Option<BlockConfigDeserializer>,
+
+    /// Builds a system for executing the block from the command line, if
+    /// the block can be executed that way.
+    #[cfg(feature = "std")]
+    pub build_stdio_system: Option<StdioSystemBuilder>,
+}
+
+impl BlockFactory {
+    pub fn new(
+        name: impl ToString,
+        instantiate: impl Fn(&mut System) -> Box<dyn Block> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            instantiate: Arc::new(instantiate),
+            #[cfg(feature = "serde")]
+            deserialize_config: None,
+            #[cfg(feature = "std")]
+            build_stdio_system: None,
+        }
+    }
+
+    /// Describes the block's ports and parameters, by instantiating it in a
+    /// scratch system. The parameters of the scratch block have no values.
+    pub fn describe(&self) -> BlockDescription {
+        let mut description = BlockDescription::default();
+        let _ = System::build(|system| {
+            let block = (self.instantiate)(system);
+            description = BlockDescription::new(0, block.as_block_descriptor());
+        });
+        for parameter in &mut description.parameters {
+            parameter.value = None;
+        }
+        description
+    }
+}
+
+impl fmt::Debug for BlockFactory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlockFactory")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A registry of the blocks that can be instantiated by name, such as from
+/// YAML and SysML system definitions and on the command line.
+///
+/// The [global](BlockRegistry::global) registry initially contains the
+/// built-in blocks, and other crates can register their own blocks in it
+/// to make them available everywhere that built-in blocks are.
+#[derive(Clone, Debug, Default)]
+pub struct BlockRegistry {
+    factories: BTreeMap<String, BlockFactory>,
+}
+
+static GLOBAL_REGISTRY: RwLock<BlockRegistry> = RwLock::new(BlockRegistry::new());
+static GLOBAL_REGISTRY_INIT: Once = Once::new();
+
+impl BlockRegistry {
+    /// Returns an empty registry.
+    pub const fn new() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// Returns a registry containing the built-in blocks.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for block_tag in BlockTag::all() {
+            registry.register(builtin_factory(block_tag));
+        }
+        registry
+    }
+
+    /// Returns the global registry, for looking up blocks.
+    pub fn global() -> RwLockReadGuard<'static, Self> {
+        Self::init_global();
+        GLOBAL_REGISTRY.read()
+    }
+
+    /// Returns the global registry, for registering blocks.
+    pub fn global_mut() -> RwLockWriteGuard<'static, Self> {
+        Self::init_global();
+        GLOBAL_REGISTRY.write()
+    }
+
+    fn init_global() {
+        GLOBAL_REGISTRY_INIT.call_once(|| *GLOBAL_REGISTRY.write() = Self::with_builtins());
+    }
+
+    /// Registers a block, returning the factory previously registered under
+    /// the same name, if any.
+    pub fn register(&mut self, factory: BlockFactory) -> Option<BlockFactory> {
+        self.factories.insert(factory.name.clone(), factory)
+    }
+
+    /// Unregisters the named block, returning its factory.
+    pub fn unregister(&mut self, name: &str) -> Option<BlockFactory> {
+        self.factories.remove(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BlockFactory> {
+        self.factories.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.factories.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.factories.len()
+    }
+
+    /// Iterates over the registered blocks, in order of name.
+    pub fn iter(&self) -> impl Iterator<Item = &BlockFactory> {
+        self.factories.values()
+    }
+
+    /// Iterates over the names of the registered blocks, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    /// Instantiates the named block in a system, with default parameters.
+    pub fn instantiate(&self, name: &str, system: &mut System) -> Option<Box<dyn Block>> {
+        self.get(name).map(|factory| (factory.instantiate)(system))
+    }
+
+    /// Deserializes a block configuration, given as a YAML mapping tagged
+    /// with the block's name.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_config(
+        &self,
+        value: serde_yml::value::TaggedValue,
+    ) -> Result<BlockConfig, Box<BlockConfigError>> {
+        let tag = value.tag.string.clone();
+        let Some(factory) = self.get(&tag) else {
+            return Err(Box::new(BlockConfigError::unknown_tag(&tag, self.names())));
+        };
+        let Some(deserialize_config) = &factory.deserialize_config else {
+            return Err(Box::new(BlockConfigError {
+                tag: Some(tag),
+                ..BlockConfigError::new("block type is not configurable")
+            }));
+        };
+        deserialize_config(value).map_err(|error| {
+            Box::new(BlockConfigError {
+                tag: Some(tag),
+                ..*error
+            })
+        })
+    }
+
+    /// Builds a system for executing the named block from the command line.
+    #[cfg(feature = "std")]
+    pub fn build_stdio_system(
+        &self,
+        system_name: String,
+        config: StdioConfig,
+    ) -> Result<System, StdioError> {
+        match self
+            .get(&system_name)
+            .and_then(|factory| factory.build_stdio_system.as_ref())
+        {
+            Some(build_stdio_system) => {
+                let config = config.untracked();
+                let system = build_stdio_system(config.clone())?;
+                config.check_redirection()?;
+                Ok(system)
+            }
+            None => Err(StdioError::UnknownSystem(system_name)),
+        }
+    }
+}
+
+fn builtin_factory(block_tag: BlockTag) -> BlockFactory {
+    BlockFactory {
+        #[cfg(feature = "serde")]
+        deserialize_config: Some(Arc::new(builtin_config_deserializer(block_tag))),
+        #[cfg(feature = "std")]
+        build_stdio_system: Some(Arc::new(builtin_stdio_system_builder(block_tag))),
+        ..BlockFactory::new(block_tag, move |system| {
+            block_tag.instantiate_default(system)
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+fn builtin_config_deserializer(
+    block_tag: BlockTag,
+) -> fn(serde_yml::value::TaggedValue) -> Result<BlockConfig, Box<BlockConfigError>> {
+    use crate::{CoreBlockConfig, IoBlockConfig, TextBlockConfig};
+    use serde_path_to_error::deserialize;
+    use BlockTag::*;
+    match block_tag {
+        Buffer | Const | Count | Delay | Drop | Random => |value| {
+            let config = deserialize::<_, CoreBlockConfig>(value)?;
+            check_message_type(config.message_type(), false)?;
+            Ok(BlockConfig::Core(config))
+        },
+        #[cfg(any(
+            feature = "hash-blake3",
+            feature = "hash-md5",
+            feature = "hash-sha1",
+            feature = "hash-sha2"
+        ))]
+        Hash => |value| {
+            Ok(BlockConfig::Hash(deserialize::<_, crate::HashBlockConfig>(
+                value,
+            )?))
+        },
+        Decode | DecodeHex | DecodeJson | Encode | EncodeHex | EncodeJson => |value| {
+            let config = deserialize::<_, IoBlockConfig>(value)?;
+            check_message_type(config.message_type(), true)?;
+            Ok(BlockConfig::Io(config))
+        },
+        #[cfg(feature = "std")]
+        ReadDir | ReadEnv | ReadFile | ReadSocket | ReadStdin | WriteFile | WriteSocket
+        | WriteStderr | WriteStdout => |value| {
+            let config = deserialize::<_, crate::SysBlockConfig>(value)?;
+            check_message_type(config.message_type(), true)?;
+            Ok(BlockConfig::Sys(config))
+        },
+        ConcatStrings | DecodeCsv | EncodeCsv | SplitString => {
+            |value| Ok(BlockConfig::Text(deserialize::<_, TextBlockConfig>(value)?))
+        }
+    }
+}
+
+/// Checks that the message type of a generic block's configuration is
+/// registered, and is a text message type if the block requires one.
+#[cfg(feature = "serde")]
+fn check_message_type(name: Option<&str>, is_text: bool) -> Result<(), Box<BlockConfigError>> {
+    match name {
+        Some(name) => crate::MessageTypeRegistry::global()
+            .resolve(name, is_text)
+            .map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "std")]
+fn builtin_stdio_system_builder(
+    block_tag: BlockTag,
+) -> fn(StdioConfig) -> Result<System, StdioError> {
+    use crate::StdioSystem;
+    use BlockTag::*;
+    match block_tag {
+        Buffer => crate::Buffer::<String>::build_system,
+        Const => crate::Const::<String>::build_system,
+        Count => |config| build_generic_stdio_system(Count, "String", config),
+        Delay => |config| build_generic_stdio_system(Delay, "String", config),
+        Drop => crate::Drop::<String>::build_system,
+        Random => |config| build_generic_stdio_system(Random, "u64", config),
+        #[cfg(any(
+            feature = "hash-blake3",
+            feature = "hash-md5",
+            feature = "hash-sha1",
+            feature = "hash-sha2"
+        ))]
+        Hash => crate::Hash::build_system,
+        Decode => crate::Decode::build_system,
+        DecodeHex => crate::DecodeHex::build_system,
+        DecodeJson => crate::DecodeJson::build_system,
+        Encode => crate::Encode::build_system,
+        EncodeHex => crate::EncodeHex::build_system,
+        EncodeJson => crate::EncodeJson::build_system,
+        ReadDir => crate::ReadDir::build_system,
+        ReadEnv => crate::ReadEnv::<String>::build_system,
+        ReadFile => crate::ReadFile::build_system,
+        #[cfg(feature = "serde")]
+        ReadSocket => crate::ReadSocket::build_system,
+        ReadStdin => crate::ReadStdin::build_system,
+        WriteFile => crate::WriteFile::build_system,
+        #[cfg(feature = "serde")]
+        WriteSocket => crate::WriteSocket::build_system,
+        WriteStderr => crate::WriteStderr::build_system,
+        WriteStdout => crate::WriteStdout::build_system,
+        ConcatStrings => crate::ConcatStrings::build_system,
+        DecodeCsv => crate::DecodeCsv::build_system,
+        EncodeCsv => crate::EncodeCsv::build_system,
+        SplitString => crate::SplitString::build_system,
+    }
+}
+
+/// Builds a system for executing a generic block from the command line,
+/// with the message type given by the `type` parameter.
+#[cfg(feature = "std")]
+fn build_generic_stdio_system(
+    block_tag: BlockTag,
+    default_type: &str,
+    mut config: StdioConfig,
+) -> Result<System, StdioError> {
+    let name = config.params.remove("type");
+    let message_types = crate::MessageTypeRegistry::global();
+    let Some(message_type) = message_types.get(name.as_deref().unwrap_or(default_type)) else {
+        return Err(StdioError::InvalidParameter("type"));
+    };
+    message_type.build_stdio_system(block_tag, config)
+}
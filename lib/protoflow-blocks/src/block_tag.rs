@@ -2,7 +2,7 @@
 
 use crate::{
     prelude::{fmt, Box, Cow, FromStr, Named, String, Vec},
-    BlockInstantiation, System,
+    BlockCategory, BlockConfigError, BlockInstantiation, System,
 };
 use enum_iterator::Sequence;
 use protoflow_core::{types::Any, Block};
@@ -69,6 +69,53 @@ impl BlockTag {
         enum_iterator::all::<Self>().collect()
     }
 
+    pub fn category(&self) -> BlockCategory {
+        use BlockTag::*;
+        match self {
+            Buffer => BlockCategory::Core,
+            Const => BlockCategory::Core,
+            Count => BlockCategory::Core,
+            Delay => BlockCategory::Core,
+            Drop => BlockCategory::Core,
+            Random => BlockCategory::Core,
+            #[cfg(any(
+                feature = "hash-blake3",
+                feature = "hash-md5",
+                feature = "hash-sha1",
+                feature = "hash-sha2"
+            ))]
+            Hash => BlockCategory::Hash,
+            Decode => BlockCategory::Io,
+            DecodeHex => BlockCategory::Io,
+            DecodeJson => BlockCategory::Io,
+            Encode => BlockCategory::Io,
+            EncodeHex => BlockCategory::Io,
+            EncodeJson => BlockCategory::Io,
+            #[cfg(feature = "std")]
+            ReadDir => BlockCategory::Sys,
+            #[cfg(feature = "std")]
+            ReadEnv => BlockCategory::Sys,
+            #[cfg(feature = "std")]
+            ReadFile => BlockCategory::Sys,
+            #[cfg(all(feature = "std", feature = "serde"))]
+            ReadSocket => BlockCategory::Sys,
+            #[cfg(feature = "std")]
+            ReadStdin => BlockCategory::Sys,
+            #[cfg(feature = "std")]
+            WriteFile => BlockCategory::Sys,
+            #[cfg(all(feature = "std", feature = "serde"))]
+            WriteSocket => BlockCategory::Sys,
+            #[cfg(feature = "std")]
+            WriteStderr => BlockCategory::Sys,
+            #[cfg(feature = "std")]
+            WriteStdout => BlockCategory::Sys,
+            ConcatStrings => BlockCategory::Text,
+            DecodeCsv => BlockCategory::Text,
+            EncodeCsv => BlockCategory::Text,
+            SplitString => BlockCategory::Text,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         use BlockTag::*;
         match self {
@@ -115,6 +162,19 @@ impl BlockTag {
             SplitString => "SplitString",
         }
     }
+
+    /// Returns the name of the port whose message type is given by the
+    /// `type` field of the block's configuration, if the block is generic.
+    pub fn generic_port(&self) -> Option<&'static str> {
+        use BlockTag::*;
+        match self {
+            Buffer | Count | Delay | Drop | Encode => Some("input"),
+            Random | Decode => Some("output"),
+            #[cfg(feature = "std")]
+            ReadEnv => Some("output"),
+            _ => None,
+        }
+    }
 }
 
 impl FromStr for BlockTag {
@@ -181,8 +241,9 @@ impl fmt::Display for BlockTag {
     }
 }
 
-impl BlockInstantiation for BlockTag {
-    fn instantiate(&self, system: &mut System) -> Box<dyn Block> {
+impl BlockTag {
+    /// Instantiates the block with its default parameters and message type.
+    pub(crate) fn instantiate_default(&self, system: &mut System) -> Box<dyn Block> {
         use BlockTag::*;
         match self {
             Buffer => Box::new(super::Buffer::<Any>::with_system(system)),
@@ -229,3 +290,9 @@ impl BlockInstantiation for BlockTag {
         }
     }
 }
+
+impl BlockInstantiation for BlockTag {
+    fn instantiate(&self, system: &mut System) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
+        Ok(self.instantiate_default(system))
+    }
+}
@@ -3,7 +3,8 @@
 pub mod core {
     use super::{
         prelude::{vec, Box, Bytes, Cow, Named, Vec},
-        BlockConnections, BlockInstantiation, InputPortName, OutputPortName, System,
+        BlockConfigError, BlockConnections, BlockInstantiation, InputPortName, OutputPortName,
+        System,
     };
     use crate::{
         prelude::{Duration, Range, String, ToString},
@@ -51,10 +52,12 @@ pub mod core {
     }
 
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
     #[derive(Clone, Debug)]
     pub enum CoreBlockConfig {
         Buffer {
             input: InputPortName,
+            r#type: Option<String>,
         },
 
         Const {
@@ -66,24 +69,44 @@ pub mod core {
             input: InputPortName,
             output: Option<OutputPortName>,
             count: OutputPortName,
+            r#type: Option<String>,
         },
 
         Delay {
             input: InputPortName,
             output: OutputPortName,
             delay: Option<DelayType>,
+            r#type: Option<String>,
         },
 
         Drop {
             input: InputPortName,
+            r#type: Option<String>,
         },
 
         Random {
             output: OutputPortName,
             seed: Option<u64>,
+            r#type: Option<String>,
         },
     }
 
+    impl CoreBlockConfig {
+        /// Returns the name of the message type that the block is
+        /// instantiated with, if the block is generic.
+        pub fn message_type(&self) -> Option<&str> {
+            use CoreBlockConfig::*;
+            match self {
+                Buffer { r#type, .. }
+                | Count { r#type, .. }
+                | Delay { r#type, .. }
+                | Drop { r#type, .. } => Some(r#type.as_deref().unwrap_or("Any")),
+                Random { r#type, .. } => Some(r#type.as_deref().unwrap_or("u64")),
+                Const { .. } => None,
+            }
+        }
+    }
+
     impl Named for CoreBlockConfig {
         fn name(&self) -> Cow<str> {
             use CoreBlockConfig::*;
@@ -99,6 +122,19 @@ pub mod core {
     }
 
     impl BlockConnections for CoreBlockConfig {
+        fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
+            use CoreBlockConfig::*;
+            match self {
+                Buffer { input, .. }
+                | Count { input, .. }
+                | Delay { input, .. }
+                | Drop { input, .. } => {
+                    vec![("input", Some(input.clone()))]
+                }
+                Const { .. } | Random { .. } => vec![],
+            }
+        }
+
         fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
             use CoreBlockConfig::*;
             match self {
@@ -115,31 +151,25 @@ pub mod core {
     }
 
     impl BlockInstantiation for CoreBlockConfig {
-        fn instantiate(&self, system: &mut System) -> Box<dyn Block> {
-            use super::SystemBuilding;
+        fn instantiate(
+            &self,
+            system: &mut System,
+        ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
             use CoreBlockConfig::*;
-            match self {
-                Buffer { .. } => Box::new(super::Buffer::new(system.input_any())), // TODO: Buffer::with_system(system)
+            let message_type = || {
+                let name = self.message_type().unwrap_or_default();
+                crate::MessageTypeRegistry::global()
+                    .resolve(name, false)
+                    .cloned()
+            };
+            Ok(match self {
+                Buffer { .. } => message_type()?.buffer_block(system),
                 Const { value, .. } => Box::new(super::Const::with_system(system, value.clone())),
-                Count { .. } => Box::new(super::Count::new(
-                    system.input_any(),
-                    system.output(),
-                    system.output(),
-                )), // TODO: Count::with_system(system)
-                Delay { delay, .. } => {
-                    Box::new(super::Delay::with_params(
-                        system.input_any(),
-                        system.output(),
-                        delay.clone(),
-                    ))
-                    // TODO: Delay::with_system(system, Some(delay.clone())))
-                }
-                Drop { .. } => Box::new(super::Drop::new(system.input_any())), // TODO: Drop::with_system(system)
-                Random { seed, .. } => {
-                    Box::new(super::Random::with_params(system.output::<u64>(), *seed))
-                    // TODO: Random::with_system(system, *seed))
-                }
-            }
+                Count { .. } => message_type()?.count_block(system),
+                Delay { delay, .. } => message_type()?.delay_block(system, delay.clone()),
+                Drop { .. } => message_type()?.drop_block(system),
+                Random { seed, .. } => message_type()?.random_block(system, *seed),
+            })
         }
     }
 
@@ -1,7 +1,10 @@
 // This is free and unencumbered software released into the public domain.
 
-use crate::{prelude::VecDeque, StdioConfig, StdioError, StdioSystem, System};
-use protoflow_core::{types::Any, Block, BlockResult, BlockRuntime, InputPort, Message};
+use crate::{
+    prelude::{Deref, VecDeque},
+    StdioConfig, StdioError, StdioSystem, System,
+};
+use protoflow_core::{types::Any, Block, BlockResult, BlockRuntime, InputPort, Message, State};
 use protoflow_derive::Block;
 use simple_mermaid::mermaid;
 
@@ -42,19 +45,21 @@ pub struct Buffer<T: Message = Any> {
 
     /// The internal state storing the messages received.
     #[state]
-    messages: VecDeque<T>,
+    messages: State<VecDeque<T>>,
 }
 
 impl<T: Message> Buffer<T> {
     pub fn new(input: InputPort<T>) -> Self {
         Self {
             input,
-            messages: VecDeque::new(),
+            messages: State::default(),
         }
     }
 
-    pub fn messages(&self) -> &VecDeque<T> {
-        &self.messages
+    /// Returns the messages received so far, locking the block's state
+    /// until the returned guard is dropped.
+    pub fn messages(&self) -> impl Deref<Target = VecDeque<T>> + '_ {
+        self.messages.read()
     }
 }
 
@@ -68,7 +73,7 @@ impl<T: Message + 'static> Buffer<T> {
 impl<T: Message> Block for Buffer<T> {
     fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
         while let Some(message) = self.input.recv()? {
-            self.messages.push_back(message);
+            self.messages.write().push_back(message);
         }
         Ok(())
     }
@@ -82,7 +87,7 @@ impl<T: Message> StdioSystem for Buffer<T> {
         config.reject_any()?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let buffer = s.buffer();
             s.connect(&stdin.output, &buffer.input);
         }))
@@ -92,6 +97,7 @@ impl<T: Message> StdioSystem for Buffer<T> {
 #[cfg(test)]
 mod tests {
     use super::Buffer;
+    use protoflow_core::BlockHooks;
     use crate::{System, SystemBuilding};
 
     #[test]
@@ -101,4 +107,15 @@ mod tests {
             let _ = s.block(Buffer::<i32>::new(s.input()));
         });
     }
+
+    #[test]
+    fn capture_state() {
+        // Check that the buffered messages are captured in checkpoints:
+        let _ = System::build(|s| {
+            let buffer = Buffer::<i32>::new(s.input());
+            let handles = buffer.state_handles();
+            assert_eq!(handles.len(), 1);
+            assert_eq!(handles[0].0, "messages");
+        });
+    }
 }
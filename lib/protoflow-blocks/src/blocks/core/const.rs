@@ -101,7 +101,7 @@ impl<T: Message> StdioSystem for Const<T> {
         Ok(System::build(|s| {
             let const_value = s.const_string(value); // FIXME
             let line_encoder = s.encode_with(config.encoding);
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             s.connect(&const_value.output, &line_encoder.input);
             s.connect(&line_encoder.output, &stdout.input);
         }))
@@ -3,6 +3,7 @@
 use crate::{StdioConfig, StdioError, StdioSystem, System};
 use protoflow_core::{
     types::Any, Block, BlockResult, BlockRuntime, InputPort, Message, OutputPort, Port, PortError,
+    State,
 };
 use protoflow_derive::Block;
 use simple_mermaid::mermaid;
@@ -43,6 +44,10 @@ use simple_mermaid::mermaid;
 /// $ protoflow execute Count
 /// ```
 ///
+/// ```console
+/// $ protoflow execute Count type=u64
+/// ```
+///
 #[derive(Block, Clone)]
 pub struct Count<T: Message = Any> {
     /// The input message stream.
@@ -59,7 +64,7 @@ pub struct Count<T: Message = Any> {
 
     /// The internal state counting the number of messages received.
     #[state]
-    counter: u64,
+    counter: State<u64>,
 }
 
 impl<T: Message> Count<T> {
@@ -68,7 +73,7 @@ impl<T: Message> Count<T> {
             input,
             output,
             count,
-            counter: 0,
+            counter: State::default(),
         }
     }
 }
@@ -83,7 +88,7 @@ impl<T: Message + 'static> Count<T> {
 impl<T: Message> Block for Count<T> {
     fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
         while let Some(message) = self.input.recv()? {
-            self.counter += 1;
+            *self.counter.write() += 1;
 
             if self.output.is_connected() {
                 self.output.send(&message)?;
@@ -95,7 +100,8 @@ impl<T: Message> Block for Count<T> {
 
         runtime.wait_for(&self.count)?;
 
-        match self.count.send(&self.counter) {
+        let count = *self.counter.read();
+        match self.count.send(&count) {
             Ok(()) => {}
             Err(PortError::Closed | PortError::Disconnected) => {
                 // TODO: log the error
@@ -117,11 +123,11 @@ impl<T: Message + crate::prelude::FromStr + crate::prelude::ToString + 'static>
         config.reject_any()?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let message_decoder = s.decode_with::<T>(config.encoding);
             let counter = s.count::<T>();
             let count_encoder = s.encode_with::<u64>(config.encoding);
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &message_decoder.input);
             s.connect(&message_decoder.output, &counter.input);
             s.connect(&counter.count, &count_encoder.input);
@@ -64,7 +64,7 @@ pub struct Delay<T: Message = Any> {
     pub output: OutputPort<T>,
 
     /// A configuration parameter for which type of delay to add.
-    #[parameter]
+    #[parameter(default = "1s")]
     pub delay: DelayType,
 }
 
@@ -126,11 +126,11 @@ impl<T: Message + crate::prelude::FromStr + crate::prelude::ToString + 'static>
         let delay = DelayType::Fixed(Duration::from_secs_f64(fixed_delay.unwrap_or(1.)));
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let message_decoder = s.decode_with::<T>(config.encoding);
             let delayer = s.delay_by(delay);
             let message_encoder = s.encode_with::<T>(config.encoding);
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &message_decoder.input);
             s.connect(&message_decoder.output, &delayer.input);
             s.connect(&delayer.output, &message_encoder.input);
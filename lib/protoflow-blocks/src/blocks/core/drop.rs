@@ -72,7 +72,7 @@ impl<T: Message> StdioSystem for Drop<T> {
         config.reject_any()?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let dropper = s.drop();
             s.connect(&stdin.output, &dropper.input);
         }))
@@ -40,6 +40,10 @@ use simple_mermaid::mermaid;
 /// $ protoflow execute Random seed=42
 /// ```
 ///
+/// ```console
+/// $ protoflow execute Random type=f64
+/// ```
+///
 #[derive(Block, Clone)]
 pub struct Random<T: Message = u64> {
     /// The port to send the value on.
@@ -79,7 +83,7 @@ impl<T: Message + Default> Block for Random<T> {
 }
 
 #[cfg(feature = "std")]
-impl StdioSystem for Random<u64> {
+impl<T: Message + crate::prelude::ToString + 'static> StdioSystem for Random<T> {
     fn build_system(config: StdioConfig) -> Result<System, StdioError> {
         use crate::{CoreBlocks, IoBlocks, SystemBuilding};
 
@@ -87,9 +91,9 @@ impl StdioSystem for Random<u64> {
         let seed = config.get_opt::<u64>("seed")?;
 
         Ok(System::build(|s| {
-            let random_generator = s.random_seeded::<u64>(seed);
-            let number_encoder = s.encode_with::<u64>(config.encoding);
-            let stdout = config.write_stdout(s);
+            let random_generator = s.random_seeded::<T>(seed);
+            let number_encoder = s.encode_with::<T>(config.encoding);
+            let stdout = config.write_output(s);
             s.connect(&random_generator.output, &number_encoder.input);
             s.connect(&number_encoder.output, &stdout.input);
         }))
@@ -2,11 +2,39 @@
 
 pub mod flow {
     use super::{
-        prelude::{Cow, Named},
-        BlockConnections, BlockInstantiation,
+        prelude::{Box, Cow, Named, Vec},
+        BlockConfigError, BlockConnections, BlockInstantiation, InputPortName, OutputPortName,
+        System,
     };
+    use protoflow_core::{Block, Message};
 
-    pub trait FlowBlocks {}
+    /// Blocks defined inline by Rust closures and iterators.
+    ///
+    /// Since closures can't be serialized, these blocks have no block tag
+    /// or configuration, and aren't available via the CLI.
+    pub trait FlowBlocks {
+        fn filter<T: Message + 'static>(
+            &mut self,
+            predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+        ) -> Filter<T>;
+
+        fn flat_map<I: Message + 'static, O: Message + 'static, R: IntoIterator<Item = O>>(
+            &mut self,
+            function: impl Fn(I) -> R + Send + Sync + 'static,
+        ) -> FlatMap<I, O>;
+
+        fn map<I: Message + 'static, O: Message + 'static>(
+            &mut self,
+            function: impl Fn(I) -> O + Send + Sync + 'static,
+        ) -> Map<I, O>;
+
+        fn sink<T: Message + 'static>(&mut self, function: impl FnMut(T) + Send + 'static)
+            -> Sink<T>;
+
+        fn source<T: Message + 'static, I: IntoIterator<Item = T>>(&mut self, iter: I) -> Source<T>
+        where
+            I::IntoIter: Send + 'static;
+    }
 
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -18,13 +46,43 @@ pub mod flow {
 
     impl Named for FlowBlockConfig {
         fn name(&self) -> Cow<str> {
-            unreachable!()
+            match *self {}
+        }
+    }
+
+    impl BlockConnections for FlowBlockConfig {
+        fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
+            match *self {}
+        }
+
+        fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
+            match *self {}
+        }
+    }
+
+    impl BlockInstantiation for FlowBlockConfig {
+        fn instantiate(
+            &self,
+            _system: &mut System,
+        ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
+            match *self {}
         }
     }
 
-    impl BlockConnections for FlowBlockConfig {}
+    mod filter;
+    pub use filter::*;
+
+    mod flat_map;
+    pub use flat_map::*;
+
+    mod map;
+    pub use map::*;
+
+    mod sink;
+    pub use sink::*;
 
-    impl BlockInstantiation for FlowBlockConfig {}
+    mod source;
+    pub use source::*;
 }
 
 pub use flow::*;
@@ -0,0 +1,100 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{prelude::Arc, System};
+use protoflow_core::{Block, BlockResult, BlockRuntime, InputPort, Message, OutputPort};
+use protoflow_derive::Block;
+use simple_mermaid::mermaid;
+
+/// A block that passes through only the messages it receives that satisfy
+/// a predicate.
+///
+/// # Block Diagram
+#[doc = mermaid!("../../../doc/flow/filter.mmd")]
+///
+/// # Sequence Diagram
+#[doc = mermaid!("../../../doc/flow/filter.seq.mmd" framed)]
+///
+/// # Examples
+///
+/// ## Using the block in a system
+///
+/// ```rust
+/// # use protoflow_blocks::*;
+/// # fn main() {
+/// System::build(|s| {
+///     let stdin = s.read_stdin();
+///     let line_decoder = s.decode_lines();
+///     let filter = s.filter(|line: &String| !line.is_empty());
+///     let line_encoder = s.encode_lines();
+///     let stdout = s.write_stdout();
+///     s.connect(&stdin.output, &line_decoder.input);
+///     s.connect(&line_decoder.output, &filter.input);
+///     s.connect(&filter.output, &line_encoder.input);
+///     s.connect(&line_encoder.output, &stdout.input);
+/// });
+/// # }
+/// ```
+///
+#[derive(Block, Clone)]
+pub struct Filter<T: Message> {
+    /// The input message stream.
+    #[input]
+    pub input: InputPort<T>,
+
+    /// The output message stream.
+    #[output]
+    pub output: OutputPort<T>,
+
+    /// The predicate that messages must satisfy to be passed through.
+    predicate: Arc<dyn Fn(&T) -> bool + Send + Sync>,
+}
+
+impl<T: Message> Filter<T> {
+    pub fn new(
+        input: InputPort<T>,
+        output: OutputPort<T>,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            input,
+            output,
+            predicate: Arc::new(predicate),
+        }
+    }
+}
+
+impl<T: Message + 'static> Filter<T> {
+    pub fn with_system(
+        system: &System,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        use crate::SystemBuilding;
+        Self::new(system.input(), system.output(), predicate)
+    }
+}
+
+impl<T: Message> Block for Filter<T> {
+    fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+        while let Some(message) = self.input.recv()? {
+            if (self.predicate)(&message) {
+                self.output.send(&message)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+    use crate::{System, SystemBuilding};
+
+    #[test]
+    fn instantiate_block() {
+        // Check that the block is constructible:
+        let _ = System::build(|s| {
+            let _ = s.block(Filter::new(s.input(), s.output(), |n: &u64| n % 2 == 0));
+        });
+    }
+}
@@ -0,0 +1,105 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{Arc, Vec},
+    System,
+};
+use protoflow_core::{Block, BlockResult, BlockRuntime, InputPort, Message, OutputPort};
+use protoflow_derive::Block;
+use simple_mermaid::mermaid;
+
+/// A block that transforms each message it receives into any number of
+/// messages, with a function.
+///
+/// # Block Diagram
+#[doc = mermaid!("../../../doc/flow/flat_map.mmd")]
+///
+/// # Sequence Diagram
+#[doc = mermaid!("../../../doc/flow/flat_map.seq.mmd" framed)]
+///
+/// # Examples
+///
+/// ## Using the block in a system
+///
+/// ```rust
+/// # use protoflow_blocks::*;
+/// # fn main() {
+/// System::build(|s| {
+///     let stdin = s.read_stdin();
+///     let line_decoder = s.decode_lines();
+///     let word_splitter = s.flat_map(|line: String| {
+///         line.split_whitespace().map(String::from).collect::<Vec<_>>()
+///     });
+///     let line_encoder = s.encode_lines();
+///     let stdout = s.write_stdout();
+///     s.connect(&stdin.output, &line_decoder.input);
+///     s.connect(&line_decoder.output, &word_splitter.input);
+///     s.connect(&word_splitter.output, &line_encoder.input);
+///     s.connect(&line_encoder.output, &stdout.input);
+/// });
+/// # }
+/// ```
+///
+#[derive(Block, Clone)]
+pub struct FlatMap<I: Message, O: Message> {
+    /// The input message stream.
+    #[input]
+    pub input: InputPort<I>,
+
+    /// The output message stream.
+    #[output]
+    pub output: OutputPort<O>,
+
+    /// The function to apply to each message.
+    function: Arc<dyn Fn(I) -> Vec<O> + Send + Sync>,
+}
+
+impl<I: Message, O: Message> FlatMap<I, O> {
+    pub fn new<R: IntoIterator<Item = O>>(
+        input: InputPort<I>,
+        output: OutputPort<O>,
+        function: impl Fn(I) -> R + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            input,
+            output,
+            function: Arc::new(move |message| function(message).into_iter().collect()),
+        }
+    }
+}
+
+impl<I: Message + 'static, O: Message + 'static> FlatMap<I, O> {
+    pub fn with_system<R: IntoIterator<Item = O>>(
+        system: &System,
+        function: impl Fn(I) -> R + Send + Sync + 'static,
+    ) -> Self {
+        use crate::SystemBuilding;
+        Self::new(system.input(), system.output(), function)
+    }
+}
+
+impl<I: Message, O: Message> Block for FlatMap<I, O> {
+    fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+        while let Some(message) = self.input.recv()? {
+            for output in (self.function)(message) {
+                self.output.send(&output)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FlatMap;
+    use crate::{System, SystemBuilding};
+
+    #[test]
+    fn instantiate_block() {
+        // Check that the block is constructible:
+        let _ = System::build(|s| {
+            let _ = s.block(FlatMap::new(s.input(), s.output(), |n: u64| [n, n]));
+        });
+    }
+}
@@ -0,0 +1,97 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{prelude::Arc, System};
+use protoflow_core::{Block, BlockResult, BlockRuntime, InputPort, Message, OutputPort};
+use protoflow_derive::Block;
+use simple_mermaid::mermaid;
+
+/// A block that transforms each message it receives with a function.
+///
+/// # Block Diagram
+#[doc = mermaid!("../../../doc/flow/map.mmd")]
+///
+/// # Sequence Diagram
+#[doc = mermaid!("../../../doc/flow/map.seq.mmd" framed)]
+///
+/// # Examples
+///
+/// ## Using the block in a system
+///
+/// ```rust
+/// # use protoflow_blocks::*;
+/// # fn main() {
+/// System::build(|s| {
+///     let stdin = s.read_stdin();
+///     let line_decoder = s.decode_lines();
+///     let uppercaser = s.map(|line: String| line.to_uppercase());
+///     let line_encoder = s.encode_lines();
+///     let stdout = s.write_stdout();
+///     s.connect(&stdin.output, &line_decoder.input);
+///     s.connect(&line_decoder.output, &uppercaser.input);
+///     s.connect(&uppercaser.output, &line_encoder.input);
+///     s.connect(&line_encoder.output, &stdout.input);
+/// });
+/// # }
+/// ```
+///
+#[derive(Block, Clone)]
+pub struct Map<I: Message, O: Message> {
+    /// The input message stream.
+    #[input]
+    pub input: InputPort<I>,
+
+    /// The output message stream.
+    #[output]
+    pub output: OutputPort<O>,
+
+    /// The function to apply to each message.
+    function: Arc<dyn Fn(I) -> O + Send + Sync>,
+}
+
+impl<I: Message, O: Message> Map<I, O> {
+    pub fn new(
+        input: InputPort<I>,
+        output: OutputPort<O>,
+        function: impl Fn(I) -> O + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            input,
+            output,
+            function: Arc::new(function),
+        }
+    }
+}
+
+impl<I: Message + 'static, O: Message + 'static> Map<I, O> {
+    pub fn with_system(
+        system: &System,
+        function: impl Fn(I) -> O + Send + Sync + 'static,
+    ) -> Self {
+        use crate::SystemBuilding;
+        Self::new(system.input(), system.output(), function)
+    }
+}
+
+impl<I: Message, O: Message> Block for Map<I, O> {
+    fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+        while let Some(message) = self.input.recv()? {
+            self.output.send(&(self.function)(message))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Map;
+    use crate::{System, SystemBuilding};
+
+    #[test]
+    fn instantiate_block() {
+        // Check that the block is constructible:
+        let _ = System::build(|s| {
+            let _ = s.block(Map::new(s.input(), s.output(), |n: u64| n + 1));
+        });
+    }
+}
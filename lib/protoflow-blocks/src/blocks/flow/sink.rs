@@ -0,0 +1,87 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{Arc, Box, Mutex},
+    System,
+};
+use protoflow_core::{Block, BlockResult, BlockRuntime, InputPort, Message};
+use protoflow_derive::Block;
+use simple_mermaid::mermaid;
+
+type SinkFunction<T> = Arc<Mutex<Box<dyn FnMut(T) + Send>>>;
+
+/// A block that consumes each message it receives with a function.
+///
+/// # Block Diagram
+#[doc = mermaid!("../../../doc/flow/sink.mmd")]
+///
+/// # Sequence Diagram
+#[doc = mermaid!("../../../doc/flow/sink.seq.mmd" framed)]
+///
+/// # Examples
+///
+/// ## Using the block in a system
+///
+/// ```rust
+/// # use protoflow_blocks::*;
+/// # fn main() {
+/// System::build(|s| {
+///     let stdin = s.read_stdin();
+///     let line_decoder = s.decode_lines();
+///     let printer = s.sink(|line: String| println!("{}", line));
+///     s.connect(&stdin.output, &line_decoder.input);
+///     s.connect(&line_decoder.output, &printer.input);
+/// });
+/// # }
+/// ```
+///
+#[derive(Block, Clone)]
+pub struct Sink<T: Message> {
+    /// The input message stream.
+    #[input]
+    pub input: InputPort<T>,
+
+    /// The function to call with each message.
+    function: SinkFunction<T>,
+}
+
+impl<T: Message> Sink<T> {
+    pub fn new(input: InputPort<T>, function: impl FnMut(T) + Send + 'static) -> Self {
+        Self {
+            input,
+            function: Arc::new(Mutex::new(Box::new(function))),
+        }
+    }
+}
+
+impl<T: Message + 'static> Sink<T> {
+    pub fn with_system(system: &System, function: impl FnMut(T) + Send + 'static) -> Self {
+        use crate::SystemBuilding;
+        Self::new(system.input(), function)
+    }
+}
+
+impl<T: Message> Block for Sink<T> {
+    fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+        let mut function = self.function.lock();
+        while let Some(message) = self.input.recv()? {
+            function(message);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sink;
+    use crate::{System, SystemBuilding};
+
+    #[test]
+    fn instantiate_block() {
+        // Check that the block is constructible:
+        let _ = System::build(|s| {
+            let _ = s.block(Sink::new(s.input(), |_: u64| {}));
+        });
+    }
+}
@@ -0,0 +1,93 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{Arc, Box, Mutex},
+    System,
+};
+use protoflow_core::{Block, BlockResult, BlockRuntime, Message, OutputPort};
+use protoflow_derive::Block;
+use simple_mermaid::mermaid;
+
+/// A block that sends each item of an iterator.
+///
+/// # Block Diagram
+#[doc = mermaid!("../../../doc/flow/source.mmd")]
+///
+/// # Sequence Diagram
+#[doc = mermaid!("../../../doc/flow/source.seq.mmd" framed)]
+///
+/// # Examples
+///
+/// ## Using the block in a system
+///
+/// ```rust
+/// # use protoflow_blocks::*;
+/// # fn main() {
+/// System::build(|s| {
+///     let numbers = s.source(1..=10u64);
+///     let number_encoder = s.encode_lines();
+///     let stdout = s.write_stdout();
+///     s.connect(&numbers.output, &number_encoder.input);
+///     s.connect(&number_encoder.output, &stdout.input);
+/// });
+/// # }
+/// ```
+///
+#[derive(Block, Clone)]
+pub struct Source<T: Message> {
+    /// The output message stream.
+    #[output]
+    pub output: OutputPort<T>,
+
+    /// The iterator yielding the messages to send.
+    iter: Arc<Mutex<Box<dyn Iterator<Item = T> + Send>>>,
+}
+
+impl<T: Message> Source<T> {
+    pub fn new<I: IntoIterator<Item = T>>(output: OutputPort<T>, iter: I) -> Self
+    where
+        I::IntoIter: Send + 'static,
+    {
+        Self {
+            output,
+            iter: Arc::new(Mutex::new(Box::new(iter.into_iter()))),
+        }
+    }
+}
+
+impl<T: Message + 'static> Source<T> {
+    pub fn with_system<I: IntoIterator<Item = T>>(system: &System, iter: I) -> Self
+    where
+        I::IntoIter: Send + 'static,
+    {
+        use crate::SystemBuilding;
+        Self::new(system.output(), iter)
+    }
+}
+
+impl<T: Message> Block for Source<T> {
+    fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
+        runtime.wait_for(&self.output)?;
+
+        let mut iter = self.iter.lock();
+        for message in iter.by_ref() {
+            self.output.send(&message)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Source;
+    use crate::{System, SystemBuilding};
+
+    #[test]
+    fn instantiate_block() {
+        // Check that the block is constructible:
+        let _ = System::build(|s| {
+            let _ = s.block(Source::new(s.output(), 1..=3u64));
+        });
+    }
+}
@@ -21,7 +21,8 @@ pub mod hash {
     use super::{
         prelude::{vec, Box, Cow, Named, Vec},
         types::HashAlgorithm,
-        BlockConnections, BlockInstantiation, InputPortName, OutputPortName, System,
+        BlockConfigError, BlockConnections, BlockInstantiation, InputPortName, OutputPortName,
+        System,
     };
     use protoflow_core::Block;
 
@@ -48,6 +49,7 @@ pub mod hash {
     }
 
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
     #[derive(Clone, Debug)]
     pub enum HashBlockConfig {
         Hash {
@@ -68,6 +70,13 @@ pub mod hash {
     }
 
     impl BlockConnections for HashBlockConfig {
+        fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
+            use HashBlockConfig::*;
+            match self {
+                Hash { input, .. } => vec![("input", Some(input.clone()))],
+            }
+        }
+
         fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
             use HashBlockConfig::*;
             match self {
@@ -79,11 +88,14 @@ pub mod hash {
     }
 
     impl BlockInstantiation for HashBlockConfig {
-        fn instantiate(&self, system: &mut System) -> Box<dyn Block> {
+        fn instantiate(
+            &self,
+            system: &mut System,
+        ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
             use HashBlockConfig::*;
-            match self {
+            Ok(match self {
                 Hash { algorithm, .. } => Box::new(super::Hash::with_system(system, *algorithm)),
-            }
+            })
         }
     }
 
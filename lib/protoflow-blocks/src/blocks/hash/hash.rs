@@ -5,7 +5,9 @@ use crate::{
     types::HashAlgorithm,
     HasherFactory, StdioConfig, StdioError, StdioSystem, System,
 };
-use protoflow_core::{Block, BlockResult, BlockRuntime, InputPort, OutputPort, Port, PortError};
+use protoflow_core::{
+    Block, BlockResult, BlockRuntime, InputPort, OutputPort, Port, PortError, State,
+};
 use protoflow_derive::Block;
 use simple_mermaid::mermaid;
 
@@ -63,7 +65,7 @@ pub struct Hash {
 
     /// The internal state for computing the hash.
     #[state]
-    buffer: BytesMut, // Buffer,
+    buffer: State<BytesMut>, // Buffer,
 }
 
 impl Hash {
@@ -86,7 +88,7 @@ impl Hash {
             output,
             hash,
             algorithm: algorithm.unwrap_or_default(),
-            buffer: BytesMut::new(),
+            buffer: State::new(BytesMut::new()),
         }
     }
 
@@ -99,7 +101,7 @@ impl Hash {
 impl Block for Hash {
     fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
         while let Some(message) = self.input.recv()? {
-            self.buffer.extend_from_slice(&message);
+            self.buffer.write().extend_from_slice(&message);
 
             if self.output.is_connected() {
                 self.output.send(&message)?;
@@ -111,8 +113,10 @@ impl Block for Hash {
 
         runtime.wait_for(&self.hash)?;
 
-        let hash = Bytes::from(HasherFactory::new(self.algorithm).compute_hash(&self.buffer));
-        self.buffer.clear();
+        let mut buffer = self.buffer.write();
+        let hash = Bytes::from(HasherFactory::new(self.algorithm).compute_hash(&buffer));
+        buffer.clear();
+        drop(buffer);
         match self.hash.send(&hash) {
             Ok(()) => {}
             Err(PortError::Closed | PortError::Disconnected) => {
@@ -133,11 +137,11 @@ impl StdioSystem for Hash {
         config.allow_only(vec!["algorithm"])?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let hash_algorithm = config.get::<HashAlgorithm>("algorithm").unwrap_or_default();
             let hasher = Hash::with_system(&s, Some(hash_algorithm));
             let hex_encoder = s.encode_hex();
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &hasher.input);
             s.connect(&hasher.hash, &hex_encoder.input);
             s.connect(&hex_encoder.output, &stdout.input);
@@ -3,7 +3,8 @@
 pub mod io {
     use super::{
         prelude::{vec, Box, Cow, Named, String, Vec},
-        BlockConnections, BlockInstantiation, InputPortName, OutputPortName, System,
+        BlockConfigError, BlockConnections, BlockInstantiation, InputPortName, OutputPortName,
+        System,
     };
     use crate::{
         prelude::{FromStr, ToString},
@@ -46,12 +47,14 @@ pub mod io {
     }
 
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
     #[derive(Clone, Debug)]
     pub enum IoBlockConfig {
         Decode {
             input: InputPortName,
             output: OutputPortName,
             encoding: Option<Encoding>,
+            r#type: Option<String>,
         },
         DecodeHex {
             input: InputPortName,
@@ -67,6 +70,7 @@ pub mod io {
             input: InputPortName,
             output: OutputPortName,
             encoding: Option<Encoding>,
+            r#type: Option<String>,
         },
 
         EncodeHex {
@@ -81,6 +85,20 @@ pub mod io {
         },
     }
 
+    impl IoBlockConfig {
+        /// Returns the name of the message type that the block is
+        /// instantiated with, if the block is generic.
+        pub fn message_type(&self) -> Option<&str> {
+            use IoBlockConfig::*;
+            match self {
+                Decode { r#type, .. } | Encode { r#type, .. } => {
+                    Some(r#type.as_deref().unwrap_or("String"))
+                }
+                DecodeHex { .. } | DecodeJson { .. } | EncodeHex { .. } | EncodeJson { .. } => None,
+            }
+        }
+    }
+
     impl Named for IoBlockConfig {
         fn name(&self) -> Cow<str> {
             use IoBlockConfig::*;
@@ -96,6 +114,20 @@ pub mod io {
     }
 
     impl BlockConnections for IoBlockConfig {
+        fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
+            use IoBlockConfig::*;
+            match self {
+                Decode { input, .. }
+                | DecodeHex { input, .. }
+                | DecodeJson { input, .. }
+                | Encode { input, .. }
+                | EncodeHex { input, .. }
+                | EncodeJson { input, .. } => {
+                    vec![("input", Some(input.clone()))]
+                }
+            }
+        }
+
         fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
             use IoBlockConfig::*;
             match self {
@@ -112,20 +144,25 @@ pub mod io {
     }
 
     impl BlockInstantiation for IoBlockConfig {
-        fn instantiate(&self, system: &mut System) -> Box<dyn Block> {
+        fn instantiate(
+            &self,
+            system: &mut System,
+        ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
             use IoBlockConfig::*;
-            match self {
-                Decode { encoding, .. } => {
-                    Box::new(super::Decode::<String>::with_system(system, *encoding))
-                }
+            let message_type = || {
+                let name = self.message_type().unwrap_or_default();
+                crate::MessageTypeRegistry::global()
+                    .resolve(name, true)
+                    .cloned()
+            };
+            Ok(match self {
+                Decode { encoding, .. } => message_type()?.decode_block(system, *encoding)?,
                 DecodeHex { .. } => Box::new(super::DecodeHex::with_system(system)),
                 DecodeJson { .. } => Box::new(super::DecodeJson::with_system(system)),
-                Encode { encoding, .. } => {
-                    Box::new(super::Encode::<String>::with_system(system, *encoding))
-                }
+                Encode { encoding, .. } => message_type()?.encode_block(system, *encoding)?,
                 EncodeHex { .. } => Box::new(super::EncodeHex::with_system(system)),
                 EncodeJson { .. } => Box::new(super::EncodeJson::with_system(system)),
-            }
+            })
         }
     }
 
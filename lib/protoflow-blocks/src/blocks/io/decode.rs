@@ -3,12 +3,12 @@
 extern crate std;
 
 use crate::{
-    prelude::{vec, Bytes, FromStr, String, ToString, Vec},
+    prelude::{vec, Bytes, BytesMut, FromStr, String, ToString},
     types::Encoding,
     StdioConfig, StdioError, StdioSystem, System,
 };
 use protoflow_core::{
-    Block, BlockError, BlockResult, BlockRuntime, InputPort, Message, OutputPort,
+    Block, BlockError, BlockResult, BlockRuntime, InputPort, Message, OutputPort, State,
 };
 use protoflow_derive::Block;
 use simple_mermaid::mermaid;
@@ -64,8 +64,12 @@ pub struct Decode<T: Message + FromStr = String> {
     pub output: OutputPort<T>,
 
     /// A configuration parameter for how to decode messages.
-    #[parameter]
+    #[parameter(default = "protobuf-with-length-prefix")]
     pub encoding: Encoding,
+
+    /// The internal state buffering any partial message received.
+    #[state]
+    buffer: State<BytesMut>,
 }
 
 impl<T: Message + FromStr> Decode<T> {
@@ -82,6 +86,7 @@ impl<T: Message + FromStr> Decode<T> {
             input,
             output,
             encoding: encoding.unwrap_or_default(),
+            buffer: State::new(BytesMut::new()),
         }
     }
 }
@@ -95,12 +100,11 @@ impl<T: Message + FromStr + 'static> Decode<T> {
 
 impl<T: Message + FromStr> Block for Decode<T> {
     fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
-        let mut buffer = Vec::<u8>::new();
-
         while let Some(chunk) = self.input.recv()? {
+            let mut buffer = self.buffer.write();
             buffer.extend_from_slice(&chunk);
 
-            let mut cursor = std::io::Cursor::new(&buffer);
+            let mut cursor = std::io::Cursor::new(&buffer[..]);
 
             let _message = match self.encoding {
                 Encoding::ProtobufWithLengthPrefix => todo!(), // TODO
@@ -128,7 +132,8 @@ impl<T: Message + FromStr> Block for Decode<T> {
                 }
             };
 
-            buffer.drain(..cursor.position() as usize);
+            let consumed = cursor.position() as usize;
+            let _ = buffer.split_to(consumed);
         }
 
         self.input.close()?;
@@ -108,9 +108,9 @@ impl StdioSystem for DecodeHex {
         config.reject_any()?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let hex_decoder = s.decode_hex();
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &hex_decoder.input);
             s.connect(&hex_decoder.output, &stdout.input);
         }))
@@ -61,7 +61,7 @@ pub struct Encode<T: Message + ToString = String> {
     pub output: OutputPort<Bytes>,
 
     /// A configuration parameter for how to encode messages.
-    #[parameter]
+    #[parameter(default = "protobuf-with-length-prefix")]
     pub encoding: Encoding,
 }
 
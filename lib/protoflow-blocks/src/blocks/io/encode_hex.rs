@@ -86,9 +86,9 @@ impl StdioSystem for EncodeHex {
         config.reject_any()?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let hex_encoder = s.encode_hex();
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &hex_encoder.input);
             s.connect(&hex_encoder.output, &stdout.input);
         }))
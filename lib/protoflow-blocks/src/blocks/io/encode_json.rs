@@ -128,9 +128,9 @@ impl StdioSystem for EncodeJson {
         config.reject_any()?;
 
         Ok(System::build(|_s| {
-            //let stdin = config.read_stdin(s);
+            //let stdin = config.read_input(s);
             //let json_encoder = s.encode_json();
-            //let stdout = config.write_stdout(s);
+            //let stdout = config.write_output(s);
             //s.connect(&stdin.output, &json_encoder.input);
             //s.connect(&json_encoder.output, &stdout.input);
             todo!() // TODO
@@ -2,9 +2,11 @@
 
 pub mod math {
     use super::{
-        prelude::{Cow, Named},
-        BlockConnections, BlockInstantiation,
+        prelude::{Box, Cow, Named, Vec},
+        BlockConfigError, BlockConnections, BlockInstantiation, InputPortName, OutputPortName,
+        System,
     };
+    use protoflow_core::Block;
 
     pub trait MathBlocks {}
 
@@ -18,13 +20,28 @@ pub mod math {
 
     impl Named for MathBlockConfig {
         fn name(&self) -> Cow<str> {
-            unreachable!()
+            match *self {}
         }
     }
 
-    impl BlockConnections for MathBlockConfig {}
+    impl BlockConnections for MathBlockConfig {
+        fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
+            match *self {}
+        }
+
+        fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
+            match *self {}
+        }
+    }
 
-    impl BlockInstantiation for MathBlockConfig {}
+    impl BlockInstantiation for MathBlockConfig {
+        fn instantiate(
+            &self,
+            _system: &mut System,
+        ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
+            match *self {}
+        }
+    }
 }
 
 pub use math::*;
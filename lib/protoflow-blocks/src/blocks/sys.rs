@@ -11,7 +11,8 @@ pub mod sys {
     use super::{
         prelude::{vec, Box, Cow, Named, String, Vec},
         types::ByteSize,
-        BlockConnections, BlockInstantiation, InputPortName, OutputPortName, System,
+        BlockConfigError, BlockConnections, BlockInstantiation, InputPortName, OutputPortName,
+        System,
     };
     use protoflow_core::Block;
 
@@ -46,6 +47,7 @@ pub mod sys {
     }
 
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
     #[derive(Clone, Debug)]
     pub enum SysBlockConfig {
         ReadDir {
@@ -56,6 +58,7 @@ pub mod sys {
         ReadEnv {
             name: InputPortName,
             output: OutputPortName,
+            r#type: Option<String>,
         },
 
         ReadFile {
@@ -95,6 +98,17 @@ pub mod sys {
         },
     }
 
+    impl SysBlockConfig {
+        /// Returns the name of the message type that the block is
+        /// instantiated with, if the block is generic.
+        pub fn message_type(&self) -> Option<&str> {
+            match self {
+                Self::ReadEnv { r#type, .. } => Some(r#type.as_deref().unwrap_or("String")),
+                _ => None,
+            }
+        }
+    }
+
     impl Named for SysBlockConfig {
         fn name(&self) -> Cow<str> {
             use SysBlockConfig::*;
@@ -115,6 +129,27 @@ pub mod sys {
     }
 
     impl BlockConnections for SysBlockConfig {
+        fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
+            use SysBlockConfig::*;
+            match self {
+                ReadDir { path, .. } | ReadFile { path, .. } => {
+                    vec![("path", Some(path.clone()))]
+                }
+                ReadEnv { name, .. } => vec![("name", Some(name.clone()))],
+                ReadStdin { .. } => vec![],
+                WriteFile { path, input, .. } => {
+                    vec![("path", Some(path.clone())), ("input", Some(input.clone()))]
+                }
+                WriteStderr { input } | WriteStdout { input } => {
+                    vec![("input", Some(input.clone()))]
+                }
+                #[cfg(feature = "serde")]
+                ReadSocket { .. } => vec![],
+                #[cfg(feature = "serde")]
+                WriteSocket { input, .. } => vec![("input", Some(input.clone()))],
+            }
+        }
+
         fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
             use SysBlockConfig::*;
             match self {
@@ -138,11 +173,16 @@ pub mod sys {
     }
 
     impl BlockInstantiation for SysBlockConfig {
-        fn instantiate(&self, system: &mut System) -> Box<dyn Block> {
+        fn instantiate(
+            &self,
+            system: &mut System,
+        ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
             use SysBlockConfig::*;
-            match self {
+            Ok(match self {
                 ReadDir { .. } => Box::new(super::ReadDir::with_system(system)),
-                ReadEnv { .. } => Box::new(super::ReadEnv::<String>::with_system(system)),
+                ReadEnv { .. } => crate::MessageTypeRegistry::global()
+                    .resolve(self.message_type().unwrap_or_default(), true)?
+                    .read_env_block(system)?,
                 ReadFile { .. } => Box::new(super::ReadFile::with_system(system)),
                 #[cfg(feature = "serde")]
                 ReadSocket { config, .. } => {
@@ -159,7 +199,7 @@ pub mod sys {
                 )),
                 WriteStderr { .. } => Box::new(super::WriteStderr::with_system(system)),
                 WriteStdout { .. } => Box::new(super::WriteStdout::with_system(system)),
-            }
+            })
         }
     }
 
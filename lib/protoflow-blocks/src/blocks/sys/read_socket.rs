@@ -51,9 +51,7 @@ pub struct ReadSocket {
     pub output: OutputPort<Bytes>,
     #[parameter]
     pub config: ReadSocketConfig,
-    #[state]
     pub listener: Arc<Mutex<Option<TcpListener>>>,
-    #[state]
     pub stream: Arc<Mutex<Option<TcpStream>>>,
 }
 
@@ -83,7 +81,7 @@ impl StdioSystem for ReadSocket {
         let buffer_size: usize = config.get("buffer_size")?;
 
         Ok(System::build(|s| {
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             let read_socket = s.block(ReadSocket::with_system(
                 s,
                 Some(ReadSocketConfig {
@@ -179,6 +177,8 @@ pub struct ReadSocketConfig {
     pub buffer_size: usize,
 }
 
+impl_parameter_value!(ReadSocketConfig);
+
 #[cfg(test)]
 pub mod read_socket_tests {
 
@@ -7,7 +7,7 @@ use crate::{
     types::ByteSize,
     StdioConfig, StdioError, StdioSystem, System,
 };
-use protoflow_core::{Block, BlockResult, BlockRuntime, OutputPort};
+use protoflow_core::{Block, BlockResult, BlockRuntime, OutputPort, State};
 use protoflow_derive::Block;
 use simple_mermaid::mermaid;
 use std::io::Read;
@@ -55,8 +55,12 @@ pub struct ReadStdin {
     pub output: OutputPort<Bytes>,
 
     /// The maximum number of bytes to read at a time.
-    #[parameter]
+    #[parameter(default = "1024")]
     pub buffer_size: ByteSize,
+
+    /// The internal state recording the number of bytes read so far.
+    #[state]
+    offset: State<u64>,
 }
 
 impl ReadStdin {
@@ -68,6 +72,7 @@ impl ReadStdin {
         Self {
             output,
             buffer_size: buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE),
+            offset: State::default(),
         }
     }
 
@@ -85,6 +90,10 @@ impl Block for ReadStdin {
 
         runtime.wait_for(&self.output)?;
 
+        // Skip any input already consumed before a restored checkpoint:
+        let offset = *self.offset.read();
+        std::io::copy(&mut reader.by_ref().take(offset), &mut std::io::sink())?;
+
         loop {
             buffer.resize(self.buffer_size.into(), b'\0'); // reinitialize the buffer
             buffer.fill(b'\0');
@@ -97,6 +106,7 @@ impl Block for ReadStdin {
                     buffer.resize(buffer_len, b'\0'); // truncate the buffer
                     let bytes = Bytes::from(buffer.clone());
                     self.output.send(&bytes)?;
+                    *self.offset.write() += buffer_len as u64;
                 }
             }
         }
@@ -113,8 +123,8 @@ impl StdioSystem for ReadStdin {
         config.allow_only(vec!["buffer_size"])?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
-            let stdout = config.write_stdout(s);
+            let stdin = config.read_input(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &stdout.input);
         }))
     }
@@ -3,7 +3,7 @@
 extern crate std;
 
 use crate::{
-    prelude::{vec, Bytes, String},
+    prelude::{vec, Arc, Bytes, Mutex, String},
     StdioConfig, StdioError, StdioSystem, System,
 };
 use protoflow_core::{Block, BlockResult, BlockRuntime, InputPort};
@@ -17,6 +17,8 @@ pub struct WriteFlags {
     pub append: bool,
 }
 
+impl_parameter_value!(WriteFlags);
+
 impl Default for WriteFlags {
     fn default() -> Self {
         Self {
@@ -65,6 +67,9 @@ pub struct WriteFile {
 
     #[parameter]
     pub flags: WriteFlags,
+
+    /// The file being written to, once its path has been received.
+    file: Arc<Mutex<Option<std::fs::File>>>,
 }
 
 impl WriteFile {
@@ -81,6 +86,7 @@ impl WriteFile {
             path,
             input,
             flags: flags.unwrap_or_default(),
+            file: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -98,22 +104,28 @@ impl Block for WriteFile {
     fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
         use std::io::prelude::Write;
 
-        runtime.wait_for(&self.path)?;
-
-        let Some(path) = self.path.recv()? else {
-            return Ok(());
-        };
-        let mut file = std::fs::OpenOptions::new()
-            .write(true)
-            .create(self.flags.create)
-            .append(self.flags.append)
-            .truncate(!self.flags.append)
-            .open(path)?;
+        let mut file = self.file.lock();
+        if file.is_none() {
+            runtime.wait_for(&self.path)?;
+
+            let Some(path) = self.path.recv()? else {
+                return Ok(());
+            };
+            *file = Some(
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .create(self.flags.create)
+                    .append(self.flags.append)
+                    .truncate(!self.flags.append)
+                    .open(path)?,
+            );
+        }
 
         while let Some(message) = self.input.recv()? {
-            file.write_all(&message)?;
+            file.as_mut().expect("file is open").write_all(&message)?;
         }
 
+        *file = None; // close the file
         drop(file);
 
         self.input.close()?;
@@ -140,7 +152,7 @@ impl StdioSystem for WriteFile {
         };
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let path_const = s.const_string(path);
             let write_file = s.write_file().with_flags(flags);
 
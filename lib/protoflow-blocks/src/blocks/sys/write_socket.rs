@@ -50,7 +50,6 @@ pub struct WriteSocket {
     pub input: InputPort<Bytes>,
     #[parameter]
     pub config: WriteSocketConfig,
-    #[state]
     pub stream: Arc<Mutex<Option<TcpStream>>>,
 }
 
@@ -81,7 +80,7 @@ impl StdioSystem for WriteSocket {
         let buffer_size: usize = config.get("buffer_size")?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let write_socket = s.write_socket().with_config(WriteSocketConfig {
                 connection,
                 buffer_size,
@@ -128,6 +127,8 @@ pub struct WriteSocketConfig {
     pub buffer_size: usize,
 }
 
+impl_parameter_value!(WriteSocketConfig);
+
 #[cfg(test)]
 pub mod write_socket_tests {
     use super::WriteSocket;
@@ -76,7 +76,7 @@ impl StdioSystem for WriteStderr {
         config.reject_any()?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
+            let stdin = config.read_input(s);
             let stderr = config.write_stderr(s);
             s.connect(&stdin.output, &stderr.input);
         }))
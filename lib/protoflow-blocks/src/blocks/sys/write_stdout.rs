@@ -75,8 +75,8 @@ impl StdioSystem for WriteStdout {
         config.reject_any()?;
 
         Ok(System::build(|s| {
-            let stdin = config.read_stdin(s);
-            let stdout = config.write_stdout(s);
+            let stdin = config.read_input(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &stdout.input);
         }))
     }
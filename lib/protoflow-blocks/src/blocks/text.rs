@@ -3,7 +3,8 @@
 pub mod text {
     use super::{
         prelude::{vec, Box, Cow, Named, Vec, String},
-        BlockConnections, BlockInstantiation, InputPortName, OutputPortName, System
+        BlockConfigError, BlockConnections, BlockInstantiation, InputPortName, OutputPortName,
+        System,
     };
     use protoflow_core::Block;
 
@@ -25,6 +26,7 @@ pub mod text {
     }
 
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
     #[derive(Clone, Debug)]
     pub enum TextBlockConfig {
         ConcatStrings {
@@ -64,6 +66,20 @@ pub mod text {
     }
 
     impl BlockConnections for TextBlockConfig {
+        fn input_connections(&self) -> Vec<(&'static str, Option<InputPortName>)> {
+            use TextBlockConfig::*;
+            match self {
+                ConcatStrings { input, .. }
+                | DecodeCsv { input, .. }
+                | SplitString { input, .. } => {
+                    vec![("input", Some(input.clone()))]
+                }
+                EncodeCsv { header, rows, .. } => {
+                    vec![("header", Some(header.clone())), ("rows", Some(rows.clone()))]
+                }
+            }
+        }
+
         fn output_connections(&self) -> Vec<(&'static str, Option<OutputPortName>)> {
             use TextBlockConfig::*;
             match self {
@@ -80,9 +96,12 @@ pub mod text {
     }
 
     impl BlockInstantiation for TextBlockConfig {
-        fn instantiate(&self, system: &mut System) -> Box<dyn Block> {
+        fn instantiate(
+            &self,
+            system: &mut System,
+        ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
             use TextBlockConfig::*;
-            match self {
+            Ok(match self {
                 ConcatStrings { delimiter, .. } => {
                     Box::new(super::ConcatStrings::with_system(system, delimiter.clone()))
                 }
@@ -95,7 +114,7 @@ pub mod text {
                 SplitString { delimiter, .. } => {
                     Box::new(super::SplitString::with_system(system, delimiter.clone()))
                 }
-            }
+            })
         }
     }
 
@@ -7,7 +7,7 @@ use crate::{
     StdioConfig, StdioError, StdioSystem, System,
 };
 use protoflow_core::{
-    Block, BlockResult, BlockRuntime, InputPort, OutputPort,
+    Block, BlockResult, BlockRuntime, InputPort, OutputPort, State,
 };
 use protoflow_derive::Block;
 use simple_mermaid::mermaid;
@@ -28,12 +28,9 @@ use simple_mermaid::mermaid;
 /// # use protoflow_blocks::*;
 /// # fn main() {
 /// System::build(|s| {
-///     let config = StdioConfig {
-///         encoding: Default::default(),
-///         params: Default::default(),
-///     };
+///     let config = StdioConfig::default();
 ///     let delimiter = ",";
-///     let stdin = s.read_stdin();
+///     let stdin = config.read_stdin(s);
 ///     let line_decoder = s.decode_with(config.encoding);
 ///     let concat_strings = s.concat_strings_by(&delimiter);
 ///     let line_encoder = s.encode_with(config.encoding);
@@ -61,8 +58,11 @@ pub struct ConcatStrings {
     #[output]
     pub output: OutputPort<String>,
     /// A parameter placed between each input parameter
-    #[parameter]
-    pub delimiter: String
+    #[parameter(default = "")]
+    pub delimiter: String,
+    /// The internal state collecting the input strings received.
+    #[state]
+    inputs: State<Vec<String>>,
 }
 
 impl ConcatStrings {
@@ -79,7 +79,8 @@ impl ConcatStrings {
         Self {
             input,
             output,
-            delimiter: delimiter.unwrap_or_default()
+            delimiter: delimiter.unwrap_or_default(),
+            inputs: State::default(),
         }
     }
 }
@@ -88,11 +89,11 @@ impl Block for ConcatStrings {
     fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
         runtime.wait_for(&self.input)?;
 
-        let mut inputs = Vec::new();
         while let Some(input) = self.input.recv()? {
-            inputs.push(input);
+            self.inputs.write().push(input);
         }
 
+        let inputs = core::mem::take(&mut *self.inputs.write());
         self.output.send(&inputs.join(&self.delimiter))?;
 
         Ok(())
@@ -102,17 +103,17 @@ impl Block for ConcatStrings {
 #[cfg(feature = "std")]
 impl StdioSystem for ConcatStrings {
     fn build_system(config: StdioConfig) -> Result<System, StdioError> {
-        use crate::{TextBlocks, IoBlocks, SysBlocks, SystemBuilding};
+        use crate::{TextBlocks, IoBlocks, SystemBuilding};
 
         config.allow_only(vec!["delimiter"])?;
         let delimiter = config.get_string("delimiter")?;
 
         Ok(System::build(|s| {
-            let stdin = s.read_stdin();
+            let stdin = config.read_input(s);
             let line_decoder = s.decode_with(config.encoding);
             let concat_strings = s.concat_strings_by(&delimiter);
             let line_encoder = s.encode_with(config.encoding);
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &line_decoder.input);
             s.connect(&line_decoder.output, &concat_strings.input);
             s.connect(&concat_strings.output, &line_encoder.input);
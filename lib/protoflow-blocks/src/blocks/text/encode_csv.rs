@@ -7,7 +7,7 @@ use crate::{
     StdioConfig, StdioError, StdioSystem, System,
 };
 use protoflow_core::{
-    types::{Value, value::Kind::*}, Block, BlockError, BlockResult, BlockRuntime, InputPort, OutputPort,
+    types::{Value, value::Kind::*}, Block, BlockError, BlockResult, BlockRuntime, InputPort, OutputPort, State,
 };
 use protoflow_derive::Block;
 use simple_mermaid::mermaid;
@@ -51,12 +51,15 @@ pub struct EncodeCsv {
     /// The output message stream.
     #[output]
     pub output: OutputPort<Bytes>,
+    /// The internal state recording whether the header was received.
+    #[state]
+    has_header: State<bool>,
     // TODO for the future to add a delimiter parameter.
 }
 
 impl EncodeCsv {
     pub fn new(header: InputPort<Value>, rows: InputPort<Value>, output: OutputPort<Bytes>) -> Self {
-        Self { header, rows, output }
+        Self { header, rows, output, has_header: State::default() }
     }
 
     pub fn with_system(system: &System) -> Self {
@@ -67,8 +70,11 @@ impl EncodeCsv {
 
 impl Block for EncodeCsv {
     fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
-        if let Some(header) = self.header.recv()? {
-            self.output.send(&encode_value_to_csv(&header)?)?;
+        if !*self.has_header.read() {
+            if let Some(header) = self.header.recv()? {
+                self.output.send(&encode_value_to_csv(&header)?)?;
+            }
+            *self.has_header.write() = true;
         }
 
         while let Some(row) = self.rows.recv()? {
@@ -28,12 +28,9 @@ use simple_mermaid::mermaid;
 /// # use protoflow_blocks::*;
 /// # fn main() {
 /// System::build(|s| {
-///     let config = StdioConfig {
-///         encoding: Default::default(),
-///         params: Default::default(),
-///     };
+///     let config = StdioConfig::default();
 ///     let delimiter = " ";
-///     let stdin = s.read_stdin();
+///     let stdin = config.read_stdin(s);
 ///     let line_decoder = s.decode_with(config.encoding);
 ///     let split_string = s.split_string(delimiter);
 ///     let line_encoder = s.encode_with(config.encoding);
@@ -61,7 +58,7 @@ pub struct SplitString {
     #[output]
     pub output: OutputPort<String>,
     /// A parameter to split the input string
-    #[parameter]
+    #[parameter(default = "")]
     pub delimiter: String
 }
 
@@ -101,17 +98,17 @@ impl Block for SplitString {
 #[cfg(feature = "std")]
 impl StdioSystem for SplitString {
     fn build_system(config: StdioConfig) -> Result<System, StdioError> {
-        use crate::{TextBlocks, IoBlocks, SysBlocks, SystemBuilding};
+        use crate::{TextBlocks, IoBlocks, SystemBuilding};
 
         config.allow_only(vec!["delimiter"])?;
         let delimiter = config.get_string("delimiter")?;
 
         Ok(System::build(|s| {
-            let stdin = s.read_stdin();
+            let stdin = config.read_input(s);
             let line_decoder = s.decode_with(config.encoding);
             let split_string = s.split_string(&delimiter);
             let line_encoder = s.encode_with(config.encoding);
-            let stdout = config.write_stdout(s);
+            let stdout = config.write_output(s);
             s.connect(&stdin.output, &line_decoder.input);
             s.connect(&line_decoder.output, &split_string.input);
             s.connect(&split_string.output, &line_encoder.input);
@@ -6,18 +6,53 @@
 #[doc(hidden)]
 pub use protoflow_core::prelude;
 
+/// Implements [`ParameterValue`](protoflow_core::ParameterValue) for
+/// parameter types with their YAML serialization, which is how they are
+/// given in block configurations.
+macro_rules! impl_parameter_value {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl protoflow_core::ParameterValue for $type {
+                fn to_parameter_value(&self) -> Option<crate::prelude::String> {
+                    #[cfg(feature = "serde")]
+                    return serde_yml::to_string(self)
+                        .ok()
+                        .map(|value| value.trim_end().into());
+                    #[cfg(not(feature = "serde"))]
+                    None
+                }
+            }
+        )*
+    };
+}
+
+mod block_category;
+pub use block_category::*;
+
 mod block_config;
 pub use block_config::*;
 
+mod block_config_error;
+pub use block_config_error::*;
+
 mod block_connections;
 pub use block_connections::*;
 
 mod block_instantiation;
 pub use block_instantiation::*;
 
+mod block_plugin;
+pub use block_plugin::*;
+
+mod block_registry;
+pub use block_registry::*;
+
 mod block_tag;
 pub use block_tag::*;
 
+mod message_type;
+pub use message_type::*;
+
 #[cfg(feature = "std")]
 mod stdio;
 #[cfg(feature = "std")]
@@ -26,9 +61,22 @@ pub use stdio::*;
 mod system;
 pub use system::*;
 
+#[cfg(all(feature = "serde", feature = "std"))]
+mod system_definition;
+#[cfg(all(feature = "serde", feature = "std"))]
+pub use system_definition::*;
+
+#[cfg(all(feature = "serde", feature = "std", feature = "sysml"))]
+mod sysml;
+#[cfg(all(feature = "serde", feature = "std", feature = "sysml"))]
+pub use sysml::*;
+
 pub mod types;
 pub use types::*;
 
+#[cfg(all(feature = "serde", feature = "std"))]
+mod yaml;
+
 pub use protoflow_core::{SystemBuilding, SystemExecution};
 
 include!("blocks/core.rs"); // CoreBlocks
@@ -50,49 +98,5 @@ pub fn build_stdio_system(
     system_name: prelude::String,
     config: StdioConfig,
 ) -> Result<System, StdioError> {
-    use prelude::String;
-    Ok(match system_name.as_ref() {
-        // CoreBlocks
-        "Buffer" => Buffer::<String>::build_system(config)?,
-        "Const" => Const::<String>::build_system(config)?,
-        "Count" => Count::<String>::build_system(config)?,
-        "Delay" => Delay::<String>::build_system(config)?,
-        "Drop" => Drop::<String>::build_system(config)?,
-        "Random" => Random::<u64>::build_system(config)?,
-        // FlowBlocks
-        // HashBlocks
-        #[cfg(any(
-            feature = "hash-blake3",
-            feature = "hash-md5",
-            feature = "hash-sha1",
-            feature = "hash-sha2"
-        ))]
-        "Hash" => Hash::build_system(config)?,
-        // IoBlocks
-        "Decode" => Decode::build_system(config)?,
-        "DecodeHex" => DecodeHex::build_system(config)?,
-        "DecodeJSON" => DecodeJson::build_system(config)?,
-        "Encode" => Encode::build_system(config)?,
-        "EncodeHex" => EncodeHex::build_system(config)?,
-        "EncodeJSON" => EncodeJson::build_system(config)?,
-        // MathBlocks
-        // SysBlocks
-        "ReadDir" => ReadDir::build_system(config)?,
-        "ReadEnv" => ReadEnv::<String>::build_system(config)?,
-        "ReadFile" => ReadFile::build_system(config)?,
-        #[cfg(feature = "serde")]
-        "ReadSocket" => ReadSocket::build_system(config)?,
-        "ReadStdin" => ReadStdin::build_system(config)?,
-        "WriteFile" => WriteFile::build_system(config)?,
-        #[cfg(feature = "serde")]
-        "WriteSocket" => WriteSocket::build_system(config)?,
-        "WriteStderr" => WriteStderr::build_system(config)?,
-        "WriteStdout" => WriteStdout::build_system(config)?,
-        // TextBlocks
-        "ConcatStrings" => ConcatStrings::build_system(config)?,
-        "DecodeCSV" => DecodeCsv::build_system(config)?,
-        "EncodeCSV" => EncodeCsv::build_system(config)?,
-        "SplitString" => SplitString::build_system(config)?,
-        _ => return Err(StdioError::UnknownSystem(system_name))?,
-    })
+    BlockRegistry::global().build_stdio_system(system_name, config)
 }
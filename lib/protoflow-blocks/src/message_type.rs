@@ -0,0 +1,397 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{
+        fmt, format, prost, type_name, Arc, BTreeMap, Box, Bytes, FromStr, PhantomData, String,
+        ToString,
+    },
+    types::{DelayType, Encoding},
+    BlockConfigError, Buffer, Count, Decode, Delay, Drop, Encode, Random, System,
+};
+use parking_lot::{Once, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use protoflow_core::{prelude::prost_types, Block, DecodeError, Message};
+
+#[cfg(feature = "std")]
+use crate::{BlockTag, StdioConfig, StdioError, StdioSystem};
+
+/// A message type that generic blocks can be instantiated with, selected by
+/// name with the `type` field of block configurations and the `type`
+/// parameter on the command line.
+#[derive(Clone)]
+pub struct MessageType {
+    pub name: String,
+    type_name: &'static str,
+    blocks: Arc<dyn GenericBlocks>,
+}
+
+impl MessageType {
+    pub fn new<T: Message + 'static>(name: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            type_name: type_name::<T>(),
+            blocks: Arc::new(Binary::<T>(PhantomData)),
+        }
+    }
+
+    /// Returns a message type that can also be parsed from and formatted as
+    /// text, so that blocks can be executed with it from the command line.
+    pub fn with_text<T: Message + FromStr + ToString + 'static>(name: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            type_name: type_name::<T>(),
+            blocks: Arc::new(Text::<T>(PhantomData)),
+        }
+    }
+
+    /// Returns the Rust type name of the message type, as given for the
+    /// ports of blocks instantiated with it.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Checks whether messages of this type can be parsed from and formatted
+    /// as text, as required by the `Decode`, `Encode`, and `ReadEnv` blocks.
+    pub fn is_text(&self) -> bool {
+        self.blocks.is_text()
+    }
+
+    /// Decodes a message of this type, as sent through ports, formatting it
+    /// as text if the message type supports that, or else with its debug
+    /// representation.
+    pub fn format_message(&self, message: &[u8]) -> Result<String, DecodeError> {
+        self.blocks.format_message(message)
+    }
+
+    /// Parses a message of this type from text and encodes it for sending
+    /// through ports, unless the message type doesn't support that or the
+    /// text is invalid.
+    pub fn parse_message(&self, input: &str) -> Option<Bytes> {
+        self.blocks.parse_message(input)
+    }
+
+    pub(crate) fn buffer_block(&self, system: &System) -> Box<dyn Block> {
+        self.blocks.buffer_block(system)
+    }
+
+    pub(crate) fn count_block(&self, system: &System) -> Box<dyn Block> {
+        self.blocks.count_block(system)
+    }
+
+    pub(crate) fn delay_block(&self, system: &System, delay: Option<DelayType>) -> Box<dyn Block> {
+        self.blocks.delay_block(system, delay)
+    }
+
+    pub(crate) fn drop_block(&self, system: &System) -> Box<dyn Block> {
+        self.blocks.drop_block(system)
+    }
+
+    pub(crate) fn random_block(&self, system: &System, seed: Option<u64>) -> Box<dyn Block> {
+        self.blocks.random_block(system, seed)
+    }
+
+    pub(crate) fn decode_block(
+        &self,
+        system: &System,
+        encoding: Option<Encoding>,
+    ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
+        self.blocks
+            .decode_block(system, encoding)
+            .ok_or_else(|| self.not_text())
+    }
+
+    pub(crate) fn encode_block(
+        &self,
+        system: &System,
+        encoding: Option<Encoding>,
+    ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
+        self.blocks
+            .encode_block(system, encoding)
+            .ok_or_else(|| self.not_text())
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn read_env_block(
+        &self,
+        system: &System,
+    ) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
+        self.blocks
+            .read_env_block(system)
+            .ok_or_else(|| self.not_text())
+    }
+
+    fn not_text(&self) -> Box<BlockConfigError> {
+        Box::new(BlockConfigError {
+            field: Some("type".into()),
+            ..BlockConfigError::new(format!("`{}` is not a text message type", self.name))
+        })
+    }
+
+    /// Builds a system for executing a generic block with this message type
+    /// from the command line.
+    #[cfg(feature = "std")]
+    pub(crate) fn build_stdio_system(
+        &self,
+        block_tag: BlockTag,
+        config: StdioConfig,
+    ) -> Result<System, StdioError> {
+        self.blocks
+            .build_stdio_system(block_tag, config)
+            .unwrap_or(Err(StdioError::InvalidParameter("type")))
+    }
+}
+
+impl fmt::Debug for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MessageType")
+            .field("name", &self.name)
+            .field("type_name", &self.type_name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A registry of the message types that generic blocks can be instantiated
+/// with.
+///
+/// The [global](MessageTypeRegistry::global) registry initially contains
+/// the primitive and well-known Protocol Buffers message types, and other
+/// crates can register their own message types in it.
+#[derive(Clone, Debug, Default)]
+pub struct MessageTypeRegistry {
+    types: BTreeMap<String, MessageType>,
+}
+
+static GLOBAL_REGISTRY: RwLock<MessageTypeRegistry> = RwLock::new(MessageTypeRegistry::new());
+static GLOBAL_REGISTRY_INIT: Once = Once::new();
+
+impl MessageTypeRegistry {
+    /// Returns an empty registry.
+    pub const fn new() -> Self {
+        Self {
+            types: BTreeMap::new(),
+        }
+    }
+
+    /// Returns a registry containing the built-in message types.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(MessageType::with_text::<bool>("bool"));
+        registry.register(MessageType::with_text::<u32>("u32"));
+        registry.register(MessageType::with_text::<u64>("u64"));
+        registry.register(MessageType::with_text::<i32>("i32"));
+        registry.register(MessageType::with_text::<i64>("i64"));
+        registry.register(MessageType::with_text::<f32>("f32"));
+        registry.register(MessageType::with_text::<f64>("f64"));
+        registry.register(MessageType::with_text::<String>("String"));
+        registry.register(MessageType::new::<Bytes>("Bytes"));
+        registry.register(MessageType::new::<prost_types::Any>("Any"));
+        registry.register(MessageType::new::<prost_types::Duration>("Duration"));
+        registry.register(MessageType::new::<prost_types::ListValue>("ListValue"));
+        registry.register(MessageType::new::<prost_types::Struct>("Struct"));
+        registry.register(MessageType::new::<prost_types::Timestamp>("Timestamp"));
+        registry.register(MessageType::new::<prost_types::Value>("Value"));
+        registry
+    }
+
+    /// Returns the global registry, for looking up message types.
+    pub fn global() -> RwLockReadGuard<'static, Self> {
+        Self::init_global();
+        GLOBAL_REGISTRY.read()
+    }
+
+    /// Returns the global registry, for registering message types.
+    pub fn global_mut() -> RwLockWriteGuard<'static, Self> {
+        Self::init_global();
+        GLOBAL_REGISTRY.write()
+    }
+
+    fn init_global() {
+        GLOBAL_REGISTRY_INIT.call_once(|| *GLOBAL_REGISTRY.write() = Self::with_builtins());
+    }
+
+    /// Registers a message type, returning the message type previously
+    /// registered under the same name, if any.
+    pub fn register(&mut self, message_type: MessageType) -> Option<MessageType> {
+        self.types.insert(message_type.name.clone(), message_type)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.types.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MessageType> {
+        self.types.get(name)
+    }
+
+    /// Returns the message type with the given Rust type name, if any.
+    pub fn get_by_type_name(&self, type_name: &str) -> Option<&MessageType> {
+        self.types
+            .values()
+            .find(|message_type| message_type.type_name == type_name)
+    }
+
+    /// Iterates over the names of the registered message types, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.types.keys().map(String::as_str)
+    }
+
+    /// Returns the message type that a generic block's `type` field names,
+    /// failing if it's unknown, or if the block requires a text message
+    /// type and it isn't one.
+    pub(crate) fn resolve(
+        &self,
+        name: &str,
+        is_text: bool,
+    ) -> Result<&MessageType, Box<BlockConfigError>> {
+        self.get(name)
+            .filter(|message_type| !is_text || message_type.is_text())
+            .ok_or_else(|| {
+                let allowed = self
+                    .types
+                    .values()
+                    .filter(|message_type| !is_text || message_type.is_text())
+                    .map(|message_type| message_type.name.as_str());
+                Box::new(BlockConfigError::unknown_value("type", name, allowed))
+            })
+    }
+}
+
+/// The generic blocks, instantiated with a given message type.
+trait GenericBlocks: Send + Sync {
+    fn buffer_block(&self, system: &System) -> Box<dyn Block>;
+    fn count_block(&self, system: &System) -> Box<dyn Block>;
+    fn delay_block(&self, system: &System, delay: Option<DelayType>) -> Box<dyn Block>;
+    fn drop_block(&self, system: &System) -> Box<dyn Block>;
+    fn random_block(&self, system: &System, seed: Option<u64>) -> Box<dyn Block>;
+    fn format_message(&self, message: &[u8]) -> Result<String, DecodeError>;
+
+    fn is_text(&self) -> bool {
+        false
+    }
+
+    fn decode_block(
+        &self,
+        _system: &System,
+        _encoding: Option<Encoding>,
+    ) -> Option<Box<dyn Block>> {
+        None
+    }
+
+    fn encode_block(
+        &self,
+        _system: &System,
+        _encoding: Option<Encoding>,
+    ) -> Option<Box<dyn Block>> {
+        None
+    }
+
+    #[cfg(feature = "std")]
+    fn read_env_block(&self, _system: &System) -> Option<Box<dyn Block>> {
+        None
+    }
+
+    fn parse_message(&self, _input: &str) -> Option<Bytes> {
+        None
+    }
+
+    #[cfg(feature = "std")]
+    fn build_stdio_system(
+        &self,
+        _block_tag: BlockTag,
+        _config: StdioConfig,
+    ) -> Option<Result<System, StdioError>> {
+        None
+    }
+}
+
+struct Binary<T>(PhantomData<fn() -> T>);
+
+impl<T: Message + 'static> GenericBlocks for Binary<T> {
+    fn buffer_block(&self, system: &System) -> Box<dyn Block> {
+        Box::new(Buffer::<T>::with_system(system))
+    }
+
+    fn count_block(&self, system: &System) -> Box<dyn Block> {
+        Box::new(Count::<T>::with_system(system))
+    }
+
+    fn delay_block(&self, system: &System, delay: Option<DelayType>) -> Box<dyn Block> {
+        Box::new(Delay::<T>::with_system(system, delay))
+    }
+
+    fn drop_block(&self, system: &System) -> Box<dyn Block> {
+        Box::new(Drop::<T>::with_system(system))
+    }
+
+    fn random_block(&self, system: &System, seed: Option<u64>) -> Box<dyn Block> {
+        Box::new(Random::<T>::with_system(system, seed))
+    }
+
+    fn format_message(&self, message: &[u8]) -> Result<String, DecodeError> {
+        <T as prost::Message>::decode_length_delimited(message)
+            .map(|message| format!("{:?}", message))
+    }
+}
+
+struct Text<T>(PhantomData<fn() -> T>);
+
+impl<T: Message + FromStr + ToString + 'static> GenericBlocks for Text<T> {
+    fn buffer_block(&self, system: &System) -> Box<dyn Block> {
+        Binary::<T>(PhantomData).buffer_block(system)
+    }
+
+    fn count_block(&self, system: &System) -> Box<dyn Block> {
+        Binary::<T>(PhantomData).count_block(system)
+    }
+
+    fn delay_block(&self, system: &System, delay: Option<DelayType>) -> Box<dyn Block> {
+        Binary::<T>(PhantomData).delay_block(system, delay)
+    }
+
+    fn drop_block(&self, system: &System) -> Box<dyn Block> {
+        Binary::<T>(PhantomData).drop_block(system)
+    }
+
+    fn random_block(&self, system: &System, seed: Option<u64>) -> Box<dyn Block> {
+        Binary::<T>(PhantomData).random_block(system, seed)
+    }
+
+    fn format_message(&self, message: &[u8]) -> Result<String, DecodeError> {
+        <T as prost::Message>::decode_length_delimited(message).map(|message| message.to_string())
+    }
+
+    fn is_text(&self) -> bool {
+        true
+    }
+
+    fn decode_block(&self, system: &System, encoding: Option<Encoding>) -> Option<Box<dyn Block>> {
+        Some(Box::new(Decode::<T>::with_system(system, encoding)))
+    }
+
+    fn encode_block(&self, system: &System, encoding: Option<Encoding>) -> Option<Box<dyn Block>> {
+        Some(Box::new(Encode::<T>::with_system(system, encoding)))
+    }
+
+    #[cfg(feature = "std")]
+    fn read_env_block(&self, system: &System) -> Option<Box<dyn Block>> {
+        Some(Box::new(crate::ReadEnv::<T>::with_system(system)))
+    }
+
+    fn parse_message(&self, input: &str) -> Option<Bytes> {
+        let message: T = input.parse().ok()?;
+        Some(prost::Message::encode_length_delimited_to_vec(&message).into())
+    }
+
+    #[cfg(feature = "std")]
+    fn build_stdio_system(
+        &self,
+        block_tag: BlockTag,
+        config: StdioConfig,
+    ) -> Option<Result<System, StdioError>> {
+        match block_tag {
+            BlockTag::Count => Some(Count::<T>::build_system(config)),
+            BlockTag::Delay => Some(Delay::<T>::build_system(config)),
+            BlockTag::Random => Some(Random::<T>::build_system(config)),
+            _ => None,
+        }
+    }
+}
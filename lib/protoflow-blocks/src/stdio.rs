@@ -2,8 +2,21 @@
 
 extern crate std;
 
-use crate::{types::Encoding, ReadStdin, SysBlocks, System, WriteStderr, WriteStdout};
-use protoflow_core::prelude::{BTreeMap, FromStr, String, Vec};
+use crate::{
+    prelude::{fmt, vec, Arc, Box, Bytes, Mutex, String, ToString},
+    types::Encoding,
+    ReadStdin, SysBlocks, System, SystemBuilding, WriteStderr, WriteStdout,
+};
+use protoflow_core::{
+    prelude::{AtomicBool, BTreeMap, FromStr, Ordering, Vec},
+    Block, BlockResult, BlockRuntime, InputPort, OutputPort,
+};
+use protoflow_derive::Block;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    path::PathBuf,
+};
 
 pub trait StdioSystem {
     fn build_system(config: StdioConfig) -> Result<System, StdioError>;
@@ -13,9 +26,41 @@ pub trait StdioSystem {
 pub struct StdioConfig {
     pub encoding: Encoding,
     pub params: BTreeMap<String, String>,
+    input: StdioEndpoint,
+    output: StdioEndpoint,
+    streams: StdioStreams,
+    usage: Arc<StdioUsage>,
 }
 
 impl StdioConfig {
+    pub fn new(encoding: Encoding, params: BTreeMap<String, String>) -> Self {
+        Self {
+            encoding,
+            params,
+            ..Default::default()
+        }
+    }
+
+    /// Redirects the input of the system from stdin to the given endpoint.
+    pub fn with_input(self, input: StdioEndpoint) -> Self {
+        Self { input, ..self }
+    }
+
+    /// Redirects the output of the system from stdout to the given endpoint.
+    pub fn with_output(self, output: StdioEndpoint) -> Self {
+        Self { output, ..self }
+    }
+
+    /// The endpoint that the system reads its input from.
+    pub fn input(&self) -> &StdioEndpoint {
+        &self.input
+    }
+
+    /// The endpoint that the system writes its output to.
+    pub fn output(&self) -> &StdioEndpoint {
+        &self.output
+    }
+
     pub fn reject_any(&self) -> Result<(), StdioError> {
         if !self.params.is_empty() {
             return Err(StdioError::UnknownParameter(
@@ -25,6 +70,57 @@ impl StdioConfig {
         Ok(())
     }
 
+    /// Fails unless the input and output are stdin and stdout, for systems
+    /// whose input and output can't be redirected.
+    pub fn reject_redirection(&self) -> Result<(), StdioError> {
+        if self.input != StdioEndpoint::Stdio || self.output != StdioEndpoint::Stdio {
+            return Err(StdioError::UnsupportedRedirection);
+        }
+        Ok(())
+    }
+
+    /// Fails if the input or output is redirected, but no system built with
+    /// this configuration (or a clone of it) has read its input with
+    /// [`read_input`](StdioConfig::read_input) or written its output with
+    /// [`write_output`](StdioConfig::write_output).
+    pub fn check_redirection(&self) -> Result<(), StdioError> {
+        if (self.input != StdioEndpoint::Stdio && !self.usage.input.load(Ordering::Acquire))
+            || (self.output != StdioEndpoint::Stdio && !self.usage.output.load(Ordering::Acquire))
+        {
+            return Err(StdioError::UnsupportedRedirection);
+        }
+        Ok(())
+    }
+
+    /// Returns a clone of this configuration that tracks the usage of its
+    /// endpoints afresh, while sharing their opened streams.
+    pub(crate) fn untracked(&self) -> Self {
+        Self {
+            usage: Default::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Opens the input and output endpoints ahead of executing the system,
+    /// so that a missing file or a refused connection fails before any
+    /// block runs. Endpoints that aren't opened here are opened by the
+    /// blocks that read and write them.
+    pub fn open(&self) -> Result<(), StdioError> {
+        if self.input != StdioEndpoint::Stdio {
+            let reader = self.input.open_reader().map_err(|error| {
+                StdioError::UnavailableInput(self.input.clone(), error.to_string())
+            })?;
+            *self.streams.input.lock() = Some(reader);
+        }
+        if self.output != StdioEndpoint::Stdio {
+            let writer = self.output.open_writer().map_err(|error| {
+                StdioError::UnavailableOutput(self.output.clone(), error.to_string())
+            })?;
+            *self.streams.output.lock() = Some(writer);
+        }
+        Ok(())
+    }
+
     pub fn allow_only(&self, keys: Vec<&'static str>) -> Result<(), StdioError> {
         for key in self.params.keys() {
             if !keys.contains(&key.as_str()) {
@@ -65,17 +161,257 @@ impl StdioConfig {
         system.write_stdout() // TODO: support override
     }
 
+    /// Adds a block that reads the system's input, from stdin or from the
+    /// configured input endpoint.
+    pub fn read_input(&self, system: &mut System) -> StdioSource {
+        self.usage.input.store(true, Ordering::Release);
+        let output = match &self.input {
+            StdioEndpoint::Stdio => system.read_stdin().output,
+            endpoint => {
+                let output = system.output();
+                system.block(ReadEndpoint {
+                    output: output.clone(),
+                    endpoint: endpoint.clone(),
+                    stream: self.streams.input.clone(),
+                });
+                output
+            }
+        };
+        StdioSource { output }
+    }
+
+    /// Adds a block that writes the system's output, to stdout or to the
+    /// configured output endpoint.
+    pub fn write_output(&self, system: &mut System) -> StdioSink {
+        self.usage.output.store(true, Ordering::Release);
+        let input = match &self.output {
+            StdioEndpoint::Stdio => system.write_stdout().input,
+            endpoint => {
+                let input = system.input();
+                system.block(WriteEndpoint {
+                    input: input.clone(),
+                    endpoint: endpoint.clone(),
+                    stream: self.streams.output.clone(),
+                });
+                input
+            }
+        };
+        StdioSink { input }
+    }
+
     pub fn write_stderr(&self, system: &mut System) -> WriteStderr {
         system.write_stderr() // TODO: support override
     }
 }
 
+/// The streams of the input and output endpoints of a [`StdioConfig`],
+/// which are shared by its clones.
+#[derive(Clone, Default)]
+struct StdioStreams {
+    input: Arc<Mutex<Option<Box<dyn Read + Send>>>>,
+    output: Arc<Mutex<Option<Box<dyn Write + Send>>>>,
+}
+
+impl fmt::Debug for StdioStreams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StdioStreams")
+            .field("input", &self.input.lock().is_some())
+            .field("output", &self.output.lock().is_some())
+            .finish()
+    }
+}
+
+/// Whether the input and output endpoints of a [`StdioConfig`] have been
+/// read from and written to by the systems built with it.
+#[derive(Debug, Default)]
+struct StdioUsage {
+    input: AtomicBool,
+    output: AtomicBool,
+}
+
+/// The block that reads the input of a system, as added by
+/// [`StdioConfig::read_input`].
+#[derive(Clone)]
+pub struct StdioSource {
+    pub output: OutputPort<Bytes>,
+}
+
+/// The block that writes the output of a system, as added by
+/// [`StdioConfig::write_output`].
+#[derive(Clone)]
+pub struct StdioSink {
+    pub input: InputPort<Bytes>,
+}
+
+/// An endpoint that the input or output of a system is redirected to.
+///
+/// Endpoints are given as `-` for stdin or stdout, as `tcp://host:port` or
+/// `unix:///path` for sockets to connect to, or as file paths, which may
+/// also name named pipes.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StdioEndpoint {
+    #[default]
+    Stdio,
+    File(PathBuf),
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl StdioEndpoint {
+    pub fn open_reader(&self) -> std::io::Result<Box<dyn Read + Send>> {
+        use StdioEndpoint::*;
+        Ok(match self {
+            Stdio => Box::new(std::io::stdin()),
+            File(path) => Box::new(std::fs::File::open(path)?),
+            Tcp(address) => Box::new(TcpStream::connect(address)?),
+            #[cfg(unix)]
+            Unix(path) => Box::new(std::os::unix::net::UnixStream::connect(path)?),
+        })
+    }
+
+    pub fn open_writer(&self) -> std::io::Result<Box<dyn Write + Send>> {
+        use StdioEndpoint::*;
+        Ok(match self {
+            Stdio => Box::new(std::io::stdout()),
+            File(path) => Box::new(std::fs::File::create(path)?),
+            Tcp(address) => Box::new(TcpStream::connect(address)?),
+            #[cfg(unix)]
+            Unix(path) => Box::new(std::os::unix::net::UnixStream::connect(path)?),
+        })
+    }
+}
+
+impl FromStr for StdioEndpoint {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        use StdioEndpoint::*;
+        Ok(match input {
+            "" => return Err(String::from(input)),
+            "-" => Stdio,
+            _ => match input.split_once("://") {
+                Some(("tcp", address)) if !address.is_empty() => Tcp(address.to_string()),
+                #[cfg(unix)]
+                Some(("unix", path)) if !path.is_empty() => Unix(PathBuf::from(path)),
+                Some(("file", path)) if !path.is_empty() => File(PathBuf::from(path)),
+                Some(_) => return Err(String::from(input)),
+                None => File(PathBuf::from(input)),
+            },
+        })
+    }
+}
+
+impl fmt::Display for StdioEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use StdioEndpoint::*;
+        match self {
+            Stdio => write!(f, "-"),
+            File(path) => write!(f, "{}", path.display()),
+            Tcp(address) => write!(f, "tcp://{}", address),
+            #[cfg(unix)]
+            Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+impl protoflow_core::ParameterValue for StdioEndpoint {
+    fn to_parameter_value(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+/// A block that reads bytes from an endpoint, in place of [`ReadStdin`](crate::ReadStdin).
+#[derive(Block, Clone)]
+struct ReadEndpoint {
+    /// The output message stream.
+    #[output]
+    pub output: OutputPort<Bytes>,
+
+    /// The endpoint to read from.
+    #[parameter]
+    pub endpoint: StdioEndpoint,
+
+    /// The stream of the endpoint, if it was opened ahead of execution.
+    stream: Arc<Mutex<Option<Box<dyn Read + Send>>>>,
+}
+
+impl Block for ReadEndpoint {
+    fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
+        runtime.wait_for(&self.output)?;
+
+        // Close the port on failure, so that connected blocks don't wait:
+        let stream = self.stream.lock().take();
+        let mut reader = match stream.map_or_else(|| self.endpoint.open_reader(), Ok) {
+            Ok(reader) => reader,
+            Err(err) => {
+                self.output.close()?;
+                return Err(err.into());
+            }
+        };
+        let mut buffer = vec![0; 1024];
+        loop {
+            match reader.read(&mut buffer) {
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+                Ok(0) => break, // EOF
+                Ok(buffer_len) => {
+                    self.output
+                        .send(&Bytes::copy_from_slice(&buffer[..buffer_len]))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A block that writes bytes to an endpoint, in place of [`WriteStdout`](crate::WriteStdout).
+#[derive(Block, Clone)]
+struct WriteEndpoint {
+    /// The input message stream.
+    #[input]
+    pub input: InputPort<Bytes>,
+
+    /// The endpoint to write to.
+    #[parameter]
+    pub endpoint: StdioEndpoint,
+
+    /// The stream of the endpoint, if it was opened ahead of execution.
+    stream: Arc<Mutex<Option<Box<dyn Write + Send>>>>,
+}
+
+impl Block for WriteEndpoint {
+    fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
+        runtime.wait_for(&self.input)?;
+
+        // Drain the input on failure, so that connected blocks don't wait:
+        let stream = self.stream.lock().take();
+        let mut writer = match stream.map_or_else(|| self.endpoint.open_writer(), Ok) {
+            Ok(writer) => writer,
+            Err(err) => {
+                while self.input.recv()?.is_some() {}
+                return Err(err.into());
+            }
+        };
+        while let Some(message) = self.input.recv()? {
+            writer.write_all(&message)?;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum StdioError {
     UnknownSystem(String),
     UnknownParameter(String),
     MissingParameter(&'static str),
     InvalidParameter(&'static str),
+    UnsupportedRedirection,
+    UnavailableInput(StdioEndpoint, String),
+    UnavailableOutput(StdioEndpoint, String),
 }
 
 impl std::error::Error for StdioError {}
@@ -96,6 +432,15 @@ impl std::fmt::Display for StdioError {
             InvalidParameter(parameter) => {
                 write!(f, "invalid parameter: {}", parameter)
             }
+            UnsupportedRedirection => {
+                write!(f, "input and output can't be redirected for this system")
+            }
+            UnavailableInput(endpoint, error) => {
+                write!(f, "can't read from {}: {}", endpoint, error)
+            }
+            UnavailableOutput(endpoint, error) => {
+                write!(f, "can't write to {}: {}", endpoint, error)
+            }
         }
     }
 }
@@ -0,0 +1,529 @@
+// This is free and unencumbered software released into the public domain.
+
+extern crate std;
+
+use crate::{
+    prelude::{fmt, format, vec, BTreeMap, String, ToString, Vec},
+    system_definition::{Location, Locations},
+    DefinitionError, PortPath, System, SystemDefinition,
+};
+use serde_yml::{
+    value::{Tag, TaggedValue},
+    Mapping, Number, Value,
+};
+
+impl System {
+    /// Builds a system from a SysML v2 definition.
+    ///
+    /// The definition is a package of block usages, typed by their block
+    /// types, with attributes for their parameters, and of connections
+    /// between their ports.
+    ///
+    /// ```rust
+    /// # use protoflow_blocks::*;
+    /// let system = System::from_sysml(r#"
+    ///     package Example {
+    ///         import Protoflow::*;
+    ///
+    ///         block greeting : Const {
+    ///             attribute value = "Hello, world!";
+    ///         }
+    ///
+    ///         block sink : Drop;
+    ///
+    ///         connect greeting.output to sink.input;
+    ///     }
+    /// "#).unwrap();
+    /// ```
+    pub fn from_sysml(input: &str) -> Result<Self, DefinitionError> {
+        SystemDefinition::from_sysml(input)?.build()
+    }
+}
+
+impl SystemDefinition {
+    /// Loads a system definition from SysML v2. See [`System::from_sysml`].
+    ///
+    /// Attribute values are string, number, and boolean literals, or
+    /// sequences of them. Attributes typed by the name of an enum variant,
+    /// such as `attribute delay : Fixed = "1s"`, select that variant, and
+    /// attributes with nested attributes define structured values.
+    pub fn from_sysml(input: &str) -> Result<Self, DefinitionError> {
+        let mut parser = Parser::new(input).map_err(DefinitionError::Sysml)?;
+        let mut model = Model::default();
+        parser
+            .members(&mut model, false)
+            .map_err(DefinitionError::Sysml)?;
+
+        for ((source_block, source_port), (target_block, target_port)) in &model.connections {
+            for (block, port, other) in [
+                (source_block, source_port, (target_block, target_port)),
+                (target_block, target_port, (source_block, source_port)),
+            ] {
+                let Some((_, Value::Tagged(config))) =
+                    model.blocks.iter_mut().find(|(name, _)| name == block)
+                else {
+                    return Err(DefinitionError::UnknownBlock(block.clone()));
+                };
+                if let Value::Mapping(fields) = &mut config.value {
+                    if !fields.contains_key(port.as_str()) {
+                        let other = format!("{}.{}", other.0, other.1);
+                        fields.insert(port.as_str().into(), other.into());
+                    }
+                }
+            }
+        }
+        Self::from_values(model.blocks, &model.locations)
+    }
+}
+
+/// The names that can't be used as basic names.
+const KEYWORDS: &[&str] = &[
+    "attribute",
+    "block",
+    "connect",
+    "false",
+    "import",
+    "package",
+    "to",
+    "true",
+];
+
+/// An error in the syntax of a SysML system definition.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SysmlError {
+    /// The line and column of the error, both starting from 1.
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl SysmlError {
+    fn new(location: Location, message: impl ToString) -> Self {
+        Self {
+            location: Some(location),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl std::error::Error for SysmlError {}
+
+impl fmt::Display for SysmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((line, column)) = self.location {
+            write!(f, "{}:{}: ", line, column)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// A basic name, which may be a keyword.
+    Name(String),
+    /// A name within single quotes, which is never a keyword.
+    QuotedName(String),
+    String(String),
+    Number(Number),
+    Symbol(&'static str),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Name(name) => write!(f, "`{}`", name),
+            Token::QuotedName(name) => write!(f, "`'{}'`", name),
+            Token::String(string) => write!(f, "{:?}", string),
+            Token::Number(number) => write!(f, "`{}`", number),
+            Token::Symbol(symbol) => write!(f, "`{}`", symbol),
+            Token::End => write!(f, "end of input"),
+        }
+    }
+}
+
+/// The block usages and connections of a SysML model, with the block
+/// configurations as YAML values.
+#[derive(Default)]
+struct Model {
+    blocks: Vec<(String, Value)>,
+    connections: Vec<(PortPath, PortPath)>,
+    locations: Locations,
+}
+
+struct Parser {
+    tokens: Vec<(Token, Location)>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Result<Self, SysmlError> {
+        Ok(Self {
+            tokens: tokenize(input)?,
+            position: 0,
+        })
+    }
+
+    fn peek(&self) -> &(Token, Location) {
+        &self.tokens[self.position]
+    }
+
+    fn next(&mut self) -> (Token, Location) {
+        let token = self.tokens[self.position].clone();
+        if token.0 != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, SysmlError> {
+        let (token, location) = self.peek();
+        Err(SysmlError::new(
+            *location,
+            format!("expected {}, found {}", expected, token),
+        ))
+    }
+
+    fn accept(&mut self, symbol: &str) -> bool {
+        let accepted = matches!(&self.peek().0, Token::Symbol(s) if *s == symbol);
+        if accepted {
+            self.next();
+        }
+        accepted
+    }
+
+    fn accept_keyword(&mut self, keyword: &str) -> bool {
+        let accepted = matches!(&self.peek().0, Token::Name(name) if name == keyword);
+        if accepted {
+            self.next();
+        }
+        accepted
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), SysmlError> {
+        if !self.accept(symbol) {
+            return self.unexpected(&format!("`{}`", symbol));
+        }
+        Ok(())
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SysmlError> {
+        if !self.accept_keyword(keyword) {
+            return self.unexpected(&format!("`{}`", keyword));
+        }
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<(String, Location), SysmlError> {
+        match self.peek().clone() {
+            (Token::Name(name), location) if !KEYWORDS.contains(&name.as_str()) => {
+                self.next();
+                Ok((name, location))
+            }
+            (Token::QuotedName(name), location) => {
+                self.next();
+                Ok((name, location))
+            }
+            _ => self.unexpected("a name"),
+        }
+    }
+
+    fn qualified_name(&mut self) -> Result<Vec<String>, SysmlError> {
+        let mut names = vec![self.name()?.0];
+        while self.accept("::") {
+            if self.accept("*") {
+                names.push("*".into());
+                break;
+            }
+            names.push(self.name()?.0);
+        }
+        Ok(names)
+    }
+
+    /// Parses the members of a package, or of the model at the top level.
+    fn members(&mut self, model: &mut Model, nested: bool) -> Result<(), SysmlError> {
+        loop {
+            let (token, location) = self.peek().clone();
+            match token {
+                Token::Symbol("}") if nested => return Ok(()),
+                Token::End if !nested => return Ok(()),
+                Token::Name(keyword) if keyword == "package" => {
+                    self.next();
+                    self.name()?;
+                    if !self.accept(";") {
+                        self.expect("{")?;
+                        self.members(model, true)?;
+                        self.expect("}")?;
+                    }
+                }
+                Token::Name(keyword) if keyword == "import" => {
+                    self.next();
+                    let imported_name = self.qualified_name()?;
+                    if imported_name.len() != 2 || imported_name[0] != "Protoflow" {
+                        return Err(SysmlError::new(
+                            location,
+                            format!("invalid import: {}", imported_name.join("::")),
+                        ));
+                    }
+                    self.expect(";")?;
+                }
+                Token::Name(keyword) if keyword == "block" => {
+                    self.next();
+                    self.block(model)?;
+                }
+                Token::Name(keyword) if keyword == "connect" => {
+                    self.next();
+                    let source = self.port_path()?;
+                    self.expect_keyword("to")?;
+                    let target = self.port_path()?;
+                    self.expect(";")?;
+                    model.connections.push((source, target));
+                }
+                _ => return self.unexpected("a package, import, block, or connection"),
+            }
+        }
+    }
+
+    fn block(&mut self, model: &mut Model) -> Result<(), SysmlError> {
+        let (name, location) = self.name()?;
+        self.expect(":")?;
+        let (type_location, block_type) = (self.peek().1, self.qualified_name()?);
+        let block_type = match block_type.as_slice() {
+            [block_type] | [_, block_type] if block_type != "*" => block_type.clone(),
+            _ => {
+                return Err(SysmlError::new(
+                    type_location,
+                    format!("invalid block type: {}", block_type.join("::")),
+                ))
+            }
+        };
+        let mut fields = Mapping::new();
+        let mut field_locations = Default::default();
+        if !self.accept(";") {
+            self.expect("{")?;
+            self.attributes(&mut fields, Some(&mut field_locations))?;
+        }
+        if model.blocks.iter().any(|(other, _)| *other == name) {
+            return Err(SysmlError::new(
+                location,
+                format!("duplicate block: {}", name),
+            ));
+        }
+        let config = TaggedValue {
+            tag: Tag::new(block_type),
+            value: Value::Mapping(fields),
+        };
+        model
+            .locations
+            .insert(name.clone(), (location, field_locations));
+        model.blocks.push((name, Value::Tagged(config.into())));
+        Ok(())
+    }
+
+    /// Parses attributes up to and including the closing brace.
+    fn attributes(
+        &mut self,
+        fields: &mut Mapping,
+        mut locations: Option<&mut BTreeMap<String, Location>>,
+    ) -> Result<(), SysmlError> {
+        while !self.accept("}") {
+            self.expect_keyword("attribute")?;
+            let (name, location) = self.name()?;
+            let variant = match self.accept(":") {
+                true => Some(self.name()?.0),
+                false => None,
+            };
+            let value = if self.accept("=") {
+                let value = self.value()?;
+                self.expect(";")?;
+                value
+            } else if self.accept("{") {
+                let mut fields = Mapping::new();
+                self.attributes(&mut fields, None)?;
+                Value::Mapping(fields)
+            } else {
+                self.expect(";")?;
+                Value::Null
+            };
+            let value = match variant {
+                Some(variant) => Value::Tagged(
+                    TaggedValue {
+                        tag: Tag::new(variant),
+                        value,
+                    }
+                    .into(),
+                ),
+                None => value,
+            };
+            if let Some(locations) = locations.as_mut() {
+                locations.insert(name.clone(), location);
+            }
+            fields.insert(name.into(), value);
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Value, SysmlError> {
+        match self.peek().0.clone() {
+            Token::String(string) => {
+                self.next();
+                Ok(Value::String(string))
+            }
+            Token::Number(number) => {
+                self.next();
+                Ok(Value::Number(number))
+            }
+            Token::Name(name) if name == "true" || name == "false" => {
+                self.next();
+                Ok(Value::Bool(name == "true"))
+            }
+            Token::Symbol("(") => {
+                self.next();
+                let mut values = Vec::new();
+                while !self.accept(")") {
+                    if !values.is_empty() {
+                        self.expect(",")?;
+                    }
+                    values.push(self.value()?);
+                }
+                Ok(Value::Sequence(values))
+            }
+            _ => self.unexpected("a literal value"),
+        }
+    }
+
+    fn port_path(&mut self) -> Result<PortPath, SysmlError> {
+        let (block, _) = self.name()?;
+        self.expect(".")?;
+        let (port, _) = self.name()?;
+        Ok((block, port))
+    }
+}
+
+fn advance(c: char, line: &mut usize, column: &mut usize) {
+    if c == '\n' {
+        *line += 1;
+        *column = 1;
+    } else {
+        *column += 1;
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, Location)>, SysmlError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let (mut line, mut column) = (1, 1);
+    while let Some(&c) = chars.peek() {
+        let location = (line, column);
+        if c.is_whitespace() {
+            chars.next();
+            advance(c, &mut line, &mut column);
+            continue;
+        }
+        chars.next();
+        advance(c, &mut line, &mut column);
+        let token = match c {
+            '/' if chars.peek() == Some(&'/') => {
+                while let Some(c) = chars.next_if(|&c| c != '\n') {
+                    advance(c, &mut line, &mut column);
+                }
+                continue;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                advance('*', &mut line, &mut column);
+                let mut previous = ' ';
+                loop {
+                    let Some(c) = chars.next() else {
+                        return Err(SysmlError::new(location, "unterminated comment"));
+                    };
+                    advance(c, &mut line, &mut column);
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                continue;
+            }
+            ':' if chars.peek() == Some(&':') => {
+                chars.next();
+                advance(':', &mut line, &mut column);
+                Token::Symbol("::")
+            }
+            '{' => Token::Symbol("{"),
+            '}' => Token::Symbol("}"),
+            '(' => Token::Symbol("("),
+            ')' => Token::Symbol(")"),
+            ';' => Token::Symbol(";"),
+            ':' => Token::Symbol(":"),
+            '=' => Token::Symbol("="),
+            '.' => Token::Symbol("."),
+            ',' => Token::Symbol(","),
+            '*' => Token::Symbol("*"),
+            '"' | '\'' => {
+                let mut string = String::new();
+                loop {
+                    let Some(next) = chars.next() else {
+                        return Err(SysmlError::new(location, "unterminated string"));
+                    };
+                    advance(next, &mut line, &mut column);
+                    match next {
+                        _ if next == c => break,
+                        '\\' => {
+                            let Some(escaped) = chars.next() else {
+                                return Err(SysmlError::new(location, "unterminated string"));
+                            };
+                            advance(escaped, &mut line, &mut column);
+                            string.push(match escaped {
+                                'n' => '\n',
+                                'r' => '\r',
+                                't' => '\t',
+                                '0' => '\0',
+                                escaped => escaped,
+                            });
+                        }
+                        next => string.push(next),
+                    }
+                }
+                match c {
+                    '"' => Token::String(string),
+                    _ => Token::QuotedName(string),
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut number = String::from(c);
+                while let Some(c) = chars
+                    .next_if(|&c| c.is_ascii_alphanumeric() || c == '.' || c == '+' || c == '-')
+                {
+                    advance(c, &mut line, &mut column);
+                    number.push(c);
+                }
+                match number.parse() {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => {
+                        return Err(SysmlError::new(
+                            location,
+                            format!("invalid number: {}", number),
+                        ))
+                    }
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = String::from(c);
+                while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_') {
+                    advance(c, &mut line, &mut column);
+                    name.push(c);
+                }
+                Token::Name(name)
+            }
+            c => {
+                return Err(SysmlError::new(
+                    location,
+                    format!("unexpected character: {:?}", c),
+                ))
+            }
+        };
+        tokens.push((token, location));
+    }
+    tokens.push((Token::End, (line, column)));
+    Ok(tokens)
+}
@@ -6,15 +6,16 @@ use crate::{
     prelude::{fmt, Arc, Box, Bytes, FromStr, Rc, String, ToString},
     types::{DelayType, Encoding},
     AllBlocks, Buffer, ConcatStrings, Const, CoreBlocks, Count, Decode, DecodeCsv, DecodeHex,
-    DecodeJson, Delay, Drop, Encode, EncodeCsv, EncodeHex, EncodeJson, FlowBlocks, HashBlocks,
-    IoBlocks, MathBlocks, Random, ReadDir, ReadEnv, ReadFile, ReadStdin, SplitString, SysBlocks,
-    TextBlocks, WriteFile, WriteStderr, WriteStdout,
+    DecodeJson, Delay, Drop, Encode, EncodeCsv, EncodeHex, EncodeJson, Filter, FlatMap, FlowBlocks,
+    HashBlocks, IoBlocks, Map, MathBlocks, Random, ReadDir, ReadEnv, ReadFile, ReadStdin, Sink,
+    Source, SplitString, SysBlocks, TextBlocks, WriteFile, WriteStderr, WriteStdout,
 };
 #[cfg(all(feature = "std", feature = "serde"))]
 use crate::{ReadSocket, WriteSocket};
 use protoflow_core::{
-    Block, BlockID, BlockResult, BoxedBlockType, InputPort, Message, OutputPort, PortID,
-    PortResult, Process, SystemBuilding, SystemExecution,
+    Block, BlockID, BlockResult, BoxedBlockType, Checkpoint, CheckpointCoordinator, CheckpointID,
+    CheckpointStore, ConnectionOptions, InputPort, Message, OutputPort, PortID, PortResult,
+    Process, SystemBuilding, SystemDescription, SystemExecution, SystemHandle,
 };
 
 #[cfg(any(
@@ -53,6 +54,11 @@ impl System {
         Self::build(f).execute()
     }
 
+    /// Builds and executes a system, returning a handle for reconfiguring it while running.
+    pub fn start<F: FnOnce(&mut System)>(f: F) -> BlockResult<SystemHandle<Transport>> {
+        Self::build(f).0.start()
+    }
+
     /// Builds a new system.
     pub fn build<F: FnOnce(&mut System)>(f: F) -> Self {
         let transport = Transport::default();
@@ -91,6 +97,44 @@ impl System {
     pub fn connect_by_id(&mut self, source_id: PortID, target_id: PortID) -> PortResult<bool> {
         self.0.connect_by_id(source_id, target_id)
     }
+
+    #[doc(hidden)]
+    pub fn connect_by_id_with(
+        &mut self,
+        source_id: PortID,
+        target_id: PortID,
+        options: ConnectionOptions,
+    ) -> PortResult<bool> {
+        self.0.connect_by_id_with(source_id, target_id, options)
+    }
+
+    /// Describes the full topology of the system.
+    pub fn describe(&self) -> SystemDescription {
+        self.0.describe()
+    }
+
+    /// Returns the transport that the system's ports are opened in.
+    pub fn transport(&self) -> &Arc<Transport> {
+        self.0.transport()
+    }
+
+    /// Enables checkpoints of the system's block state, written to the given store.
+    pub fn enable_checkpoints(
+        &mut self,
+        store: Arc<dyn CheckpointStore>,
+    ) -> BlockResult<Arc<CheckpointCoordinator>> {
+        self.0.enable_checkpoints(store)
+    }
+
+    /// Restores the state of the system's blocks from the given checkpoint.
+    pub fn restore(&self, checkpoint: &Checkpoint) -> BlockResult {
+        self.0.restore(checkpoint)
+    }
+
+    /// Restores the state of the system's blocks from the latest checkpoint in the store.
+    pub fn restore_latest(&self, store: &dyn CheckpointStore) -> BlockResult<Option<CheckpointID>> {
+        self.0.restore_latest(store)
+    }
 }
 
 impl fmt::Debug for System {
@@ -131,6 +175,15 @@ impl SystemBuilding for System {
         self.0.connect(source, target)
     }
 
+    fn connect_with<M: Message>(
+        &mut self,
+        source: &OutputPort<M>,
+        target: &InputPort<M>,
+        options: ConnectionOptions,
+    ) -> bool {
+        self.0.connect_with(source, target, options)
+    }
+
     fn validate(&self) -> BlockResult<()> {
         self.0.validate()
     }
@@ -178,7 +231,39 @@ impl CoreBlocks for System {
     }
 }
 
-impl FlowBlocks for System {}
+impl FlowBlocks for System {
+    fn filter<T: Message + 'static>(
+        &mut self,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Filter<T> {
+        self.0.block(Filter::<T>::with_system(self, predicate))
+    }
+
+    fn flat_map<I: Message + 'static, O: Message + 'static, R: IntoIterator<Item = O>>(
+        &mut self,
+        function: impl Fn(I) -> R + Send + Sync + 'static,
+    ) -> FlatMap<I, O> {
+        self.0.block(FlatMap::<I, O>::with_system(self, function))
+    }
+
+    fn map<I: Message + 'static, O: Message + 'static>(
+        &mut self,
+        function: impl Fn(I) -> O + Send + Sync + 'static,
+    ) -> Map<I, O> {
+        self.0.block(Map::<I, O>::with_system(self, function))
+    }
+
+    fn sink<T: Message + 'static>(&mut self, function: impl FnMut(T) + Send + 'static) -> Sink<T> {
+        self.0.block(Sink::<T>::with_system(self, function))
+    }
+
+    fn source<T: Message + 'static, I: IntoIterator<Item = T>>(&mut self, iter: I) -> Source<T>
+    where
+        I::IntoIter: Send + 'static,
+    {
+        self.0.block(Source::<T>::with_system(self, iter))
+    }
+}
 
 #[cfg(not(any(
     feature = "hash-blake3",
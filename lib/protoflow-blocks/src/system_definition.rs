@@ -0,0 +1,488 @@
+// This is free and unencumbered software released into the public domain.
+
+extern crate std;
+
+use crate::{
+    prelude::{format, type_name, vec, BTreeMap, BTreeSet, String, ToString, Vec},
+    BlockConfig, BlockConfigError, BlockConnections, BlockInstantiation, BlockTag,
+    MessageTypeRegistry, StdioConfig, StdioEndpoint, StdioError, SysBlockConfig, System,
+};
+use protoflow_core::{
+    types::Any, ConnectionOptions, PortDescriptor, PortError, PortID, SystemDescription,
+};
+use serde_yml::{
+    value::{Tag, TaggedValue},
+    Mapping, Value,
+};
+
+#[cfg(feature = "sysml")]
+use crate::SysmlError;
+
+/// A definition of a system, as the configurations of its named blocks.
+///
+/// Definitions can be loaded from YAML and SysML, and written to YAML, or
+/// to SysML with the `protoflow-syntax` crate, and are built into systems
+/// by instantiating their blocks in order and connecting their ports.
+#[derive(Clone, Debug, Default)]
+pub struct SystemDefinition {
+    pub blocks: Vec<(String, BlockConfig)>,
+}
+
+/// A port of a block in a system definition, as a block name and port name.
+pub type PortPath = (String, String);
+
+/// The line and column of an element in a system definition, both
+/// starting from 1.
+pub(crate) type Location = (usize, usize);
+
+/// The locations of the blocks, and of the fields of their configurations,
+/// in a system definition.
+pub(crate) type Locations = BTreeMap<String, (Location, BTreeMap<String, Location>)>;
+
+type Ports = BTreeMap<String, PortDescriptor>;
+
+impl SystemDefinition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines a system with the blocks and connections of a system built
+    /// in Rust.
+    ///
+    /// Blocks are named after their labels, if any, and otherwise after
+    /// their types. Their parameters are defined with the configured values
+    /// described by the blocks, so blocks whose parameter values can't be
+    /// described, such as functions, can't be defined.
+    pub fn from_system(system: &System) -> Result<Self, DefinitionError> {
+        Self::from_description(&system.describe())
+    }
+
+    /// Defines a system with the blocks and connections of a system
+    /// description. See [`SystemDefinition::from_system`].
+    pub fn from_description(description: &SystemDescription) -> Result<Self, DefinitionError> {
+        let mut names = BTreeMap::new();
+        for block in &description.blocks {
+            let name = block
+                .label
+                .clone()
+                .unwrap_or_else(|| snake_case(block.name.as_deref().unwrap_or("block")));
+            let mut unique_name = name.clone();
+            for suffix in 2.. {
+                if !names.values().any(|other| other == &unique_name) {
+                    break;
+                }
+                unique_name = format!("{}_{}", name, suffix);
+            }
+            names.insert(block.id, unique_name);
+        }
+        let port_path = |port: PortID| {
+            let block = description.block_of(port)?;
+            let port = description.port(port)?;
+            Some(format!("{}.{}", names[&block.id], port.name.as_deref()?))
+        };
+
+        let mut configs = Vec::with_capacity(description.blocks.len());
+        for block in &description.blocks {
+            let mut fields = Mapping::new();
+            for input in &block.inputs {
+                let source = description
+                    .connections
+                    .iter()
+                    .find(|connection| PortID::from(connection.target) == input.id)
+                    .and_then(|connection| port_path(connection.source.into()));
+                if let (Some(name), Some(source)) = (&input.name, source) {
+                    fields.insert(name.as_str().into(), source.into());
+                }
+            }
+            for output in &block.outputs {
+                let target = description
+                    .connections
+                    .iter()
+                    .find(|connection| PortID::from(connection.source) == output.id)
+                    .and_then(|connection| port_path(connection.target.into()));
+                if let (Some(name), Some(target)) = (&output.name, target) {
+                    fields.insert(name.as_str().into(), target.into());
+                }
+            }
+            // Generic blocks are instantiated with the message type of
+            // their generic port:
+            let generic_port = block
+                .name
+                .as_deref()
+                .and_then(|name| name.parse::<BlockTag>().ok())
+                .and_then(|tag| tag.generic_port());
+            let message_type = block
+                .ports()
+                .find(|port| generic_port.is_some_and(|name| port.name.as_deref() == Some(name)))
+                .and_then(|port| {
+                    let registry = MessageTypeRegistry::global();
+                    Some(
+                        registry
+                            .get_by_type_name(port.r#type.as_deref()?)?
+                            .name
+                            .clone(),
+                    )
+                });
+            if let Some(message_type) = message_type {
+                fields.insert("type".into(), message_type.into());
+            }
+            for parameter in &block.parameters {
+                if let Some(value) = &parameter.value {
+                    let value =
+                        serde_yml::from_str(value).unwrap_or_else(|_| Value::from(&**value));
+                    fields.insert(parameter.name.as_str().into(), value);
+                }
+            }
+            let config = Value::Tagged(
+                TaggedValue {
+                    tag: Tag::new(block.name.as_deref().unwrap_or_default()),
+                    value: Value::Mapping(fields),
+                }
+                .into(),
+            );
+            configs.push((names[&block.id].clone(), config));
+        }
+        Self::from_values(configs, &Locations::new())
+    }
+
+    /// Defines a system with the given block configurations, reporting the
+    /// errors in all of them along with their locations, if known.
+    pub(crate) fn from_values(
+        configs: impl IntoIterator<Item = (String, Value)>,
+        locations: &Locations,
+    ) -> Result<Self, DefinitionError> {
+        let mut blocks = Vec::new();
+        let mut errors = Vec::new();
+        for (name, config) in configs {
+            match BlockConfig::try_from(config) {
+                Ok(config) => blocks.push((name, config)),
+                Err(error) => {
+                    let location = locations.get(&name).map(|(location, fields)| {
+                        error
+                            .field
+                            .as_deref()
+                            .and_then(|field| fields.get(field.split('.').next()?))
+                            .unwrap_or(location)
+                    });
+                    errors.push(BlockConfigError {
+                        block: Some(name),
+                        location: location.copied(),
+                        ..*error
+                    });
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(DefinitionError::Config(errors));
+        }
+        Ok(Self { blocks })
+    }
+
+    /// Sets a parameter of a block, given as `block.parameter`, to a value
+    /// given in YAML syntax, such as from the command line.
+    ///
+    /// Values that aren't valid for the parameter in YAML syntax are tried
+    /// again as plain strings.
+    pub fn set_parameter(&mut self, path: &str, value: &str) -> Result<(), DefinitionError> {
+        let Some((block_name, field)) = path.rsplit_once('.') else {
+            return Err(DefinitionError::UnknownParameter(path.to_string()));
+        };
+        let Some((_, config)) = self.blocks.iter_mut().find(|(name, _)| name == block_name) else {
+            return Err(DefinitionError::UnknownBlock(block_name.to_string()));
+        };
+        let Value::Tagged(tagged) = serde_yml::to_value(&*config).map_err(DefinitionError::Yaml)?
+        else {
+            unreachable!("block configurations are tagged");
+        };
+
+        let mut candidates = Vec::with_capacity(2);
+        if let Ok(value) = serde_yml::from_str::<Value>(value) {
+            candidates.push(value);
+        }
+        candidates.push(Value::from(value));
+        let mut last_error = None;
+        for candidate in candidates {
+            let mut tagged = tagged.clone();
+            if let Value::Mapping(fields) = &mut tagged.value {
+                fields.insert(field.into(), candidate);
+            }
+            match BlockConfig::try_from(Value::Tagged(tagged)) {
+                Ok(new_config) => {
+                    *config = new_config;
+                    return Ok(());
+                }
+                Err(error)
+                    if error.field.as_deref() == Some(field)
+                        && error.message.starts_with("unknown field") =>
+                {
+                    return Err(DefinitionError::UnknownParameter(path.to_string()));
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+        let error = last_error.expect("at least one candidate value");
+        Err(DefinitionError::Config(vec![BlockConfigError {
+            block: Some(block_name.to_string()),
+            ..*error
+        }]))
+    }
+
+    /// Returns the connections between the ports of the blocks, each given
+    /// at its source, its target, or both, as pairs of source and target.
+    ///
+    /// Ports given just as `block` default to its `output` port at the
+    /// source, and to its `input` port at the target.
+    pub fn connections(&self) -> BTreeSet<(PortPath, PortPath)> {
+        let mut connections = BTreeSet::new();
+        for (name, config) in &self.blocks {
+            for (port_name, source) in config.input_connections() {
+                if let Some(source) = source {
+                    let (source_block, source_port) =
+                        source.split_once('.').unwrap_or((&source, "output"));
+                    connections.insert((
+                        (source_block.to_string(), source_port.to_string()),
+                        (name.clone(), port_name.to_string()),
+                    ));
+                }
+            }
+            for (port_name, target) in config.output_connections() {
+                if let Some(target) = target {
+                    let (target_block, target_port) =
+                        target.split_once('.').unwrap_or((&target, "input"));
+                    connections.insert((
+                        (name.clone(), port_name.to_string()),
+                        (target_block.to_string(), target_port.to_string()),
+                    ));
+                }
+            }
+        }
+        connections
+    }
+
+    /// Returns the configurations of the blocks in canonical form, with
+    /// every connection given at both ends as `block.port`, and without
+    /// unset fields.
+    pub fn canonical_configs(&self) -> Result<Vec<(&str, TaggedValue)>, DefinitionError> {
+        let connections = self.connections();
+        let mut configs = Vec::with_capacity(self.blocks.len());
+        for (name, config) in &self.blocks {
+            let Value::Tagged(mut tagged) =
+                serde_yml::to_value(config).map_err(DefinitionError::Yaml)?
+            else {
+                unreachable!("block configurations are tagged");
+            };
+            if let Value::Mapping(fields) = &mut tagged.value {
+                for (port_name, _) in config.input_connections() {
+                    let source = connections
+                        .iter()
+                        .find(|(_, target)| target.0 == *name && target.1 == port_name)
+                        .map(|(source, _)| format!("{}.{}", source.0, source.1));
+                    fields.insert(port_name.into(), source.map_or(Value::Null, Value::from));
+                }
+                for (port_name, _) in config.output_connections() {
+                    let target = connections
+                        .iter()
+                        .find(|(source, _)| source.0 == *name && source.1 == port_name)
+                        .map(|(_, target)| format!("{}.{}", target.0, target.1));
+                    fields.insert(port_name.into(), target.map_or(Value::Null, Value::from));
+                }
+            }
+            remove_nulls(&mut tagged.value);
+            configs.push((name.as_str(), *tagged));
+        }
+        Ok(configs)
+    }
+
+    /// Builds the defined system, instantiating its blocks and connecting
+    /// their ports. Every input port must be connected.
+    pub fn build(&self) -> Result<System, DefinitionError> {
+        self.build_with(ConnectionOptions::default())
+    }
+
+    /// Builds the defined system, connecting its ports with the given
+    /// options. See [`SystemDefinition::build`].
+    pub fn build_with(&self, options: ConnectionOptions) -> Result<System, DefinitionError> {
+        self.build_with_stdio(options, &StdioConfig::default())
+    }
+
+    /// Builds the defined system, with its `ReadStdin` and `WriteStdout`
+    /// blocks reading from and writing to the input and output endpoints of
+    /// the given stdio configuration. Fails if the input or output is
+    /// redirected but the system has no such block. See
+    /// [`SystemDefinition::build`].
+    pub fn build_with_stdio(
+        &self,
+        options: ConnectionOptions,
+        stdio: &StdioConfig,
+    ) -> Result<System, DefinitionError> {
+        let stdio = stdio.untracked();
+        let mut system = System::build(|_| {});
+        let mut blocks = BTreeMap::new();
+        for (name, config) in &self.blocks {
+            let ports = match config {
+                BlockConfig::Sys(SysBlockConfig::ReadStdin { .. })
+                    if *stdio.input() != StdioEndpoint::Stdio =>
+                {
+                    let source = stdio.read_input(&mut system);
+                    let output = PortDescriptor::from(&source.output);
+                    (Ports::new(), Ports::from([("output".into(), output)]))
+                }
+                BlockConfig::Sys(SysBlockConfig::WriteStdout { .. })
+                    if *stdio.output() != StdioEndpoint::Stdio =>
+                {
+                    let sink = stdio.write_output(&mut system);
+                    let input = PortDescriptor::from(&sink.input);
+                    (Ports::from([("input".into(), input)]), Ports::new())
+                }
+                _ => {
+                    let instance = config
+                        .instantiate_with_ports(&mut system)
+                        .map_err(|error| {
+                            DefinitionError::Config(vec![BlockConfigError {
+                                block: Some(name.clone()),
+                                ..*error
+                            }])
+                        })?;
+                    system.add_block(instance.block);
+                    (instance.inputs, instance.outputs)
+                }
+            };
+            blocks.insert(name.as_str(), ports);
+        }
+
+        let mut connected = BTreeSet::new();
+        for ((source_block, source_port), (target_block, target_port)) in self.connections() {
+            let source = find_port(&blocks, &source_block, &source_port, |(_, outputs)| outputs)?;
+            let target = find_port(&blocks, &target_block, &target_port, |(inputs, _)| inputs)?;
+            if !is_compatible(source, target) {
+                return Err(DefinitionError::IncompatiblePorts(
+                    format!("{}.{}", source_block, source_port),
+                    format!("{}.{}", target_block, target_port),
+                ));
+            }
+            system
+                .connect_by_id_with(source.id, target.id, options)
+                .map_err(DefinitionError::Port)?;
+            connected.insert(target.id);
+        }
+
+        for (name, (inputs, _)) in &blocks {
+            if let Some(input) = inputs.values().find(|input| !connected.contains(&input.id)) {
+                return Err(DefinitionError::UnconnectedInput(format!(
+                    "{}.{}",
+                    name,
+                    input.name.as_deref().unwrap_or_default()
+                )));
+            }
+        }
+
+        stdio.check_redirection().map_err(DefinitionError::Stdio)?;
+        Ok(system)
+    }
+}
+
+fn find_port<'a>(
+    blocks: &'a BTreeMap<&str, (Ports, Ports)>,
+    block_name: &str,
+    port_name: &str,
+    ports: impl Fn(&'a (Ports, Ports)) -> &'a Ports,
+) -> Result<&'a PortDescriptor, DefinitionError> {
+    let Some(block) = blocks.get(block_name) else {
+        return Err(DefinitionError::UnknownBlock(block_name.to_string()));
+    };
+    ports(block)
+        .get(port_name)
+        .ok_or_else(|| DefinitionError::UnknownPort(format!("{}.{}", block_name, port_name)))
+}
+
+/// Checks whether messages sent on the source port can be received on the
+/// target port, which is the case if either port accepts any message.
+fn is_compatible(source: &PortDescriptor, target: &PortDescriptor) -> bool {
+    let any = Some(type_name::<Any>());
+    source.r#type == target.r#type
+        || source.r#type.as_deref() == any
+        || target.r#type.as_deref() == any
+}
+
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Mapping(mapping) => {
+            mapping.retain(|_, value| !value.is_null());
+            mapping.values_mut().for_each(remove_nulls);
+        }
+        Value::Sequence(sequence) => sequence.iter_mut().for_each(remove_nulls),
+        Value::Tagged(tagged) => remove_nulls(&mut tagged.value),
+        _ => {}
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, c) in name.char_indices() {
+        if c.is_uppercase() && i > 0 {
+            result.push('_');
+        }
+        result.extend(c.to_lowercase());
+    }
+    result
+}
+
+#[derive(Debug)]
+pub enum DefinitionError {
+    Yaml(serde_yml::Error),
+    #[cfg(feature = "sysml")]
+    Sysml(SysmlError),
+    Config(Vec<BlockConfigError>),
+    UnknownBlock(String),
+    UnknownPort(String),
+    UnknownParameter(String),
+    IncompatiblePorts(String, String),
+    UnconnectedInput(String),
+    Port(PortError),
+    Stdio(StdioError),
+}
+
+impl std::error::Error for DefinitionError {}
+
+impl std::fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use DefinitionError::*;
+        match self {
+            Yaml(error) => {
+                write!(f, "invalid system definition: {}", error)
+            }
+            #[cfg(feature = "sysml")]
+            Sysml(error) => {
+                write!(f, "invalid system definition: {}", error)
+            }
+            Config(errors) => {
+                write!(f, "invalid block configuration")?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+            UnknownBlock(block) => {
+                write!(f, "unknown block: {}", block)
+            }
+            UnknownPort(port) => {
+                write!(f, "unknown port: {}", port)
+            }
+            UnknownParameter(parameter) => {
+                write!(f, "unknown parameter: {}", parameter)
+            }
+            IncompatiblePorts(source, target) => {
+                write!(f, "incompatible ports: {} → {}", source, target)
+            }
+            UnconnectedInput(port) => {
+                write!(f, "unconnected input port: {}", port)
+            }
+            Port(error) => {
+                write!(f, "{}", error)
+            }
+            Stdio(error) => {
+                write!(f, "{}", error)
+            }
+        }
+    }
+}
@@ -7,6 +7,8 @@ use crate::prelude::{fmt, FromStr};
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ByteSize(ubyte::ByteUnit);
 
+impl_parameter_value!(ByteSize);
+
 impl ByteSize {
     pub const fn new(value: u64) -> Self {
         Self(ubyte::ByteUnit::Byte(value))
@@ -2,19 +2,27 @@
 
 use crate::prelude::{Duration, FromStr, Range, String};
 
+#[cfg(feature = "serde")]
+use crate::prelude::format;
+
 /// The type of delay (fixed or random) to apply to message relay.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DelayType {
     #[cfg_attr(
         feature = "serde",
-        serde(deserialize_with = "duration_str::deserialize_duration")
+        serde(
+            deserialize_with = "duration_str::deserialize_duration",
+            serialize_with = "serialize_duration"
+        )
     )]
     Fixed(Duration),
 
     Random(Range<Duration>),
 }
 
+impl_parameter_value!(DelayType);
+
 impl Default for DelayType {
     fn default() -> Self {
         Self::Fixed(Duration::from_secs(1))
@@ -37,6 +45,22 @@ impl FromStr for DelayType {
     }
 }
 
+/// Serializes a duration in the format that `duration_str` parses, such as
+/// `1s` or `1500ms`.
+#[cfg(feature = "serde")]
+fn serialize_duration<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let nanos = duration.as_nanos();
+    serializer.serialize_str(&match nanos {
+        _ if nanos % 1_000_000_000 == 0 => format!("{}s", nanos / 1_000_000_000),
+        _ if nanos % 1_000_000 == 0 => format!("{}ms", nanos / 1_000_000),
+        _ if nanos % 1_000 == 0 => format!("{}us", nanos / 1_000),
+        _ => format!("{}ns", nanos),
+    })
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum InvalidDelayType {
     InvalidDuration(String),
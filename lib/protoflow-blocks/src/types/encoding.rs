@@ -12,6 +12,8 @@ pub enum Encoding {
     TextWithNewlineSuffix,
 }
 
+impl_parameter_value!(Encoding);
+
 impl FromStr for Encoding {
     type Err = String;
 
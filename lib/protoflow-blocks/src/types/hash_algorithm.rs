@@ -16,6 +16,8 @@ pub enum HashAlgorithm {
     MD5,
 }
 
+impl_parameter_value!(HashAlgorithm);
+
 impl FromStr for HashAlgorithm {
     type Err = String;
 
@@ -0,0 +1,134 @@
+// This is free and unencumbered software released into the public domain.
+
+extern crate std;
+
+use crate::{
+    prelude::{BTreeMap, String, Vec},
+    system_definition::Locations,
+    DefinitionError, System, SystemDefinition,
+};
+use serde_yml::{Mapping, Value};
+
+impl System {
+    /// Builds a system from a YAML definition.
+    ///
+    /// The definition is a mapping from block names to block configurations,
+    /// each tagged with its block type. Output ports are connected to the
+    /// input ports they name, given as `block.port`, or just as `block` for
+    /// its `input` port. Every input port must be connected.
+    ///
+    /// ```rust
+    /// # use protoflow_blocks::*;
+    /// let system = System::from_yaml(r#"
+    ///     greeting: !Const
+    ///       output: counter
+    ///       value: Hello, world!
+    ///     counter: !Count
+    ///       input: greeting.output
+    ///       count: buffer.input
+    ///     buffer: !Buffer
+    ///       input: counter.count
+    /// "#).unwrap();
+    /// ```
+    pub fn from_yaml(input: &str) -> Result<Self, DefinitionError> {
+        SystemDefinition::from_yaml(input)?.build()
+    }
+}
+
+impl SystemDefinition {
+    /// Loads a system definition from YAML. See [`System::from_yaml`].
+    pub fn from_yaml(input: &str) -> Result<Self, DefinitionError> {
+        let mapping: Mapping = serde_yml::from_str(input).map_err(DefinitionError::Yaml)?;
+        let mut configs = Vec::with_capacity(mapping.len());
+        for (name, config) in mapping {
+            let Some(name) = name.as_str().map(String::from) else {
+                return Err(DefinitionError::Yaml(serde::de::Error::custom(
+                    "expected block names as mapping keys",
+                )));
+            };
+            configs.push((name, config));
+        }
+        Self::from_values(configs, &locate_blocks(input))
+    }
+
+    /// Writes the system definition as YAML, in canonical form: every
+    /// connection is given at both ends as `block.port`, and unset fields
+    /// are omitted.
+    ///
+    /// ```rust
+    /// # use protoflow_blocks::*;
+    /// let definition = SystemDefinition::from_yaml(r#"
+    ///     greeting: !Const { output: sink, value: "Hello, world!" }
+    ///     sink: !Drop { input: greeting }
+    /// "#).unwrap();
+    /// assert_eq!(definition.to_yaml().unwrap(), r#"greeting: !Const
+    ///   output: sink.input
+    ///   value: Hello, world!
+    /// sink: !Drop
+    ///   input: greeting.output
+    /// "#);
+    /// ```
+    pub fn to_yaml(&self) -> Result<String, DefinitionError> {
+        let mapping: Mapping = self
+            .canonical_configs()?
+            .into_iter()
+            .map(|(name, config)| (name.into(), Value::Tagged(config.into())))
+            .collect();
+        serde_yml::to_string(&mapping).map_err(DefinitionError::Yaml)
+    }
+}
+
+/// Locates the blocks, and the fields of their configurations, in a YAML
+/// system definition, for reporting errors.
+fn locate_blocks(input: &str) -> Locations {
+    use serde_yml::{
+        de::{Event, Progress},
+        loader::Loader,
+    };
+
+    let mut blocks = Locations::new();
+    let Some(document) = Loader::new(Progress::Str(input))
+        .ok()
+        .and_then(|mut loader| loader.next_document())
+    else {
+        return blocks;
+    };
+
+    // For each enclosing collection, whether it's a mapping expecting a key:
+    let mut expecting_keys: Vec<Option<bool>> = Vec::new();
+    let mut block = None;
+    for (event, mark) in &document.events {
+        let location = (mark.line() as usize + 1, mark.column() as usize + 1);
+        let is_key = match expecting_keys.last_mut() {
+            Some(Some(expecting_key)) if !matches!(event, Event::MappingEnd) => {
+                *expecting_key = !*expecting_key;
+                !*expecting_key
+            }
+            _ => false,
+        };
+        match event {
+            Event::Scalar(scalar) if is_key => {
+                let key = String::from_utf8_lossy(&scalar.value).into_owned();
+                match expecting_keys.len() {
+                    1 => {
+                        blocks.insert(key.clone(), (location, BTreeMap::new()));
+                        block = Some(key);
+                    }
+                    2 => {
+                        if let Some((_, fields)) = block.as_ref().and_then(|b| blocks.get_mut(b)) {
+                            fields.insert(key, location);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::MappingStart(_) => expecting_keys.push(Some(true)),
+            Event::SequenceStart(_) => expecting_keys.push(None),
+            Event::MappingEnd | Event::SequenceEnd => {
+                expecting_keys.pop();
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
@@ -0,0 +1,56 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{BlockConfig, BlockConnections, BlockInstantiation, System, SystemBuilding};
+use protoflow_core::Port;
+
+#[test]
+fn instantiate_with_ports() {
+    let config: BlockConfig =
+        serde_yml::from_str("!Count { input: numbers, count: counter }").unwrap();
+    assert_eq!(
+        config.input_connections(),
+        [("input", Some(String::from("numbers")))]
+    );
+    assert_eq!(
+        config.output_connections(),
+        [("output", None), ("count", Some(String::from("counter")))]
+    );
+
+    System::build(|system| {
+        let instance = config.instantiate_with_ports(system).unwrap();
+        assert_eq!(instance.inputs.len(), 1);
+        assert_eq!(instance.outputs.len(), 2);
+        let count = instance.output("count").unwrap();
+        assert_eq!(count.r#type.as_deref(), Some("u64"));
+
+        let output = system.input::<u64>();
+        assert!(system.connect_by_id(count.id, output.id()).unwrap());
+    });
+}
+
+#[test]
+fn declare_typed_ports() {
+    let config: BlockConfig =
+        serde_yml::from_str("!WriteFile { path: paths, input: contents }").unwrap();
+    assert_eq!(
+        config.input_connections(),
+        [
+            ("path", Some(String::from("paths"))),
+            ("input", Some(String::from("contents")))
+        ]
+    );
+    assert!(config.output_connections().is_empty());
+
+    System::build(|system| {
+        let instance = config.instantiate_with_ports(system).unwrap();
+        let types: Vec<_> = instance
+            .inputs
+            .values()
+            .map(|port| port.r#type.as_deref())
+            .collect();
+        assert_eq!(
+            types,
+            [Some("bytes::bytes::Bytes"), Some("alloc::string::String")]
+        );
+    });
+}
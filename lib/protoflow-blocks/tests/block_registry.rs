@@ -0,0 +1,131 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{
+    build_stdio_system, prelude::Named, BlockCategory, BlockConfig, BlockConfigError,
+    BlockConnections, BlockFactory, BlockInstantiation, BlockRegistry, BlockTag, Count,
+    StdioConfig, StdioError, System, SystemBuilding,
+};
+use protoflow_core::Block;
+use serde::Deserialize;
+use std::{borrow::Cow, sync::Arc};
+
+#[derive(Debug, Deserialize)]
+struct TallyConfig {
+    input: String,
+}
+
+impl Named for TallyConfig {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed("Tally")
+    }
+}
+
+impl BlockConnections for TallyConfig {
+    fn input_connections(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![("input", Some(self.input.clone()))]
+    }
+
+    fn output_connections(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![]
+    }
+}
+
+impl BlockInstantiation for TallyConfig {
+    fn instantiate(&self, system: &mut System) -> Result<Box<dyn Block>, Box<BlockConfigError>> {
+        Ok(Box::new(Count::<u64>::new(
+            system.input(),
+            system.output(),
+            system.output(),
+        )))
+    }
+}
+
+fn tally_factory() -> BlockFactory {
+    BlockFactory {
+        deserialize_config: Some(Arc::new(|value| {
+            let config = TallyConfig::deserialize(value.value)?;
+            Ok(BlockConfig::Custom(Arc::new(config)))
+        })),
+        build_stdio_system: Some(Arc::new(|config| {
+            config.reject_any()?;
+            Ok(System::build(|_| {}))
+        })),
+        ..BlockFactory::new("Tally", |system| {
+            Box::new(Count::<u64>::new(
+                system.input(),
+                system.output(),
+                system.output(),
+            ))
+        })
+    }
+}
+
+#[test]
+fn describe_builtins() {
+    let registry = BlockRegistry::with_builtins();
+    assert!(registry.contains("Count"));
+    assert!(registry.contains("DecodeJSON"));
+    assert!(!registry.contains("Tally"));
+
+    let description = registry.get("Count").unwrap().describe();
+    assert_eq!(description.name.as_deref(), Some("Count"));
+    assert_eq!(description.inputs.len(), 1);
+    assert_eq!(description.outputs.len(), 2);
+}
+
+#[test]
+fn describe_builtin_parameters() {
+    let registry = BlockRegistry::with_builtins();
+    let description = registry.get("Delay").unwrap().describe();
+    let [delay] = description.parameters.as_slice() else {
+        panic!("expected a single parameter");
+    };
+    assert_eq!(delay.name, "delay");
+    assert_eq!(delay.r#type.as_deref(), Some("DelayType"));
+    assert_eq!(delay.default_value.as_deref(), Some("1s"));
+    assert_eq!(delay.value, None);
+
+    let description = registry.get("Random").unwrap().describe();
+    assert_eq!(
+        description.parameters[0].r#type.as_deref(),
+        Some("Option<u64>")
+    );
+    assert_eq!(description.parameters[0].default_value, None);
+}
+
+#[test]
+fn categorize_builtins() {
+    assert_eq!(BlockTag::Count.category(), BlockCategory::Core);
+    assert_eq!(BlockTag::DecodeJson.category(), BlockCategory::Io);
+    assert_eq!(BlockTag::ReadStdin.category(), BlockCategory::Sys);
+    assert_eq!(BlockTag::SplitString.category(), BlockCategory::Text);
+    assert_eq!(BlockCategory::Io.to_string(), "IO");
+}
+
+#[test]
+fn register_custom_block() {
+    BlockRegistry::global_mut().register(tally_factory());
+    assert!(BlockRegistry::global().names().any(|name| name == "Tally"));
+
+    let config: BlockConfig = serde_yml::from_str("!Tally { input: numbers }").unwrap();
+    assert_eq!(config.name(), "Tally");
+    let BlockConfig::Custom(config) = config else {
+        panic!("expected a custom block configuration");
+    };
+    assert_eq!(
+        config.input_connections(),
+        [("input", Some(String::from("numbers")))]
+    );
+
+    assert!(build_stdio_system("Tally".into(), StdioConfig::default()).is_ok());
+    assert!(matches!(
+        build_stdio_system("Untold".into(), StdioConfig::default()),
+        Err(StdioError::UnknownSystem(_))
+    ));
+    assert!(serde_yml::from_str::<BlockConfig>("!Untold { input: numbers }").is_err());
+
+    let error = BlockRegistry::global()
+        .deserialize_config(serde_yml::from_str("!Taly { input: numbers }").unwrap())
+        .unwrap_err();
+    assert_eq!(error.suggestions, ["Tally"]);
+}
@@ -0,0 +1,52 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{types::DelayType, Count, Delay};
+use protoflow_core::{BlockTester, PortState};
+use std::time::Duration;
+
+#[test]
+fn count_messages() {
+    let mut tester =
+        BlockTester::new(|s| Count::<String>::new(s.input(), s.output(), s.output())).unwrap();
+    tester.push("input", &"hello".to_string()).unwrap();
+    tester.push("input", &"world".to_string()).unwrap();
+    tester.close("input").unwrap();
+    tester.run().unwrap();
+
+    tester.assert_outputs("output", &["hello".to_string(), "world".to_string()]);
+    tester.assert_outputs("count", &[2u64]);
+    assert_eq!(tester.state("output").unwrap(), PortState::Closed);
+}
+
+#[test]
+fn count_empty_stream() {
+    let mut tester =
+        BlockTester::new(|s| Count::<u64>::new(s.input(), s.output(), s.output())).unwrap();
+    tester.run().unwrap();
+
+    tester.assert_outputs::<u64>("output", &[]);
+    tester.assert_outputs("count", &[0u64]);
+}
+
+#[test]
+fn delay_on_virtual_clock() {
+    let delay = DelayType::Fixed(Duration::from_secs(60));
+    let mut tester =
+        BlockTester::new(|s| Delay::<u64>::with_params(s.input(), s.output(), Some(delay)))
+            .unwrap();
+    for n in 1..=3u64 {
+        tester.push("input", &n).unwrap();
+    }
+    tester.run().unwrap();
+
+    tester.assert_outputs("output", &[1u64, 2, 3]);
+    assert_eq!(tester.runtime().now(), Duration::from_secs(180));
+}
+
+#[test]
+fn unknown_ports() {
+    let tester =
+        BlockTester::new(|s| Count::<u64>::new(s.input(), s.output(), s.output())).unwrap();
+    assert!(tester.push("nonesuch", &1u64).is_err());
+    assert!(tester.outputs::<u64>("nonesuch").is_err());
+}
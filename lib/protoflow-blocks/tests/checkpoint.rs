@@ -0,0 +1,214 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{CoreBlocks, System, SystemBuilding, SystemExecution};
+use protoflow_core::{
+    prelude::prost::Message, Block, BlockResult, BlockRuntime, BlockSnapshot, CheckpointStore,
+    InputPort, MemoryCheckpointStore, OutputPort, State, StateHandle,
+};
+use protoflow_derive::Block;
+use std::sync::Arc;
+
+mod common;
+use common::{Collect, Numbers};
+
+/// A sink block that collects the messages it receives on its first input
+/// until its end, then those on its second input.
+#[derive(Block, Clone)]
+struct CollectBoth {
+    #[input]
+    first: InputPort<u64>,
+
+    #[input]
+    second: InputPort<u64>,
+
+    #[state]
+    messages: State<Vec<u64>>,
+}
+
+impl Block for CollectBoth {
+    fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+        while let Some(message) = self.first.recv()? {
+            self.messages.write().push(message);
+        }
+        while let Some(message) = self.second.recv()? {
+            self.messages.write().push(message);
+        }
+        Ok(())
+    }
+}
+
+struct Pipeline {
+    system: System,
+    messages: Collect,
+    counts: Collect,
+}
+
+fn build_pipeline(store: &Arc<MemoryCheckpointStore>, trigger_at: u64) -> Pipeline {
+    let mut system = System::build(|_| {});
+    let checkpoints = system.enable_checkpoints(store.clone()).unwrap();
+    let numbers =
+        system.block(Numbers::new(system.output(), 0..5).with_trigger(trigger_at, checkpoints));
+    let counter = system.count::<u64>();
+    let messages = system.block(Collect::new(system.input()));
+    let counts = system.block(Collect::new(system.input()));
+    system.connect(&numbers.output, &counter.input);
+    system.connect(&counter.output, &messages.input);
+    system.connect(&counter.count, &counts.input);
+    Pipeline {
+        system,
+        messages,
+        counts,
+    }
+}
+
+/// Builds a system of two sources, sending `0..5` and `100..105`, with the
+/// first triggering a checkpoint just before sending `trigger_at`, and of
+/// a sink for both, whose messages are returned.
+fn build_sources(
+    store: &Arc<MemoryCheckpointStore>,
+    trigger_at: u64,
+    connect: impl FnOnce(&mut System, &OutputPort<u64>, &OutputPort<u64>) -> State<Vec<u64>>,
+) -> (System, State<Vec<u64>>) {
+    let mut system = System::build(|_| {});
+    let checkpoints = system.enable_checkpoints(store.clone()).unwrap();
+    let first =
+        system.block(Numbers::new(system.output(), 0..5).with_trigger(trigger_at, checkpoints));
+    let second = system.block(Numbers::new(system.output(), 100..105));
+    let messages = connect(&mut system, &first.output, &second.output);
+    (system, messages)
+}
+
+/// Returns the messages that the sink had collected at the checkpoint,
+/// along with those that were in flight to it.
+fn load_collected(snapshot: &BlockSnapshot) -> Vec<u64> {
+    let messages = State::<Vec<u64>>::default();
+    messages.load(&snapshot.state["messages"]).unwrap();
+    let mut messages = messages.read().clone();
+    for input in snapshot.inputs.values() {
+        for message in input.messages.iter() {
+            messages.push(u64::decode_length_delimited(message.clone()).unwrap());
+        }
+    }
+    messages
+}
+
+fn load_u64(state: &[u8]) -> u64 {
+    let value = State::<u64>::default();
+    value.load(state).unwrap();
+    let value = *value.read();
+    value
+}
+
+#[test]
+fn checkpoint_is_consistent() {
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let pipeline = build_pipeline(&store, 3);
+    pipeline.system.execute().unwrap().join().unwrap();
+
+    let checkpoint = store.latest().unwrap().expect("a completed checkpoint");
+    assert_eq!(checkpoint.id, 1);
+    assert_eq!(checkpoint.blocks.len(), 4);
+
+    // The source had sent 3 numbers when the barrier was injected, and the
+    // counter had seen exactly those 3 numbers when the barrier reached it:
+    assert_eq!(load_u64(&checkpoint.blocks[&0].state["next"]), 3);
+    assert_eq!(load_u64(&checkpoint.blocks[&1].state["counter"]), 3);
+    assert!(checkpoint.blocks[&2].state.contains_key("messages"));
+}
+
+#[test]
+fn restart_from_latest_checkpoint() {
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let pipeline = build_pipeline(&store, 3);
+    pipeline.system.execute().unwrap().join().unwrap();
+    assert_eq!(*pipeline.messages.messages.read(), [0, 1, 2, 3, 4]);
+    assert_eq!(*pipeline.counts.messages.read(), [5]);
+
+    // Restarting resumes the source from its recorded offset, with the
+    // downstream blocks restored to the state they had at the barrier:
+    let pipeline = build_pipeline(&store, u64::MAX);
+    assert_eq!(
+        pipeline.system.restore_latest(store.as_ref()).unwrap(),
+        Some(1)
+    );
+    assert_eq!(*pipeline.messages.messages.read(), [0, 1, 2]);
+    pipeline.system.execute().unwrap().join().unwrap();
+    assert_eq!(*pipeline.messages.messages.read(), [0, 1, 2, 3, 4]);
+    assert_eq!(*pipeline.counts.messages.read(), [5]);
+}
+
+#[test]
+fn align_barriers_of_inputs() {
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let connect_both = |system: &mut System, first: &OutputPort<u64>, second: &OutputPort<u64>| {
+        let sink = system.block(CollectBoth {
+            first: system.input(),
+            second: system.input(),
+            messages: State::default(),
+        });
+        system.connect(first, &sink.first);
+        system.connect(second, &sink.second);
+        sink.messages
+    };
+    let (system, messages) = build_sources(&store, 3, connect_both);
+    system.execute().unwrap().join().unwrap();
+    let expected = (0..5).chain(100..105).collect::<Vec<_>>();
+    assert_eq!(*messages.read(), expected);
+
+    // The sink was snapshotted once it had received the barrier on both its
+    // inputs, without processing the messages that followed the barrier on
+    // its first input, and with those that it received on its second input
+    // meanwhile in flight:
+    let checkpoint = store.latest().unwrap().expect("a completed checkpoint");
+    assert_eq!(load_u64(&checkpoint.blocks[&0].state["next"]), 3);
+    let second_next = load_u64(&checkpoint.blocks[&1].state["next"]);
+    let collected = load_collected(&checkpoint.blocks[&2]);
+    assert_eq!(
+        collected,
+        (0..3).chain(100..second_next).collect::<Vec<_>>()
+    );
+
+    // Restarting delivers the in-flight messages before any further ones:
+    let (system, messages) = build_sources(&store, u64::MAX, connect_both);
+    system.restore_latest(store.as_ref()).unwrap();
+    system.execute().unwrap().join().unwrap();
+    assert_eq!(*messages.read(), expected);
+}
+
+#[test]
+fn align_barriers_of_shared_inputs() {
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let connect_shared =
+        |system: &mut System, first: &OutputPort<u64>, second: &OutputPort<u64>| {
+            let sink = system.block(Collect::new(system.input()));
+            system.connect(first, &sink.input);
+            system.connect(second, &sink.input);
+            sink.messages
+        };
+    let (system, messages) = build_sources(&store, 3, connect_shared);
+    system.execute().unwrap().join().unwrap();
+    let expected = (0..5).chain(100..105).collect::<Vec<_>>();
+    let mut collected = messages.read().clone();
+    collected.sort();
+    assert_eq!(collected, expected);
+
+    // The sink was snapshotted once the barrier had arrived from both of the
+    // sources sharing its input, having processed exactly the messages that
+    // they had sent before it:
+    let checkpoint = store.latest().unwrap().expect("a completed checkpoint");
+    assert_eq!(load_u64(&checkpoint.blocks[&0].state["next"]), 3);
+    let second_next = load_u64(&checkpoint.blocks[&1].state["next"]);
+    let mut collected = load_collected(&checkpoint.blocks[&2]);
+    collected.sort();
+    assert_eq!(
+        collected,
+        (0..3).chain(100..second_next).collect::<Vec<_>>()
+    );
+
+    let (system, messages) = build_sources(&store, u64::MAX, connect_shared);
+    system.restore_latest(store.as_ref()).unwrap();
+    system.execute().unwrap().join().unwrap();
+    let mut collected = messages.read().clone();
+    collected.sort();
+    assert_eq!(collected, expected);
+}
@@ -0,0 +1,40 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{FlowBlocks, System, SystemBuilding};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn run_inline_pipeline() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink_received = received.clone();
+    System::run(|s| {
+        let numbers = s.source(1..=6u64);
+        let doubler = s.map(|n: u64| n * 2);
+        let filter = s.filter(|n: &u64| n % 4 == 0);
+        let repeater = s.flat_map(|n: u64| [n, n + 1]);
+        let sink = s.sink(move |n: u64| sink_received.lock().unwrap().push(n));
+        s.connect(&numbers.output, &doubler.input);
+        s.connect(&doubler.output, &filter.input);
+        s.connect(&filter.output, &repeater.input);
+        s.connect(&repeater.output, &sink.input);
+    })
+    .unwrap();
+    assert_eq!(*received.lock().unwrap(), [4, 5, 8, 9, 12, 13]);
+}
+
+#[test]
+fn describe_closure_blocks() {
+    let system = System::build(|s| {
+        let lines = s.source(["hello".to_string()]);
+        let lengths = s.map(|line: String| line.len() as u64);
+        s.connect(&lines.output, &lengths.input);
+    });
+    let description = system.describe();
+    let names = description
+        .blocks
+        .iter()
+        .map(|block| block.name.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [Some("Source"), Some("Map")]);
+    assert_eq!(description.connections.len(), 1);
+}
@@ -0,0 +1,95 @@
+// This is free and unencumbered software released into the public domain.
+
+//! Blocks shared by the integration tests.
+
+#![allow(dead_code)]
+
+use protoflow_core::{
+    Block, BlockResult, BlockRuntime, CheckpointCoordinator, InputPort, OutputPort, State,
+};
+use protoflow_derive::Block;
+use std::{ops::Range, sync::Arc};
+
+/// A source block that sends the numbers from `next` up to `until`, keeping
+/// its progress in its state so that it can be resumed, and optionally
+/// triggering a checkpoint just before sending a given number.
+#[derive(Block, Clone)]
+pub struct Numbers {
+    #[output]
+    pub output: OutputPort<u64>,
+
+    #[parameter]
+    pub until: u64,
+
+    #[state]
+    pub next: State<u64>,
+
+    pub trigger: Option<(u64, Arc<CheckpointCoordinator>)>,
+}
+
+impl Numbers {
+    pub fn new(output: OutputPort<u64>, range: Range<u64>) -> Self {
+        Self {
+            output,
+            until: range.end,
+            next: State::new(range.start),
+            trigger: None,
+        }
+    }
+
+    pub fn with_trigger(self, trigger_at: u64, checkpoints: Arc<CheckpointCoordinator>) -> Self {
+        Self {
+            trigger: Some((trigger_at, checkpoints)),
+            ..self
+        }
+    }
+}
+
+impl Block for Numbers {
+    fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
+        runtime.wait_for(&self.output)?;
+        loop {
+            let next = *self.next.read();
+            if next == self.until {
+                break;
+            }
+            if let Some((trigger_at, checkpoints)) = &self.trigger {
+                if next == *trigger_at {
+                    checkpoints.trigger()?;
+                }
+            }
+            self.output.send(&next)?;
+            *self.next.write() = next + 1;
+        }
+        Ok(())
+    }
+}
+
+/// A sink block that collects the messages it receives in its state, which
+/// is shared with its clones.
+#[derive(Block, Clone)]
+pub struct Collect {
+    #[input]
+    pub input: InputPort<u64>,
+
+    #[state]
+    pub messages: State<Vec<u64>>,
+}
+
+impl Collect {
+    pub fn new(input: InputPort<u64>) -> Self {
+        Self {
+            input,
+            messages: State::default(),
+        }
+    }
+}
+
+impl Block for Collect {
+    fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+        while let Some(message) = self.input.recv()? {
+            self.messages.write().push(message);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{
+    types::{DelayType, Encoding},
+    Const, Count, Decode, Delay, Encode,
+};
+use protoflow_core::{
+    runtimes::CooperativeRuntime, transports::StaticBufferTransport, Block, BlockResult,
+    BlockRuntime, Clock, ConnectionOptions, OutputPort, State, StdClock, System,
+};
+use protoflow_derive::Block;
+use std::{sync::Mutex, time::Duration};
+
+mod common;
+use common::{Collect, Numbers};
+
+type Transport = StaticBufferTransport<4>;
+
+/// A source block that sends the numbers from 1 up to `until`, yielding
+/// after each one, so that the blocks it sends to are resumed with their
+/// inputs drained.
+#[derive(Block, Clone)]
+struct Trickle {
+    #[output]
+    output: OutputPort<u64>,
+
+    #[parameter]
+    until: u64,
+
+    #[state]
+    sent: State<u64>,
+}
+
+impl Block for Trickle {
+    fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
+        runtime.wait_for(&self.output)?;
+        if *self.sent.read() < self.until {
+            let next = *self.sent.read() + 1;
+            self.output.send(&next)?;
+            *self.sent.write() = next;
+            return runtime.yield_now();
+        }
+        Ok(())
+    }
+}
+
+/// A clock that advances only when slept on.
+#[derive(Default)]
+struct VirtualClock(Mutex<Duration>);
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        *self.0.lock().unwrap()
+    }
+
+    fn sleep_until(&self, instant: Duration) {
+        let mut now = self.0.lock().unwrap();
+        *now = (*now).max(instant);
+    }
+}
+
+#[test]
+fn run_with_backpressure() {
+    let mut system = System::with_transport(Transport::new());
+    let numbers = system.block(Numbers::new(system.output(), 1..101));
+    let counter = system.block(Count::<u64>::new(
+        system.input(),
+        system.output(),
+        system.output(),
+    ));
+    let messages = system.block(Collect::new(system.input()));
+    let counts = system.block(Collect::new(system.input()));
+    system.connect(&numbers.output, &counter.input);
+    system.connect(&counter.output, &messages.input);
+    system.connect(&counter.count, &counts.input);
+
+    let runtime = CooperativeRuntime::new(StdClock::new());
+    let mut system = runtime.spawn(system).unwrap();
+    let mut polls = 1;
+    while system.poll().unwrap() {
+        polls += 1;
+    }
+
+    // The buffers hold only 4 messages, so the blocks had to take turns:
+    assert!(polls >= 100 / 4);
+    assert!(messages.messages.read().iter().copied().eq(1..=100));
+    assert_eq!(*counts.messages.read(), [100]);
+}
+
+#[test]
+fn resume_blocked_send() {
+    let mut system = System::with_transport(Transport::new());
+    let numbers = system.block(Numbers::new(system.output(), 1..21));
+    let counter = system.block(Count::<u64>::new(
+        system.input(),
+        system.output(),
+        system.output(),
+    ));
+    let messages = system.block(Collect::new(system.input()));
+    let counts = system.block(Collect::new(system.input()));
+    system.connect(&numbers.output, &counter.input);
+    system.connect_with(
+        &counter.output,
+        &messages.input,
+        ConnectionOptions::with_capacity(1),
+    );
+    system.connect(&counter.count, &counts.input);
+
+    // The counter receives more messages than it can forward at once, so its
+    // sends are kept pending rather than making it count or lose messages:
+    let runtime = CooperativeRuntime::new(StdClock::new());
+    runtime.run(system).unwrap();
+    assert!(messages.messages.read().iter().copied().eq(1..=20));
+    assert_eq!(*counts.messages.read(), [20]);
+}
+
+#[test]
+fn resume_blocks_waiting_for_inputs() {
+    let encoding = Some(Encoding::TextWithNewlineSuffix);
+    let mut system = System::with_transport(Transport::new());
+    let numbers = system.block(Trickle {
+        output: system.output(),
+        until: 20,
+        sent: State::default(),
+    });
+    let encode = system.block(Encode::<u64>::with_params(
+        system.input(),
+        system.output(),
+        encoding,
+    ));
+    let decode = system.block(Decode::<u64>::with_params(
+        system.input(),
+        system.output(),
+        encoding,
+    ));
+    let messages = system.block(Collect::new(system.input()));
+    system.connect(&numbers.output, &encode.input);
+    system.connect(&encode.output, &decode.input);
+    system.connect(&decode.output, &messages.input);
+
+    // The encoder waits for its input again each time it is resumed, even
+    // once all the numbers were sent and its input has disconnected:
+    let runtime = CooperativeRuntime::new(StdClock::new());
+    runtime.run(system).unwrap();
+    assert!(messages.messages.read().iter().copied().eq(1..=20));
+}
+
+#[test]
+fn sleep_on_clock() {
+    let delay = DelayType::Fixed(Duration::from_secs(60));
+    let mut system = System::with_transport(Transport::new());
+    let source = system.block(Const::with_params(system.output(), 42u64));
+    let delay = system.block(Delay::with_params(
+        system.input(),
+        system.output(),
+        Some(delay),
+    ));
+    let sink = system.block(Collect::new(system.input()));
+    system.connect(&source.output, &delay.input);
+    system.connect(&delay.output, &sink.input);
+
+    let runtime = CooperativeRuntime::new(VirtualClock::default());
+    runtime.run(system).unwrap();
+    assert_eq!(*sink.messages.read(), [42]);
+    assert_eq!(runtime.clock().now(), Duration::from_secs(60));
+}
+
+#[test]
+fn detect_deadlock() {
+    let mut system = System::with_transport(Transport::new());
+    let a = system.block(Count::<u64>::new(
+        system.input(),
+        system.output(),
+        system.output(),
+    ));
+    let b = system.block(Count::<u64>::new(
+        system.input(),
+        system.output(),
+        system.output(),
+    ));
+    system.connect(&a.output, &b.input);
+    system.connect(&b.output, &a.input);
+
+    let runtime = CooperativeRuntime::new(StdClock::new());
+    let error = runtime.run(system).unwrap_err();
+    assert!(error.to_string().contains("deadlock"));
+}
@@ -0,0 +1,99 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{CoreBlocks, System, SystemBuilding};
+use protoflow_core::{
+    prelude::Duration, ConnectionOptions, PortDirection, PortID, PortState, SystemExecution,
+};
+
+#[test]
+fn describe_system() {
+    let system = System::build(|s| {
+        let constant = s.const_string("Hello, world!");
+        let counter = s.count::<String>();
+        let output = s.input::<u64>();
+        s.connect(&constant.output, &counter.input);
+        s.connect_with(
+            &counter.count,
+            &output,
+            ConnectionOptions::with_capacity(16),
+        );
+    });
+
+    let description = system.describe();
+
+    let block_names: Vec<_> = description
+        .blocks
+        .iter()
+        .map(|block| block.name.as_deref())
+        .collect();
+    assert_eq!(block_names, vec![Some("Const"), Some("Count")]);
+
+    let counter = &description.blocks[1];
+    assert_eq!(counter.inputs.len(), 1);
+    assert_eq!(counter.outputs.len(), 2);
+    assert!(counter
+        .ports()
+        .all(|port| port.state == PortState::Open && port.r#type.is_some()));
+
+    assert!(description.outputs.is_empty());
+    assert_eq!(description.inputs.len(), 1);
+    let output = &description.inputs[0];
+    assert_eq!(output.direction, PortDirection::Input);
+    assert_eq!(output.r#type.as_deref(), Some("u64"));
+    assert!(description.block_of(output.id).is_none());
+
+    assert_eq!(description.connections.len(), 2);
+    let connection = description
+        .connections
+        .iter()
+        .find(|connection| PortID::from(connection.target) == output.id)
+        .unwrap();
+    assert_eq!(connection.options.capacity, Some(16));
+    let source = description.block_of(connection.source.into()).unwrap();
+    assert_eq!(source.name.as_deref(), Some("Count"));
+}
+
+#[test]
+fn describe_prepared_system() {
+    let system = System::build(|s| {
+        let constant = s.const_string("Hello, world!");
+        let counter = s.count::<String>();
+        let output = s.input::<u64>();
+        s.connect(&constant.output, &counter.input);
+        s.connect(&counter.count, &output);
+    });
+    system.prepare().unwrap();
+
+    // The ports are described by the IDs that the transport gave them:
+    let description = system.describe();
+    let output = &description.inputs[0];
+    assert_eq!(output.r#type.as_deref(), Some("u64"));
+    assert!(description.block_of(output.id).is_none());
+    for connection in &description.connections {
+        let source = description.port(connection.source.into()).unwrap();
+        let target = description.port(connection.target.into()).unwrap();
+        assert_eq!(source.r#type, target.r#type);
+    }
+}
+
+#[test]
+fn describe_parameter_values() {
+    let system = System::build(|s| {
+        let constant = s.const_string("Hello, world!");
+        let delay = s.delay_by_fixed::<String>(Duration::from_millis(1500));
+        let random = s.random_seeded::<u64>(Some(42));
+        let unseeded = s.random::<u64>();
+        let drops = (s.drop::<String>(), s.drop::<u64>(), s.drop::<u64>());
+        s.connect(&constant.output, &delay.input);
+        s.connect(&delay.output, &drops.0.input);
+        s.connect(&random.output, &drops.1.input);
+        s.connect(&unseeded.output, &drops.2.input);
+    });
+
+    let description = system.describe();
+    let value = |block: usize| description.blocks[block].parameters[0].value.as_deref();
+    assert_eq!(value(0), Some("\"Hello, world!\""));
+    assert_eq!(value(1), Some("!Fixed '1500ms'"));
+    assert_eq!(value(2), Some("42"));
+    assert_eq!(value(3), None);
+}
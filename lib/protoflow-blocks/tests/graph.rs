@@ -0,0 +1,58 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{CoreBlocks, System, SystemBuilding};
+use protoflow_core::{ConnectionOptions, GraphFormat};
+
+fn build_system() -> System {
+    System::build(|s| {
+        let constant = s.const_string("Hello, world!");
+        let counter = s.count::<String>();
+        let output = s.input::<u64>();
+        s.connect(&constant.output, &counter.input);
+        s.connect_with(
+            &counter.count,
+            &output,
+            ConnectionOptions::with_capacity(16),
+        );
+    })
+}
+
+#[test]
+fn render_mermaid() {
+    let graph = build_system().describe().to_graph(GraphFormat::Mermaid);
+    assert_eq!(
+        graph,
+        r#"flowchart LR
+    block0["Const"]
+    block1["Count"]
+    input1(["input: u64"])
+    block0 -->|"output → input<br>String"| block1
+    block1 -->|"count → input1<br>u64 (capacity=16)"| input1
+"#
+    );
+}
+
+#[test]
+fn render_dot() {
+    let graph = build_system().describe().to_graph(GraphFormat::Dot);
+    assert_eq!(
+        graph,
+        r#"digraph system {
+    rankdir=LR;
+    node [shape=record];
+    block0 [label="{Const|{<output0> output}}"];
+    block1 [label="{{<input0> input}|Count|{<output1> output|<output2> count}}"];
+    input1 [shape=ellipse, label="input: u64"];
+    block0:output0 -> block1:input0 [label="String"];
+    block1:output2 -> input1 [label="u64 (capacity=16)"];
+}
+"#
+    );
+}
+
+#[test]
+fn parse_graph_format() {
+    assert_eq!("mermaid".parse(), Ok(GraphFormat::Mermaid));
+    assert_eq!("dot".parse(), Ok(GraphFormat::Dot));
+    assert_eq!("svg".parse::<GraphFormat>(), Err(String::from("svg")));
+}
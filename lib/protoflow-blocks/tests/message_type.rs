@@ -0,0 +1,123 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{
+    build_stdio_system, prelude::prost_types, BlockConfig, BlockInstantiation, CoreBlockConfig,
+    DefinitionError, MessageType, MessageTypeRegistry, StdioConfig, StdioError, System,
+};
+
+#[test]
+fn instantiate_with_message_type() {
+    let config: BlockConfig = serde_yml::from_str("!Random { output: sink, type: f64 }").unwrap();
+    System::build(|system| {
+        let instance = config.instantiate_with_ports(system).unwrap();
+        let output = instance.output("output").unwrap();
+        assert_eq!(output.r#type.as_deref(), Some("f64"));
+    });
+
+    let Err(DefinitionError::Config(errors)) =
+        System::from_yaml("counter: !Count { input: x, count: y, type: f46 }")
+    else {
+        panic!("expected a block configuration error");
+    };
+    assert_eq!(errors[0].field.as_deref(), Some("type"));
+    assert_eq!(errors[0].suggestions, ["f64"]);
+}
+
+#[test]
+fn register_message_type() {
+    assert!(!MessageTypeRegistry::global().contains("Timestamps"));
+    MessageTypeRegistry::global_mut()
+        .register(MessageType::new::<prost_types::Timestamp>("Timestamps"));
+    let config: BlockConfig =
+        serde_yml::from_str("!Buffer { input: clock, type: Timestamps }").unwrap();
+    System::build(|system| {
+        let instance = config.instantiate_with_ports(system).unwrap();
+        let input = instance.input("input").unwrap();
+        assert_eq!(
+            input.r#type.as_deref(),
+            Some("prost_types::protobuf::Timestamp")
+        );
+    });
+}
+
+#[test]
+fn execute_with_message_type() {
+    let config = |params: &[(&str, &str)]| {
+        StdioConfig::new(
+            Default::default(),
+            params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        )
+    };
+    assert!(build_stdio_system("Count".into(), config(&[("type", "u64")])).is_ok());
+    assert!(build_stdio_system("Random".into(), config(&[("type", "f64")])).is_ok());
+    assert!(matches!(
+        build_stdio_system("Random".into(), config(&[("type", "Struct")])),
+        Err(StdioError::InvalidParameter("type"))
+    ));
+    assert!(matches!(
+        build_stdio_system("Count".into(), config(&[("type", "u46")])),
+        Err(StdioError::InvalidParameter("type"))
+    ));
+}
+
+#[test]
+fn format_and_parse_messages() {
+    let registry = MessageTypeRegistry::global();
+    let u64_type = registry.get("u64").unwrap();
+    let message = u64_type.parse_message("42").unwrap();
+    assert_eq!(u64_type.format_message(&message).unwrap(), "42");
+    assert_eq!(u64_type.parse_message("forty-two"), None);
+
+    let timestamp_type = registry.get("Timestamp").unwrap();
+    assert_eq!(timestamp_type.parse_message("0"), None);
+    assert_eq!(
+        timestamp_type.format_message(&[2, 8, 1]).unwrap(),
+        "Timestamp { seconds: 1, nanos: 0 }"
+    );
+    assert!(timestamp_type.format_message(&[1, 8]).is_err());
+}
+
+#[test]
+fn encode_with_message_type() {
+    let system = System::from_yaml(
+        r#"
+        numbers: !Random { output: counter, type: u64 }
+        counter: !Count { input: numbers, count: encoder, type: u64 }
+        encoder: !Encode { input: counter.count, output: stdout, type: u64 }
+        stdout: !WriteStdout { input: encoder }
+        "#,
+    )
+    .unwrap();
+    let encoder = &system.describe().blocks[2];
+    assert_eq!(encoder.inputs[0].r#type.as_deref(), Some("u64"));
+
+    let config_error = |yaml: &str| {
+        let Err(DefinitionError::Config(errors)) = System::from_yaml(yaml) else {
+            panic!("expected a block configuration error");
+        };
+        errors[0].clone()
+    };
+    let error = config_error("encoder: !Encode { input: x, output: y, type: Struct }");
+    assert_eq!(error.field.as_deref(), Some("type"));
+    assert!(!error.allowed.contains(&"Struct".to_string()));
+    let error = config_error("env: !ReadEnv { name: x, output: y, type: u46 }");
+    assert_eq!(error.suggestions, ["u64"]);
+    let error = config_error("greeting: !Const { output: x, value: Hi, type: u64 }");
+    assert_eq!(error.field.as_deref(), Some("type"));
+}
+
+#[test]
+fn instantiate_unknown_message_type() {
+    let config = BlockConfig::Core(CoreBlockConfig::Drop {
+        input: "x".into(),
+        r#type: Some("f46".into()),
+    });
+    System::build(|system| {
+        let error = config.instantiate(system).err().unwrap();
+        assert_eq!(error.field.as_deref(), Some("type"));
+        assert_eq!(error.suggestions, ["f64"]);
+    });
+}
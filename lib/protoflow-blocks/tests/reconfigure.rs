@@ -0,0 +1,138 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{Buffer, CoreBlocks, System, SystemBuilding};
+use protoflow_core::{Block, BlockResult, BlockRuntime, OutputPort, PortError};
+use protoflow_derive::Block;
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+mod common;
+use common::Collect;
+
+/// A source block that forwards numbers from a channel, until the channel
+/// is closed.
+#[derive(Block, Clone)]
+struct Feed {
+    #[output]
+    output: OutputPort<u64>,
+
+    receiver: Arc<Mutex<mpsc::Receiver<u64>>>,
+}
+
+impl Feed {
+    fn new(output: OutputPort<u64>) -> (Self, mpsc::Sender<u64>) {
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        (Self { output, receiver }, sender)
+    }
+}
+
+impl Block for Feed {
+    fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
+        runtime.wait_for(&self.output)?;
+        while let Ok(number) = self.receiver.lock().unwrap().recv() {
+            self.output.send(&number)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn reconfigure_running_system() {
+    let mut feed = None;
+    let mut sink = None;
+    let mut counter_input = None;
+    let mut handle = System::start(|s| {
+        let (source, sender) = Feed::new(s.output());
+        let source = s.block(source);
+        let counter = s.count::<u64>();
+        let messages = s.block(Collect::new(s.input()));
+        s.connect(&source.output, &counter.input);
+        s.connect(&counter.output, &messages.input);
+        feed = Some(sender);
+        sink = Some(messages);
+        counter_input = Some(counter.input.clone());
+    })
+    .unwrap();
+    let (feed, sink, counter_input) = (feed.unwrap(), sink.unwrap(), counter_input.unwrap());
+
+    feed.send(1).unwrap();
+    while sink.messages.read().is_empty() {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    // Pick up a new source while the system is running:
+    let (source, new_feed) = Feed::new(handle.output().unwrap());
+    handle.connect(&source.output, &counter_input).unwrap();
+    handle.add_block(source).unwrap();
+    new_feed.send(2).unwrap();
+
+    // Retire the original source; the counter keeps receiving from the new one:
+    drop(feed);
+    handle.remove_block(0).unwrap();
+    new_feed.send(3).unwrap();
+    while sink.messages.read().len() < 3 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    // Removing the counter ends the stream into the sink:
+    handle.remove_block(1).unwrap();
+    drop(new_feed);
+    handle.join().unwrap();
+
+    let mut messages: Vec<u64> = sink.messages.read().iter().copied().collect();
+    messages.sort();
+    assert_eq!(messages, [1, 2, 3]);
+}
+
+#[test]
+fn reconnect_after_removing_downstream() {
+    let mut feed = None;
+    let mut sink = None;
+    let mut feed_output = None;
+    let mut handle = System::start(|s| {
+        let (source, sender) = Feed::new(s.output());
+        let source = s.block(source);
+        let messages = s.block(Collect::new(s.input()));
+        s.connect(&source.output, &messages.input);
+        feed = Some(sender);
+        sink = Some(messages);
+        feed_output = Some(source.output.clone());
+    })
+    .unwrap();
+    let (feed, sink, feed_output) = (feed.unwrap(), sink.unwrap(), feed_output.unwrap());
+
+    feed.send(1).unwrap();
+    while sink.messages.read().is_empty() {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    // Removing the sink leaves the source running, but disconnected:
+    handle.remove_block(1).unwrap();
+    assert!(handle.is_alive());
+    assert!(matches!(feed_output.send(&2), Err(PortError::Disconnected)));
+
+    // Reconnect the source to a new sink:
+    let new_sink = Collect::new(handle.input().unwrap());
+    handle.connect(&feed_output, &new_sink.input).unwrap();
+    handle.add_block(new_sink.clone()).unwrap();
+    feed.send(3).unwrap();
+    drop(feed);
+    handle.join().unwrap();
+
+    assert_eq!(*sink.messages.read(), [1]);
+    assert_eq!(*new_sink.messages.read(), [3]);
+}
+
+#[test]
+fn remove_unknown_block() {
+    let mut handle = System::start(|s| {
+        let _ = s.block(Buffer::<u64>::with_system(s));
+    })
+    .unwrap();
+    assert!(handle.remove_block(42).is_err());
+    handle.join().unwrap();
+}
@@ -0,0 +1,105 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{
+    build_stdio_system, DefinitionError, StdioConfig, StdioEndpoint, StdioError, SystemDefinition,
+    SystemExecution,
+};
+use protoflow_core::ConnectionOptions;
+use std::path::PathBuf;
+
+#[test]
+fn parse_endpoints() {
+    use StdioEndpoint::*;
+    assert_eq!("-".parse(), Ok(Stdio));
+    assert_eq!("input.txt".parse(), Ok(File(PathBuf::from("input.txt"))));
+    assert_eq!("file:///tmp/x".parse(), Ok(File(PathBuf::from("/tmp/x"))));
+    assert_eq!(
+        "tcp://localhost:7070".parse(),
+        Ok(Tcp(String::from("localhost:7070")))
+    );
+    #[cfg(unix)]
+    assert_eq!(
+        "unix:///tmp/protoflow.sock".parse(),
+        Ok(Unix(PathBuf::from("/tmp/protoflow.sock")))
+    );
+    assert!("ftp://example.org".parse::<StdioEndpoint>().is_err());
+    assert_eq!(
+        Tcp(String::from("localhost:7070")).to_string(),
+        "tcp://localhost:7070"
+    );
+}
+
+#[test]
+fn redirect_stdio() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.txt");
+    let output = dir.path().join("output.txt");
+    std::fs::write(&input, "Hello, world!\n").unwrap();
+
+    let config = StdioConfig::default()
+        .with_input(StdioEndpoint::File(input))
+        .with_output(StdioEndpoint::File(output.clone()));
+    let system = build_stdio_system("ReadStdin".into(), config).unwrap();
+    system.execute().unwrap().join().unwrap();
+    assert_eq!(std::fs::read_to_string(output).unwrap(), "Hello, world!\n");
+
+    let config =
+        StdioConfig::default().with_output(StdioEndpoint::File(dir.path().join("output.yaml")));
+    assert!(matches!(
+        config.reject_redirection(),
+        Err(StdioError::UnsupportedRedirection)
+    ));
+}
+
+#[test]
+fn reject_unused_redirection() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = StdioConfig::new(Default::default(), [("value".into(), "x".into())].into())
+        .with_input(StdioEndpoint::File(dir.path().join("input.txt")));
+    assert!(matches!(
+        build_stdio_system("Const".into(), config),
+        Err(StdioError::UnsupportedRedirection)
+    ));
+
+    let definition = SystemDefinition::from_yaml(
+        "greeting: !Const { output: sink, value: x }\nsink: !Drop { input: greeting }\n",
+    )
+    .unwrap();
+    let config =
+        StdioConfig::default().with_output(StdioEndpoint::File(dir.path().join("output.txt")));
+    assert!(matches!(
+        definition.build_with_stdio(ConnectionOptions::default(), &config),
+        Err(DefinitionError::Stdio(StdioError::UnsupportedRedirection))
+    ));
+}
+
+#[test]
+fn open_endpoints_before_execution() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.txt");
+    let output = dir.path().join("output.txt");
+
+    let config = StdioConfig::default()
+        .with_input(StdioEndpoint::File(input.clone()))
+        .with_output(StdioEndpoint::File(output.clone()));
+    assert!(matches!(
+        config.open(),
+        Err(StdioError::UnavailableInput(StdioEndpoint::File(path), _)) if path == input
+    ));
+    let config = config.with_output(StdioEndpoint::File(
+        dir.path().join("missing").join("output.txt"),
+    ));
+    std::fs::write(&input, "Hello, world!\n").unwrap();
+    assert!(matches!(
+        config.open(),
+        Err(StdioError::UnavailableOutput(..))
+    ));
+
+    // The opened streams are shared with the system built from a clone:
+    let config = config.with_output(StdioEndpoint::File(output.clone()));
+    let system = build_stdio_system("ReadStdin".into(), config.clone()).unwrap();
+    config.open().unwrap();
+    std::fs::remove_file(&input).unwrap();
+    system.execute().unwrap().join().unwrap();
+    assert_eq!(std::fs::read_to_string(output).unwrap(), "Hello, world!\n");
+}
@@ -0,0 +1,248 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{
+    prelude::{Bytes, Duration, String},
+    CoreBlocks, DefinitionError, DelayType, IoBlocks, StdioConfig, StdioEndpoint, System,
+    SystemBuilding, SystemDefinition, SystemExecution,
+};
+use protoflow_core::ConnectionOptions;
+
+const PIPELINE: &str = r#"
+random: !Random { output: delay, seed: 42, type: f64 }
+delay: !Delay { input: random, output: counter, delay: !Fixed 1500ms, type: f64 }
+counter: !Count { input: delay, count: sink 2, type: f64 }
+jitter: !Delay
+  input: counter.output
+  output: sink
+  delay: !Random
+    start: { secs: 0, nanos: 0 }
+    end: { secs: 1, nanos: 500 }
+  type: f64
+sink: !Drop { input: jitter, type: f64 }
+'sink 2': !Drop { input: counter.count, type: u64 }
+"#;
+
+#[test]
+fn round_trip_yaml() {
+    let definition = SystemDefinition::from_yaml(PIPELINE).unwrap();
+    let yaml = definition.to_yaml().unwrap();
+    assert!(yaml.starts_with("random: !Random\n  output: delay.input\n  seed: 42\n  type: f64\n"));
+    assert!(yaml.contains("  delay: !Fixed '1500ms'\n"));
+
+    let reloaded = SystemDefinition::from_yaml(&yaml).unwrap();
+    assert_eq!(reloaded.to_yaml().unwrap(), yaml);
+    assert_eq!(reloaded.connections(), definition.connections());
+    assert!(reloaded.build().is_ok());
+}
+
+#[test]
+fn define_rust_systems() {
+    let system = System::build(|s| {
+        let random = s.random::<u64>();
+        let counter = s.count::<u64>();
+        let sink = s.drop::<u64>();
+        let counts = s.drop::<u64>();
+        s.connect(&random.output, &counter.input);
+        s.connect(&counter.output, &sink.input);
+        s.connect(&counter.count, &counts.input);
+    });
+    let definition = SystemDefinition::from_system(&system).unwrap();
+    let block_names: Vec<_> = definition
+        .blocks
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(block_names, ["random", "count", "drop", "drop_2"]);
+    assert_eq!(
+        definition.to_yaml().unwrap(),
+        r#"random: !Random
+  output: count.input
+  type: u64
+count: !Count
+  input: random.output
+  output: drop.input
+  count: drop_2.input
+  type: u64
+drop: !Drop
+  input: count.output
+  type: u64
+drop_2: !Drop
+  input: count.count
+  type: u64
+"#
+    );
+    assert_eq!(
+        definition.build().unwrap().describe().connections.len(),
+        system.describe().connections.len()
+    );
+
+    let system = System::build(|s| {
+        let greeting = s.const_string("Hello, world!");
+        let random = s.random_seeded::<u64>(Some(42));
+        let delay = s.delay_by::<u64>(DelayType::Fixed(Duration::from_millis(1500)));
+        let strings = s.drop::<String>();
+        let sink = s.drop::<u64>();
+        s.connect(&greeting.output, &strings.input);
+        s.connect(&random.output, &delay.input);
+        s.connect(&delay.output, &sink.input);
+    });
+    let definition = SystemDefinition::from_system(&system).unwrap();
+    let yaml = definition.to_yaml().unwrap();
+    assert!(yaml.starts_with("const: !Const\n  output: drop.input\n  value: Hello, world!\n"));
+    assert!(yaml.contains("random: !Random\n  output: delay.input\n  seed: 42\n  type: u64\n"));
+    assert!(yaml.contains("  delay: !Fixed '1500ms'\n"));
+    assert!(definition.build().is_ok());
+}
+
+#[test]
+fn define_generic_io_blocks() {
+    let system = System::build(|s| {
+        let random = s.random::<u64>();
+        let counter = s.count::<u64>();
+        let encoder = s.encode::<u64>();
+        let sink = s.drop::<Bytes>();
+        s.connect(&random.output, &counter.input);
+        s.connect(&counter.count, &encoder.input);
+        s.connect(&encoder.output, &sink.input);
+    });
+    let definition = SystemDefinition::from_system(&system).unwrap();
+    let yaml = definition.to_yaml().unwrap();
+    assert!(yaml.contains(
+        "encode: !Encode\n  input: count.count\n  output: drop.input\n  encoding: ProtobufWithLengthPrefix\n  type: u64\n"
+    ));
+    assert!(definition.build().is_ok());
+}
+
+#[test]
+fn reject_invalid_sysml() {
+    let Err(DefinitionError::Sysml(error)) = System::from_sysml(
+        r#"
+        package Example {
+            block greeting : Const {
+                attribute value "Hello";
+            }
+        }
+        "#,
+    ) else {
+        panic!("expected a syntax error");
+    };
+    assert_eq!(error.location, Some((4, 33)));
+    assert_eq!(error.to_string(), "4:33: expected `;`, found \"Hello\"");
+
+    let Err(DefinitionError::Config(errors)) = System::from_sysml(
+        r#"
+        package Example {
+            block random : Protoflow::Random {
+                attribute seed = "abc";
+            }
+            block sink : Drop;
+            connect random.output to sink.input;
+        }
+        "#,
+    ) else {
+        panic!("expected a block configuration error");
+    };
+    assert_eq!(errors[0].block.as_deref(), Some("random"));
+    assert_eq!(errors[0].field.as_deref(), Some("seed"));
+    assert_eq!(errors[0].location, Some((4, 27)));
+
+    let result = System::from_sysml("package Example { connect a.output to b.input; }");
+    assert!(matches!(result, Err(DefinitionError::UnknownBlock(block)) if block == "a"));
+}
+
+#[test]
+fn override_parameters() {
+    let mut definition = SystemDefinition::from_sysml(
+        r#"
+        package Example {
+            block random : Random {
+                attribute seed = 42;
+            }
+            block greeting : Const {
+                attribute value = "Hello";
+            }
+            block sink : Drop;
+            block strings : Drop;
+            connect random.output to sink.input;
+            connect greeting.output to strings.input;
+        }
+        "#,
+    )
+    .unwrap();
+    definition.set_parameter("random.seed", "7").unwrap();
+    definition.set_parameter("greeting.value", "123").unwrap();
+    definition
+        .set_parameter("greeting.value", "Hello, world!")
+        .unwrap();
+    let yaml = definition.to_yaml().unwrap();
+    assert!(yaml.contains("  seed: 7\n"));
+    assert!(yaml.contains("  value: Hello, world!\n"));
+    assert!(definition.build().is_ok());
+
+    assert!(matches!(
+        definition.set_parameter("random.sed", "7"),
+        Err(DefinitionError::UnknownParameter(parameter)) if parameter == "random.sed"
+    ));
+    assert!(matches!(
+        definition.set_parameter("random.seed", "abc"),
+        Err(DefinitionError::Config(errors)) if errors[0].block.as_deref() == Some("random")
+    ));
+    assert!(matches!(
+        definition.set_parameter("nope.seed", "7"),
+        Err(DefinitionError::UnknownBlock(block)) if block == "nope"
+    ));
+}
+
+#[test]
+fn redirect_stdio() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.txt");
+    let output = dir.path().join("output.txt");
+    std::fs::write(&input, "Hello, world!\n").unwrap();
+
+    let definition = SystemDefinition::from_yaml(
+        r#"
+        stdin: !ReadStdin { output: stdout }
+        stdout: !WriteStdout { input: stdin }
+        "#,
+    )
+    .unwrap();
+    let config = StdioConfig::default()
+        .with_input(StdioEndpoint::File(input))
+        .with_output(StdioEndpoint::File(output.clone()));
+    let system = definition
+        .build_with_stdio(ConnectionOptions::default(), &config)
+        .unwrap();
+    system.execute().unwrap().join().unwrap();
+    assert_eq!(std::fs::read_to_string(output).unwrap(), "Hello, world!\n");
+}
+
+#[test]
+fn execute_sysml_definitions() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("output.txt");
+    let mut definition = SystemDefinition::from_sysml(
+        r#"
+        package Greeting {
+            import Protoflow::*;
+            block greeting : Const {
+                attribute value = "Hello, world!";
+            }
+            block encoder : Encode {
+                attribute encoding = "TextWithNewlineSuffix";
+            }
+            block stdout : WriteStdout;
+            connect greeting.output to encoder.input;
+            connect encoder.output to stdout.input;
+        }
+        "#,
+    )
+    .unwrap();
+    definition.set_parameter("greeting.value", "Hi").unwrap();
+    let config = StdioConfig::default().with_output(StdioEndpoint::File(output.clone()));
+    let system = definition
+        .build_with_stdio(ConnectionOptions::default(), &config)
+        .unwrap();
+    system.execute().unwrap().join().unwrap();
+    assert_eq!(std::fs::read_to_string(output).unwrap(), "Hi\n");
+}
@@ -0,0 +1,120 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{DefinitionError, System};
+
+#[test]
+fn build_system_from_yaml() {
+    let system = System::from_yaml(
+        r#"
+        greeting: !Const
+          output: counter
+          value: Hello, world!
+        counter: !Count
+          input: greeting.output
+          output: sink
+          count: buffer.input
+        buffer: !Buffer
+          input: counter.count
+        sink: !Drop
+          input: counter.output
+        "#,
+    )
+    .unwrap();
+
+    let description = system.describe();
+    let block_names: Vec<_> = description
+        .blocks
+        .iter()
+        .map(|block| block.name.as_deref())
+        .collect();
+    assert_eq!(
+        block_names,
+        vec![Some("Const"), Some("Count"), Some("Buffer"), Some("Drop")]
+    );
+    assert_eq!(description.connections.len(), 3);
+}
+
+#[test]
+fn connect_inputs_to_outputs() {
+    let system = System::from_yaml(
+        r#"
+        greeting: !Const { output: encoder, value: "Hello, world!" }
+        encoder: !Encode { input: greeting, output: stdout }
+        stdout: !WriteStdout { input: encoder }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(system.describe().connections.len(), 2);
+}
+
+#[test]
+fn reject_invalid_systems() {
+    let result = System::from_yaml("greeting: !Const { output: nowhere, value: hi }");
+    assert!(matches!(result, Err(DefinitionError::UnknownBlock(block)) if block == "nowhere"));
+
+    let result = System::from_yaml(
+        r#"
+        greeting: !Const { output: sink.output, value: hi }
+        sink: !Drop { input: greeting.output }
+        "#,
+    );
+    assert!(matches!(result, Err(DefinitionError::UnknownPort(port)) if port == "sink.output"));
+
+    let result = System::from_yaml("sink: !Drop { input: nowhere }");
+    assert!(matches!(result, Err(DefinitionError::UnknownBlock(block)) if block == "nowhere"));
+
+    let result = System::from_yaml(
+        r#"
+        random: !Random { output: sink }
+        sink: !WriteStdout { input: random }
+        "#,
+    );
+    assert!(
+        matches!(result, Err(DefinitionError::IncompatiblePorts(source, target)) if source == "random.output" && target == "sink.input")
+    );
+
+    let result = System::from_yaml("sink: [Drop]");
+    assert!(matches!(result, Err(DefinitionError::Config(_))));
+}
+
+#[test]
+fn report_config_errors() {
+    let Err(DefinitionError::Config(errors)) = System::from_yaml(
+        r#"
+        greeting: !Cosnt
+          output: counter
+          value: Hello, world!
+        counter: !Count
+          input: greeting.output
+        encoder: !Encode { input: counter.count, output: sink, encoding: txet }
+        random: !Random
+          output: sink
+          seed: abc
+        "#,
+    ) else {
+        panic!("expected block configuration errors");
+    };
+    assert_eq!(errors.len(), 4);
+
+    assert_eq!(errors[0].block.as_deref(), Some("greeting"));
+    assert_eq!(errors[0].tag.as_deref(), Some("Cosnt"));
+    assert_eq!(errors[0].suggestions, ["Const", "Count"]);
+    assert_eq!(errors[0].location, Some((2, 9)));
+
+    assert_eq!(errors[1].field.as_deref(), Some("count"));
+    assert_eq!(errors[1].location, Some((5, 9)));
+
+    assert_eq!(errors[2].field.as_deref(), Some("encoding"));
+    assert!(errors[2]
+        .allowed
+        .contains(&String::from("TextWithNewlineSuffix")));
+    assert_eq!(errors[2].location, Some((7, 64)));
+
+    assert_eq!(errors[3].tag.as_deref(), Some("Random"));
+    assert_eq!(errors[3].field.as_deref(), Some("seed"));
+    assert_eq!(errors[3].location, Some((10, 11)));
+    assert_eq!(
+        errors[3].to_string(),
+        "10:11: random (!Random): field `seed`: invalid type: string \"abc\", expected u64"
+    );
+}
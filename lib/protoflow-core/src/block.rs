@@ -1,8 +1,8 @@
 // This is free and unencumbered software released into the public domain.
 
 use crate::{
-    prelude::{fmt, Box},
-    BlockDescriptor, BlockResult, BlockRuntime,
+    prelude::{fmt, vec, Box, Vec},
+    BlockDescriptor, BlockResult, BlockRuntime, StateHandle,
 };
 
 #[cfg(feature = "tokio")]
@@ -24,6 +24,25 @@ pub enum BoxedBlockType {
     Async(BoxedAsyncBlock),
 }
 
+impl BoxedBlockType {
+    pub fn as_block_descriptor(&self) -> &dyn BlockDescriptor {
+        match self {
+            Self::Normal(block) => block.as_block_descriptor(),
+            #[cfg(feature = "tokio")]
+            Self::Async(block) => block.as_block_descriptor(),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn state_handles(&self) -> Vec<(&'static str, Box<dyn StateHandle>)> {
+        match self {
+            Self::Normal(block) => block.state_handles(),
+            #[cfg(feature = "tokio")]
+            Self::Async(block) => block.state_handles(),
+        }
+    }
+}
+
 /// A block is an autonomous unit of computation in a system.
 pub trait Block: AsBlock + BlockDescriptor + BlockHooks + Send + Sync {
     /// Prepares this block for execution.
@@ -35,6 +54,15 @@ pub trait Block: AsBlock + BlockDescriptor + BlockHooks + Send + Sync {
     }
 
     /// Executes this block's computation.
+    ///
+    /// Runtimes that execute blocks cooperatively, such as the
+    /// [`CooperativeRuntime`](crate::runtimes::CooperativeRuntime), call this
+    /// again from the top whenever it fails with
+    /// [`PortError::WouldBlock`](crate::PortError::WouldBlock), as receiving
+    /// on a port or yielding does when the block would have to wait. Blocks
+    /// must therefore be re-entrant: any progress made before they would
+    /// block, such as the messages already received, is to be kept in their
+    /// fields, typically in `#[state]` fields, rather than in local variables.
     fn execute(&mut self, runtime: &dyn BlockRuntime) -> BlockResult;
 }
 
@@ -48,6 +76,14 @@ pub trait BlockHooks {
     fn post_execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
         Ok(()) // implemented by protoflow_derive
     }
+
+    /// Returns handles to the `#[state]` fields captured in checkpoints.
+    fn state_handles(&self) -> Vec<(&'static str, Box<dyn StateHandle>)>
+    where
+        Self: 'static,
+    {
+        vec![] // implemented by protoflow_derive
+    }
 }
 
 pub trait AsBlock {
@@ -0,0 +1,176 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{fmt, format, BTreeMap, String, Vec},
+    runtimes::{MockRuntime, StdRuntime},
+    transports::MockTransport,
+    Block, BlockError, BlockResult, InputPortID, Message, OutputPortID, Port, PortID, PortState,
+    System, SystemExecution, Transport,
+};
+
+/// A harness for unit testing a single block, without threads.
+///
+/// The block's input ports are fed with scripted messages, and the messages
+/// it sends on its output ports are collected, all through an in-memory
+/// [`MockTransport`]. The block executes on the current thread, with a
+/// [`MockRuntime`] providing a virtual clock.
+pub struct BlockTester<B: Block> {
+    block: B,
+    system: System<MockTransport>,
+    runtime: MockRuntime,
+    inputs: BTreeMap<String, (InputPortID, OutputPortID)>,
+    outputs: BTreeMap<String, OutputPortID>,
+}
+
+impl<B: Block> BlockTester<B> {
+    /// Instantiates the block to test, wiring all its ports to the test
+    /// harness.
+    ///
+    /// The block's ports must be created with the given system.
+    pub fn new(f: impl FnOnce(&System<MockTransport>) -> B) -> BlockResult<Self> {
+        let runtime = StdRuntime::new(MockTransport::new())?;
+        let system = System::new(&runtime);
+        let block = f(&system);
+
+        // Feed each input port from, and drain each output port into, a
+        // port of the test harness:
+        let mut feeds = Vec::new();
+        for input in block.inputs() {
+            let feed = system.output::<()>();
+            system.connect_by_id(feed.id(), input.id)?;
+            feeds.push(feed);
+        }
+        for output in block.outputs() {
+            let sink = system.input::<()>();
+            system.connect_by_id(output.id, sink.id())?;
+        }
+        system.prepare()?;
+
+        // Map the port names to the transport port IDs assigned by `prepare`:
+        let inputs = block
+            .inputs()
+            .into_iter()
+            .zip(feeds)
+            .filter_map(|(input, feed)| {
+                let PortID::Input(input_id) = input.id else {
+                    return None;
+                };
+                let PortID::Output(feed_id) = feed.id() else {
+                    return None;
+                };
+                Some((input.name?, (input_id, feed_id)))
+            })
+            .collect();
+        let outputs = block
+            .outputs()
+            .into_iter()
+            .filter_map(|output| match output.id {
+                PortID::Output(output_id) => Some((output.name?, output_id)),
+                PortID::Input(_) => None,
+            })
+            .collect();
+
+        Ok(Self {
+            block,
+            system,
+            runtime: MockRuntime::new(),
+            inputs,
+            outputs,
+        })
+    }
+
+    /// Returns the block under test.
+    pub fn block(&self) -> &B {
+        &self.block
+    }
+
+    /// Returns the block under test, mutably.
+    pub fn block_mut(&mut self) -> &mut B {
+        &mut self.block
+    }
+
+    /// Returns the runtime that the block executes with.
+    pub fn runtime(&self) -> &MockRuntime {
+        &self.runtime
+    }
+
+    /// Returns the transport that the block's ports are opened in.
+    pub fn transport(&self) -> &MockTransport {
+        &self.system.transport
+    }
+
+    /// Pushes a message into the named input port.
+    pub fn push<M: Message>(&self, input: &str, message: &M) -> BlockResult {
+        let (input_id, _) = self.input(input)?;
+        Ok(self.transport().push_message(input_id, message)?)
+    }
+
+    /// Signals end of stream on the named input port, once the messages
+    /// already pushed into it have been received.
+    pub fn close(&self, input: &str) -> BlockResult<bool> {
+        let (_, feed_id) = self.input(input)?;
+        Ok(self.transport().close_output(feed_id)?)
+    }
+
+    /// Executes the block to completion on the current thread.
+    ///
+    /// Since no further messages can be pushed while the block executes,
+    /// end of stream is first signaled on any input ports still open.
+    pub fn run(&mut self) -> BlockResult {
+        for &(_, feed_id) in self.inputs.values() {
+            self.system.transport.close_output(feed_id)?;
+        }
+        let runtime = &self.runtime;
+        self.block.prepare(runtime)?;
+        self.block.pre_execute(runtime)?;
+        self.block.execute(runtime)?;
+        self.block.post_execute(runtime)
+    }
+
+    /// Returns the current state of the named input or output port.
+    pub fn state(&self, port: &str) -> BlockResult<PortState> {
+        if let Some(&(input_id, _)) = self.inputs.get(port) {
+            return Ok(self.transport().input_state(input_id)?);
+        }
+        Ok(self.transport().output_state(self.output(port)?)?)
+    }
+
+    /// Decodes the messages sent on the named output port, in the order
+    /// they were sent.
+    pub fn outputs<M: Message>(&self, output: &str) -> BlockResult<Vec<M>> {
+        Ok(self.transport().sent_messages(self.output(output)?)?)
+    }
+
+    /// Asserts that exactly the expected messages were sent on the named
+    /// output port.
+    #[track_caller]
+    pub fn assert_outputs<M: Message + PartialEq>(&self, output: &str, expected: &[M]) {
+        match self.outputs::<M>(output) {
+            Ok(actual) => assert_eq!(actual, expected, "messages sent on `{}`", output),
+            Err(error) => panic!("failed to collect messages sent on `{}`: {}", output, error),
+        }
+    }
+
+    fn input(&self, name: &str) -> BlockResult<(InputPortID, OutputPortID)> {
+        self.inputs
+            .get(name)
+            .copied()
+            .ok_or_else(|| BlockError::Other(format!("unknown input port `{}`", name)))
+    }
+
+    fn output(&self, name: &str) -> BlockResult<OutputPortID> {
+        self.outputs
+            .get(name)
+            .copied()
+            .ok_or_else(|| BlockError::Other(format!("unknown output port `{}`", name)))
+    }
+}
+
+impl<B: Block> fmt::Debug for BlockTester<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlockTester")
+            .field("inputs", &self.inputs.keys())
+            .field("outputs", &self.outputs.keys())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,418 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{
+        fmt, Arc, AtomicU64, BTreeMap, BTreeSet, Box, Bytes, Ordering, RwLock, String, ToString,
+        Vec,
+    },
+    BlockID, BlockResult, CheckpointStore, InputPortConnection, InputPortID, InputPortState,
+    OutputPortConnection, OutputPortState, PortError, PortResult, StateHandle, TransportEvent,
+};
+use parking_lot::Mutex;
+
+/// A machine-readable identifier for a checkpoint of a system.
+///
+/// Checkpoint identifiers increase monotonically, starting from 1.
+pub type CheckpointID = u64;
+
+/// A consistent snapshot of the state of all blocks in a system.
+#[derive(Clone, Eq, PartialEq, prost::Message)]
+pub struct Checkpoint {
+    /// The identifier of this checkpoint.
+    #[prost(uint64, tag = "1")]
+    pub id: CheckpointID,
+
+    /// The state snapshots of the blocks in the system, keyed by block ID.
+    #[prost(btree_map = "uint64, message", tag = "2")]
+    pub blocks: BTreeMap<u64, BlockSnapshot>,
+}
+
+/// A snapshot of the `#[state]` fields of a single block.
+#[derive(Clone, Eq, PartialEq, prost::Message)]
+pub struct BlockSnapshot {
+    /// The serialized state fields of the block, keyed by field name.
+    #[prost(btree_map = "string, bytes", tag = "1")]
+    pub state: BTreeMap<String, Vec<u8>>,
+
+    /// The messages that the block had received but not yet processed, in
+    /// the course of aligning the checkpoint's barriers, keyed by the index
+    /// of the input port among the block's inputs.
+    #[prost(btree_map = "uint32, message", tag = "2")]
+    pub inputs: BTreeMap<u32, InputSnapshot>,
+}
+
+/// The in-flight messages of a single input port of a block.
+#[derive(Clone, Eq, PartialEq, prost::Message)]
+pub struct InputSnapshot {
+    /// The encoded messages, in the order that they were received.
+    #[prost(bytes = "bytes", repeated, tag = "1")]
+    pub messages: Vec<Bytes>,
+}
+
+/// Coordinates checkpoints across the blocks of a running system.
+///
+/// Triggering a checkpoint injects a barrier marker at the system's source
+/// blocks. As the barrier flows downstream, each block snapshots its state
+/// and forwards the barrier through its outputs. Once every block has
+/// reported its snapshot, the checkpoint is written to the store.
+pub struct CheckpointCoordinator {
+    store: Arc<dyn CheckpointStore>,
+    triggered: AtomicU64,
+    progress: Mutex<CheckpointProgress>,
+}
+
+#[derive(Default)]
+struct CheckpointProgress {
+    block_count: usize,
+    pending: BTreeMap<CheckpointID, Checkpoint>,
+    finished: BTreeMap<BlockID, BlockSnapshot>,
+    completed: Option<CheckpointID>,
+}
+
+impl CheckpointCoordinator {
+    pub fn new(store: Arc<dyn CheckpointStore>) -> BlockResult<Self> {
+        let completed = store.latest()?.map(|checkpoint| checkpoint.id);
+        Ok(Self {
+            store,
+            triggered: AtomicU64::new(completed.unwrap_or(0)),
+            progress: Mutex::new(CheckpointProgress {
+                completed,
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Returns the store that completed checkpoints are written to.
+    pub fn store(&self) -> &Arc<dyn CheckpointStore> {
+        &self.store
+    }
+
+    /// Triggers a new checkpoint, returning its identifier.
+    ///
+    /// The checkpoint completes asynchronously, once the barrier has passed
+    /// through all blocks in the system.
+    pub fn trigger(&self) -> BlockResult<CheckpointID> {
+        let mut progress = self.progress.lock();
+        let checkpoint_id = self.triggered.fetch_add(1, Ordering::SeqCst) + 1;
+        progress.pending.insert(
+            checkpoint_id,
+            Checkpoint {
+                id: checkpoint_id,
+                blocks: BTreeMap::new(),
+            },
+        );
+        self.complete_pending(&mut progress)?;
+        Ok(checkpoint_id)
+    }
+
+    /// Returns the identifier of the most recently triggered checkpoint.
+    pub fn triggered(&self) -> Option<CheckpointID> {
+        Some(self.triggered.load(Ordering::SeqCst)).filter(|&id| id > 0)
+    }
+
+    /// Returns the identifier of the most recently completed checkpoint.
+    pub fn completed(&self) -> Option<CheckpointID> {
+        self.progress.lock().completed
+    }
+
+    pub(crate) fn set_block_count(&self, block_count: usize) {
+        self.progress.lock().block_count = block_count;
+    }
+
+    pub(crate) fn report(
+        &self,
+        checkpoint_id: CheckpointID,
+        block_id: BlockID,
+        snapshot: BlockSnapshot,
+    ) -> BlockResult {
+        let mut progress = self.progress.lock();
+        if let Some(checkpoint) = progress.pending.get_mut(&checkpoint_id) {
+            checkpoint.blocks.insert(block_id as u64, snapshot);
+        }
+        self.complete_pending(&mut progress)
+    }
+
+    pub(crate) fn finish(&self, block_id: BlockID, snapshot: BlockSnapshot) -> BlockResult {
+        let mut progress = self.progress.lock();
+        progress.finished.insert(block_id, snapshot);
+        self.complete_pending(&mut progress)
+    }
+
+    fn complete_pending(&self, progress: &mut CheckpointProgress) -> BlockResult {
+        while let Some(mut entry) = progress.pending.first_entry() {
+            let checkpoint = entry.get_mut();
+            for (&block_id, snapshot) in progress.finished.iter() {
+                checkpoint
+                    .blocks
+                    .entry(block_id as u64)
+                    .or_insert_with(|| snapshot.clone());
+            }
+            if checkpoint.blocks.len() < progress.block_count {
+                break;
+            }
+            let checkpoint = entry.remove();
+            self.store.save(&checkpoint)?;
+            progress.completed = Some(checkpoint.id);
+        }
+        Ok(())
+    }
+}
+
+/// Handles checkpoint barriers on behalf of a single block.
+///
+/// This is shared by all the ports of a block, and is always invoked from
+/// the block's own thread of execution, in between its sends and receives.
+///
+/// Once one of the block's inputs has received the barrier for a
+/// checkpoint, it's fenced: the block receives no further messages on it
+/// until the barrier has been received on all the block's inputs, and the
+/// block has been snapshotted. Rather than blocking a receive on a fenced
+/// input, which would deadlock blocks that receive on their inputs one
+/// after the other, the other inputs are received from ahead of time until
+/// their barriers arrive. The messages received ahead of time are buffered
+/// on their input ports, and recorded in the snapshot as in flight.
+pub(crate) struct BlockCheckpointer {
+    block_id: BlockID,
+    coordinator: Arc<CheckpointCoordinator>,
+    state: Vec<(&'static str, Box<dyn StateHandle>)>,
+    inputs: Vec<Arc<RwLock<InputPortState>>>,
+    outputs: Vec<Arc<RwLock<OutputPortState>>>,
+    alignment: Mutex<BarrierAlignment>,
+}
+
+impl fmt::Debug for BlockCheckpointer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlockCheckpointer")
+            .field("block_id", &self.block_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Default)]
+struct BarrierAlignment {
+    inputs: BTreeSet<InputPortID>,
+    barriers: BTreeMap<CheckpointID, BTreeSet<InputPortID>>,
+    completed: CheckpointID,
+    finished: bool,
+}
+
+impl BarrierAlignment {
+    /// Returns whether the input has received a barrier that hasn't been
+    /// aligned yet.
+    fn is_fenced(&self, input: InputPortID) -> bool {
+        self.barriers
+            .values()
+            .any(|received| received.contains(&input))
+    }
+}
+
+impl BlockCheckpointer {
+    pub(crate) fn new(
+        block_id: BlockID,
+        coordinator: Arc<CheckpointCoordinator>,
+        state: Vec<(&'static str, Box<dyn StateHandle>)>,
+        inputs: Vec<Arc<RwLock<InputPortState>>>,
+        outputs: Vec<Arc<RwLock<OutputPortState>>>,
+    ) -> Self {
+        let completed = coordinator.triggered().unwrap_or(0);
+        Self {
+            block_id,
+            coordinator,
+            state,
+            inputs,
+            outputs,
+            alignment: Mutex::new(BarrierAlignment {
+                completed,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Registers a connected input port of the block, whose barriers must
+    /// be aligned before the block can be snapshotted.
+    pub(crate) fn add_input(&self, input: InputPortID) {
+        self.alignment.lock().inputs.insert(input);
+    }
+
+    /// Called when a barrier is received on one of the block's inputs.
+    ///
+    /// Once the barrier has been received on all open inputs, the block is
+    /// snapshotted and the barrier is forwarded through its outputs.
+    pub(crate) fn on_barrier(
+        &self,
+        input: InputPortID,
+        checkpoint_id: CheckpointID,
+    ) -> PortResult<()> {
+        let mut alignment = self.alignment.lock();
+        if checkpoint_id <= alignment.completed {
+            return Ok(()); // already checkpointed
+        }
+        // The barrier stands in for those of any earlier checkpoints too:
+        alignment.barriers.entry(checkpoint_id).or_default();
+        for (_, received) in alignment.barriers.range_mut(..=checkpoint_id) {
+            received.insert(input);
+        }
+        self.align(alignment)
+    }
+
+    /// Called before the block receives on one of its inputs from the
+    /// transport. While the input is fenced, receives on the block's other
+    /// inputs ahead of time, until the barrier has been aligned.
+    pub(crate) fn on_recv(&self, input: InputPortID) -> PortResult<()> {
+        loop {
+            let next = {
+                let alignment = self.alignment.lock();
+                if !alignment.is_fenced(input) {
+                    return Ok(());
+                }
+                alignment
+                    .inputs
+                    .iter()
+                    .copied()
+                    .find(|&other| !alignment.is_fenced(other))
+            };
+            let Some(other) = next else {
+                return Ok(()); // unreachable, as the barrier is then aligned
+            };
+            let Some(port) = self.inputs.iter().find(|port| port.read().id == other) else {
+                self.on_close(other)?;
+                continue;
+            };
+            let transport = match port.read().connection {
+                InputPortConnection::Running(ref transport) => transport.clone(),
+                _ => {
+                    self.on_close(other)?;
+                    continue;
+                }
+            };
+            match transport.recv_event(other) {
+                Ok(Some(TransportEvent::Message(message))) => {
+                    port.write().buffered.push_back(message);
+                }
+                Ok(Some(TransportEvent::Barrier(checkpoint_id))) => {
+                    self.on_barrier(other, checkpoint_id)?;
+                }
+                Ok(None) | Err(PortError::Closed) => {
+                    port.write().buffered.push_back(Bytes::new()); // EOS
+                    self.on_close(other)?;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Called when one of the block's inputs has reached end of stream.
+    pub(crate) fn on_close(&self, input: InputPortID) -> PortResult<()> {
+        let mut alignment = self.alignment.lock();
+        if !alignment.inputs.remove(&input) {
+            return Ok(());
+        }
+        if alignment.inputs.is_empty() {
+            return self.finish(alignment);
+        }
+        self.align(alignment)
+    }
+
+    /// Called before the block sends a message on one of its outputs.
+    ///
+    /// Source blocks, which have no connected inputs to receive barriers
+    /// on, inject the barriers for any newly triggered checkpoints here.
+    pub(crate) fn on_send(&self) -> PortResult<()> {
+        let Some(triggered) = self.coordinator.triggered() else {
+            return Ok(());
+        };
+        let alignment = self.alignment.lock();
+        if !alignment.inputs.is_empty() || alignment.finished || triggered <= alignment.completed {
+            return Ok(());
+        }
+        self.complete(alignment, triggered)
+    }
+
+    /// Called when the block has closed one of its outputs.
+    pub(crate) fn on_output_close(&self) -> PortResult<()> {
+        let alignment = self.alignment.lock();
+        let all_closed = self
+            .outputs
+            .iter()
+            .all(|output| !matches!(output.read().connection, OutputPortConnection::Running(_)));
+        if !alignment.inputs.is_empty() || !all_closed {
+            return Ok(());
+        }
+        self.finish(alignment)
+    }
+
+    fn align(&self, mut alignment: parking_lot::MutexGuard<BarrierAlignment>) -> PortResult<()> {
+        let aligned = alignment
+            .barriers
+            .iter()
+            .rev()
+            .find(|(_, received)| alignment.inputs.is_subset(received))
+            .map(|(&checkpoint_id, _)| checkpoint_id);
+        let Some(checkpoint_id) = aligned else {
+            return Ok(());
+        };
+        alignment.barriers = alignment.barriers.split_off(&(checkpoint_id + 1));
+        self.complete(alignment, checkpoint_id)
+    }
+
+    fn complete(
+        &self,
+        mut alignment: parking_lot::MutexGuard<BarrierAlignment>,
+        checkpoint_id: CheckpointID,
+    ) -> PortResult<()> {
+        let snapshot = self.snapshot();
+        for checkpoint_id in (alignment.completed + 1)..=checkpoint_id {
+            self.coordinator
+                .report(checkpoint_id, self.block_id, snapshot.clone())
+                .map_err(|error| PortError::Other(error.to_string()))?;
+            for output in self.outputs.iter() {
+                let output = output.read();
+                let OutputPortConnection::Running(ref transport) = output.connection else {
+                    continue;
+                };
+                match transport.send_barrier(output.id, checkpoint_id) {
+                    Ok(()) | Err(PortError::Closed | PortError::Disconnected) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+        alignment.completed = checkpoint_id;
+        Ok(())
+    }
+
+    fn finish(&self, mut alignment: parking_lot::MutexGuard<BarrierAlignment>) -> PortResult<()> {
+        if alignment.finished {
+            return Ok(());
+        }
+        alignment.finished = true;
+        self.coordinator
+            .finish(self.block_id, self.snapshot())
+            .map_err(|error| PortError::Other(error.to_string()))
+    }
+
+    fn snapshot(&self) -> BlockSnapshot {
+        BlockSnapshot {
+            state: self
+                .state
+                .iter()
+                .map(|(name, handle)| (name.to_string(), handle.save()))
+                .collect(),
+            inputs: self
+                .inputs
+                .iter()
+                .enumerate()
+                .filter_map(|(index, input)| {
+                    let messages = input
+                        .read()
+                        .buffered
+                        .iter()
+                        .filter(|message| !message.is_empty()) // not EOS
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    let input = InputSnapshot { messages };
+                    (!input.messages.is_empty()).then_some((index as u32, input))
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{BTreeMap, RwLock},
+    BlockResult, Checkpoint, CheckpointID,
+};
+
+#[cfg(feature = "std")]
+extern crate std;
+
+/// A store for completed checkpoints of a system.
+pub trait CheckpointStore: Send + Sync {
+    /// Persists a completed checkpoint.
+    fn save(&self, checkpoint: &Checkpoint) -> BlockResult;
+
+    /// Loads a previously persisted checkpoint, if it exists.
+    fn load(&self, checkpoint_id: CheckpointID) -> BlockResult<Option<Checkpoint>>;
+
+    /// Loads the most recently persisted checkpoint, if any.
+    fn latest(&self) -> BlockResult<Option<Checkpoint>>;
+}
+
+/// A checkpoint store that keeps checkpoints in memory.
+#[derive(Debug, Default)]
+pub struct MemoryCheckpointStore {
+    checkpoints: RwLock<BTreeMap<CheckpointID, Checkpoint>>,
+}
+
+impl MemoryCheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CheckpointStore for MemoryCheckpointStore {
+    fn save(&self, checkpoint: &Checkpoint) -> BlockResult {
+        self.checkpoints
+            .write()
+            .insert(checkpoint.id, checkpoint.clone());
+        Ok(())
+    }
+
+    fn load(&self, checkpoint_id: CheckpointID) -> BlockResult<Option<Checkpoint>> {
+        Ok(self.checkpoints.read().get(&checkpoint_id).cloned())
+    }
+
+    fn latest(&self) -> BlockResult<Option<Checkpoint>> {
+        Ok(self
+            .checkpoints
+            .read()
+            .last_key_value()
+            .map(|(_, checkpoint)| checkpoint.clone()))
+    }
+}
+
+/// A checkpoint store that writes checkpoints as files in a local directory.
+///
+/// Each checkpoint is written to a file named after its identifier, e.g.,
+/// `00000000000000000001.checkpoint`, containing the Protobuf encoding of
+/// the [`Checkpoint`].
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct FileCheckpointStore {
+    path: std::path::PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl FileCheckpointStore {
+    const EXTENSION: &'static str = "checkpoint";
+
+    /// Opens the checkpoint store at the given directory, creating the
+    /// directory if needed.
+    pub fn open(path: impl Into<std::path::PathBuf>) -> BlockResult<Self> {
+        let path = path.into();
+        std::fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+
+    /// Returns the directory of this checkpoint store.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    fn checkpoint_path(&self, checkpoint_id: CheckpointID) -> std::path::PathBuf {
+        self.path
+            .join(std::format!("{:020}.{}", checkpoint_id, Self::EXTENSION))
+    }
+}
+
+#[cfg(feature = "std")]
+impl CheckpointStore for FileCheckpointStore {
+    fn save(&self, checkpoint: &Checkpoint) -> BlockResult {
+        use prost::Message;
+        let path = self.checkpoint_path(checkpoint.id);
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, checkpoint.encode_to_vec())?;
+        std::fs::rename(temp_path, path)?; // atomically replace
+        Ok(())
+    }
+
+    fn load(&self, checkpoint_id: CheckpointID) -> BlockResult<Option<Checkpoint>> {
+        use prost::Message;
+        let bytes = match std::fs::read(self.checkpoint_path(checkpoint_id)) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        Checkpoint::decode(bytes.as_slice())
+            .map(Some)
+            .map_err(|error| crate::PortError::from(error).into())
+    }
+
+    fn latest(&self) -> BlockResult<Option<Checkpoint>> {
+        let mut latest_id = None;
+        for entry in std::fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(Self::EXTENSION) {
+                continue;
+            }
+            let checkpoint_id = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<CheckpointID>().ok());
+            latest_id = latest_id.max(checkpoint_id);
+        }
+        match latest_id {
+            Some(checkpoint_id) => self.load(checkpoint_id),
+            None => Ok(None),
+        }
+    }
+}
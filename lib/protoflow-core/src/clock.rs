@@ -0,0 +1,58 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::prelude::Instant;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+/// A source of time for runtimes, such as a hardware timer.
+pub trait Clock: Send + Sync {
+    /// Returns the current time, measured from an arbitrary epoch.
+    fn now(&self) -> Instant;
+
+    /// Blocks the current thread until the given time.
+    ///
+    /// The default implementation busy-waits.
+    fn sleep_until(&self, instant: Instant) {
+        while self.now() < instant {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// A clock based on the operating system's monotonic clock, measured from
+/// when the clock was instantiated.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct StdClock {
+    epoch: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self {
+            epoch: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now(&self) -> Instant {
+        self.epoch.elapsed()
+    }
+
+    fn sleep_until(&self, instant: Instant) {
+        if let Some(duration) = instant.checked_sub(self.now()) {
+            std::thread::sleep(duration);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{Duration, Vec},
+    InputPortID, OutputPortID,
+};
+
+/// The metrics recorded for a connection between an output port and an
+/// input port, by transports that support them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectionMetrics {
+    /// The output port that messages were sent from.
+    pub source: OutputPortID,
+
+    /// The input port that messages were received on.
+    pub target: InputPortID,
+
+    /// The number of messages received.
+    pub messages: u64,
+
+    /// The total size of the messages received, in bytes.
+    pub bytes: u64,
+
+    /// The latency of every message received, from when it was sent until
+    /// when it was received, in order of receipt.
+    pub latencies: Vec<Duration>,
+}
+
+impl ConnectionMetrics {
+    pub fn new(source: OutputPortID, target: InputPortID) -> Self {
+        Self {
+            source,
+            target,
+            messages: 0,
+            bytes: 0,
+            latencies: Vec::new(),
+        }
+    }
+
+    /// Records a received message, given its size and latency.
+    pub fn record(&mut self, bytes: usize, latency: Duration) {
+        self.messages += 1;
+        self.bytes += bytes as u64;
+        self.latencies.push(latency);
+    }
+
+    /// Returns the given percentiles of the message latencies, such as
+    /// `50.0` for the median, or nothing if no messages were received.
+    pub fn latency_percentiles(&self, percentiles: &[f64]) -> Option<Vec<Duration>> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let mut latencies = self.latencies.clone();
+        latencies.sort_unstable();
+        let last = latencies.len() - 1;
+        Some(
+            percentiles
+                .iter()
+                .map(|percentile| {
+                    let rank = (percentile.clamp(0.0, 100.0) / 100.0 * last as f64).round();
+                    latencies[rank as usize]
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,46 @@
+// This is free and unencumbered software released into the public domain.
+
+/// The delivery priority of a message.
+///
+/// Transports deliver higher-priority messages first, while keeping FIFO
+/// order among messages of the same priority.
+pub type Priority = u8;
+
+/// Options for a connection between an output port and an input port.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConnectionOptions {
+    /// The maximum number of messages buffered on the connection, if any.
+    ///
+    /// When not given, the transport's default capacity is used.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub capacity: Option<usize>,
+
+    /// The default priority of messages sent on the connection, if any.
+    ///
+    /// When not given, messages are sent with the lowest priority.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub priority: Option<Priority>,
+}
+
+impl ConnectionOptions {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_priority(priority: Priority) -> Self {
+        Self {
+            priority: Some(priority),
+            ..Default::default()
+        }
+    }
+}
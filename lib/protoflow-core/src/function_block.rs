@@ -2,6 +2,15 @@
 
 use crate::{prelude::Result, BlockError};
 
+/// A block that computes one output message for each input message.
+///
+/// `#[derive(FunctionBlock)]` implements [`Block`](crate::Block) for a
+/// struct with an input port for `I` and an output port for `O`, given
+/// either as a tuple struct's first two fields or as fields marked with
+/// `#[input]` and `#[output]`. An optional error port of type
+/// `OutputPort<String>`, a tuple struct's third field or a field marked with
+/// `#[error]`, receives the errors returned by `compute` instead of them
+/// failing the block.
 pub trait FunctionBlock<I, O> {
     fn compute(&self, input: I) -> Result<O, BlockError>;
 }
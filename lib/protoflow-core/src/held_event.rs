@@ -0,0 +1,42 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{prelude::Bytes, CheckpointID, InputPortID, OutputPortID};
+
+/// An event that a transport holds back from delivery, while delivery is
+/// paused for debugging, by transports that support it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HeldEvent {
+    /// The sequence number of the event, in the order that events were held.
+    pub id: u64,
+
+    /// The output port that the event was sent from.
+    pub source: OutputPortID,
+
+    /// The input port that the event is to be delivered to.
+    pub target: InputPortID,
+
+    /// The event itself.
+    pub kind: HeldEventKind,
+}
+
+impl HeldEvent {
+    /// Returns the message, if the event is one.
+    pub fn message(&self) -> Option<&Bytes> {
+        match &self.kind {
+            HeldEventKind::Message(message) => Some(message),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HeldEventKind {
+    /// A message.
+    Message(Bytes),
+
+    /// A checkpoint barrier.
+    Barrier(CheckpointID),
+
+    /// The disconnection of the output port, once it was closed.
+    Disconnect,
+}
@@ -1,9 +1,9 @@
 // This is free and unencumbered software released into the public domain.
 
 use crate::{
-    prelude::{fmt, Arc, Cow, MaybeLabeled, MaybeNamed, PhantomData, RwLock},
-    InputPortID, Message, MessageReceiver, Port, PortError, PortID, PortResult, PortState, System,
-    Transport,
+    prelude::{fmt, Arc, Bytes, Cow, MaybeLabeled, MaybeNamed, PhantomData, RwLock, VecDeque},
+    BlockCheckpointer, InputPortID, Message, MessageReceiver, Port, PortError, PortID, PortResult,
+    PortState, System, Transport, TransportEvent,
 };
 
 #[derive(Clone)] //, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -16,7 +16,12 @@ impl<T: Message> InputPort<T> {
     pub fn new<X: Transport + Default>(system: &System<X>) -> Self {
         let id = system.connection_config.borrow_mut().add_input();
         let connection = Default::default();
-        let state = Arc::new(RwLock::new(InputPortState { id, connection }));
+        let state = Arc::new(RwLock::new(InputPortState {
+            id,
+            connection,
+            checkpointer: None,
+            buffered: VecDeque::new(),
+        }));
         Self {
             _phantom: PhantomData,
             state,
@@ -30,37 +35,73 @@ impl<T: Message> InputPort<T> {
         };
         transport.close(PortID::Input(state.id))?;
         state.connection = InputPortConnection::Closed;
+        state.buffered.clear();
+        let (id, checkpointer) = (state.id, state.checkpointer.clone());
+        drop(state);
+        if let Some(checkpointer) = checkpointer {
+            checkpointer.on_close(id)?;
+        }
         Ok(true)
     }
 
     pub fn recv(&self) -> PortResult<Option<T>> {
-        let state = self.state.read();
-        let InputPortConnection::Running(ref transport) = state.connection else {
-            return Err(PortError::Disconnected);
+        // Don't hold the port state lock while receiving, as the checkpointer
+        // buffers messages received ahead of time on the block's inputs:
+        let (id, transport, checkpointer) = {
+            let state = self.state.read();
+            let InputPortConnection::Running(ref transport) = state.connection else {
+                return Err(PortError::Disconnected);
+            };
+            (state.id, transport.clone(), state.checkpointer.clone())
         };
 
-        match transport.recv(state.id)? {
-            None => Ok(None), // EOS (port closed)
-            Some(encoded_message) => {
-                if encoded_message.is_empty() {
-                    Ok(None) // EOS (port disconnected)
-                } else {
-                    match T::decode_length_delimited(encoded_message) {
-                        Ok(message) => Ok(Some(message)),
-                        Err(err) => Err(err.into()),
+        loop {
+            let buffered = self.state.write().buffered.pop_front();
+            let encoded_message = match buffered {
+                Some(encoded_message) => encoded_message,
+                None => {
+                    if let Some(checkpointer) = &checkpointer {
+                        checkpointer.on_recv(id)?; // wait for barrier alignment
                     }
+                    match transport.recv_event(id)? {
+                        Some(TransportEvent::Message(encoded_message)) => encoded_message,
+                        Some(TransportEvent::Barrier(checkpoint)) => {
+                            if let Some(checkpointer) = &checkpointer {
+                                checkpointer.on_barrier(id, checkpoint)?;
+                            }
+                            continue;
+                        }
+                        None => Default::default(), // EOS (port closed)
+                    }
+                }
+            };
+            if encoded_message.is_empty() {
+                // EOS (port closed or disconnected)
+                if let Some(checkpointer) = &checkpointer {
+                    checkpointer.on_close(id)?;
                 }
+                return Ok(None);
             }
+            return match T::decode_length_delimited(encoded_message) {
+                Ok(message) => Ok(Some(message)),
+                Err(err) => Err(err.into()),
+            };
         }
     }
 
     pub fn try_recv(&self) -> PortResult<Option<T>> {
-        let state = self.state.read();
+        let mut state = self.state.write();
         let InputPortConnection::Running(ref transport) = state.connection else {
             return Err(PortError::Disconnected);
         };
+        let transport = transport.clone();
 
-        match transport.try_recv(state.id)? {
+        let encoded_message = match state.buffered.pop_front() {
+            Some(encoded_message) if encoded_message.is_empty() => None, // EOS
+            Some(encoded_message) => Some(encoded_message),
+            None => transport.try_recv(state.id)?,
+        };
+        match encoded_message {
             None => Ok(None), // EOS
             Some(encoded_message) => match T::decode(encoded_message) {
                 Ok(message) => Ok(Some(message)),
@@ -88,14 +129,7 @@ impl<T: Message> Port for InputPort<T> {
     }
 
     fn state(&self) -> PortState {
-        let state = self.state.read();
-        match state.connection {
-            InputPortConnection::Closed => PortState::Closed,
-            InputPortConnection::Ready => PortState::Open,
-            InputPortConnection::Running(ref transport) => transport
-                .state(PortID::Input(state.id))
-                .unwrap_or(PortState::Closed),
-        }
+        self.state.read().state()
     }
 
     fn close(&mut self) -> PortResult<bool> {
@@ -131,6 +165,25 @@ impl<T: Message> fmt::Debug for InputPort<T> {
 pub(crate) struct InputPortState {
     pub(crate) id: InputPortID,
     pub(crate) connection: InputPortConnection,
+    pub(crate) checkpointer: Option<Arc<BlockCheckpointer>>,
+
+    /// The encoded messages received ahead of time during checkpoint
+    /// barrier alignment, or restored from a checkpoint, which are received
+    /// before any further messages from the transport. An empty message
+    /// marks EOS.
+    pub(crate) buffered: VecDeque<Bytes>,
+}
+
+impl InputPortState {
+    pub(crate) fn state(&self) -> PortState {
+        match self.connection {
+            InputPortConnection::Closed => PortState::Closed,
+            InputPortConnection::Ready => PortState::Open,
+            InputPortConnection::Running(ref transport) => transport
+                .state(PortID::Input(self.id))
+                .unwrap_or(PortState::Closed),
+        }
+    }
 }
 
 #[derive(Clone, Default)]
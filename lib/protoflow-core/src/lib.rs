@@ -25,9 +25,32 @@ pub use block_error::*;
 mod block_runtime;
 pub use block_runtime::*;
 
+#[cfg(feature = "std")]
+mod block_tester;
+#[cfg(feature = "std")]
+pub use block_tester::*;
+
+mod checkpoint;
+pub use checkpoint::*;
+
+mod checkpoint_store;
+pub use checkpoint_store::*;
+
+mod clock;
+pub use clock::*;
+
+mod connection_metrics;
+pub use connection_metrics::*;
+
+mod connection_options;
+pub use connection_options::*;
+
 mod function_block;
 pub use function_block::*;
 
+mod held_event;
+pub use held_event::*;
+
 mod input_port;
 pub use input_port::*;
 
@@ -55,6 +78,9 @@ pub use output_ports::*;
 mod parameter_descriptor;
 pub use parameter_descriptor::*;
 
+mod parameter_value;
+pub use parameter_value::*;
+
 mod port;
 pub use port::*;
 
@@ -76,11 +102,25 @@ pub use process::*;
 mod runtime;
 pub use runtime::*;
 
+mod state;
+pub use state::*;
+
 pub mod runtimes;
 
 mod system;
 pub use system::*;
 
+mod system_description;
+pub use system_description::*;
+
+mod system_graph;
+pub use system_graph::*;
+
+#[cfg(feature = "std")]
+mod system_handle;
+#[cfg(feature = "std")]
+pub use system_handle::*;
+
 mod transport;
 pub use transport::*;
 
@@ -94,6 +134,10 @@ pub(crate) mod utils {
 
 pub use prost_types as types;
 
+/// The version of this crate, which dynamically loaded block plugins must
+/// have been built against.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub use prost::DecodeError;
 
 #[cfg(feature = "tracing")]
@@ -2,8 +2,8 @@
 
 use crate::{
     prelude::{fmt, Arc, Bytes, Cow, MaybeLabeled, MaybeNamed, PhantomData, RwLock},
-    Message, MessageSender, OutputPortID, Port, PortError, PortID, PortResult, PortState, System,
-    Transport,
+    BlockCheckpointer, Message, MessageSender, OutputPortID, Port, PortError, PortID, PortResult,
+    PortState, Priority, System, Transport,
 };
 
 #[derive(Clone)] //, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -16,7 +16,11 @@ impl<T: Message> OutputPort<T> {
     pub fn new<X: Transport + Default>(system: &System<X>) -> Self {
         let id = system.connection_config.borrow_mut().add_output();
         let connection = Default::default();
-        let state = Arc::new(RwLock::new(OutputPortState { id, connection }));
+        let state = Arc::new(RwLock::new(OutputPortState {
+            id,
+            connection,
+            checkpointer: None,
+        }));
         Self {
             _phantom: PhantomData,
             state,
@@ -30,6 +34,11 @@ impl<T: Message> OutputPort<T> {
         };
         transport.close(PortID::Output(state.id))?;
         state.connection = OutputPortConnection::Closed;
+        let checkpointer = state.checkpointer.clone();
+        drop(state);
+        if let Some(checkpointer) = checkpointer {
+            checkpointer.on_output_close()?;
+        }
         Ok(true)
     }
 
@@ -37,13 +46,39 @@ impl<T: Message> OutputPort<T> {
     where
         T: 'a,
     {
+        self.send_event(message.into(), None)
+    }
+
+    /// Sends a message with the given priority, overriding the default
+    /// priority of the connection.
+    ///
+    /// Higher-priority messages are delivered ahead of any lower-priority
+    /// messages still pending on the connection.
+    pub fn send_with_priority<'a>(
+        &self,
+        message: impl Into<&'a T>,
+        priority: Priority,
+    ) -> PortResult<()>
+    where
+        T: 'a,
+    {
+        self.send_event(message.into(), Some(priority))
+    }
+
+    fn send_event(&self, message: &T, priority: Option<Priority>) -> PortResult<()> {
+        let checkpointer = self.state.read().checkpointer.clone();
+        if let Some(checkpointer) = checkpointer {
+            checkpointer.on_send()?; // inject any pending checkpoint barriers
+        }
         let state = self.state.read();
         let OutputPortConnection::Running(ref transport) = state.connection else {
             return Err(PortError::Disconnected);
         };
-        let message: &T = message.into();
         let bytes = Bytes::from(message.encode_length_delimited_to_vec());
-        transport.send(state.id, bytes)
+        match priority {
+            Some(priority) => transport.send_with_priority(state.id, bytes, priority),
+            None => transport.send(state.id, bytes),
+        }
     }
 }
 
@@ -65,14 +100,7 @@ impl<T: Message> Port for OutputPort<T> {
     }
 
     fn state(&self) -> PortState {
-        let state = self.state.read();
-        match state.connection {
-            OutputPortConnection::Closed => PortState::Closed,
-            OutputPortConnection::Ready => PortState::Open,
-            OutputPortConnection::Running(ref transport) => transport
-                .state(PortID::Output(state.id))
-                .unwrap_or(PortState::Closed),
-        }
+        self.state.read().state()
     }
 
     fn close(&mut self) -> PortResult<bool> {
@@ -107,6 +135,19 @@ impl<T: Message> fmt::Debug for OutputPort<T> {
 pub(crate) struct OutputPortState {
     pub(crate) id: OutputPortID,
     pub(crate) connection: OutputPortConnection,
+    pub(crate) checkpointer: Option<Arc<BlockCheckpointer>>,
+}
+
+impl OutputPortState {
+    pub(crate) fn state(&self) -> PortState {
+        match self.connection {
+            OutputPortConnection::Closed => PortState::Closed,
+            OutputPortConnection::Ready => PortState::Open,
+            OutputPortConnection::Running(ref transport) => transport
+                .state(PortID::Output(self.id))
+                .unwrap_or(PortState::Closed),
+        }
+    }
 }
 
 #[derive(Clone, Default)]
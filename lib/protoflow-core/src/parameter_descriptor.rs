@@ -20,6 +20,15 @@ pub struct ParameterDescriptor {
     /// A default value, if any, for this parameter.
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub default_value: Option<String>,
+
+    /// The configured value, if known, of this parameter in a block.
+    ///
+    /// See [`ParameterValue`](crate::ParameterValue) for its syntax.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub value: Option<String>,
 }
 
 impl Named for ParameterDescriptor {
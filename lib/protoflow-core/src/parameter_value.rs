@@ -0,0 +1,73 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{format, Duration, String},
+    Message,
+};
+
+/// A value of a block parameter that can be described.
+///
+/// Values are described in the YAML syntax of block configurations, so that
+/// a described system can be defined again with the same parameters.
+pub trait ParameterValue {
+    /// Returns this value in YAML syntax, or `None` if it can't be described.
+    fn to_parameter_value(&self) -> Option<String>;
+}
+
+/// Messages are described with their debug representation, which for
+/// scalars and strings is also valid YAML.
+impl<T: Message> ParameterValue for T {
+    fn to_parameter_value(&self) -> Option<String> {
+        Some(format!("{:?}", self))
+    }
+}
+
+impl<T: ParameterValue> ParameterValue for Option<T> {
+    fn to_parameter_value(&self) -> Option<String> {
+        self.as_ref()?.to_parameter_value()
+    }
+}
+
+impl ParameterValue for Duration {
+    fn to_parameter_value(&self) -> Option<String> {
+        let nanos = self.as_nanos();
+        Some(match nanos {
+            _ if nanos % 1_000_000_000 == 0 => format!("{}s", nanos / 1_000_000_000),
+            _ if nanos % 1_000_000 == 0 => format!("{}ms", nanos / 1_000_000),
+            _ if nanos % 1_000 == 0 => format!("{}us", nanos / 1_000),
+            _ => format!("{}ns", nanos),
+        })
+    }
+}
+
+/// Describes the value of a block parameter for the `Block` derive, with
+/// [`ParameterValue`] if its type implements it, and as `None` otherwise.
+///
+/// The derive calls `(&&DescribedParameter(&value)).describe_value()`, which
+/// resolves to [`DescribeParameterValue`] for types that implement
+/// [`ParameterValue`], and falls back to [`DescribeParameterFallback`] for
+/// any other type.
+#[doc(hidden)]
+pub struct DescribedParameter<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait DescribeParameterValue {
+    fn describe_value(&self) -> Option<String>;
+}
+
+impl<T: ParameterValue> DescribeParameterValue for &DescribedParameter<'_, T> {
+    fn describe_value(&self) -> Option<String> {
+        self.0.to_parameter_value()
+    }
+}
+
+#[doc(hidden)]
+pub trait DescribeParameterFallback {
+    fn describe_value(&self) -> Option<String>;
+}
+
+impl<T> DescribeParameterFallback for DescribedParameter<'_, T> {
+    fn describe_value(&self) -> Option<String> {
+        None
+    }
+}
@@ -17,6 +17,9 @@ pub enum PortError {
     Disconnected,
     RecvFailed,
     SendFailed,
+    /// The operation can't complete without blocking, and should be retried
+    /// later.
+    WouldBlock,
     DecodeFailed(DecodeError),
     Other(String),
 }
@@ -29,6 +32,7 @@ impl fmt::Display for PortError {
             Self::Disconnected => write!(f, "Port is not connected"),
             Self::RecvFailed => write!(f, "Port receive failed"),
             Self::SendFailed => write!(f, "Port send failed"),
+            Self::WouldBlock => write!(f, "Port operation would block"),
             Self::DecodeFailed(error) => write!(f, "Port decode failed: {}", error),
             Self::Other(message) => write!(f, "{}", message),
         }
@@ -26,7 +26,7 @@ pub use alloc::{
 #[allow(unused)]
 pub use core::{
     any::type_name,
-    cell::RefCell,
+    cell::{Cell, RefCell},
     convert::{AsRef, TryFrom},
     fmt,
     marker::PhantomData,
@@ -35,7 +35,7 @@ pub use core::{
     result::Result,
     slice,
     str::FromStr,
-    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     time::Duration,
 };
 
@@ -47,7 +47,7 @@ pub type Instant = Duration;
 pub use bytes;
 
 #[doc(hidden)]
-pub use parking_lot::RwLock;
+pub use parking_lot::{Mutex, RwLock};
 
 #[doc(hidden)]
 pub use prost;
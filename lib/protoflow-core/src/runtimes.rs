@@ -1,5 +1,11 @@
 // This is free and unencumbered software released into the public domain.
 
+mod cooperative;
+pub use cooperative::*;
+
+mod mock;
+pub use mock::*;
+
 #[cfg(feature = "std")]
 mod std;
 #[cfg(feature = "std")]
@@ -0,0 +1,240 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{Arc, AtomicBool, AtomicU64, Duration, Instant, Ordering, Range, String, Vec},
+    transports::StaticBufferTransport,
+    BlockError, BlockResult, BlockRuntime, BoxedBlock, BoxedBlockType, Clock, OutputPortID, Port,
+    PortError, PortID, System, SystemExecution,
+};
+
+/// A single-threaded runtime that executes the blocks of a system
+/// cooperatively, without requiring the standard library.
+///
+/// Blocks execute as resumable state machines: a block runs until it
+/// would have to wait for a message, at which point the receive fails
+/// with [`PortError::WouldBlock`] and the block yields by returning the
+/// error. Its `execute` method is called again later, once other blocks
+/// have made progress, and so blocks must be re-entrant, as documented for
+/// [`Block::execute`](crate::Block::execute). Yielding with
+/// [`BlockRuntime::yield_now`] likewise returns control to the runtime.
+///
+/// Waiting for a port that isn't connected returns at once, as the
+/// connections of the system are fixed by the time it executes: an input
+/// whose outputs have all disconnected reads as end of stream, even when
+/// the block waits for it again after being resumed.
+///
+/// Sending to a full input port doesn't yield: the message is kept pending
+/// in the output port, and the runtime retries delivering it before the
+/// block is resumed. A block is resumed only once all the messages it
+/// previously sent have been received, so that a block sending at most one
+/// message per message received never exceeds the pending messages that
+/// the [`StaticBufferTransport`] can keep.
+///
+/// Sleeping blocks the whole runtime, using the runtime's [`Clock`].
+#[derive(Debug)]
+pub struct CooperativeRuntime<C: Clock> {
+    clock: C,
+    is_alive: AtomicBool,
+    has_yielded: AtomicBool,
+    seed: AtomicU64,
+}
+
+/// A system executing in a [`CooperativeRuntime`].
+pub struct CooperativeSystem<'a, C: Clock, const CAPACITY: usize> {
+    runtime: &'a CooperativeRuntime<C>,
+    transport: Arc<StaticBufferTransport<CAPACITY>>,
+    tasks: Vec<CooperativeTask>,
+}
+
+struct CooperativeTask {
+    block: BoxedBlock,
+    outputs: Vec<OutputPortID>,
+    is_started: bool,
+    is_finished: bool,
+}
+
+impl<C: Clock> CooperativeRuntime<C> {
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            is_alive: AtomicBool::new(true),
+            has_yielded: AtomicBool::new(false),
+            seed: AtomicU64::new(0x9E37_79B9_7F4A_7C15),
+        }
+    }
+
+    /// Returns the clock that the runtime sleeps with.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Terminates the runtime, causing any executing system to fail with
+    /// [`BlockError::Terminated`].
+    pub fn terminate(&self) {
+        self.is_alive.store(false, Ordering::SeqCst);
+    }
+
+    /// Prepares a system for execution, without executing any of its
+    /// blocks yet.
+    ///
+    /// The system executes as [`CooperativeSystem::poll`] is called, which
+    /// allows interleaving its execution with other work, such as in the
+    /// main loop of an embedded application.
+    pub fn spawn<const CAPACITY: usize>(
+        &self,
+        mut system: System<StaticBufferTransport<CAPACITY>>,
+    ) -> BlockResult<CooperativeSystem<'_, C, CAPACITY>> {
+        SystemExecution::prepare(&system)?;
+        let tasks = system
+            .blocks
+            .drain(..)
+            .map(|block| match block {
+                BoxedBlockType::Normal(block) => Ok(CooperativeTask {
+                    outputs: block
+                        .outputs()
+                        .into_iter()
+                        .filter_map(|port| match port.id {
+                            PortID::Output(id) => Some(id),
+                            PortID::Input(_) => None,
+                        })
+                        .collect(),
+                    block,
+                    is_started: false,
+                    is_finished: false,
+                }),
+                #[cfg(feature = "tokio")]
+                BoxedBlockType::Async(_) => Err(BlockError::Other(String::from(
+                    "async blocks are not supported by the cooperative runtime",
+                ))),
+            })
+            .collect::<BlockResult<_>>()?;
+        Ok(CooperativeSystem {
+            runtime: self,
+            transport: system.transport.clone(),
+            tasks,
+        })
+    }
+
+    /// Executes a system to completion on the current thread.
+    pub fn run<const CAPACITY: usize>(
+        &self,
+        system: System<StaticBufferTransport<CAPACITY>>,
+    ) -> BlockResult {
+        let mut system = self.spawn(system)?;
+        while system.poll()? {}
+        Ok(())
+    }
+}
+
+impl<C: Clock, const CAPACITY: usize> CooperativeSystem<'_, C, CAPACITY> {
+    /// Checks whether any of the system's blocks have yet to finish
+    /// executing.
+    pub fn is_alive(&self) -> bool {
+        self.tasks.iter().any(|task| !task.is_finished)
+    }
+
+    /// Resumes each block that can make progress once, returning whether
+    /// the system is still executing.
+    ///
+    /// Fails with the first error returned by a block, or if no block can
+    /// make progress.
+    pub fn poll(&mut self) -> BlockResult<bool> {
+        let runtime = self.runtime;
+        if !runtime.is_alive() {
+            return Err(BlockError::Terminated);
+        }
+        runtime.has_yielded.store(false, Ordering::SeqCst);
+        let operations = self.transport.operations();
+        let mut has_finished = false;
+
+        for task in self.tasks.iter_mut() {
+            // Retry the block's pending sends, even once it has finished,
+            // and wait for its previous messages to be received:
+            let is_blocked = task.outputs.iter().try_fold(false, |blocked, &output| {
+                let pending = self.transport.flush(output)?;
+                Ok::<_, PortError>(blocked || pending > 0 || self.transport.backlog(output)? > 0)
+            })?;
+            if is_blocked || task.is_finished {
+                continue;
+            }
+            match task.resume(runtime) {
+                Ok(()) => {
+                    task.is_finished = true;
+                    has_finished = true;
+                }
+                Err(BlockError::PortError(PortError::WouldBlock)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        let is_alive = self.is_alive();
+        if is_alive
+            && !has_finished
+            && !runtime.has_yielded.load(Ordering::SeqCst)
+            && operations == self.transport.operations()
+        {
+            return Err(BlockError::Other(String::from(
+                "deadlock: no block can make progress",
+            )));
+        }
+        Ok(is_alive)
+    }
+}
+
+impl CooperativeTask {
+    fn resume(&mut self, runtime: &dyn BlockRuntime) -> BlockResult {
+        let block = self.block.as_mut();
+        if !self.is_started {
+            block.prepare(runtime)?;
+            block.pre_execute(runtime)?;
+            self.is_started = true;
+        }
+        block.execute(runtime)?;
+        block.post_execute(runtime)
+    }
+}
+
+impl<C: Clock> BlockRuntime for CooperativeRuntime<C> {
+    fn is_alive(&self) -> bool {
+        self.is_alive.load(Ordering::SeqCst)
+    }
+
+    fn sleep_for(&self, duration: Duration) -> BlockResult {
+        self.clock.sleep_until(self.clock.now() + duration);
+        Ok(())
+    }
+
+    fn sleep_until(&self, instant: Instant) -> BlockResult {
+        self.clock.sleep_until(instant);
+        Ok(())
+    }
+
+    fn wait_for(&self, port: &dyn Port) -> BlockResult {
+        if !self.is_alive() {
+            return Err(BlockError::Terminated);
+        }
+        if port.is_closed() {
+            return Err(BlockError::PortError(PortError::Closed));
+        }
+        Ok(()) // connected, or else never will be
+    }
+
+    fn yield_now(&self) -> BlockResult {
+        self.has_yielded.store(true, Ordering::SeqCst);
+        Err(BlockError::PortError(PortError::WouldBlock))
+    }
+
+    fn random_duration(&self, range: Range<Duration>) -> Duration {
+        // A xorshift generator, which is good enough for jitter:
+        let mut seed = self.seed.load(Ordering::Relaxed);
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        self.seed.store(seed, Ordering::Relaxed);
+        let span = range.end.saturating_sub(range.start).as_nanos() as u64;
+        if span == 0 {
+            return range.start;
+        }
+        range.start + Duration::from_nanos(seed % span)
+    }
+}
@@ -0,0 +1,90 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{AtomicBool, Duration, Instant, Ordering, Range, String},
+    BlockError, BlockResult, BlockRuntime, Port, PortError,
+};
+use parking_lot::Mutex;
+
+/// A block runtime for testing blocks, with a virtual clock.
+///
+/// Sleeping advances the virtual clock instantly, without blocking the
+/// current thread, and random durations are deterministically the shortest
+/// duration in the requested range.
+#[derive(Debug)]
+pub struct MockRuntime {
+    clock: Mutex<Instant>,
+    is_alive: AtomicBool,
+}
+
+impl Default for MockRuntime {
+    fn default() -> Self {
+        Self {
+            clock: Mutex::new(Instant::ZERO),
+            is_alive: AtomicBool::new(true),
+        }
+    }
+}
+
+impl MockRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current time on the virtual clock, measured from when
+    /// the runtime was instantiated.
+    pub fn now(&self) -> Instant {
+        *self.clock.lock()
+    }
+
+    /// Advances the virtual clock by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        *self.clock.lock() += duration;
+    }
+
+    /// Terminates the runtime, causing any blocks waiting for ports to fail
+    /// with [`BlockError::Terminated`].
+    pub fn terminate(&self) {
+        self.is_alive.store(false, Ordering::SeqCst);
+    }
+}
+
+impl BlockRuntime for MockRuntime {
+    fn is_alive(&self) -> bool {
+        self.is_alive.load(Ordering::SeqCst)
+    }
+
+    fn sleep_for(&self, duration: Duration) -> BlockResult {
+        self.advance(duration);
+        Ok(())
+    }
+
+    fn sleep_until(&self, instant: Instant) -> BlockResult {
+        let mut clock = self.clock.lock();
+        *clock = (*clock).max(instant);
+        Ok(())
+    }
+
+    fn wait_for(&self, port: &dyn Port) -> BlockResult {
+        if !self.is_alive() {
+            return Err(BlockError::Terminated);
+        }
+        if port.is_connected() {
+            return Ok(());
+        }
+        // Nothing else could connect the port while we're waiting:
+        Err(BlockError::PortError(if port.is_closed() {
+            PortError::Closed
+        } else {
+            PortError::Other(String::from("port is not connected"))
+        }))
+    }
+
+    fn yield_now(&self) -> BlockResult {
+        Ok(())
+    }
+
+    fn random_duration(&self, range: Range<Duration>) -> Duration {
+        range.start
+    }
+}
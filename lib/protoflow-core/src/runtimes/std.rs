@@ -7,8 +7,8 @@ use crate::{
     },
     transport::Transport,
     transports::MpscTransport,
-    Block, BlockError, BlockResult, BlockRuntime, BoxedBlockType, Port, Process, ProcessID,
-    Runtime, System,
+    Block, BlockError, BlockResult, BlockRuntime, BoxedBlockType, Port, PortError, Process,
+    ProcessID, Runtime, System,
 };
 
 #[cfg(feature = "tokio")]
@@ -182,6 +182,9 @@ impl<T: Transport> BlockRuntime for Arc<StdRuntime<T>> {
             if port.is_connected() {
                 return Ok(());
             }
+            if port.is_closed() {
+                return Err(BlockError::PortError(PortError::Closed));
+            }
             self.yield_now()?;
         }
     }
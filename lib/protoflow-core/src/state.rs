@@ -0,0 +1,166 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{fmt, Arc, Box, Bytes, BytesMut, RwLock, Vec, VecDeque},
+    DecodeError, Message,
+};
+use parking_lot::{RwLockReadGuard, RwLockWriteGuard};
+use prost::encoding::{decode_varint, encode_varint};
+
+/// A block's internal state, as captured in and restored from checkpoints.
+///
+/// Block fields marked `#[state]` whose type is `State<T>` are serialized by
+/// `#[derive(Block)]` whenever a checkpoint barrier passes through the block.
+/// Clones of a `State<T>` share the same underlying value.
+///
+/// Marking fields of any other type `#[state]` is deprecated: they aren't
+/// captured in checkpoints.
+#[derive(Default)]
+pub struct State<T>(Arc<RwLock<T>>);
+
+impl<T> State<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(RwLock::new(value)))
+    }
+
+    /// Locks this state for reading.
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read()
+    }
+
+    /// Locks this state for writing.
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.0.write()
+    }
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> From<T> for State<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("State").field(&*self.read()).finish()
+    }
+}
+
+/// A value that can be serialized into, and deserialized from, a checkpoint.
+pub trait StateValue: Sized {
+    fn encode_state(&self, buffer: &mut Vec<u8>);
+    fn decode_state(buffer: &mut Bytes) -> Result<Self, DecodeError>;
+}
+
+impl<M: Message> StateValue for M {
+    fn encode_state(&self, buffer: &mut Vec<u8>) {
+        self.encode_length_delimited(buffer).unwrap()
+    }
+
+    fn decode_state(buffer: &mut Bytes) -> Result<Self, DecodeError> {
+        Self::decode_length_delimited(buffer)
+    }
+}
+
+impl StateValue for BytesMut {
+    fn encode_state(&self, buffer: &mut Vec<u8>) {
+        Bytes::copy_from_slice(self).encode_state(buffer)
+    }
+
+    fn decode_state(buffer: &mut Bytes) -> Result<Self, DecodeError> {
+        Ok(BytesMut::from(Bytes::decode_state(buffer)?))
+    }
+}
+
+impl<T: Message> StateValue for Vec<T> {
+    fn encode_state(&self, buffer: &mut Vec<u8>) {
+        encode_varint(self.len() as u64, buffer);
+        for message in self {
+            message.encode_length_delimited(buffer).unwrap();
+        }
+    }
+
+    fn decode_state(buffer: &mut Bytes) -> Result<Self, DecodeError> {
+        let len = decode_varint(buffer)?;
+        (0..len)
+            .map(|_| T::decode_length_delimited(&mut *buffer))
+            .collect()
+    }
+}
+
+impl<T: Message> StateValue for VecDeque<T> {
+    fn encode_state(&self, buffer: &mut Vec<u8>) {
+        encode_varint(self.len() as u64, buffer);
+        for message in self {
+            message.encode_length_delimited(buffer).unwrap();
+        }
+    }
+
+    fn decode_state(buffer: &mut Bytes) -> Result<Self, DecodeError> {
+        Ok(Vec::<T>::decode_state(buffer)?.into())
+    }
+}
+
+/// A type-erased handle to a block's `State<T>` field.
+#[doc(hidden)]
+pub trait StateHandle: Send + Sync {
+    /// Serializes the current value of the state.
+    fn save(&self) -> Vec<u8>;
+
+    /// Replaces the current value of the state with a serialized one.
+    fn load(&self, state: &[u8]) -> Result<(), DecodeError>;
+}
+
+impl<T: StateValue + Send + Sync> StateHandle for State<T> {
+    fn save(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.read().encode_state(&mut buffer);
+        buffer
+    }
+
+    fn load(&self, state: &[u8]) -> Result<(), DecodeError> {
+        let value = T::decode_state(&mut Bytes::copy_from_slice(state))?;
+        *self.write() = value;
+        Ok(())
+    }
+}
+
+/// Captures a `#[state]` field for the `Block` derive, as a [`StateHandle`]
+/// if it's a `State<T>`, and not at all otherwise.
+///
+/// The derive calls `(&&CapturedState(&field)).capture_state()`, which
+/// resolves to [`CaptureStateHandle`] for fields of type `State<T>`, and
+/// falls back to the deprecated [`CaptureStateFallback`] for any other type.
+#[doc(hidden)]
+pub struct CapturedState<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait CaptureStateHandle {
+    fn capture_state(&self) -> Option<Box<dyn StateHandle>>;
+}
+
+impl<T: StateValue + Send + Sync + 'static> CaptureStateHandle for &CapturedState<'_, State<T>> {
+    fn capture_state(&self) -> Option<Box<dyn StateHandle>> {
+        Some(Box::new(self.0.clone()))
+    }
+}
+
+#[doc(hidden)]
+pub trait CaptureStateFallback {
+    #[deprecated(
+        note = "`#[state]` fields that aren't of type `State<T>` aren't captured in checkpoints"
+    )]
+    fn capture_state(&self) -> Option<Box<dyn StateHandle>>;
+}
+
+impl<T> CaptureStateFallback for CapturedState<'_, T> {
+    fn capture_state(&self) -> Option<Box<dyn StateHandle>> {
+        None
+    }
+}
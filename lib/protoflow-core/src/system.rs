@@ -2,17 +2,28 @@
 
 use crate::{
     prelude::{
-        fmt, Arc, BTreeMap, BTreeSet, Box, Bytes, PhantomData, Rc, RefCell, RwLock, String,
-        ToString, VecDeque,
+        fmt, format, type_name, Arc, BTreeMap, BTreeSet, Box, Bytes, Cell, PhantomData, RefCell,
+        RwLock, String, ToString, Vec, VecDeque,
     },
-    runtimes::StdRuntime,
-    transports::MpscTransport,
     types::Any,
-    Block, BlockError, BlockID, BlockResult, BoxedBlock, BoxedBlockType, InputPort,
-    InputPortConnection, InputPortID, InputPortState, Message, OutputPort, OutputPortConnection,
-    OutputPortID, OutputPortState, Port, PortID, PortResult, Process, Runtime, Transport,
+    Block, BlockCheckpointer, BlockDescription, BlockError, BlockID, BlockResult, BoxedBlock,
+    BoxedBlockType, Checkpoint, CheckpointCoordinator, CheckpointID, CheckpointStore,
+    ConnectionDescription, ConnectionOptions, InputPort, InputPortConnection, InputPortID,
+    InputPortState, Message, OutputPort, OutputPortConnection, OutputPortID, OutputPortState, Port,
+    PortDescription, PortDirection, PortError, PortID, PortResult, SystemDescription, Transport,
 };
 
+#[cfg(feature = "std")]
+use crate::{prelude::Rc, runtimes::StdRuntime, Process, Runtime, SystemHandle};
+
+/// The transport that systems use by default.
+#[cfg(feature = "std")]
+type DefaultTransport = crate::transports::MpscTransport;
+
+/// The transport that systems use by default.
+#[cfg(not(feature = "std"))]
+type DefaultTransport = crate::transports::StaticBufferTransport;
+
 #[cfg(feature = "tokio")]
 use crate::{AsyncBlock, BoxedAsyncBlock};
 
@@ -62,6 +73,17 @@ pub trait SystemBuilding {
     /// Both ports must be of the same message type.
     fn connect<M: Message>(&mut self, source: &OutputPort<M>, target: &InputPort<M>) -> bool;
 
+    /// Connects two ports of two blocks in the system, using the given
+    /// connection options.
+    ///
+    /// Both ports must be of the same message type.
+    fn connect_with<M: Message>(
+        &mut self,
+        source: &OutputPort<M>,
+        target: &InputPort<M>,
+        options: ConnectionOptions,
+    ) -> bool;
+
     /// Validates system for execution.
     fn validate(&self) -> BlockResult<()>;
 }
@@ -72,18 +94,29 @@ pub trait SystemExecution {
     ///    The connections are defined by `SystemBuilding.connect()`.
     fn prepare(&self) -> BlockResult<()>;
     /// Executes the system, returning the system process.
+    #[cfg(feature = "std")]
     fn execute(self) -> BlockResult<Rc<dyn Process>>;
 }
 
 /// A system is a collection of blocks that are connected together.
-pub struct System<X: Transport + Default + 'static = MpscTransport> {
+pub struct System<X: Transport + Default + 'static = DefaultTransport> {
+    #[cfg(feature = "std")]
     pub(crate) runtime: Arc<StdRuntime<X>>,
 
+    /// The transport that the system's ports are opened in.
+    pub(crate) transport: Arc<X>,
+
     /// The registered blocks in the system.
     pub(crate) blocks: VecDeque<BoxedBlockType>,
 
     pub(crate) connection_config: RefCell<SystemConnections>,
 
+    /// The checkpoint coordinator, if checkpoints are enabled.
+    pub(crate) checkpoints: Option<Arc<CheckpointCoordinator>>,
+
+    /// Whether the ports have been opened in the transport and connected.
+    pub(crate) is_prepared: Cell<bool>,
+
     _phantom: PhantomData<X>,
 }
 
@@ -91,7 +124,8 @@ pub struct System<X: Transport + Default + 'static = MpscTransport> {
 pub(crate) struct SystemConnections {
     pub(crate) outputs: BTreeMap<OutputPortID, Arc<RwLock<OutputPortState>>>,
     pub(crate) inputs: BTreeMap<InputPortID, Arc<RwLock<InputPortState>>>,
-    pub(crate) connections: BTreeSet<(OutputPortID, InputPortID)>,
+    pub(crate) connections: BTreeMap<(OutputPortID, InputPortID), ConnectionOptions>,
+    pub(crate) types: BTreeMap<PortID, &'static str>,
 }
 
 impl SystemConnections {
@@ -119,33 +153,70 @@ impl fmt::Debug for System {
 impl<X: Transport + Default + 'static> System<X> {
     /// Builds a new system.
     pub fn build<F: FnOnce(&mut System<X>)>(f: F) -> Self {
-        let transport = X::default();
-        let runtime = StdRuntime::new(transport).unwrap();
-        let mut system = System::new(&runtime);
+        let mut system = System::with_transport(X::default());
         f(&mut system);
         system
     }
 
     /// Instantiates a new system.
+    #[cfg(feature = "std")]
     pub fn new(runtime: &Arc<StdRuntime<X>>) -> Self {
         Self {
             runtime: runtime.clone(),
+            transport: runtime.transport.clone(),
+            blocks: VecDeque::new(),
+            connection_config: Default::default(),
+            checkpoints: None,
+            is_prepared: Cell::new(false),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Instantiates a new system with its ports opened in the given
+    /// transport.
+    pub fn with_transport(transport: X) -> Self {
+        #[cfg(feature = "std")]
+        {
+            Self::new(&StdRuntime::new(transport).unwrap())
+        }
+
+        #[cfg(not(feature = "std"))]
+        Self {
+            transport: Arc::new(transport),
             blocks: VecDeque::new(),
             connection_config: Default::default(),
+            checkpoints: None,
+            is_prepared: Cell::new(false),
             _phantom: PhantomData,
         }
     }
 
+    #[cfg(feature = "std")]
     pub fn execute(self) -> BlockResult<Rc<dyn Process>> {
         let mut runtime = self.runtime.clone();
         runtime.execute(self)
     }
 
+    /// Executes the system, returning a handle for reconfiguring it while
+    /// it is running.
+    #[cfg(feature = "std")]
+    pub fn start(self) -> BlockResult<SystemHandle<X>> {
+        SystemExecution::prepare(&self)?;
+        SystemHandle::start(self)
+    }
+
+    /// Returns the transport that the system's ports are opened in.
+    pub fn transport(&self) -> &Arc<X> {
+        &self.transport
+    }
+
     pub fn input<M: Message + 'static>(&self) -> InputPort<M> {
         let port = InputPort::new(self);
         let state = port.state.clone();
         let id = state.read().id;
-        self.connection_config.borrow_mut().inputs.insert(id, state);
+        let mut connection_config = self.connection_config.borrow_mut();
+        connection_config.inputs.insert(id, state);
+        connection_config.types.insert(id.into(), type_name::<M>());
         port
     }
 
@@ -153,10 +224,9 @@ impl<X: Transport + Default + 'static> System<X> {
         let port = OutputPort::new(self);
         let state = port.state.clone();
         let id = state.read().id;
-        self.connection_config
-            .borrow_mut()
-            .outputs
-            .insert(id, state);
+        let mut connection_config = self.connection_config.borrow_mut();
+        connection_config.outputs.insert(id, state);
+        connection_config.types.insert(id.into(), type_name::<M>());
         port
     }
 
@@ -192,17 +262,198 @@ impl<X: Transport + Default + 'static> System<X> {
     }
 
     pub fn connect<M: Message>(&self, source: &OutputPort<M>, target: &InputPort<M>) -> bool {
-        self.connect_by_id(source.id(), target.id()).unwrap()
+        self.connect_with(source, target, ConnectionOptions::default())
+    }
+
+    pub fn connect_with<M: Message>(
+        &self,
+        source: &OutputPort<M>,
+        target: &InputPort<M>,
+        options: ConnectionOptions,
+    ) -> bool {
+        self.connect_by_id_with(source.id(), target.id(), options)
+            .unwrap()
     }
 
     #[doc(hidden)]
     pub fn connect_by_id(&self, source_id: PortID, target_id: PortID) -> PortResult<bool> {
-        self.connection_config.borrow_mut().connections.insert((
-            OutputPortID(source_id.into()),
-            InputPortID(target_id.into()),
-        ));
+        self.connect_by_id_with(source_id, target_id, ConnectionOptions::default())
+    }
+
+    #[doc(hidden)]
+    pub fn connect_by_id_with(
+        &self,
+        source_id: PortID,
+        target_id: PortID,
+        options: ConnectionOptions,
+    ) -> PortResult<bool> {
+        self.connection_config.borrow_mut().connections.insert(
+            (
+                OutputPortID(source_id.into()),
+                InputPortID(target_id.into()),
+            ),
+            options,
+        );
         Ok(true)
     }
+
+    /// Describes the full topology of the system.
+    ///
+    /// The description covers the blocks with their parameters and ports,
+    /// the connections between ports, and any system-boundary ports that
+    /// are not owned by a block.
+    pub fn describe(&self) -> SystemDescription {
+        let connection_config = self.connection_config.borrow();
+
+        let blocks: Vec<BlockDescription> = self
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(block_id, block)| BlockDescription::new(block_id, block.as_block_descriptor()))
+            .collect();
+
+        let block_ports: BTreeSet<PortID> = blocks
+            .iter()
+            .flat_map(|block| block.ports())
+            .map(|port| port.id)
+            .collect();
+
+        // Once the system is prepared, its ports have the IDs given to them
+        // by the transport, rather than those they were created with:
+        let input_id = |id: &InputPortID| {
+            connection_config
+                .inputs
+                .get(id)
+                .map_or(*id, |state| state.read().id)
+        };
+        let output_id = |id: &OutputPortID| {
+            connection_config
+                .outputs
+                .get(id)
+                .map_or(*id, |state| state.read().id)
+        };
+
+        let boundary_port =
+            |system_id: PortID, id: PortID, direction: PortDirection, state| PortDescription {
+                id,
+                direction,
+                name: None,
+                label: None,
+                r#type: connection_config
+                    .types
+                    .get(&system_id)
+                    .map(|s| s.to_string()),
+                state,
+            };
+
+        let inputs = connection_config
+            .inputs
+            .iter()
+            .filter(|(id, _)| !block_ports.contains(&input_id(id).into()))
+            .map(|(&id, state)| {
+                let state = state.read();
+                boundary_port(
+                    id.into(),
+                    state.id.into(),
+                    PortDirection::Input,
+                    state.state(),
+                )
+            })
+            .collect();
+
+        let outputs = connection_config
+            .outputs
+            .iter()
+            .filter(|(id, _)| !block_ports.contains(&output_id(id).into()))
+            .map(|(&id, state)| {
+                let state = state.read();
+                boundary_port(
+                    id.into(),
+                    state.id.into(),
+                    PortDirection::Output,
+                    state.state(),
+                )
+            })
+            .collect();
+
+        let connections = connection_config
+            .connections
+            .iter()
+            .map(|(&(source, target), &options)| ConnectionDescription {
+                source: output_id(&source),
+                target: input_id(&target),
+                options,
+            })
+            .collect();
+
+        SystemDescription {
+            blocks,
+            connections,
+            inputs,
+            outputs,
+        }
+    }
+
+    /// Enables checkpoints of the system's block state, written to the given
+    /// store.
+    ///
+    /// Returns the coordinator for triggering checkpoints once the system is
+    /// executing.
+    pub fn enable_checkpoints(
+        &mut self,
+        store: Arc<dyn CheckpointStore>,
+    ) -> BlockResult<Arc<CheckpointCoordinator>> {
+        let coordinator = Arc::new(CheckpointCoordinator::new(store)?);
+        self.checkpoints = Some(coordinator.clone());
+        Ok(coordinator)
+    }
+
+    /// Restores the state of the system's blocks from the given checkpoint.
+    ///
+    /// This must be called before the system is executed.
+    pub fn restore(&self, checkpoint: &Checkpoint) -> BlockResult {
+        for (&block_id, snapshot) in checkpoint.blocks.iter() {
+            let Some(block) = self.blocks.get(block_id as usize) else {
+                return Err(BlockError::Other(format!(
+                    "checkpoint #{} refers to unknown block #{}",
+                    checkpoint.id, block_id
+                )));
+            };
+            for (name, handle) in block.state_handles() {
+                if let Some(state) = snapshot.state.get(name) {
+                    handle.load(state).map_err(PortError::from)?;
+                }
+            }
+            // The in-flight messages are received before any further ones:
+            let inputs = block.as_block_descriptor().inputs();
+            for (&index, input) in snapshot.inputs.iter() {
+                let port = inputs.get(index as usize).and_then(|port| match port.id {
+                    PortID::Input(id) => self.connection_config.borrow().inputs.get(&id).cloned(),
+                    PortID::Output(_) => None,
+                });
+                let Some(port) = port else {
+                    return Err(BlockError::Other(format!(
+                        "checkpoint #{} refers to unknown input #{} of block #{}",
+                        checkpoint.id, index, block_id
+                    )));
+                };
+                let mut port = port.write();
+                port.buffered.clear();
+                port.buffered.extend(input.messages.iter().cloned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores the state of the system's blocks from the latest checkpoint
+    /// in the given store, if any, returning its identifier.
+    pub fn restore_latest(&self, store: &dyn CheckpointStore) -> BlockResult<Option<CheckpointID>> {
+        let Some(checkpoint) = store.latest()? else {
+            return Ok(None);
+        };
+        self.restore(&checkpoint)?;
+        Ok(Some(checkpoint.id))
+    }
 }
 
 impl SystemBuilding for System {
@@ -227,25 +478,46 @@ impl SystemBuilding for System {
         System::connect(self, source, target)
     }
 
+    fn connect_with<M: Message>(
+        &mut self,
+        source: &OutputPort<M>,
+        target: &InputPort<M>,
+        options: ConnectionOptions,
+    ) -> bool {
+        System::connect_with(self, source, target, options)
+    }
+
     fn validate(&self) -> BlockResult<()> {
         Ok(()) // TODO
     }
 }
 
-impl SystemExecution for System {
+impl<X: Transport + Default + 'static> SystemExecution for System<X> {
     fn prepare(&self) -> BlockResult<()> {
         // Prepare opens ports in the runtime's transport and connects them
-        // according to `self.connection_config`.
+        // according to `self.connection_config`, once.
+        if self.is_prepared.replace(true) {
+            return Ok(());
+        }
 
         let connection_config = self.connection_config.borrow();
 
+        // The system port IDs of each block, before they are replaced by transport port IDs.
+        let block_ports: Vec<Vec<PortID>> = self
+            .blocks
+            .iter()
+            .map(|block| {
+                let block = block.as_block_descriptor();
+                block.ports().iter().map(|port| port.id).collect()
+            })
+            .collect();
+
         // A map to go from the pre-created system port IDs to the actual transport port IDs.
         let mut output_port_system_to_transport_id = BTreeMap::new();
 
         // Open output ports in transport
         for (system_id, state) in connection_config.outputs.iter() {
             let transport_id = self
-                .runtime
                 .transport
                 .open_output()
                 .map_err(BlockError::PortError)?;
@@ -256,7 +528,7 @@ impl SystemExecution for System {
             // Update the port's state with the transport port ID.
             state.id = transport_id;
             // And give the port access to the transport.
-            state.connection = OutputPortConnection::Running(self.runtime.transport.clone());
+            state.connection = OutputPortConnection::Running(self.transport.clone());
         }
 
         // A map to go from the pre-created system port IDs to the actual transport port IDs.
@@ -264,11 +536,7 @@ impl SystemExecution for System {
 
         // Open input ports in transport.
         for (system_id, state) in connection_config.inputs.iter() {
-            let transport_id = self
-                .runtime
-                .transport
-                .open_input()
-                .map_err(BlockError::PortError)?;
+            let transport_id = self.transport.open_input().map_err(BlockError::PortError)?;
 
             input_port_system_to_transport_id.insert(system_id, transport_id);
 
@@ -276,11 +544,11 @@ impl SystemExecution for System {
             // Update the port's state with the transport port ID.
             state.id = transport_id;
             // And give the port access to the transport.
-            state.connection = InputPortConnection::Running(self.runtime.transport.clone());
+            state.connection = InputPortConnection::Running(self.transport.clone());
         }
 
         // Connect all the ports.
-        for (system_out_id, system_in_id) in connection_config.connections.clone() {
+        for (&(system_out_id, system_in_id), &options) in connection_config.connections.iter() {
             let transport_out_id = output_port_system_to_transport_id.get(&system_out_id);
             let transport_in_id = input_port_system_to_transport_id.get(&system_in_id);
 
@@ -294,15 +562,63 @@ impl SystemExecution for System {
                 ));
             };
 
-            self.runtime
-                .transport
-                .connect(transport_out_id, transport_in_id)
+            self.transport
+                .connect_with(transport_out_id, transport_in_id, options)
                 .map_err(BlockError::PortError)?;
         }
 
+        // Install the checkpoint barrier handling for each block.
+        if let Some(coordinator) = &self.checkpoints {
+            coordinator.set_block_count(self.blocks.len());
+            for (block_id, (block, ports)) in self.blocks.iter().zip(block_ports).enumerate() {
+                let inputs = ports
+                    .iter()
+                    .filter_map(|port| match port {
+                        PortID::Input(id) => connection_config.inputs.get(id).cloned(),
+                        PortID::Output(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+                let outputs = ports
+                    .iter()
+                    .filter_map(|port| match port {
+                        PortID::Output(id) => connection_config.outputs.get(id).cloned(),
+                        PortID::Input(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+                let checkpointer = Arc::new(BlockCheckpointer::new(
+                    block_id,
+                    coordinator.clone(),
+                    block.state_handles(),
+                    inputs,
+                    outputs.clone(),
+                ));
+                for output in outputs {
+                    output.write().checkpointer = Some(checkpointer.clone());
+                }
+                for port in ports.iter() {
+                    let PortID::Input(system_id) = port else {
+                        continue;
+                    };
+                    let Some(input) = connection_config.inputs.get(system_id) else {
+                        continue;
+                    };
+                    let is_connected = connection_config
+                        .connections
+                        .keys()
+                        .any(|(_, target)| target == system_id);
+                    let mut input = input.write();
+                    if is_connected {
+                        checkpointer.add_input(input.id);
+                    }
+                    input.checkpointer = Some(checkpointer.clone());
+                }
+            }
+        }
+
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     fn execute(self) -> BlockResult<Rc<dyn Process>> {
         SystemExecution::prepare(&self)?;
         self.execute()
@@ -0,0 +1,162 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{String, ToString, Vec},
+    BlockDescriptor, BlockID, ConnectionOptions, InputPortID, OutputPortID, ParameterDescriptor,
+    PortDescriptor, PortDirection, PortID, PortState,
+};
+
+/// A description of a system's full topology.
+///
+/// This is a snapshot of the blocks, ports, and connections of a system,
+/// suitable for serialization, diffing, and visualization.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemDescription {
+    /// The blocks instantiated in the system.
+    pub blocks: Vec<BlockDescription>,
+
+    /// The connections between ports in the system.
+    pub connections: Vec<ConnectionDescription>,
+
+    /// The system-boundary input ports, which are not owned by any block.
+    pub inputs: Vec<PortDescription>,
+
+    /// The system-boundary output ports, which are not owned by any block.
+    pub outputs: Vec<PortDescription>,
+}
+
+impl SystemDescription {
+    /// Returns the block that owns the given port, if any.
+    pub fn block_of(&self, port: PortID) -> Option<&BlockDescription> {
+        self.blocks
+            .iter()
+            .find(|block| block.ports().any(|block_port| block_port.id == port))
+    }
+
+    /// Returns the description of the given port, if it is known.
+    pub fn port(&self, port: PortID) -> Option<&PortDescription> {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.ports())
+            .chain(self.inputs.iter())
+            .chain(self.outputs.iter())
+            .find(|description| description.id == port)
+    }
+}
+
+/// A description of a block instantiated in a system.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockDescription {
+    /// The identifier of this block within its system.
+    pub id: BlockID,
+
+    /// The machine-readable name of this block, if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub name: Option<String>,
+
+    /// A human-readable label for this block, if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub label: Option<String>,
+
+    /// The parameters of this block.
+    pub parameters: Vec<ParameterDescriptor>,
+
+    /// The input ports of this block.
+    pub inputs: Vec<PortDescription>,
+
+    /// The output ports of this block.
+    pub outputs: Vec<PortDescription>,
+}
+
+impl BlockDescription {
+    /// Describes the given block, identified by `id` within its system.
+    pub fn new(id: BlockID, block: &dyn BlockDescriptor) -> Self {
+        Self {
+            id,
+            name: block.name().map(|s| s.to_string()),
+            label: block.label().map(|s| s.to_string()),
+            parameters: block.parameters(),
+            inputs: block.inputs().into_iter().map(Into::into).collect(),
+            outputs: block.outputs().into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Iterates over all input and output ports of this block.
+    pub fn ports(&self) -> impl Iterator<Item = &PortDescription> {
+        self.inputs.iter().chain(self.outputs.iter())
+    }
+}
+
+/// A description of a port in a system.
+///
+/// Unlike [`PortDescriptor`], this always includes the port's identifier
+/// and current state when serialized.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortDescription {
+    /// The unique identifier for this port.
+    pub id: PortID,
+
+    /// The dataflow direction of this port.
+    pub direction: PortDirection,
+
+    /// The machine-readable name of this port, if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub name: Option<String>,
+
+    /// A human-readable label for this port, if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub label: Option<String>,
+
+    /// The data type for messages on this port.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub r#type: Option<String>,
+
+    /// The current state of this port.
+    pub state: PortState,
+}
+
+impl From<PortDescriptor> for PortDescription {
+    fn from(port: PortDescriptor) -> Self {
+        Self {
+            id: port.id,
+            direction: port.direction,
+            name: port.name,
+            label: port.label,
+            r#type: port.r#type,
+            state: port.state,
+        }
+    }
+}
+
+/// A description of a connection between two ports in a system.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConnectionDescription {
+    /// The output port that messages are sent from.
+    pub source: OutputPortID,
+
+    /// The input port that messages are received on.
+    pub target: InputPortID,
+
+    /// The options given for this connection.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub options: ConnectionOptions,
+}
@@ -0,0 +1,293 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{fmt, format, FromStr, String, ToString, Vec},
+    BlockDescription, ConnectionDescription, PortDescription, PortDirection, PortID,
+    SystemDescription,
+};
+use core::fmt::Write;
+
+/// The diagram format to render a system graph in.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphFormat {
+    /// A Mermaid flowchart.
+    ///
+    /// See: https://mermaid.js.org/syntax/flowchart.html
+    #[default]
+    Mermaid,
+
+    /// A Graphviz DOT digraph.
+    ///
+    /// See: https://graphviz.org/doc/info/lang.html
+    Dot,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        use GraphFormat::*;
+        Ok(match input {
+            "mermaid" | "mmd" => Mermaid,
+            "dot" | "graphviz" | "gv" => Dot,
+            _ => return Err(String::from(input)),
+        })
+    }
+}
+
+impl fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use GraphFormat::*;
+        match self {
+            Mermaid => write!(f, "mermaid"),
+            Dot => write!(f, "dot"),
+        }
+    }
+}
+
+impl SystemDescription {
+    /// Renders this system as a diagram in the given format.
+    pub fn to_graph(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Mermaid => self.to_mermaid(),
+            GraphFormat::Dot => self.to_dot(),
+        }
+    }
+
+    /// Renders this system as a Mermaid flowchart.
+    ///
+    /// Blocks become nodes, system-boundary ports become rounded nodes, and
+    /// connections become edges annotated with their port names, message
+    /// type, and capacity.
+    pub fn to_mermaid(&self) -> String {
+        let mut output = String::from("flowchart LR\n");
+        for block in &self.blocks {
+            let _ = writeln!(
+                output,
+                "    {}[\"{}\"]",
+                block_node(block),
+                escape_mermaid(&block_title(block)),
+            );
+        }
+        for port in self.inputs.iter().chain(self.outputs.iter()) {
+            let _ = writeln!(
+                output,
+                "    {}([\"{}\"])",
+                port_node(port.id),
+                escape_mermaid(&port_title(port)),
+            );
+        }
+        for connection in &self.connections {
+            let (source, target) = self.endpoints(connection);
+            let mut label = format!(
+                "{} → {}",
+                source.map(port_name).unwrap_or_default(),
+                target.map(port_name).unwrap_or_default(),
+            );
+            if let Some(annotation) = edge_annotation(connection, source) {
+                label.push_str("<br>");
+                label.push_str(&annotation);
+            }
+            let _ = writeln!(
+                output,
+                "    {} -->|\"{}\"| {}",
+                self.node_of(connection.source.into()),
+                escape_mermaid(&label),
+                self.node_of(connection.target.into()),
+            );
+        }
+        output
+    }
+
+    /// Renders this system as a Graphviz DOT digraph.
+    ///
+    /// Blocks become record nodes with one field per port, so that edges
+    /// attach to the exact ports they connect. Edges are annotated with
+    /// their message type and capacity.
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph system {\n");
+        output.push_str("    rankdir=LR;\n");
+        output.push_str("    node [shape=record];\n");
+        for block in &self.blocks {
+            let mut fields = String::new();
+            if !block.inputs.is_empty() {
+                let _ = write!(fields, "{{{}}}|", dot_port_fields(&block.inputs));
+            }
+            fields.push_str(&escape_dot_record(&block_title(block)));
+            if !block.outputs.is_empty() {
+                let _ = write!(fields, "|{{{}}}", dot_port_fields(&block.outputs));
+            }
+            let _ = writeln!(
+                output,
+                "    {} [label=\"{{{}}}\"];",
+                block_node(block),
+                fields
+            );
+        }
+        for port in self.inputs.iter().chain(self.outputs.iter()) {
+            let _ = writeln!(
+                output,
+                "    {} [shape=ellipse, label=\"{}\"];",
+                port_node(port.id),
+                escape_dot(&port_title(port)),
+            );
+        }
+        for connection in &self.connections {
+            let (source, _) = self.endpoints(connection);
+            let _ = write!(
+                output,
+                "    {} -> {}",
+                self.dot_endpoint(connection.source.into()),
+                self.dot_endpoint(connection.target.into()),
+            );
+            if let Some(annotation) = edge_annotation(connection, source) {
+                let _ = write!(output, " [label=\"{}\"]", escape_dot(&annotation));
+            }
+            output.push_str(";\n");
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    fn endpoints(
+        &self,
+        connection: &ConnectionDescription,
+    ) -> (Option<&PortDescription>, Option<&PortDescription>) {
+        (
+            self.port(connection.source.into()),
+            self.port(connection.target.into()),
+        )
+    }
+
+    fn node_of(&self, port: PortID) -> String {
+        match self.block_of(port) {
+            Some(block) => block_node(block),
+            None => port_node(port),
+        }
+    }
+
+    fn dot_endpoint(&self, port: PortID) -> String {
+        match self.block_of(port) {
+            Some(block) => format!("{}:{}", block_node(block), port_node(port)),
+            None => port_node(port),
+        }
+    }
+}
+
+fn block_node(block: &BlockDescription) -> String {
+    format!("block{}", block.id)
+}
+
+fn port_node(port: PortID) -> String {
+    match port {
+        PortID::Input(id) => format!("input{}", id.index()),
+        PortID::Output(id) => format!("output{}", id.index()),
+    }
+}
+
+fn block_title(block: &BlockDescription) -> String {
+    match (&block.label, &block.name) {
+        (Some(label), Some(name)) => format!("{}: {}", label, name),
+        (Some(label), None) => label.clone(),
+        (None, Some(name)) => name.clone(),
+        (None, None) => format!("block{}", block.id),
+    }
+}
+
+fn port_name(port: &PortDescription) -> String {
+    port.name.clone().unwrap_or_else(|| port_node(port.id))
+}
+
+fn port_title(port: &PortDescription) -> String {
+    let name = port.name.clone().unwrap_or_else(|| match port.direction {
+        PortDirection::Input => "input".to_string(),
+        PortDirection::Output => "output".to_string(),
+    });
+    match &port.r#type {
+        Some(r#type) => format!("{}: {}", name, short_type_name(r#type)),
+        None => name,
+    }
+}
+
+fn edge_annotation(
+    connection: &ConnectionDescription,
+    source: Option<&PortDescription>,
+) -> Option<String> {
+    let r#type = source
+        .and_then(|port| port.r#type.as_deref())
+        .map(short_type_name);
+    let options = [
+        connection
+            .options
+            .capacity
+            .map(|capacity| format!("capacity={}", capacity)),
+        connection
+            .options
+            .priority
+            .map(|priority| format!("priority={}", priority)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ");
+    match (r#type, options.is_empty()) {
+        (Some(r#type), false) => Some(format!("{} ({})", r#type, options)),
+        (Some(r#type), true) => Some(r#type),
+        (None, false) => Some(options),
+        (None, true) => None,
+    }
+}
+
+fn dot_port_fields(ports: &[PortDescription]) -> String {
+    let mut fields = String::new();
+    for (index, port) in ports.iter().enumerate() {
+        if index > 0 {
+            fields.push('|');
+        }
+        let _ = write!(
+            fields,
+            "<{}> {}",
+            port_node(port.id),
+            escape_dot_record(&port_name(port))
+        );
+    }
+    fields
+}
+
+/// Strips module paths from a fully-qualified type name, such that e.g.
+/// `alloc::vec::Vec<alloc::string::String>` becomes `Vec<String>`.
+fn short_type_name(type_name: &str) -> String {
+    let mut output = String::with_capacity(type_name.len());
+    let mut segment_start = 0;
+    for c in type_name.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            output.push(c);
+        } else if c == ':' {
+            output.truncate(segment_start);
+        } else {
+            output.push(c);
+            segment_start = output.len();
+        }
+    }
+    output
+}
+
+fn escape_mermaid(input: &str) -> String {
+    input.replace('"', "#quot;")
+}
+
+fn escape_dot(input: &str) -> String {
+    input.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_dot_record(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output
+}
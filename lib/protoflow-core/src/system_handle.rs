@@ -0,0 +1,254 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{format, Arc, BTreeMap, BTreeSet, Box, Rc, Vec},
+    runtimes::StdRuntime,
+    transports::MpscTransport,
+    Block, BlockError, BlockID, BlockResult, BoxedBlockType, ConnectionOptions, InputPort,
+    InputPortConnection, InputPortID, Message, OutputPort, OutputPortConnection, OutputPortID,
+    PortError, PortID, PortResult, Process, Runtime, System, Transport,
+};
+
+/// A control handle on a running system, for reconfiguring its topology
+/// without a restart.
+///
+/// Blocks can be added to and removed from the running system, and ports
+/// connected and disconnected, through the runtime's transport.
+///
+/// Ports for new blocks are created with [`SystemHandle::input`] and
+/// [`SystemHandle::output`]. An input port that is not yet connected reads
+/// as end of stream, so connect a new block's inputs before adding it.
+pub struct SystemHandle<X: Transport + Default + 'static = MpscTransport> {
+    system: System<X>,
+    blocks: BTreeMap<BlockID, RunningBlock>,
+    connections: BTreeSet<(OutputPortID, InputPortID)>,
+    next_block_id: BlockID,
+}
+
+struct RunningBlock {
+    process: Rc<dyn Process>,
+    inputs: Vec<InputPortID>,
+    outputs: Vec<OutputPortID>,
+}
+
+impl<X: Transport + Default + 'static> SystemHandle<X> {
+    /// Executes the blocks of an already prepared system.
+    pub(crate) fn start(mut system: System<X>) -> BlockResult<Self> {
+        let connections = {
+            let connection_config = system.connection_config.borrow();
+            connection_config
+                .connections
+                .keys()
+                .filter_map(|(source, target)| {
+                    let source = connection_config.outputs.get(source)?.read().id;
+                    let target = connection_config.inputs.get(target)?.read().id;
+                    Some((source, target))
+                })
+                .collect()
+        };
+        let mut handle = Self {
+            blocks: BTreeMap::new(),
+            connections,
+            next_block_id: system.blocks.len(),
+            system: System::new(&system.runtime),
+        };
+        handle.system.connection_config = core::mem::take(&mut system.connection_config);
+        handle.system.checkpoints = system.checkpoints.take();
+        for (block_id, block) in system.blocks.drain(..).enumerate() {
+            handle.spawn(block_id, block)?;
+        }
+        Ok(handle)
+    }
+
+    /// Returns the transport of the running system.
+    pub fn transport(&self) -> &Arc<X> {
+        &self.system.transport
+    }
+
+    /// Creates a new input port, opened in the running system's transport.
+    pub fn input<M: Message + 'static>(&self) -> PortResult<InputPort<M>> {
+        let port = self.system.input();
+        let mut state = port.state.write();
+        state.id = self.transport().open_input()?;
+        state.connection = InputPortConnection::Running(self.transport().clone());
+        drop(state);
+        Ok(port)
+    }
+
+    /// Creates a new output port, opened in the running system's transport.
+    pub fn output<M: Message + 'static>(&self) -> PortResult<OutputPort<M>> {
+        let port = self.system.output();
+        let mut state = port.state.write();
+        state.id = self.transport().open_output()?;
+        state.connection = OutputPortConnection::Running(self.transport().clone());
+        drop(state);
+        Ok(port)
+    }
+
+    /// Adds a block to the running system and starts executing it.
+    ///
+    /// The block's ports must have been created with this handle.
+    pub fn add_block<B: Block + Clone + 'static>(&mut self, block: B) -> BlockResult<BlockID> {
+        let block_id = self.next_block_id;
+        self.spawn(block_id, BoxedBlockType::Normal(Box::new(block)))?;
+        Ok(block_id)
+    }
+
+    /// Removes a block from the running system, once it has drained its
+    /// inputs.
+    ///
+    /// The outputs connected to the block's inputs are disconnected, so that
+    /// the block processes all messages already sent to it before it reaches
+    /// end of stream. A block without connected inputs has its outputs
+    /// closed instead. Either way, this waits for the block to finish, and
+    /// then closes all its outputs.
+    ///
+    /// The upstream blocks keep running, and their disconnected outputs can
+    /// be connected again; until then, sending on them fails with
+    /// [`PortError::Disconnected`].
+    ///
+    /// Any blocks downstream of the removed block reach end of stream on
+    /// their inputs, unless other outputs remain connected to them.
+    pub fn remove_block(&mut self, block_id: BlockID) -> BlockResult {
+        let Some(block) = self.blocks.remove(&block_id) else {
+            return Err(BlockError::Other(format!("unknown block #{}", block_id)));
+        };
+
+        let upstream: Vec<_> = self
+            .connections
+            .iter()
+            .filter(|(_, target)| block.inputs.contains(target))
+            .copied()
+            .collect();
+        // Outputs that nothing receives from are closed right away, as are
+        // all outputs of a block that has no inputs to drain:
+        let idle_outputs: Vec<_> = block
+            .outputs
+            .iter()
+            .filter(|&&output| {
+                upstream.is_empty() || !self.connections.iter().any(|&(source, _)| source == output)
+            })
+            .copied()
+            .collect();
+        self.close_outputs(&idle_outputs)?;
+        for (source, target) in upstream {
+            self.disconnect_by_id(source, target)?;
+        }
+
+        let result = block.process.join();
+        self.close_outputs(&block.outputs)?;
+        self.connections
+            .retain(|(source, _)| !block.outputs.contains(source));
+
+        match result {
+            Ok(()) | Err(BlockError::PortError(PortError::Closed | PortError::Disconnected)) => {
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Connects two ports in the running system.
+    pub fn connect<M: Message>(
+        &mut self,
+        source: &OutputPort<M>,
+        target: &InputPort<M>,
+    ) -> PortResult<bool> {
+        self.connect_with(source, target, ConnectionOptions::default())
+    }
+
+    /// Connects two ports in the running system, using the given connection
+    /// options.
+    pub fn connect_with<M: Message>(
+        &mut self,
+        source: &OutputPort<M>,
+        target: &InputPort<M>,
+        options: ConnectionOptions,
+    ) -> PortResult<bool> {
+        let source = source.state.read().id;
+        let target = target.state.read().id;
+        self.connect_by_id_with(source, target, options)
+    }
+
+    #[doc(hidden)]
+    pub fn connect_by_id_with(
+        &mut self,
+        source: OutputPortID,
+        target: InputPortID,
+        options: ConnectionOptions,
+    ) -> PortResult<bool> {
+        let result = self.transport().connect_with(source, target, options)?;
+        self.connections.insert((source, target));
+        Ok(result)
+    }
+
+    /// Disconnects two ports in the running system.
+    ///
+    /// The source port is detached but not closed, so that it can be
+    /// connected again, and the target port reaches end of stream once no
+    /// other outputs remain connected to it.
+    pub fn disconnect<M: Message>(
+        &mut self,
+        source: &OutputPort<M>,
+        target: &InputPort<M>,
+    ) -> PortResult<bool> {
+        let source = source.state.read().id;
+        let target = target.state.read().id;
+        self.disconnect_by_id(source, target)
+    }
+
+    #[doc(hidden)]
+    pub fn disconnect_by_id(
+        &mut self,
+        source: OutputPortID,
+        target: InputPortID,
+    ) -> PortResult<bool> {
+        if !self.connections.remove(&(source, target)) {
+            return Ok(false);
+        }
+        self.transport().disconnect(source)
+    }
+
+    /// Checks whether any block in the system is still executing.
+    pub fn is_alive(&self) -> bool {
+        self.blocks.values().any(|block| block.process.is_alive())
+    }
+
+    /// Waits for all blocks in the system to finish executing.
+    pub fn join(self) -> BlockResult {
+        for block in self.blocks.values() {
+            block.process.join()?;
+        }
+        Ok(())
+    }
+
+    fn spawn(&mut self, block_id: BlockID, block: BoxedBlockType) -> BlockResult {
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        for port in block.as_block_descriptor().ports() {
+            match port.id {
+                PortID::Input(id) => inputs.push(id),
+                PortID::Output(id) => outputs.push(id),
+            }
+        }
+        let mut runtime: Arc<StdRuntime<X>> = self.system.runtime.clone();
+        let process = runtime.execute_block(block)?;
+        self.blocks.insert(
+            block_id,
+            RunningBlock {
+                process,
+                inputs,
+                outputs,
+            },
+        );
+        self.next_block_id = self.next_block_id.max(block_id + 1);
+        Ok(())
+    }
+
+    fn close_outputs(&self, outputs: &[OutputPortID]) -> PortResult<()> {
+        for &output in outputs {
+            self.transport().close(PortID::Output(output))?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,20 @@
 // This is free and unencumbered software released into the public domain.
 
-use crate::{prelude::Bytes, InputPortID, OutputPortID, PortID, PortResult, PortState};
+use crate::{
+    prelude::{Bytes, String},
+    CheckpointID, ConnectionOptions, InputPortID, OutputPortID, PortError, PortID, PortResult,
+    PortState, Priority,
+};
+
+/// An event received on an input port.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransportEvent {
+    /// An encoded message.
+    Message(Bytes),
+
+    /// A checkpoint barrier marker, flowing in-band with messages.
+    Barrier(CheckpointID),
+}
 
 #[allow(unused)]
 pub trait Transport: AsTransport + Send + Sync {
@@ -26,10 +40,59 @@ pub trait Transport: AsTransport + Send + Sync {
 
     fn close_input(&self, input: InputPortID) -> PortResult<bool>;
     fn close_output(&self, output: OutputPortID) -> PortResult<bool>;
+
+    /// Detaches an output port from its connected input, without closing it.
+    ///
+    /// The input reaches end of stream after the messages already sent, once
+    /// no other outputs remain connected to it. Sends on the output fail with
+    /// [`PortError::Disconnected`] until it is connected again.
+    fn disconnect(&self, _output: OutputPortID) -> PortResult<bool> {
+        Err(PortError::Other(String::from(
+            "disconnecting ports is not supported by this transport",
+        )))
+    }
+
     fn connect(&self, source: OutputPortID, target: InputPortID) -> PortResult<bool>;
+
+    fn connect_with(
+        &self,
+        source: OutputPortID,
+        target: InputPortID,
+        _options: ConnectionOptions,
+    ) -> PortResult<bool> {
+        self.connect(source, target)
+    }
+
     fn send(&self, output: OutputPortID, message: Bytes) -> PortResult<()>;
+
+    /// Sends a message with the given priority, overriding the connection's
+    /// default priority.
+    ///
+    /// Transports that don't support priorities send the message in FIFO
+    /// order.
+    fn send_with_priority(
+        &self,
+        output: OutputPortID,
+        message: Bytes,
+        _priority: Priority,
+    ) -> PortResult<()> {
+        self.send(output, message)
+    }
     fn recv(&self, input: InputPortID) -> PortResult<Option<Bytes>>;
     fn try_recv(&self, input: InputPortID) -> PortResult<Option<Bytes>>;
+
+    /// Sends a checkpoint barrier marker, in order after any messages
+    /// previously sent on the output port.
+    fn send_barrier(&self, _output: OutputPortID, _checkpoint: CheckpointID) -> PortResult<()> {
+        Err(PortError::Other(String::from(
+            "checkpoint barriers are not supported by this transport",
+        )))
+    }
+
+    /// Receives the next message or checkpoint barrier on the input port.
+    fn recv_event(&self, input: InputPortID) -> PortResult<Option<TransportEvent>> {
+        Ok(self.recv(input)?.map(TransportEvent::Message))
+    }
 }
 
 pub trait AsTransport {
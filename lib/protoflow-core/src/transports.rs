@@ -1,9 +1,14 @@
 // This is free and unencumbered software released into the public domain.
 
 #[cfg(feature = "std")]
-pub type MockTransport = MpscTransport;
+mod mock;
+#[cfg(feature = "std")]
+pub use mock::*;
 
 #[cfg(feature = "std")]
 mod mpsc;
 #[cfg(feature = "std")]
 pub use mpsc::*;
+
+mod static_buffer;
+pub use static_buffer::*;
@@ -3,219 +3,497 @@
 extern crate std;
 
 use crate::{
-    prelude::{vec, Bytes, ToString, Vec},
-    transport::Transport,
-    InputPortID, MessageBuffer, OutputPortID, PortError, PortID, PortResult, PortState,
+    prelude::{BTreeMap, Bytes, Duration, Vec, VecDeque},
+    transport::{Transport, TransportEvent},
+    CheckpointID, ConnectionOptions, InputPortID, Message, OutputPortID, PortError, PortID,
+    PortResult, PortState, Priority,
 };
-use parking_lot::RwLock;
-use std::sync::{Condvar, Mutex};
+use parking_lot::{Condvar, Mutex};
 
+/// An in-memory transport for testing blocks, which can be inspected and
+/// manipulated from tests.
+///
+/// Every input port has an inbox of pending messages, which tests can
+/// inspect and push messages into. Every message sent on an output port is
+/// recorded, and faults can be injected per port: failing sends, forced
+/// disconnects, and send delays.
+///
+/// Like other transports, inboxes deliver higher-priority messages first,
+/// in FIFO order within each priority. Barriers fence every priority level:
+/// a barrier is received only after all messages sent before it, and before
+/// all messages sent after it, whatever their priorities.
+///
+/// Inputs that are not connected read as end of stream once their inbox
+/// has been drained, so a block can be executed to completion on a single
+/// thread by pushing its input messages up front. Receiving on a closed
+/// input fails.
 #[derive(Debug, Default)]
 pub struct MockTransport {
-    pub state: RwLock<MockTransportState>,
-    pub(crate) wakeup: (Mutex<bool>, Condvar),
+    state: Mutex<MockTransportState>,
+    wakeup: Condvar,
 }
 
 #[derive(Debug, Default)]
-pub struct MockTransportState {
-    outputs: Vec<PortState>,
-    inputs: Vec<PortState>,
-    inboxes: Vec<MessageBuffer>,
+struct MockTransportState {
+    inputs: Vec<MockInputPort>,
+    outputs: Vec<MockOutputPort>,
+    sent: Vec<MockTransportRecord>,
+}
+
+#[derive(Debug, Default)]
+struct MockInputPort {
+    state: PortState,
+    inbox: MockInbox,
+}
+
+/// Pending events, in FIFO order within each priority, with their sequence
+/// numbers.
+#[derive(Clone, Debug, Default)]
+struct MockInbox {
+    levels: BTreeMap<Priority, VecDeque<(u64, TransportEvent)>>,
+    barriers: VecDeque<(u64, TransportEvent)>,
+    next_seq: u64,
+}
+
+#[derive(Debug, Default)]
+struct MockOutputPort {
+    state: PortState,
+    target: Option<InputPortID>,
+    priority: Priority,
+    failure: Option<PortError>,
+    delay: Option<Duration>,
+}
+
+/// A record of a message sent through a [`MockTransport`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MockTransportRecord {
+    /// The output port the message was sent on.
+    pub output: OutputPortID,
+
+    /// The input port the message was delivered to.
+    pub input: InputPortID,
+
+    /// The priority the message was sent with.
+    pub priority: Priority,
+
+    /// The encoded message.
+    pub message: Bytes,
 }
 
 impl MockTransport {
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Instantiates a transport with the given number of already opened
+    /// input and output ports.
+    pub fn with_ports(inputs: usize, outputs: usize) -> Self {
+        let transport = Self::new();
+        {
+            let mut state = transport.state.lock();
+            state.inputs.resize_with(inputs, MockInputPort::open);
+            state.outputs.resize_with(outputs, MockOutputPort::open);
+        }
+        transport
+    }
+
+    /// Returns the current state of every port, keyed by port ID.
+    pub fn states(&self) -> BTreeMap<PortID, PortState> {
+        let state = self.state.lock();
+        let inputs = (0..state.inputs.len()).map(|index| {
+            let id = PortID::Input(input_id(index));
+            (id, state.inputs[index].state)
+        });
+        let outputs = (0..state.outputs.len()).map(|index| {
+            let id = PortID::Output(output_id(index));
+            (id, state.outputs[index].state)
+        });
+        inputs.chain(outputs).collect()
+    }
+
+    /// Returns the current connections, keyed by output port ID.
+    pub fn connections(&self) -> BTreeMap<OutputPortID, InputPortID> {
+        let state = self.state.lock();
+        (0..state.outputs.len())
+            .filter_map(|index| Some((output_id(index), state.outputs[index].target?)))
+            .collect()
+    }
+
+    /// Returns the pending encoded messages in an input port's inbox, in
+    /// the order they will be received.
+    pub fn inbox(&self, input: InputPortID) -> PortResult<Vec<Bytes>> {
+        let mut state = self.state.lock();
+        let input = state.input(input)?;
+        Ok(input
+            .inbox
+            .events()
+            .into_iter()
+            .filter_map(|event| match event {
+                TransportEvent::Message(message) => Some(message),
+                TransportEvent::Barrier(_) => None,
+            })
+            .collect())
+    }
+
+    /// Pushes an encoded message into an input port's inbox, as if it had
+    /// been sent to it.
+    pub fn push(&self, input: InputPortID, message: Bytes) -> PortResult<()> {
+        let mut state = self.state.lock();
+        let port = state.input(input)?;
+        if port.state.is_closed() {
+            return Err(PortError::Closed);
+        }
+        port.inbox
+            .push(Priority::MIN, TransportEvent::Message(message));
+        self.wakeup.notify_all();
+        Ok(())
+    }
+
+    /// Pushes a message into an input port's inbox, encoding it the same
+    /// way as `OutputPort::send()`.
+    pub fn push_message<M: Message>(&self, input: InputPortID, message: &M) -> PortResult<()> {
+        self.push(input, Bytes::from(message.encode_length_delimited_to_vec()))
+    }
+
+    /// Returns a record of every message sent through this transport, in
+    /// the order they were sent.
+    pub fn sent(&self) -> Vec<MockTransportRecord> {
+        self.state.lock().sent.clone()
+    }
+
+    /// Decodes the messages sent on an output port, in the order they were
+    /// sent.
+    pub fn sent_messages<M: Message>(&self, output: OutputPortID) -> PortResult<Vec<M>> {
+        self.state
+            .lock()
+            .sent
+            .iter()
+            .filter(|record| record.output == output)
+            .map(|record| Ok(M::decode_length_delimited(record.message.clone())?))
+            .collect()
+    }
+
+    /// Makes all further sends on an output port fail with the given error,
+    /// or clears a previously injected failure.
+    pub fn fail_sends(&self, output: OutputPortID, error: Option<PortError>) -> PortResult<()> {
+        self.state.lock().output(output)?.failure = error;
+        Ok(())
+    }
+
+    /// Delays all further sends on an output port by the given duration,
+    /// or clears a previously injected delay.
+    pub fn delay_sends(&self, output: OutputPortID, delay: Option<Duration>) -> PortResult<()> {
+        self.state.lock().output(output)?.delay = delay;
+        Ok(())
+    }
+
+    /// Forcibly disconnects a port from its peer, as if the connection had
+    /// been lost.
+    ///
+    /// Both ports are left open; the input port reads as end of stream once
+    /// its inbox has been drained, and sends on the output port fail.
+    pub fn disconnect(&self, port: PortID) -> PortResult<bool> {
+        let mut state = self.state.lock();
+        let (output, input) = match port {
+            PortID::Output(output) => (output, state.output(output)?.target),
+            PortID::Input(input) => {
+                state.input(input)?;
+                let output = (0..state.outputs.len())
+                    .map(output_id)
+                    .find(|&output| state.outputs[output.index()].target == Some(input));
+                let Some(output) = output else {
+                    return Ok(false);
+                };
+                (output, Some(input))
+            }
+        };
+        let Some(input) = input else {
+            return Ok(false);
+        };
+        let output = state.output(output)?;
+        output.state = PortState::Open;
+        output.target = None;
+        state.input(input)?.state = PortState::Open;
+        self.wakeup.notify_all();
+        Ok(true)
+    }
+
+    fn send_event(
+        &self,
+        output: OutputPortID,
+        event: TransportEvent,
+        priority: Option<Priority>,
+    ) -> PortResult<()> {
+        let delay = self.state.lock().output(output)?.delay;
+        if let Some(delay) = delay {
+            std::thread::sleep(delay);
+        }
+
+        let mut state = self.state.lock();
+        let port = state.output(output)?;
+        if let Some(error) = &port.failure {
+            return Err(error.clone());
+        }
+        let input = match (port.state, port.target) {
+            (PortState::Closed, _) => return Err(PortError::Closed),
+            (PortState::Connected, Some(input)) => input,
+            (PortState::Open | PortState::Connected, _) => return Err(PortError::Disconnected),
+        };
+        let priority = priority.unwrap_or(port.priority);
+        if let TransportEvent::Message(ref message) = event {
+            state.sent.push(MockTransportRecord {
+                output,
+                input,
+                priority,
+                message: message.clone(),
+            });
+        }
+        state.input(input)?.inbox.push(priority, event);
+        self.wakeup.notify_all();
+        Ok(())
+    }
+
+    fn close_connection(state: &mut MockTransportState, output: OutputPortID) -> PortResult<()> {
+        if let Some(input) = state.output(output)?.target.take() {
+            let input = state.input(input)?;
+            if input.state.is_connected() {
+                input.state = PortState::Open;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MockTransportState {
+    fn input(&mut self, input: InputPortID) -> PortResult<&mut MockInputPort> {
+        self.inputs
+            .get_mut(input.index())
+            .ok_or(PortError::Invalid(input.into()))
+    }
+
+    fn output(&mut self, output: OutputPortID) -> PortResult<&mut MockOutputPort> {
+        self.outputs
+            .get_mut(output.index())
+            .ok_or(PortError::Invalid(output.into()))
+    }
+}
+
+impl MockInbox {
+    fn push(&mut self, priority: Priority, event: TransportEvent) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        match event {
+            TransportEvent::Barrier(_) => self.barriers.push_back((seq, event)),
+            _ => self
+                .levels
+                .entry(priority)
+                .or_default()
+                .push_back((seq, event)),
+        }
+    }
+
+    /// Pops the highest-priority event sent before the first pending
+    /// barrier, or else that barrier.
+    fn pop(&mut self) -> Option<TransportEvent> {
+        let fence = self.barriers.front().map(|(seq, _)| *seq);
+        let priority = self
+            .levels
+            .iter()
+            .rev()
+            .find(|(_, level)| fence.map_or(true, |fence| level[0].0 < fence))
+            .map(|(&priority, _)| priority);
+        let Some(priority) = priority else {
+            return self.barriers.pop_front().map(|(_, event)| event);
+        };
+        let level = self.levels.get_mut(&priority).unwrap();
+        let (_, event) = level.pop_front().unwrap();
+        if level.is_empty() {
+            self.levels.remove(&priority);
+        }
+        Some(event)
+    }
+
+    /// Returns the pending events in the order they will be received.
+    fn events(&self) -> Vec<TransportEvent> {
+        let mut inbox = self.clone();
+        core::iter::from_fn(|| inbox.pop()).collect()
+    }
+
+    fn clear(&mut self) {
+        self.levels.clear();
+        self.barriers.clear();
+    }
+}
+
+impl MockInputPort {
+    fn open() -> Self {
         Self {
-            state: RwLock::new(MockTransportState::default()),
-            wakeup: (Mutex::new(false), Condvar::new()),
+            state: PortState::Open,
+            ..Default::default()
         }
     }
+}
 
-    pub fn with_ports(input: usize, output: usize) -> Self {
-        let mut inboxes = Vec::with_capacity(input);
-        inboxes.resize_with(input, MessageBuffer::new);
+impl MockOutputPort {
+    fn open() -> Self {
         Self {
-            state: RwLock::new(MockTransportState {
-                outputs: vec![PortState::Open; output],
-                inputs: vec![PortState::Open; input],
-                inboxes,
-            }),
-            wakeup: (Mutex::new(false), Condvar::new()),
+            state: PortState::Open,
+            ..Default::default()
         }
     }
 }
 
 impl Transport for MockTransport {
-    fn state(&self, port: PortID) -> PortResult<PortState> {
-        let state = self.state.read();
-        match port {
-            PortID::Input(inport) => match state.inputs.get(inport.index()) {
-                None => Err(PortError::Invalid(port)),
-                Some(state) => Ok(*state),
-            },
-            PortID::Output(outport) => match state.outputs.get(outport.index()) {
-                None => Err(PortError::Invalid(port)),
-                Some(state) => Ok(*state),
-            },
-        }
+    fn input_state(&self, input: InputPortID) -> PortResult<PortState> {
+        Ok(self.state.lock().input(input)?.state)
     }
 
-    fn open_input(&self) -> PortResult<InputPortID> {
-        let mut state = self.state.write();
-        state.inputs.push(PortState::Open);
-        state.inboxes.push(MessageBuffer::new());
+    fn output_state(&self, output: OutputPortID) -> PortResult<PortState> {
+        Ok(self.state.lock().output(output)?.state)
+    }
 
-        InputPortID::try_from(-(state.inputs.len() as isize))
-            .map_err(|s| PortError::Other(s.to_string()))
+    fn open_input(&self) -> PortResult<InputPortID> {
+        let mut state = self.state.lock();
+        state.inputs.push(MockInputPort::open());
+        Ok(input_id(state.inputs.len() - 1))
     }
 
     fn open_output(&self) -> PortResult<OutputPortID> {
-        let mut state = self.state.write();
-        state.outputs.push(PortState::Open);
-
-        OutputPortID::try_from(state.outputs.len() as isize)
-            .map_err(|s| PortError::Other(s.to_string()))
+        let mut state = self.state.lock();
+        state.outputs.push(MockOutputPort::open());
+        Ok(output_id(state.outputs.len() - 1))
     }
 
     fn close_input(&self, input: InputPortID) -> PortResult<bool> {
-        let input_index = input.index();
-        let mut state = self.state.upgradable_read();
-        Ok(match state.inputs.get(input_index) {
-            None => return Err(PortError::Invalid(input.into())),
-            Some(input_state) => match input_state {
-                PortState::Closed => false, // already closed
-                PortState::Open => {
-                    state.with_upgraded(|state| {
-                        state.inputs[input_index] = PortState::Closed;
-                    });
-                    true
-                }
-                PortState::Connected(PortID::Output(output)) => {
-                    let output_index = output.index();
-                    debug_assert!(matches!(
-                        state.outputs[output_index],
-                        PortState::Connected(PortID::Input(_))
-                    ));
-                    state.with_upgraded(|state| {
-                        state.outputs[output_index] = PortState::Open;
-                        state.inputs[input_index] = PortState::Closed;
-                        state.inboxes[input_index].clear();
-                    });
-                    true
-                }
-                PortState::Connected(PortID::Input(_)) => unreachable!(),
-            },
-        })
+        let mut state = self.state.lock();
+        let port = state.input(input)?;
+        if port.state.is_closed() {
+            return Ok(false); // already closed
+        }
+        port.state = PortState::Closed;
+        port.inbox.clear();
+        for output in state.outputs.iter_mut() {
+            if output.target == Some(input) {
+                output.state = PortState::Open;
+                output.target = None;
+            }
+        }
+        self.wakeup.notify_all();
+        Ok(true)
     }
 
     fn close_output(&self, output: OutputPortID) -> PortResult<bool> {
-        let output_index = output.index();
-        let mut state = self.state.upgradable_read();
-        Ok(match state.outputs.get(output_index) {
-            None => return Err(PortError::Invalid(output.into())),
-            Some(output_state) => match output_state {
-                PortState::Closed => false, // already closed
-                PortState::Open => {
-                    state.with_upgraded(|state| {
-                        state.outputs[output_index] = PortState::Closed;
-                    });
-                    true
-                }
-                PortState::Connected(PortID::Input(input)) => {
-                    let input = input.clone();
-                    let input_index = input.index();
-                    debug_assert!(matches!(
-                        state.inputs[input_index],
-                        PortState::Connected(PortID::Output(_))
-                    ));
-                    state.with_upgraded(|state| {
-                        state.outputs[output_index] = PortState::Closed;
-                        state.inputs[input_index] = PortState::Open;
-                    });
-                    drop(state);
-                    self.recv_notify(input); // wake up the receiving thread
-                    true
-                }
-                PortState::Connected(PortID::Output(_)) => unreachable!(),
-            },
-        })
+        let mut state = self.state.lock();
+        if state.output(output)?.state.is_closed() {
+            return Ok(false); // already closed
+        }
+        Self::close_connection(&mut state, output)?;
+        state.output(output)?.state = PortState::Closed;
+        self.wakeup.notify_all(); // wake up the receiving thread
+        Ok(true)
+    }
+
+    fn disconnect(&self, output: OutputPortID) -> PortResult<bool> {
+        let mut state = self.state.lock();
+        if !state.output(output)?.state.is_connected() {
+            return Ok(false); // not connected
+        }
+        Self::close_connection(&mut state, output)?;
+        state.output(output)?.state = PortState::Open;
+        self.wakeup.notify_all(); // wake up the receiving thread
+        Ok(true)
     }
 
     fn connect(&self, source: OutputPortID, target: InputPortID) -> PortResult<bool> {
-        let mut state = self.state.write();
-        match (
-            state.outputs.get(source.index()),
-            state.inputs.get(target.index()),
-        ) {
-            (Some(PortState::Open), Some(PortState::Open)) => {
-                state.outputs[source.index()] = PortState::Connected(PortID::Input(target));
-                state.inputs[target.index()] = PortState::Connected(PortID::Output(source));
-            }
-            _ => return Err(PortError::Invalid(PortID::Output(source))), // TODO: better errors
-        };
+        self.connect_with(source, target, ConnectionOptions::default())
+    }
+
+    fn connect_with(
+        &self,
+        source: OutputPortID,
+        target: InputPortID,
+        options: ConnectionOptions,
+    ) -> PortResult<bool> {
+        let mut state = self.state.lock();
+        if !state.output(source)?.state.is_open() {
+            return Err(PortError::Invalid(source.into()));
+        }
+        if !state.input(target)?.state.is_open() {
+            return Err(PortError::Invalid(target.into()));
+        }
+        let output = state.output(source)?;
+        output.state = PortState::Connected;
+        output.target = Some(target);
+        output.priority = options.priority.unwrap_or(Priority::MIN);
+        state.input(target)?.state = PortState::Connected;
         Ok(true)
     }
 
     fn send(&self, output: OutputPortID, message: Bytes) -> PortResult<()> {
-        let input = {
-            let state = self.state.read();
-            match state.outputs.get(output.index()) {
-                None => return Err(PortError::Invalid(PortID::Output(output))),
-                Some(PortState::Closed) => return Err(PortError::Closed),
-                Some(PortState::Open) => return Err(PortError::Disconnected),
-                Some(PortState::Connected(PortID::Output(_))) => unreachable!(),
-                Some(PortState::Connected(PortID::Input(input))) => *input,
-            }
-        };
-        {
-            let mut state = self.state.write();
-            state.inboxes[input.index()].push(message);
-        }
-        self.recv_notify(input); // wake up the receiving thread
-        Ok(())
+        self.send_event(output, TransportEvent::Message(message), None)
     }
 
-    fn recv(&self, input: InputPortID) -> PortResult<Option<Bytes>> {
-        let state = self.state.read();
-        if state.inputs.get(input.index()).is_none() {
-            return Err(PortError::Invalid(PortID::Input(input)));
-        }
-        drop(state);
+    fn send_with_priority(
+        &self,
+        output: OutputPortID,
+        message: Bytes,
+        priority: Priority,
+    ) -> PortResult<()> {
+        self.send_event(output, TransportEvent::Message(message), Some(priority))
+    }
 
+    fn recv(&self, input: InputPortID) -> PortResult<Option<Bytes>> {
         loop {
-            let mut state = self.state.upgradable_read();
-            if !state.inboxes[input.index()].is_empty() {
-                return Ok(state.with_upgraded(|state| state.inboxes[input.index()].pop()));
+            match self.recv_event(input)? {
+                None => return Ok(None), // EOS
+                Some(TransportEvent::Message(message)) => return Ok(Some(message)),
+                Some(TransportEvent::Barrier(_)) => continue, // not checkpointing
             }
-            if state.inputs[input.index()].is_closed() {
-                return Ok(None);
+        }
+    }
+
+    fn try_recv(&self, input: InputPortID) -> PortResult<Option<Bytes>> {
+        let mut state = self.state.lock();
+        let port = state.input(input)?;
+        if port.state.is_closed() {
+            return Err(PortError::Closed);
+        }
+        while let Some(event) = port.inbox.pop() {
+            if let TransportEvent::Message(message) = event {
+                return Ok(Some(message));
             }
-            drop(state);
-            self.recv_wait(input); // sleep until something happens
         }
+        Ok(None)
     }
 
-    fn try_recv(&self, _input: InputPortID) -> PortResult<Option<Bytes>> {
-        todo!() // TODO: implement try_recv()
+    fn send_barrier(&self, output: OutputPortID, checkpoint: CheckpointID) -> PortResult<()> {
+        // Barriers fence every priority level of the inbox, so as to stay in
+        // order with all messages, whatever their priorities:
+        self.send_event(output, TransportEvent::Barrier(checkpoint), None)
     }
-}
 
-impl MockTransport {
-    fn recv_wait(&self, _input: InputPortID) {
-        let (ref recv_lock, ref recv_cvar) = self.wakeup;
-        let mut recv_guard = recv_lock.lock().unwrap();
-        while !*recv_guard {
-            recv_guard = recv_cvar.wait(recv_guard).unwrap(); // blocks the current thread
+    fn recv_event(&self, input: InputPortID) -> PortResult<Option<TransportEvent>> {
+        let mut state = self.state.lock();
+        loop {
+            let port = state.input(input)?;
+            if port.state.is_closed() {
+                return Err(PortError::Closed);
+            }
+            if let Some(event) = port.inbox.pop() {
+                return Ok(Some(event));
+            }
+            if !port.state.is_connected() {
+                return Ok(None); // EOS
+            }
+            self.wakeup.wait(&mut state); // blocks until something happens
         }
-        *recv_guard = false;
     }
+}
 
-    fn recv_notify(&self, _input: InputPortID) {
-        let (ref recv_lock, ref recv_cvar) = self.wakeup;
-        let mut recv_guard = recv_lock.lock().unwrap();
-        *recv_guard = true;
-        recv_cvar.notify_all();
-    }
+fn input_id(index: usize) -> InputPortID {
+    InputPortID(-(index as isize + 1))
 }
 
-impl MockTransportState {}
+fn output_id(index: usize) -> OutputPortID {
+    OutputPortID(index as isize + 1)
+}
@@ -1,8 +1,21 @@
 // This is free and unencumbered software released into the public domain.
 
+mod channel;
+use channel::*;
+
 mod event;
 use event::*;
 
+#[cfg(feature = "instrument")]
+mod hold;
+#[cfg(feature = "instrument")]
+use hold::*;
+
+#[cfg(feature = "instrument")]
+mod instrument;
+#[cfg(feature = "instrument")]
+pub use instrument::*;
+
 mod input;
 use input::*;
 
@@ -12,13 +25,13 @@ use output::*;
 extern crate std;
 
 use crate::{
-    prelude::{Bytes, ToString},
-    transport::Transport,
-    InputPortID, OutputPortID, PortError, PortResult, PortState,
+    prelude::{Arc, Bytes, ToString},
+    transport::{Transport, TransportEvent},
+    CheckpointID, ConnectionOptions, InputPortID, OutputPortID, PortError, PortResult, PortState,
+    Priority,
 };
 use parking_lot::{Mutex, RwLock};
 use sharded_slab::Slab;
-use std::sync::mpsc::sync_channel;
 
 pub(crate) const DEFAULT_CONNECTION_CAPACITY: usize = 1;
 
@@ -26,12 +39,137 @@ pub(crate) const DEFAULT_CONNECTION_CAPACITY: usize = 1;
 pub struct MpscTransport {
     outputs: Slab<RwLock<MpscTransportOutputPortState>>,
     inputs: Slab<RwLock<MpscTransportInputPortState>>,
+
+    /// The connection metrics and the debugger's held events.
+    #[cfg(feature = "instrument")]
+    instrumentation: MpscInstrumentation,
 }
 
 impl MpscTransport {
     pub fn new() -> Self {
         Self::default()
     }
+
+    #[cfg(feature = "instrument")]
+    fn message_event(&self, output: OutputPortID, message: Bytes) -> MpscTransportEvent {
+        MpscTransportEvent::Message(message, output, self.stamp())
+    }
+
+    #[cfg(not(feature = "instrument"))]
+    fn message_event(&self, output: OutputPortID, message: Bytes) -> MpscTransportEvent {
+        MpscTransportEvent::Message(message, output)
+    }
+
+    /// Sends an event with the given priority, or else with the default
+    /// priority of the connection.
+    fn send_event(
+        &self,
+        output: OutputPortID,
+        event: MpscTransportEvent,
+        priority: Option<Priority>,
+    ) -> PortResult<()> {
+        let connection = self.output_connection(output)?;
+        let priority = priority.unwrap_or(connection.priority);
+        self.deliver(output, &connection, priority, event)
+    }
+
+    fn output_connection(&self, output: OutputPortID) -> PortResult<MpscTransportOutputConnection> {
+        let Some(output_entry) = self.outputs.get(output.index()) else {
+            return Err(PortError::Invalid(output.into()));
+        };
+
+        use MpscTransportOutputPortState::*;
+        let connection = match *output_entry.read() {
+            Closed => return Err(PortError::Closed),
+            Open => return Err(PortError::Disconnected),
+            Connected(ref connection) => connection.clone(),
+        };
+        Ok(connection)
+    }
+
+    /// Receives the next event on an input, blocking until there is one
+    /// unless `is_blocking` is false, in which case `None` is returned if
+    /// there is none yet.
+    ///
+    /// An input that isn't connected yet has no events to receive, and so
+    /// reaches EOS at once, like an input whose outputs have all
+    /// disconnected. Receiving on a closed input fails.
+    ///
+    /// The checkpoint barriers of the outputs sharing the input's channel
+    /// are aligned into one, see [`MpscTransportInputSenders`].
+    fn next_event(
+        &self,
+        input: InputPortID,
+        is_blocking: bool,
+    ) -> PortResult<Option<TransportEvent>> {
+        let Some(input_entry) = self.inputs.get(input.index()) else {
+            return Err(PortError::Invalid(input.into()));
+        };
+        // Don't hold the port state lock while blocking on the channel, so
+        // that further outputs can be connected to this input meanwhile:
+        use MpscTransportInputPortState::*;
+        let connection = match *input_entry.read() {
+            Closed => return Err(PortError::Closed),
+            Open => return Ok(None), // EOS (not connected)
+            Connected(ref connection) => connection.clone(),
+        };
+
+        use MpscTransportEvent::*;
+        loop {
+            // Unless delivery is paused, deliver any events held back for
+            // this input that it now has capacity for:
+            #[cfg(feature = "instrument")]
+            self.release_held(input);
+            let released = connection.senders.lock().next_released();
+            let event = match released {
+                Some(event) => event,
+                None => {
+                    // Fails with `PortError::Closed` if the input is closed meanwhile:
+                    let event = match is_blocking {
+                        true => connection.channel.recv()?, // blocking
+                        false => match connection.channel.try_recv()? {
+                            Some(event) => event,
+                            None => return Ok(None), // no event pending
+                        },
+                    };
+                    #[cfg(feature = "instrument")]
+                    if let Message(bytes, source, Some(stamp)) = &event {
+                        self.record_metrics(stamp, *source, input, bytes.len());
+                    }
+                    event
+                }
+            };
+            let Some(event) = connection.senders.lock().align(event) else {
+                continue; // held back, or other outputs remain connected
+            };
+            match event {
+                Connect => unreachable!(),
+                Message(bytes, ..) => return Ok(Some(TransportEvent::Message(bytes))),
+                Barrier(checkpoint, _) => return Ok(Some(TransportEvent::Barrier(checkpoint))),
+                Disconnect(_) => {
+                    let mut input_state = input_entry.write();
+                    *input_state = Closed;
+                    return Ok(None); // EOS
+                }
+            }
+        }
+    }
+
+    /// Delivers an event on a connection, unless it must be held back.
+    #[cfg_attr(not(feature = "instrument"), allow(unused_variables))]
+    fn deliver(
+        &self,
+        source: OutputPortID,
+        connection: &MpscTransportOutputConnection,
+        priority: Priority,
+        event: MpscTransportEvent,
+    ) -> PortResult<()> {
+        #[cfg(feature = "instrument")]
+        let Some(event) = self.hold_back(source, connection, priority, event) else {
+            return Ok(());
+        };
+        connection.channel.send(priority, event) // blocking
+    }
 }
 
 impl Transport for MpscTransport {
@@ -78,7 +216,14 @@ impl Transport for MpscTransport {
         use MpscTransportInputPortState::*;
         Ok(match *input_state {
             Closed => false, // already closed
-            Open | Connected(_) => {
+            Open => {
+                *input_state = MpscTransportInputPortState::Closed;
+                true
+            }
+            Connected(ref connection) => {
+                // Wake the outputs blocked on sending to this input, and
+                // any receive blocked on it, so that they fail:
+                connection.channel.close();
                 *input_state = MpscTransportInputPortState::Closed;
                 true
             }
@@ -98,17 +243,61 @@ impl Transport for MpscTransport {
                 *output_state = MpscTransportOutputPortState::Closed;
                 true
             }
-            Connected(ref sender) => {
-                let sender = sender.clone();
+            Connected(ref connection) => {
+                let connection = connection.clone();
                 *output_state = MpscTransportOutputPortState::Closed;
                 drop(output_state);
-                sender.send(MpscTransportEvent::Disconnect).unwrap(); // blocking
-                true
+                // Sent with the lowest priority, so as to be delivered after
+                // all messages sent before it. If the input was closed
+                // already, there's no one left to notify:
+                match self.deliver(
+                    output,
+                    &connection,
+                    Priority::MIN,
+                    MpscTransportEvent::Disconnect(output),
+                ) {
+                    Ok(()) | Err(PortError::Disconnected) => true,
+                    Err(error) => return Err(error),
+                }
             }
         })
     }
 
+    fn disconnect(&self, output: OutputPortID) -> PortResult<bool> {
+        let Some(output_entry) = self.outputs.get(output.index()) else {
+            return Err(PortError::Invalid(output.into()));
+        };
+        let mut output_state = output_entry.write();
+
+        use MpscTransportOutputPortState::*;
+        let connection = match *output_state {
+            Open | Closed => return Ok(false), // not connected
+            Connected(ref connection) => connection.clone(),
+        };
+        *output_state = MpscTransportOutputPortState::Open;
+        drop(output_state);
+        // As when closing the output, but leaving it open for reconnecting:
+        match self.deliver(
+            output,
+            &connection,
+            Priority::MIN,
+            MpscTransportEvent::Disconnect(output),
+        ) {
+            Ok(()) | Err(PortError::Disconnected) => Ok(true),
+            Err(error) => Err(error),
+        }
+    }
+
     fn connect(&self, source: OutputPortID, target: InputPortID) -> PortResult<bool> {
+        self.connect_with(source, target, ConnectionOptions::default())
+    }
+
+    fn connect_with(
+        &self,
+        source: OutputPortID,
+        target: InputPortID,
+        options: ConnectionOptions,
+    ) -> PortResult<bool> {
         let Some(output_entry) = self.outputs.get(source.index()) else {
             return Err(PortError::Invalid(source.into()));
         };
@@ -119,65 +308,81 @@ impl Transport for MpscTransport {
 
         let mut output_state = output_entry.write();
         let mut input_state = input_entry.write();
-        if !output_state.state().is_open() && !input_state.state().is_open() {
+        if output_state.state().is_connected() || input_state.state().is_closed() {
             return Err(PortError::Other("connect".to_string())); // TODO: better errors
         }
 
-        let (sender, receiver) = sync_channel(DEFAULT_CONNECTION_CAPACITY);
-        *output_state = MpscTransportOutputPortState::Connected(sender);
-        *input_state = MpscTransportInputPortState::Connected(Mutex::new(receiver));
+        let priority = options.priority.unwrap_or(Priority::MIN);
+        use MpscTransportInputPortState::*;
+        let channel = match *input_state {
+            Connected(ref connection) => {
+                // Additional outputs share the channel of the existing connection:
+                connection.senders.lock().connect(source);
+                connection.channel.clone()
+            }
+            Open | Closed => {
+                let capacity = options.capacity.unwrap_or(DEFAULT_CONNECTION_CAPACITY);
+                let channel = Arc::new(MpscChannel::new(capacity));
+                *input_state = Connected(Arc::new(MpscTransportInputConnection {
+                    channel: channel.clone(),
+                    senders: Mutex::new(MpscTransportInputSenders::new(source)),
+                }));
+                channel
+            }
+        };
+        *output_state = MpscTransportOutputPortState::Connected(MpscTransportOutputConnection {
+            channel,
+            #[cfg(feature = "instrument")]
+            target,
+            priority,
+        });
         Ok(true)
     }
 
     fn send(&self, output: OutputPortID, message: Bytes) -> PortResult<()> {
-        let Some(output_entry) = self.outputs.get(output.index()) else {
-            return Err(PortError::Invalid(output.into()));
-        };
-        let output_state = output_entry.read();
+        self.send_event(output, self.message_event(output, message), None)
+    }
 
-        use MpscTransportOutputPortState::*;
-        match *output_state {
-            Closed => return Err(PortError::Closed),
-            Open => return Err(PortError::Disconnected),
-            Connected(ref sender) => {
-                let sender = sender.clone();
-                Ok(sender.send(MpscTransportEvent::Message(message)).unwrap()) // blocking (TODO: error handling)
-            }
-        }
+    fn send_with_priority(
+        &self,
+        output: OutputPortID,
+        message: Bytes,
+        priority: Priority,
+    ) -> PortResult<()> {
+        self.send_event(output, self.message_event(output, message), Some(priority))
     }
 
     fn recv(&self, input: InputPortID) -> PortResult<Option<Bytes>> {
-        let Some(input_entry) = self.inputs.get(input.index()) else {
-            return Err(PortError::Invalid(input.into()));
-        };
-        let input_state = input_entry.read();
+        loop {
+            match self.recv_event(input)? {
+                None => return Ok(None), // EOS
+                Some(TransportEvent::Message(bytes)) => return Ok(Some(bytes)),
+                Some(TransportEvent::Barrier(_)) => continue, // not checkpointing
+            }
+        }
+    }
 
-        use MpscTransportInputPortState::*;
-        match *input_state {
-            Closed => return Ok(None), // EOS
-            Open => return Ok(None),   // FIXME
-            Connected(ref receiver) => {
-                use MpscTransportEvent::*;
-                let receiver = receiver.lock();
-                match receiver
-                    .recv() // blocking
-                    .map_err(|_| PortError::Disconnected)?
-                {
-                    Connect => unreachable!(),
-                    Message(bytes) => Ok(Some(bytes)),
-                    Disconnect => {
-                        drop(receiver);
-                        drop(input_state);
-                        let mut input_state = input_entry.write();
-                        *input_state = Closed;
-                        Ok(None) // EOS
-                    }
-                }
+    fn try_recv(&self, input: InputPortID) -> PortResult<Option<Bytes>> {
+        loop {
+            match self.next_event(input, false)? {
+                None => return Ok(None), // EOS, or no message pending
+                Some(TransportEvent::Message(bytes)) => return Ok(Some(bytes)),
+                Some(TransportEvent::Barrier(_)) => continue, // not checkpointing
             }
         }
     }
 
-    fn try_recv(&self, _input: InputPortID) -> PortResult<Option<Bytes>> {
-        todo!() // TODO: implement try_recv()
+    fn send_barrier(&self, output: OutputPortID, checkpoint: CheckpointID) -> PortResult<()> {
+        // Barriers fence every priority level of the channel, so as to stay
+        // in order with all messages, whatever their priorities:
+        self.send_event(
+            output,
+            MpscTransportEvent::Barrier(checkpoint, output),
+            None,
+        )
+    }
+
+    fn recv_event(&self, input: InputPortID) -> PortResult<Option<TransportEvent>> {
+        self.next_event(input, true)
     }
 }
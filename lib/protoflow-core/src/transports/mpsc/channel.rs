@@ -0,0 +1,182 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::MpscTransportEvent;
+use crate::{
+    prelude::{BTreeMap, VecDeque},
+    PortError, PortResult, Priority,
+};
+use parking_lot::{Condvar, Mutex};
+
+/// A bounded multi-producer, single-consumer channel that delivers
+/// higher-priority events first.
+///
+/// Events of the same priority are delivered in FIFO order. The capacity
+/// bounds each priority level separately, so that a backlog of low-priority
+/// events never blocks the sending of higher-priority events.
+///
+/// Barriers fence every priority level: a barrier is delivered only after
+/// all events sent before it, and before all events sent after it, whatever
+/// their priorities.
+#[derive(Debug)]
+pub struct MpscChannel {
+    queue: Mutex<MpscChannelQueue>,
+    readable: Condvar,
+    writable: Condvar,
+    capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct MpscChannelQueue {
+    /// The queued events of each priority level, with their sequence numbers.
+    levels: BTreeMap<Priority, VecDeque<(u64, MpscTransportEvent)>>,
+
+    /// The queued barriers, with their sequence numbers.
+    barriers: VecDeque<(u64, MpscTransportEvent)>,
+
+    /// The sequence number of the next event sent.
+    next_seq: u64,
+
+    /// Whether the receiving input port was closed.
+    is_closed: bool,
+}
+
+impl MpscChannelQueue {
+    fn is_full(&self, priority: Priority, capacity: usize) -> bool {
+        self.levels
+            .get(&priority)
+            .is_some_and(|level| level.len() >= capacity)
+    }
+
+    fn push(&mut self, priority: Priority, event: MpscTransportEvent) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        match event {
+            MpscTransportEvent::Barrier(..) => self.barriers.push_back((seq, event)),
+            _ => self
+                .levels
+                .entry(priority)
+                .or_default()
+                .push_back((seq, event)),
+        }
+    }
+
+    /// Pops the highest-priority event sent before the first queued barrier,
+    /// or else that barrier.
+    fn pop(&mut self) -> Option<MpscTransportEvent> {
+        let fence = self.barriers.front().map(|(seq, _)| *seq);
+        let priority = self
+            .levels
+            .iter()
+            .rev()
+            .find(|(_, level)| fence.map_or(true, |fence| level[0].0 < fence))
+            .map(|(&priority, _)| priority);
+        let Some(priority) = priority else {
+            return self.barriers.pop_front().map(|(_, event)| event);
+        };
+        let level = self.levels.get_mut(&priority).unwrap();
+        let (_, event) = level.pop_front().unwrap();
+        if level.is_empty() {
+            self.levels.remove(&priority);
+        }
+        Some(event)
+    }
+}
+
+impl MpscChannel {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            queue: Mutex::new(MpscChannelQueue::default()),
+            readable: Condvar::new(),
+            writable: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Sends an event, blocking while its priority level is at capacity.
+    /// Barriers are never blocked.
+    ///
+    /// Fails with [`PortError::Disconnected`] once the channel is closed.
+    pub fn send(&self, priority: Priority, event: MpscTransportEvent) -> PortResult<()> {
+        let mut queue = self.queue.lock();
+        loop {
+            if queue.is_closed {
+                return Err(PortError::Disconnected);
+            }
+            if matches!(event, MpscTransportEvent::Barrier(..))
+                || !queue.is_full(priority, self.capacity)
+            {
+                break;
+            }
+            self.writable.wait(&mut queue);
+        }
+        queue.push(priority, event);
+        self.readable.notify_one();
+        Ok(())
+    }
+
+    /// Sends an event unless its priority level is at capacity, in which
+    /// case the event is returned. Events sent once the channel is closed
+    /// are discarded.
+    #[cfg(feature = "instrument")]
+    pub fn try_send(
+        &self,
+        priority: Priority,
+        event: MpscTransportEvent,
+    ) -> Result<(), MpscTransportEvent> {
+        let mut queue = self.queue.lock();
+        if queue.is_closed {
+            return Ok(());
+        }
+        if !matches!(event, MpscTransportEvent::Barrier(..))
+            && queue.is_full(priority, self.capacity)
+        {
+            return Err(event);
+        }
+        queue.push(priority, event);
+        self.readable.notify_one();
+        Ok(())
+    }
+
+    /// Receives the next event, blocking until there is one.
+    ///
+    /// Fails with [`PortError::Closed`] once the channel is closed.
+    pub fn recv(&self) -> PortResult<MpscTransportEvent> {
+        let mut queue = self.queue.lock();
+        loop {
+            if queue.is_closed {
+                return Err(PortError::Closed);
+            }
+            if let Some(event) = queue.pop() {
+                self.writable.notify_all();
+                return Ok(event);
+            }
+            self.readable.wait(&mut queue);
+        }
+    }
+
+    /// Receives the next event if there is one, without blocking.
+    ///
+    /// Fails with [`PortError::Closed`] once the channel is closed.
+    pub fn try_recv(&self) -> PortResult<Option<MpscTransportEvent>> {
+        let mut queue = self.queue.lock();
+        if queue.is_closed {
+            return Err(PortError::Closed);
+        }
+        let event = queue.pop();
+        if event.is_some() {
+            self.writable.notify_all();
+        }
+        Ok(event)
+    }
+
+    /// Closes the channel, discarding its queued events and waking any
+    /// senders and receiver waiting on it.
+    pub fn close(&self) {
+        let mut queue = self.queue.lock();
+        queue.is_closed = true;
+        queue.levels.clear();
+        queue.barriers.clear();
+        self.readable.notify_all();
+        self.writable.notify_all();
+    }
+}
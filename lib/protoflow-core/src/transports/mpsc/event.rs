@@ -1,11 +1,28 @@
 // This is free and unencumbered software released into the public domain.
 
-use crate::prelude::Bytes;
+extern crate std;
 
+use crate::{prelude::Bytes, CheckpointID, OutputPortID};
+#[cfg(feature = "instrument")]
+use std::time::Instant;
+
+/// An event sent on a channel, along with the output port that sent it.
 #[derive(Clone, Debug)]
 pub enum MpscTransportEvent {
     #[allow(unused)]
     Connect,
-    Message(Bytes),
-    Disconnect,
+    Message(
+        Bytes,
+        OutputPortID,
+        #[cfg(feature = "instrument")] Option<MpscMessageStamp>,
+    ),
+    Barrier(CheckpointID, OutputPortID),
+    Disconnect(OutputPortID),
+}
+
+/// When a message was sent, for recording connection metrics.
+#[cfg(feature = "instrument")]
+#[derive(Clone, Copy, Debug)]
+pub struct MpscMessageStamp {
+    pub sent_at: Instant,
 }
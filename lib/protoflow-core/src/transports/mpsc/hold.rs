@@ -0,0 +1,182 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{MpscChannel, MpscTransportEvent, MpscTransportOutputConnection};
+use crate::{
+    prelude::{Arc, BTreeSet, Vec, VecDeque},
+    HeldEvent, HeldEventKind, InputPortID, OutputPortID, PortID, Priority,
+};
+
+/// The events held back from delivery for debugging, and the breakpoints
+/// that pause delivery.
+#[derive(Debug, Default)]
+pub struct MpscHold {
+    /// Whether delivery is paused, holding back every event sent.
+    pub(crate) is_paused: bool,
+
+    /// The ports that pause delivery when an event is sent through them.
+    pub(crate) breakpoints: BTreeSet<PortID>,
+
+    /// The held events, in the order that they are to be delivered.
+    pub(crate) events: VecDeque<MpscHeldEvent>,
+
+    /// The held event that hit a breakpoint, pausing delivery, if any.
+    pub(crate) hit: Option<u64>,
+
+    next_id: u64,
+}
+
+#[derive(Debug)]
+pub struct MpscHeldEvent {
+    pub(crate) id: u64,
+    pub(crate) source: OutputPortID,
+    pub(crate) target: InputPortID,
+    pub(crate) priority: Priority,
+    pub(crate) channel: Arc<MpscChannel>,
+    pub(crate) event: MpscTransportEvent,
+}
+
+impl MpscHeldEvent {
+    pub fn to_held_event(&self) -> HeldEvent {
+        use MpscTransportEvent::*;
+        let kind = match &self.event {
+            Connect => unreachable!(),
+            Message(bytes, ..) => HeldEventKind::Message(bytes.clone()),
+            Barrier(checkpoint, _) => HeldEventKind::Barrier(*checkpoint),
+            Disconnect(_) => HeldEventKind::Disconnect,
+        };
+        HeldEvent {
+            id: self.id,
+            source: self.source,
+            target: self.target,
+            kind,
+        }
+    }
+}
+
+impl MpscHold {
+    /// Returns whether an event sent from the given output port must be
+    /// held, because delivery is paused or because earlier events from the
+    /// same port are still held, so as to deliver them in order.
+    pub fn is_holding(&self, source: OutputPortID) -> bool {
+        self.is_paused || self.events.iter().any(|held| held.source == source)
+    }
+
+    /// Returns whether there's a breakpoint on either end of a connection.
+    pub fn is_breakpoint(&self, source: OutputPortID, target: InputPortID) -> bool {
+        self.breakpoints.contains(&source.into()) || self.breakpoints.contains(&target.into())
+    }
+
+    /// Holds an event sent on the given connection, returning its ID.
+    pub fn hold(
+        &mut self,
+        source: OutputPortID,
+        connection: &MpscTransportOutputConnection,
+        priority: Priority,
+        event: MpscTransportEvent,
+    ) -> u64 {
+        self.next_id += 1;
+        self.events.push_back(MpscHeldEvent {
+            id: self.next_id,
+            source,
+            target: connection.target,
+            priority,
+            channel: connection.channel.clone(),
+            event,
+        });
+        self.next_id
+    }
+
+    /// Holds an event sent from an output port that has since been closed,
+    /// ahead of the port's held disconnection, returning its ID and the
+    /// input port it is to be delivered to. Returns nothing if the port's
+    /// disconnection isn't held.
+    pub fn hold_before_disconnect(
+        &mut self,
+        source: OutputPortID,
+        event: MpscTransportEvent,
+    ) -> Option<(u64, InputPortID)> {
+        let index = self.events.iter().position(|held| {
+            held.source == source && matches!(held.event, MpscTransportEvent::Disconnect(_))
+        })?;
+        let disconnect = &self.events[index];
+        let target = disconnect.target;
+        self.next_id += 1;
+        let held = MpscHeldEvent {
+            id: self.next_id,
+            source,
+            target,
+            priority: disconnect.priority,
+            channel: disconnect.channel.clone(),
+            event,
+        };
+        self.events.insert(index, held);
+        Some((self.next_id, target))
+    }
+
+    /// Delivers up to `limit` held events, oldest first, to the given input
+    /// port or else to any, without blocking on inputs that are at capacity.
+    ///
+    /// The events from each output port stay in order, so those following
+    /// an event that couldn't be delivered are skipped.
+    pub fn release(&mut self, target: Option<InputPortID>, limit: usize) -> Vec<HeldEvent> {
+        let mut released = Vec::new();
+        let mut blocked = BTreeSet::new();
+        let mut index = 0;
+        while index < self.events.len() && released.len() < limit {
+            let held = &self.events[index];
+            if target.is_some_and(|target| target != held.target) || blocked.contains(&held.source)
+            {
+                index += 1;
+                continue;
+            }
+            let held = self.events.remove(index).unwrap();
+            let held_event = held.to_held_event();
+            match held.channel.try_send(held.priority, held.event) {
+                Ok(()) => released.push(held_event),
+                Err(event) => {
+                    blocked.insert(held.source);
+                    self.events.insert(index, MpscHeldEvent { event, ..held });
+                    index += 1;
+                }
+            }
+        }
+        released
+    }
+
+    /// Resumes delivery, delivering the held events in order until one hits
+    /// a breakpoint, which pauses delivery again. The event that last hit a
+    /// breakpoint doesn't hit it again.
+    pub fn resume(&mut self) {
+        self.is_paused = false;
+        let hit = self.hit.take();
+        let next_hit = self
+            .events
+            .iter()
+            .find(|held| Some(held.id) != hit && self.is_breakpoint(held.source, held.target))
+            .map(|held| held.id);
+        let Some(next_hit) = next_hit else {
+            self.release(None, usize::MAX);
+            return;
+        };
+        // Deliver only the events held before the one hitting a breakpoint:
+        let count = self
+            .events
+            .iter()
+            .position(|held| held.id == next_hit)
+            .unwrap();
+        let mut later = self.events.split_off(count);
+        self.release(None, usize::MAX);
+        self.events.append(&mut later);
+        self.is_paused = true;
+        self.hit = Some(next_hit);
+    }
+
+    /// Discards a held message, returning it, unless there's no such
+    /// message.
+    pub fn drop_message(&mut self, id: u64) -> Option<HeldEvent> {
+        let index = self.events.iter().position(|held| {
+            held.id == id && matches!(held.event, MpscTransportEvent::Message(..))
+        })?;
+        self.events.remove(index).map(|held| held.to_held_event())
+    }
+}
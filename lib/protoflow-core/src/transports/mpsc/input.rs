@@ -1,17 +1,17 @@
 // This is free and unencumbered software released into the public domain.
 
-extern crate std;
-
-use super::MpscTransportEvent;
-use crate::PortState;
+use super::{MpscChannel, MpscTransportEvent};
+use crate::{
+    prelude::{Arc, BTreeSet, VecDeque},
+    CheckpointID, OutputPortID, PortState,
+};
 use parking_lot::Mutex;
-use std::sync::mpsc::Receiver;
 
 #[derive(Debug, Default)]
 pub enum MpscTransportInputPortState {
     #[default]
     Open,
-    Connected(Mutex<Receiver<MpscTransportEvent>>),
+    Connected(Arc<MpscTransportInputConnection>),
     Closed,
 }
 
@@ -24,3 +24,104 @@ impl MpscTransportInputPortState {
         }
     }
 }
+
+#[derive(Debug)]
+pub struct MpscTransportInputConnection {
+    /// The channel shared by all outputs connected to this input.
+    pub(crate) channel: Arc<MpscChannel>,
+
+    /// The outputs connected to this input.
+    pub(crate) senders: Mutex<MpscTransportInputSenders>,
+}
+
+/// The outputs sharing the channel of an input port.
+///
+/// Each output sends its own barrier for a checkpoint, whereas the input
+/// delivers a single barrier once all of its outputs have sent theirs.
+/// Meanwhile, the events sent by outputs after their barrier are held
+/// back, so that the delivered barrier separates all the events sent
+/// before the checkpoint from those sent after it.
+#[derive(Debug, Default)]
+pub struct MpscTransportInputSenders {
+    /// The outputs that are connected to the input.
+    connected: BTreeSet<OutputPortID>,
+
+    /// The checkpoint whose barrier is being aligned, and the outputs that
+    /// have sent it.
+    aligning: Option<(CheckpointID, BTreeSet<OutputPortID>)>,
+
+    /// The events held back from the outputs that have sent the barrier.
+    held: VecDeque<MpscTransportEvent>,
+
+    /// The held events, once released by the barrier's delivery, which are
+    /// received ahead of any further events on the channel.
+    released: VecDeque<MpscTransportEvent>,
+}
+
+impl MpscTransportInputSenders {
+    pub fn new(source: OutputPortID) -> Self {
+        Self {
+            connected: BTreeSet::from([source]),
+            ..Default::default()
+        }
+    }
+
+    pub fn connect(&mut self, source: OutputPortID) {
+        self.connected.insert(source);
+    }
+
+    /// Returns the next released event, if any.
+    pub fn next_released(&mut self) -> Option<MpscTransportEvent> {
+        self.released.pop_front()
+    }
+
+    /// Aligns an event received on the channel, returning the event to
+    /// deliver to the input port, if any. A disconnection is returned only
+    /// once the last output has disconnected.
+    pub fn align(&mut self, event: MpscTransportEvent) -> Option<MpscTransportEvent> {
+        use MpscTransportEvent::*;
+        let source = match event {
+            Connect => unreachable!(),
+            Message(_, source, ..) | Barrier(_, source) | Disconnect(source) => source,
+        };
+        let is_aligned =
+            |(_, sent): &(CheckpointID, BTreeSet<OutputPortID>)| sent.contains(&source);
+        if self.aligning.as_ref().is_some_and(is_aligned) {
+            self.held.push_back(event);
+            return None;
+        }
+        match event {
+            Connect => unreachable!(),
+            Message(..) => Some(event),
+            Barrier(checkpoint, _) => {
+                let (aligning, sent) = self
+                    .aligning
+                    .get_or_insert_with(|| (checkpoint, BTreeSet::new()));
+                *aligning = checkpoint.max(*aligning);
+                sent.insert(source);
+                self.deliver_barrier(source)
+            }
+            Disconnect(_) => {
+                self.connected.remove(&source);
+                if self.connected.is_empty() {
+                    return Some(event); // EOS
+                }
+                self.deliver_barrier(source)
+            }
+        }
+    }
+
+    /// Returns the barrier being aligned, once all the connected outputs
+    /// have sent it, releasing the events held back meanwhile.
+    fn deliver_barrier(&mut self, source: OutputPortID) -> Option<MpscTransportEvent> {
+        let (_, sent) = self.aligning.as_ref()?;
+        if !self.connected.is_subset(sent) {
+            return None;
+        }
+        let (checkpoint, _) = self.aligning.take()?;
+        let mut released = core::mem::take(&mut self.held);
+        released.append(&mut self.released);
+        self.released = released;
+        Some(MpscTransportEvent::Barrier(checkpoint, source))
+    }
+}
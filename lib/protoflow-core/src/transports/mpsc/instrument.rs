@@ -0,0 +1,263 @@
+// This is free and unencumbered software released into the public domain.
+
+extern crate std;
+
+use super::{
+    MpscHeldEvent, MpscHold, MpscMessageStamp, MpscTransport, MpscTransportEvent,
+    MpscTransportOutputConnection,
+};
+use crate::{
+    prelude::{AtomicBool, BTreeMap, Bytes, Duration, Ordering, Vec},
+    ConnectionMetrics, HeldEvent, InputPortID, OutputPortID, PortError, PortID, PortResult,
+    Priority,
+};
+use parking_lot::{Condvar, Mutex};
+
+/// The connection metrics and the debugger's held events of an
+/// [`MpscTransport`], which is only instrumented with the `instrument`
+/// feature, so that other builds don't check for them on every delivery.
+#[derive(Debug, Default)]
+pub struct MpscInstrumentation {
+    /// Whether to record connection metrics.
+    is_metered: AtomicBool,
+
+    /// The metrics of each connection, by its output and input port.
+    metrics: Mutex<BTreeMap<(OutputPortID, InputPortID), ConnectionMetrics>>,
+
+    /// Whether events may be held back from delivery, for debugging.
+    is_debugged: AtomicBool,
+
+    /// The events held back from delivery, and the breakpoints.
+    hold: Mutex<MpscHold>,
+
+    /// Notified whenever an event is held.
+    held: Condvar,
+}
+
+impl MpscTransport {
+    /// Starts recording the metrics of every connection, for the messages
+    /// sent from then on.
+    ///
+    /// Message latencies are measured from when a message is sent until
+    /// when it is received, including the time it was buffered.
+    pub fn enable_metrics(&self) {
+        self.instrumentation
+            .is_metered
+            .store(true, Ordering::SeqCst);
+    }
+
+    /// Returns the metrics recorded for each connection that received
+    /// messages, ordered by output port and input port.
+    pub fn metrics(&self) -> Vec<ConnectionMetrics> {
+        self.instrumentation
+            .metrics
+            .lock()
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Pauses delivery, holding back every event sent from then on until
+    /// it is stepped through or delivery is resumed.
+    ///
+    /// Events that were delivered before pausing can still be received.
+    pub fn pause(&self) {
+        self.instrumentation
+            .is_debugged
+            .store(true, Ordering::SeqCst);
+        self.instrumentation.hold.lock().is_paused = true;
+    }
+
+    /// Resumes delivery, delivering the held events in order, unless one of
+    /// them is at a breakpoint, in which case delivery pauses there again.
+    pub fn resume(&self) {
+        let mut hold = self.instrumentation.hold.lock();
+        hold.resume();
+        if hold.is_paused {
+            self.instrumentation.held.notify_all();
+        }
+    }
+
+    /// Returns whether delivery is paused, either explicitly or because a
+    /// breakpoint was hit.
+    pub fn is_paused(&self) -> bool {
+        self.instrumentation.hold.lock().is_paused
+    }
+
+    /// Returns the held event that hit a breakpoint, pausing delivery, if
+    /// it is still held.
+    pub fn paused_at(&self) -> Option<HeldEvent> {
+        let hold = self.instrumentation.hold.lock();
+        let hit = hold.hit?;
+        hold.events
+            .iter()
+            .find(|held| held.id == hit)
+            .map(MpscHeldEvent::to_held_event)
+    }
+
+    /// Sets a breakpoint on a port, so that delivery is paused when an event
+    /// is sent through it, holding back that event. Returns whether the
+    /// breakpoint wasn't already set.
+    pub fn set_breakpoint(&self, port: PortID) -> bool {
+        self.instrumentation
+            .is_debugged
+            .store(true, Ordering::SeqCst);
+        self.instrumentation.hold.lock().breakpoints.insert(port)
+    }
+
+    /// Clears a breakpoint on a port, returning whether it was set.
+    pub fn clear_breakpoint(&self, port: PortID) -> bool {
+        self.instrumentation.hold.lock().breakpoints.remove(&port)
+    }
+
+    /// Returns the ports that have breakpoints, in order.
+    pub fn breakpoints(&self) -> Vec<PortID> {
+        self.instrumentation
+            .hold
+            .lock()
+            .breakpoints
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Returns the events held back from delivery, in the order that they
+    /// are to be delivered.
+    pub fn held_events(&self) -> Vec<HeldEvent> {
+        let hold = self.instrumentation.hold.lock();
+        hold.events
+            .iter()
+            .map(MpscHeldEvent::to_held_event)
+            .collect()
+    }
+
+    /// Blocks until delivery is paused with events held, or until the
+    /// timeout elapses, returning whether events are held.
+    pub fn wait_held(&self, timeout: Duration) -> bool {
+        let deadline = std::time::Instant::now() + timeout;
+        let mut hold = self.instrumentation.hold.lock();
+        while !hold.is_paused || hold.events.is_empty() {
+            if self
+                .instrumentation
+                .held
+                .wait_until(&mut hold, deadline)
+                .timed_out()
+            {
+                return hold.is_paused && !hold.events.is_empty();
+            }
+        }
+        true
+    }
+
+    /// Delivers the oldest held event whose input has capacity for it,
+    /// returning the event, or nothing if no event could be delivered.
+    pub fn step(&self) -> Option<HeldEvent> {
+        self.instrumentation.hold.lock().release(None, 1).pop()
+    }
+
+    /// Sends a message as if from the given output port, holding it in turn
+    /// while delivery is paused. Returns the ID of the held message.
+    ///
+    /// If the output port was closed but its disconnection is still held,
+    /// the message is held ahead of the disconnection.
+    pub fn inject(&self, output: OutputPortID, message: Bytes) -> PortResult<u64> {
+        let connection = self.output_connection(output);
+        self.instrumentation
+            .is_debugged
+            .store(true, Ordering::SeqCst);
+        let mut hold = self.instrumentation.hold.lock();
+        let event = self.message_event(output, message);
+        let (id, target) = match connection {
+            Ok(connection) => (
+                hold.hold(output, &connection, connection.priority, event),
+                connection.target,
+            ),
+            Err(PortError::Closed) => hold
+                .hold_before_disconnect(output, event)
+                .ok_or(PortError::Closed)?,
+            Err(error) => return Err(error),
+        };
+        if !hold.is_paused {
+            hold.release(Some(target), usize::MAX);
+        }
+        self.instrumentation.held.notify_all();
+        Ok(id)
+    }
+
+    /// Discards a held message, returning it, unless there's no held
+    /// message with the given ID.
+    ///
+    /// Held barriers and disconnections can't be discarded.
+    pub fn drop_held(&self, id: u64) -> Option<HeldEvent> {
+        self.instrumentation.hold.lock().drop_message(id)
+    }
+
+    /// Returns the stamp of a message being sent, if connection metrics are
+    /// being recorded.
+    pub(super) fn stamp(&self) -> Option<MpscMessageStamp> {
+        self.instrumentation
+            .is_metered
+            .load(Ordering::Relaxed)
+            .then(|| MpscMessageStamp {
+                sent_at: std::time::Instant::now(),
+            })
+    }
+
+    /// Records the metrics of a stamped message received on an input port
+    /// from the given output port.
+    pub(super) fn record_metrics(
+        &self,
+        stamp: &MpscMessageStamp,
+        source: OutputPortID,
+        input: InputPortID,
+        len: usize,
+    ) {
+        self.instrumentation
+            .metrics
+            .lock()
+            .entry((source, input))
+            .or_insert_with(|| ConnectionMetrics::new(source, input))
+            .record(len, stamp.sent_at.elapsed());
+    }
+
+    /// Holds back an event from delivery, if delivery is paused or the
+    /// event hits a breakpoint, returning the event otherwise.
+    pub(super) fn hold_back(
+        &self,
+        source: OutputPortID,
+        connection: &MpscTransportOutputConnection,
+        priority: Priority,
+        event: MpscTransportEvent,
+    ) -> Option<MpscTransportEvent> {
+        if self.instrumentation.is_debugged.load(Ordering::SeqCst) {
+            let mut hold = self.instrumentation.hold.lock();
+            let is_hit = !hold.is_paused && hold.is_breakpoint(source, connection.target);
+            if is_hit {
+                hold.is_paused = true;
+            }
+            if hold.is_holding(source) {
+                let id = hold.hold(source, connection, priority, event);
+                if is_hit {
+                    hold.hit = Some(id);
+                }
+                if !hold.is_paused {
+                    hold.release(Some(connection.target), usize::MAX);
+                }
+                self.instrumentation.held.notify_all();
+                return None;
+            }
+        }
+        Some(event)
+    }
+
+    /// Delivers any events held back for the input port that it now has
+    /// capacity for, unless delivery is paused.
+    pub(super) fn release_held(&self, input: InputPortID) {
+        if self.instrumentation.is_debugged.load(Ordering::SeqCst) {
+            let mut hold = self.instrumentation.hold.lock();
+            if !hold.is_paused {
+                hold.release(Some(input), usize::MAX);
+            }
+        }
+    }
+}
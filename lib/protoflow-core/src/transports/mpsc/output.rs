@@ -1,16 +1,15 @@
 // This is free and unencumbered software released into the public domain.
 
-extern crate std;
-
-use super::MpscTransportEvent;
-use crate::PortState;
-use std::sync::mpsc::SyncSender;
+use super::MpscChannel;
+#[cfg(feature = "instrument")]
+use crate::InputPortID;
+use crate::{prelude::Arc, PortState, Priority};
 
 #[derive(Clone, Debug, Default)]
 pub enum MpscTransportOutputPortState {
     #[default]
     Open,
-    Connected(SyncSender<MpscTransportEvent>),
+    Connected(MpscTransportOutputConnection),
     Closed,
 }
 
@@ -23,3 +22,16 @@ impl MpscTransportOutputPortState {
         }
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct MpscTransportOutputConnection {
+    /// The channel to the connected input.
+    pub(crate) channel: Arc<MpscChannel>,
+
+    /// The connected input.
+    #[cfg(feature = "instrument")]
+    pub(crate) target: InputPortID,
+
+    /// The default priority of messages sent on the connection.
+    pub(crate) priority: Priority,
+}
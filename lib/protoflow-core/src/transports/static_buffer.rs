@@ -0,0 +1,326 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{Bytes, Vec},
+    transport::Transport,
+    ConnectionOptions, InputPortID, OutputPortID, PortError, PortResult, PortState,
+};
+use parking_lot::Mutex;
+
+/// A transport for single-threaded, cooperative execution, with a
+/// fixed-capacity message buffer for every input port.
+///
+/// No operation ever blocks: receiving from an empty input port fails with
+/// [`PortError::WouldBlock`] so that the block can yield to other blocks and
+/// be resumed later by a
+/// [`CooperativeRuntime`](crate::runtimes::CooperativeRuntime).
+///
+/// A message sent to a full input port is kept pending in the output port,
+/// and is delivered by the runtime before the sending block is resumed.
+/// Each output port keeps up to `CAPACITY` pending messages; sending more
+/// than that fails with [`PortError::WouldBlock`].
+///
+/// Each input port buffers up to `CAPACITY` messages. A connection's
+/// capacity can be lowered, but not raised, with
+/// [`ConnectionOptions::capacity`]. Several output ports may be connected to
+/// the same input port, which then reads as end of stream once all of them
+/// have been closed and its buffer drained.
+///
+/// The buffers of input ports and the pending messages of output ports are
+/// fixed-size arrays, kept in the transport's tables of ports. These tables
+/// grow on the heap as ports are opened, after which sending and receiving
+/// allocates nothing further in the transport, though the encoded messages
+/// themselves are still heap-allocated [`Bytes`].
+#[derive(Debug, Default)]
+pub struct StaticBufferTransport<const CAPACITY: usize = 16> {
+    state: Mutex<StaticBufferTransportState<CAPACITY>>,
+}
+
+#[derive(Debug, Default)]
+struct StaticBufferTransportState<const CAPACITY: usize> {
+    inputs: Vec<StaticInputPort<CAPACITY>>,
+    outputs: Vec<StaticOutputPort<CAPACITY>>,
+    operations: u64,
+}
+
+#[derive(Debug)]
+struct StaticInputPort<const CAPACITY: usize> {
+    is_closed: bool,
+    senders: usize,
+    capacity: usize,
+    buffer: StaticBuffer<CAPACITY>,
+}
+
+#[derive(Debug, Default)]
+struct StaticOutputPort<const CAPACITY: usize> {
+    state: PortState,
+    target: Option<InputPortID>,
+    pending: StaticBuffer<CAPACITY>,
+}
+
+/// A fixed-capacity FIFO ring buffer of encoded messages.
+#[derive(Debug)]
+struct StaticBuffer<const CAPACITY: usize> {
+    slots: [Option<Bytes>; CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl<const CAPACITY: usize> StaticBufferTransport<CAPACITY> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of messages buffered at the input port that the
+    /// given output port is connected to.
+    pub fn backlog(&self, output: OutputPortID) -> PortResult<usize> {
+        let mut state = self.state.lock();
+        let Some(input) = state.output(output)?.target else {
+            return Ok(0);
+        };
+        Ok(state.input(input)?.buffer.len)
+    }
+
+    /// Delivers as many of the given output port's pending messages as its
+    /// input port has room for, returning the number still pending.
+    pub(crate) fn flush(&self, output: OutputPortID) -> PortResult<usize> {
+        let mut state = self.state.lock();
+        let StaticBufferTransportState {
+            inputs,
+            outputs,
+            operations,
+        } = &mut *state;
+        let Some(port) = outputs.get_mut(output.index()) else {
+            return Err(PortError::Invalid(output.into()));
+        };
+        if port.pending.len == 0 {
+            return Ok(0);
+        }
+        if let Some(input) = port.target {
+            let Some(input) = inputs.get_mut(input.index()) else {
+                return Err(PortError::Invalid(input.into()));
+            };
+            if input.is_closed {
+                port.pending.clear();
+            }
+            while input.buffer.len < input.capacity {
+                let Some(message) = port.pending.pop() else {
+                    break;
+                };
+                input.buffer.push(message);
+                *operations += 1;
+            }
+        }
+        let (remaining, is_closed) = (port.pending.len, port.state.is_closed());
+        if remaining == 0 && is_closed {
+            state.disconnect(output)?; // deferred by `close_output`
+        }
+        Ok(remaining)
+    }
+
+    /// Returns the number of messages sent and received so far, which
+    /// increases whenever execution makes progress.
+    pub(crate) fn operations(&self) -> u64 {
+        self.state.lock().operations
+    }
+}
+
+impl<const CAPACITY: usize> StaticBufferTransportState<CAPACITY> {
+    fn input(&mut self, input: InputPortID) -> PortResult<&mut StaticInputPort<CAPACITY>> {
+        self.inputs
+            .get_mut(input.index())
+            .ok_or(PortError::Invalid(input.into()))
+    }
+
+    fn output(&mut self, output: OutputPortID) -> PortResult<&mut StaticOutputPort<CAPACITY>> {
+        self.outputs
+            .get_mut(output.index())
+            .ok_or(PortError::Invalid(output.into()))
+    }
+
+    fn disconnect(&mut self, output: OutputPortID) -> PortResult<()> {
+        if let Some(input) = self.output(output)?.target.take() {
+            let input = self.input(input)?;
+            input.senders = input.senders.saturating_sub(1);
+        }
+        Ok(())
+    }
+}
+
+impl<const CAPACITY: usize> StaticInputPort<CAPACITY> {
+    fn open() -> Self {
+        Self {
+            is_closed: false,
+            senders: 0,
+            capacity: CAPACITY,
+            buffer: StaticBuffer::default(),
+        }
+    }
+
+    /// An input port remains connected until its buffer has been drained.
+    fn state(&self) -> PortState {
+        if self.is_closed {
+            PortState::Closed
+        } else if self.senders > 0 || self.buffer.len > 0 {
+            PortState::Connected
+        } else {
+            PortState::Open
+        }
+    }
+}
+
+impl<const CAPACITY: usize> Default for StaticBuffer<CAPACITY> {
+    fn default() -> Self {
+        Self {
+            slots: core::array::from_fn(|_| None),
+            head: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<const CAPACITY: usize> StaticBuffer<CAPACITY> {
+    fn push(&mut self, message: Bytes) {
+        debug_assert!(self.len < CAPACITY);
+        self.slots[(self.head + self.len) % CAPACITY] = Some(message);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<Bytes> {
+        if self.len == 0 {
+            return None;
+        }
+        let message = self.slots[self.head].take();
+        self.head = (self.head + 1) % CAPACITY;
+        self.len -= 1;
+        message
+    }
+
+    fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<const CAPACITY: usize> Transport for StaticBufferTransport<CAPACITY> {
+    fn input_state(&self, input: InputPortID) -> PortResult<PortState> {
+        Ok(self.state.lock().input(input)?.state())
+    }
+
+    fn output_state(&self, output: OutputPortID) -> PortResult<PortState> {
+        Ok(self.state.lock().output(output)?.state)
+    }
+
+    fn open_input(&self) -> PortResult<InputPortID> {
+        let mut state = self.state.lock();
+        state.inputs.push(StaticInputPort::open());
+        Ok(InputPortID(-(state.inputs.len() as isize)))
+    }
+
+    fn open_output(&self) -> PortResult<OutputPortID> {
+        let mut state = self.state.lock();
+        state.outputs.push(StaticOutputPort {
+            state: PortState::Open,
+            ..Default::default()
+        });
+        Ok(OutputPortID(state.outputs.len() as isize))
+    }
+
+    fn close_input(&self, input: InputPortID) -> PortResult<bool> {
+        let mut state = self.state.lock();
+        let port = state.input(input)?;
+        if port.is_closed {
+            return Ok(false); // already closed
+        }
+        port.is_closed = true;
+        port.buffer.clear();
+        Ok(true)
+    }
+
+    fn close_output(&self, output: OutputPortID) -> PortResult<bool> {
+        let mut state = self.state.lock();
+        if state.output(output)?.state.is_closed() {
+            return Ok(false); // already closed
+        }
+        let port = state.output(output)?;
+        port.state = PortState::Closed;
+        if port.pending.len == 0 {
+            state.disconnect(output)?; // else, once the pending messages are flushed
+        }
+        state.operations += 1;
+        Ok(true)
+    }
+
+    fn connect(&self, source: OutputPortID, target: InputPortID) -> PortResult<bool> {
+        self.connect_with(source, target, ConnectionOptions::default())
+    }
+
+    fn connect_with(
+        &self,
+        source: OutputPortID,
+        target: InputPortID,
+        options: ConnectionOptions,
+    ) -> PortResult<bool> {
+        let mut state = self.state.lock();
+        if !state.output(source)?.state.is_open() {
+            return Err(PortError::Invalid(source.into()));
+        }
+        let input = state.input(target)?;
+        if input.is_closed {
+            return Err(PortError::Invalid(target.into()));
+        }
+        if let Some(capacity) = options.capacity {
+            input.capacity = input.capacity.min(capacity.max(1));
+        }
+        input.senders += 1;
+        let output = state.output(source)?;
+        output.state = PortState::Connected;
+        output.target = Some(target);
+        Ok(true)
+    }
+
+    fn send(&self, output: OutputPortID, message: Bytes) -> PortResult<()> {
+        let mut state = self.state.lock();
+        let port = state.output(output)?;
+        let input = match (port.state, port.target) {
+            (PortState::Closed, _) => return Err(PortError::Closed),
+            (PortState::Connected, Some(input)) => input,
+            (PortState::Open | PortState::Connected, _) => return Err(PortError::Disconnected),
+        };
+        let port = state.input(input)?;
+        if port.is_closed {
+            return Err(PortError::Closed);
+        }
+        let is_full = port.buffer.len >= port.capacity;
+        let output = state.output(output)?;
+        if is_full || output.pending.len > 0 {
+            if output.pending.len >= CAPACITY {
+                return Err(PortError::WouldBlock);
+            }
+            output.pending.push(message);
+        } else {
+            state.input(input)?.buffer.push(message);
+        }
+        state.operations += 1;
+        Ok(())
+    }
+
+    fn recv(&self, input: InputPortID) -> PortResult<Option<Bytes>> {
+        let mut state = self.state.lock();
+        let port = state.input(input)?;
+        if let Some(message) = port.buffer.pop() {
+            state.operations += 1;
+            return Ok(Some(message));
+        }
+        match port.state() {
+            PortState::Connected => Err(PortError::WouldBlock),
+            PortState::Open | PortState::Closed => Ok(None), // EOS
+        }
+    }
+
+    fn try_recv(&self, input: InputPortID) -> PortResult<Option<Bytes>> {
+        match self.recv(input) {
+            Err(PortError::WouldBlock) => Ok(None),
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_core::{
+    transports::MockTransport, ConnectionOptions, InputPortID, OutputPortID, PortError, PortID,
+    PortState, Transport, TransportEvent,
+};
+use std::time::{Duration, Instant};
+
+fn connected_pair(transport: &MockTransport) -> (OutputPortID, InputPortID) {
+    let output = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    assert!(transport.connect(output, input).unwrap());
+    (output, input)
+}
+
+#[test]
+fn send_and_recv() {
+    let transport = MockTransport::new();
+    let (output, input) = connected_pair(&transport);
+
+    transport.send(output, "hello".into()).unwrap();
+    transport.send(output, "world".into()).unwrap();
+    assert_eq!(transport.inbox(input).unwrap(), ["hello", "world"]);
+    assert_eq!(transport.recv(input).unwrap(), Some("hello".into()));
+    assert_eq!(transport.try_recv(input).unwrap(), Some("world".into()));
+    assert_eq!(transport.try_recv(input).unwrap(), None);
+
+    let sent = transport.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!((sent[0].output, sent[0].input), (output, input));
+    assert_eq!(sent[1].message, "world");
+}
+
+#[test]
+fn port_states() {
+    let transport = MockTransport::with_ports(2, 1);
+    let (output, input) = (
+        OutputPortID::try_from(1).unwrap(),
+        InputPortID::try_from(-2).unwrap(),
+    );
+    transport.connect(output, input).unwrap();
+    assert_eq!(
+        transport.states().into_iter().collect::<Vec<_>>(),
+        [
+            (PortID::Input(input), PortState::Connected),
+            (
+                PortID::Input(InputPortID::try_from(-1).unwrap()),
+                PortState::Open
+            ),
+            (PortID::Output(output), PortState::Connected),
+        ]
+    );
+    assert_eq!(transport.connections().get(&output), Some(&input));
+
+    assert!(transport.close_output(output).unwrap());
+    assert!(!transport.close_output(output).unwrap());
+    assert_eq!(transport.output_state(output).unwrap(), PortState::Closed);
+    assert_eq!(transport.input_state(input).unwrap(), PortState::Open);
+    assert!(transport.connections().is_empty());
+    assert_eq!(transport.send(output, "x".into()), Err(PortError::Closed));
+}
+
+#[test]
+fn deliver_by_priority() {
+    let transport = MockTransport::new();
+    let output = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    transport
+        .connect_with(output, input, ConnectionOptions::with_priority(5))
+        .unwrap();
+
+    transport.send_with_priority(output, "a".into(), 0).unwrap();
+    transport.send(output, "b".into()).unwrap();
+    transport.send(output, "c".into()).unwrap();
+    transport.send_with_priority(output, "d".into(), 9).unwrap();
+    assert_eq!(transport.inbox(input).unwrap(), ["d", "b", "c", "a"]);
+    assert_eq!(
+        transport
+            .sent()
+            .into_iter()
+            .map(|record| record.priority)
+            .collect::<Vec<_>>(),
+        [0, 5, 5, 9]
+    );
+    for expected in ["d", "b", "c", "a"] {
+        assert_eq!(transport.recv(input).unwrap(), Some(expected.into()));
+    }
+}
+
+#[test]
+fn fence_priorities_with_barriers() {
+    let transport = MockTransport::new();
+    let (output, input) = connected_pair(&transport);
+
+    transport.send(output, "a".into()).unwrap();
+    transport.send_barrier(output, 1).unwrap();
+    transport.send_with_priority(output, "b".into(), 9).unwrap();
+    assert_eq!(transport.inbox(input).unwrap(), ["a", "b"]);
+    assert_eq!(
+        transport.recv_event(input).unwrap(),
+        Some(TransportEvent::Message("a".into()))
+    );
+    assert_eq!(
+        transport.recv_event(input).unwrap(),
+        Some(TransportEvent::Barrier(1))
+    );
+    assert_eq!(
+        transport.recv_event(input).unwrap(),
+        Some(TransportEvent::Message("b".into()))
+    );
+}
+
+#[test]
+fn fail_receives_on_closed_inputs() {
+    let transport = MockTransport::new();
+    let (output, input) = connected_pair(&transport);
+
+    transport.send(output, "a".into()).unwrap();
+    assert!(transport.close_input(input).unwrap());
+    assert_eq!(transport.recv(input), Err(PortError::Closed));
+    assert_eq!(transport.try_recv(input), Err(PortError::Closed));
+    assert_eq!(transport.recv_event(input), Err(PortError::Closed));
+}
+
+#[test]
+fn unconnected_inputs_read_as_end_of_stream() {
+    let transport = MockTransport::new();
+    let input = transport.open_input().unwrap();
+    transport.push_message(input, &42u64).unwrap();
+    assert_eq!(transport.inbox(input).unwrap().len(), 1);
+    assert!(transport.recv(input).unwrap().is_some());
+    assert_eq!(transport.recv(input).unwrap(), None);
+}
+
+#[test]
+fn decode_sent_messages() {
+    let transport = MockTransport::new();
+    let (output, _) = connected_pair(&transport);
+    for n in [1u64, 2, 3] {
+        transport
+            .send(
+                output,
+                prost::Message::encode_length_delimited_to_vec(&n).into(),
+            )
+            .unwrap();
+    }
+    assert_eq!(transport.sent_messages::<u64>(output).unwrap(), [1, 2, 3]);
+}
+
+#[test]
+fn inject_send_failures() {
+    let transport = MockTransport::new();
+    let (output, input) = connected_pair(&transport);
+    transport
+        .fail_sends(output, Some(PortError::SendFailed))
+        .unwrap();
+    assert_eq!(
+        transport.send(output, "x".into()),
+        Err(PortError::SendFailed)
+    );
+    assert!(transport.sent().is_empty());
+    transport.fail_sends(output, None).unwrap();
+    transport.send(output, "y".into()).unwrap();
+    assert_eq!(transport.inbox(input).unwrap(), ["y"]);
+}
+
+#[test]
+fn inject_disconnects() {
+    let transport = MockTransport::new();
+    let (output, input) = connected_pair(&transport);
+    transport.send(output, "x".into()).unwrap();
+    assert!(transport.disconnect(PortID::Input(input)).unwrap());
+    assert!(!transport.disconnect(PortID::Output(output)).unwrap());
+    assert_eq!(
+        transport.send(output, "y".into()),
+        Err(PortError::Disconnected)
+    );
+    assert_eq!(transport.recv(input).unwrap(), Some("x".into()));
+    assert_eq!(transport.recv(input).unwrap(), None);
+
+    // Both ports remain open, and can be reconnected:
+    assert!(transport.connect(output, input).unwrap());
+}
+
+#[test]
+fn inject_delays() {
+    let transport = MockTransport::new();
+    let (output, _) = connected_pair(&transport);
+    transport
+        .delay_sends(output, Some(Duration::from_millis(20)))
+        .unwrap();
+    let start = Instant::now();
+    transport.send(output, "x".into()).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
+
+#[test]
+fn invalid_ports() {
+    let transport = MockTransport::new();
+    let input = InputPortID::try_from(-1).unwrap();
+    assert_eq!(transport.recv(input), Err(PortError::Invalid(input.into())));
+}
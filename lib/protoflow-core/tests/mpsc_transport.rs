@@ -0,0 +1,298 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_core::{
+    transports::MpscTransport, ConnectionOptions, PortError, Transport, TransportEvent,
+};
+#[cfg(feature = "instrument")]
+use protoflow_core::{HeldEventKind, PortID};
+use std::{sync::Arc, thread, time::Duration};
+
+#[test]
+fn deliver_by_priority() {
+    let transport = MpscTransport::new();
+    let bulk = transport.open_output().unwrap();
+    let urgent = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    let options = ConnectionOptions::with_capacity(4);
+    transport.connect_with(bulk, input, options).unwrap();
+    let options = ConnectionOptions {
+        priority: Some(5),
+        ..options
+    };
+    transport.connect_with(urgent, input, options).unwrap();
+
+    transport.send(bulk, "a".into()).unwrap();
+    transport.send(bulk, "b".into()).unwrap();
+    transport.send(urgent, "c".into()).unwrap();
+    transport.send_with_priority(bulk, "d".into(), 9).unwrap();
+    for expected in ["d", "c", "a", "b"] {
+        assert_eq!(transport.recv(input).unwrap(), Some(expected.into()));
+    }
+
+    transport.close_output(urgent).unwrap();
+    transport.close_output(bulk).unwrap();
+    assert_eq!(transport.recv(input).unwrap(), None);
+}
+
+#[test]
+fn fence_priorities_with_barriers() {
+    let transport = MpscTransport::new();
+    let bulk = transport.open_output().unwrap();
+    let urgent = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    let options = ConnectionOptions::with_capacity(4);
+    transport.connect_with(bulk, input, options).unwrap();
+    let options = ConnectionOptions {
+        priority: Some(5),
+        ..options
+    };
+    transport.connect_with(urgent, input, options).unwrap();
+
+    // Messages sent before the barrier arrive before it, and messages sent
+    // after it arrive after it, whatever their priorities:
+    transport.send(bulk, "a".into()).unwrap();
+    transport.send(urgent, "b".into()).unwrap();
+    transport.send_barrier(bulk, 1).unwrap();
+    transport.send_barrier(urgent, 1).unwrap();
+    transport.send(urgent, "c".into()).unwrap();
+    transport.send(bulk, "d".into()).unwrap();
+    let mut events = Vec::new();
+    for _ in 0..5 {
+        events.push(match transport.recv_event(input).unwrap().unwrap() {
+            TransportEvent::Message(bytes) => String::from_utf8(bytes.to_vec()).unwrap(),
+            TransportEvent::Barrier(checkpoint) => format!("#{}", checkpoint),
+        });
+    }
+    assert_eq!(events, ["b", "a", "#1", "c", "d"]);
+}
+
+#[test]
+fn align_barriers_of_shared_inputs() {
+    let transport = MpscTransport::new();
+    let first = transport.open_output().unwrap();
+    let second = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    let options = ConnectionOptions::with_capacity(4);
+    transport.connect_with(first, input, options).unwrap();
+    transport.connect_with(second, input, options).unwrap();
+    let recv_events = |count| {
+        let mut events = Vec::new();
+        for _ in 0..count {
+            events.push(match transport.recv_event(input).unwrap() {
+                Some(TransportEvent::Message(bytes)) => String::from_utf8(bytes.to_vec()).unwrap(),
+                Some(TransportEvent::Barrier(checkpoint)) => format!("#{}", checkpoint),
+                None => String::from("EOS"),
+            });
+        }
+        events
+    };
+
+    // The barrier is delivered once both outputs have sent it, with the
+    // messages sent after it held back until then:
+    transport.send(first, "a".into()).unwrap();
+    transport.send_barrier(first, 1).unwrap();
+    transport.send(first, "b".into()).unwrap();
+    transport.send(second, "c".into()).unwrap();
+    transport.send_barrier(second, 1).unwrap();
+    assert_eq!(recv_events(4), ["a", "c", "#1", "b"]);
+
+    // An output disconnecting stops the barrier waiting for it:
+    transport.send_barrier(first, 2).unwrap();
+    transport.send(first, "d".into()).unwrap();
+    transport.send(second, "e".into()).unwrap();
+    transport.close_output(second).unwrap();
+    transport.close_output(first).unwrap();
+    assert_eq!(recv_events(4), ["e", "#2", "d", "EOS"]);
+}
+
+#[test]
+fn fail_on_closed_input() {
+    let transport = Arc::new(MpscTransport::new());
+    let output = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    let options = ConnectionOptions::with_capacity(1);
+    transport.connect_with(output, input, options).unwrap();
+
+    // A send blocked on a full channel fails once the input is closed:
+    transport.send(output, "a".into()).unwrap();
+    let sender = {
+        let transport = transport.clone();
+        thread::spawn(move || transport.send(output, "b".into()))
+    };
+    thread::sleep(Duration::from_millis(10));
+    transport.close_input(input).unwrap();
+    assert_eq!(sender.join().unwrap(), Err(PortError::Disconnected));
+    assert_eq!(
+        transport.send(output, "c".into()),
+        Err(PortError::Disconnected)
+    );
+    assert_eq!(transport.close_output(output), Ok(true));
+}
+
+#[test]
+fn disconnect_and_reconnect() {
+    let transport = MpscTransport::new();
+    let output = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    let options = ConnectionOptions::with_capacity(4);
+    transport.connect_with(output, input, options).unwrap();
+
+    // The input drains the messages already sent before reaching EOS, while
+    // the output stays open for reconnecting:
+    transport.send(output, "a".into()).unwrap();
+    assert_eq!(transport.disconnect(output), Ok(true));
+    assert_eq!(transport.disconnect(output), Ok(false));
+    assert_eq!(
+        transport.send(output, "b".into()),
+        Err(PortError::Disconnected)
+    );
+    assert_eq!(transport.recv(input), Ok(Some("a".into())));
+    assert_eq!(transport.recv(input), Ok(None)); // EOS
+
+    let other_input = transport.open_input().unwrap();
+    transport
+        .connect_with(output, other_input, options)
+        .unwrap();
+    transport.send(output, "c".into()).unwrap();
+    assert_eq!(transport.recv(other_input), Ok(Some("c".into())));
+}
+
+#[test]
+fn wake_receive_on_closed_input() {
+    let transport = Arc::new(MpscTransport::new());
+    let output = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    transport.connect(output, input).unwrap();
+
+    let receiver = {
+        let transport = transport.clone();
+        thread::spawn(move || transport.recv(input))
+    };
+    thread::sleep(Duration::from_millis(10));
+    transport.close_input(input).unwrap();
+    assert_eq!(receiver.join().unwrap(), Err(PortError::Closed));
+    assert_eq!(transport.recv(input), Err(PortError::Closed));
+    assert_eq!(transport.try_recv(input), Err(PortError::Closed));
+}
+
+#[test]
+fn try_receive_without_blocking() {
+    let transport = MpscTransport::new();
+    let output = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    assert_eq!(transport.try_recv(input), Ok(None)); // not connected
+
+    let options = ConnectionOptions::with_capacity(4);
+    transport.connect_with(output, input, options).unwrap();
+    assert_eq!(transport.try_recv(input), Ok(None)); // no message pending
+    transport.send(output, "a".into()).unwrap();
+    transport.send_barrier(output, 1).unwrap();
+    transport.send(output, "b".into()).unwrap();
+    assert_eq!(transport.try_recv(input), Ok(Some("a".into())));
+    assert_eq!(transport.try_recv(input), Ok(Some("b".into())));
+    assert_eq!(transport.try_recv(input), Ok(None));
+
+    transport.close_output(output).unwrap();
+    assert_eq!(transport.try_recv(input), Ok(None)); // EOS
+    assert_eq!(transport.recv(input), Err(PortError::Closed));
+}
+
+#[test]
+#[cfg(feature = "instrument")]
+fn record_connection_metrics() {
+    let transport = MpscTransport::new();
+    let first = transport.open_output().unwrap();
+    let second = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    let options = ConnectionOptions::with_capacity(4);
+    transport.connect_with(first, input, options).unwrap();
+    transport.connect_with(second, input, options).unwrap();
+
+    // Messages sent before metrics are enabled aren't recorded:
+    transport.send(first, "a".into()).unwrap();
+    assert_eq!(transport.recv(input).unwrap(), Some("a".into()));
+    assert!(transport.metrics().is_empty());
+
+    transport.enable_metrics();
+    transport.send(first, "bc".into()).unwrap();
+    transport.send(first, "def".into()).unwrap();
+    transport.send(second, "g".into()).unwrap();
+    std::thread::sleep(Duration::from_millis(10));
+    for _ in 0..3 {
+        transport.recv(input).unwrap();
+    }
+
+    let metrics = transport.metrics();
+    assert_eq!(metrics.len(), 2);
+    assert_eq!((metrics[0].source, metrics[0].target), (first, input));
+    assert_eq!((metrics[0].messages, metrics[0].bytes), (2, 5));
+    assert_eq!((metrics[1].source, metrics[1].target), (second, input));
+    assert_eq!((metrics[1].messages, metrics[1].bytes), (1, 1));
+    let percentiles = metrics[0].latency_percentiles(&[0.0, 100.0]).unwrap();
+    assert!(percentiles[0] >= Duration::from_millis(10));
+    assert!(percentiles[0] <= percentiles[1]);
+}
+
+#[test]
+#[cfg(feature = "instrument")]
+fn hold_deliveries_for_debugging() {
+    let transport = MpscTransport::new();
+    let output = transport.open_output().unwrap();
+    let input = transport.open_input().unwrap();
+    let options = ConnectionOptions::with_capacity(1);
+    transport.connect_with(output, input, options).unwrap();
+
+    // While paused, sending doesn't block even though the input is at
+    // capacity, and nothing is delivered until stepped through:
+    transport.pause();
+    for message in ["a", "b", "c"] {
+        transport.send(output, message.into()).unwrap();
+    }
+    assert!(transport.wait_held(Duration::ZERO));
+    let held = transport.held_events();
+    assert_eq!(
+        held.iter().map(|event| event.id).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert_eq!(held[0].kind, HeldEventKind::Message("a".into()));
+    assert_eq!((held[0].source, held[0].target), (output, input));
+
+    assert_eq!(transport.step().unwrap().id, 1);
+    assert_eq!(transport.step(), None); // the input is at capacity
+    assert_eq!(transport.recv(input).unwrap(), Some("a".into()));
+
+    assert_eq!(transport.drop_held(2).unwrap().id, 2);
+    assert_eq!(transport.drop_held(2), None);
+    assert_eq!(transport.inject(output, "d".into()).unwrap(), 4);
+    assert_eq!(transport.step().unwrap().id, 3);
+    assert_eq!(transport.recv(input).unwrap(), Some("c".into()));
+
+    // Once resumed, the held events are delivered in order, until one hits
+    // a breakpoint, which pauses delivery again:
+    assert!(transport.set_breakpoint(PortID::Input(input)));
+    transport.resume();
+    assert_eq!(transport.paused_at().unwrap().id, 4);
+    transport.resume();
+    assert!(!transport.is_paused());
+    assert_eq!(transport.recv(input).unwrap(), Some("d".into()));
+    transport.send(output, "e".into()).unwrap();
+    assert!(transport.is_paused());
+    assert_eq!(transport.paused_at().unwrap().id, 5);
+    assert_eq!(
+        transport.held_events()[0].kind,
+        HeldEventKind::Message("e".into())
+    );
+
+    // Messages can still be injected once the output port is closed, as
+    // long as its disconnection is held:
+    assert!(transport.clear_breakpoint(PortID::Input(input)));
+    transport.close_output(output).unwrap();
+    assert_eq!(transport.held_events()[1].kind, HeldEventKind::Disconnect);
+    assert_eq!(transport.inject(output, "f".into()).unwrap(), 7);
+    assert_eq!(transport.held_events()[2].kind, HeldEventKind::Disconnect);
+    transport.resume();
+    assert_eq!(transport.recv(input).unwrap(), Some("e".into()));
+    assert_eq!(transport.recv(input).unwrap(), Some("f".into()));
+    assert_eq!(transport.recv(input).unwrap(), None);
+    assert!(transport.inject(output, "g".into()).is_err());
+}
@@ -1,11 +1,14 @@
 // This is free and unencumbered software released into the public domain.
 
-use crate::{meta::BlockFieldAttribute, util::protoflow_crate};
+use crate::{
+    meta::{parameter_default, BlockFieldAttribute},
+    util::protoflow_crate,
+};
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::{
-    self, Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed, Ident, Result,
-    Type,
+    self, spanned::Spanned, Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed,
+    FieldsUnnamed, Ident, Result, Type,
 };
 
 pub(crate) fn expand_derive_block(input: &DeriveInput) -> Result<TokenStream> {
@@ -40,6 +43,73 @@ pub(crate) fn expand_derive_block_for_struct(
     let generics = &input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
+    // `#[state]` fields are captured in checkpoints if their type is
+    // `State<T>`. Fields of any other type are deprecated, and not captured:
+    let state_handles: Vec<TokenStream> = fields
+        .iter()
+        .filter(|field| {
+            field
+                .attrs
+                .iter()
+                .any(|attr| matches!(BlockFieldAttribute::try_from(attr), Ok(State)))
+        })
+        .map(|field| {
+            let field_name = field
+                .ident
+                .as_ref()
+                .ok_or_else(|| syn::Error::new_spanned(field, "`#[state]` fields must be named"))?;
+            let field_name_str = field_name.to_string();
+            let capture_state = quote_spanned! {field.ty.span()=>
+                (&&#protoflow::CapturedState(&self.#field_name)).capture_state()
+            };
+            Ok(quote! {
+                {
+                    #[allow(unused_imports)]
+                    use #protoflow::{CaptureStateFallback as _, CaptureStateHandle as _};
+                    if let Some(handle) = #capture_state {
+                        state_handles.push((#field_name_str, handle));
+                    }
+                }
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    // Parameters are described with their full type, declared default, and
+    // configured value, if their type implements `ParameterValue`:
+    let parameter_descriptors: Vec<TokenStream> = fields
+        .iter()
+        .filter(|field| {
+            field
+                .attrs
+                .iter()
+                .any(|attr| matches!(BlockFieldAttribute::try_from(attr), Ok(Parameter)))
+        })
+        .filter_map(|field| Some((field.ident.as_ref()?, field)))
+        .map(|(param_name, field)| {
+            // TODO: implement label
+            let param_name_str = param_name.to_string();
+            let mut param_type_str = field.ty.to_token_stream().to_string();
+            param_type_str.retain(|c| !c.is_ascii_whitespace());
+            let default_value = match parameter_default(&field.attrs)? {
+                Some(default) => quote! { Some(#protoflow::prelude::String::from(#default)) },
+                None => quote! { None },
+            };
+            Ok(quote! {
+                #protoflow::ParameterDescriptor {
+                    name: #protoflow::prelude::String::from(#param_name_str),
+                    label: None,
+                    r#type: Some(#protoflow::prelude::String::from(#param_type_str)),
+                    default_value: #default_value,
+                    value: {
+                        #[allow(unused_imports)]
+                        use #protoflow::{DescribeParameterFallback as _, DescribeParameterValue as _};
+                        (&&#protoflow::DescribedParameter(&self.#param_name)).describe_value()
+                    },
+                }
+            })
+        })
+        .collect::<Result<_>>()?;
+
     let fields: Vec<(Ident, Option<Type>, Option<BlockFieldAttribute>)> = fields
         .iter()
         .filter_map(|field| {
@@ -103,14 +173,13 @@ pub(crate) fn expand_derive_block_for_struct(
         .map(|(port_name, port_type)| {
             // TODO: mandatory name; implement label
             let port_name_str = port_name.to_string();
-            let mut port_type_str = port_type.to_token_stream().to_string();
-            port_type_str.retain(|c| !c.is_ascii_whitespace());
+            let port_type = port_type_name(port_type);
             quote! {
                 #protoflow::PortDescriptor {
                     direction: #protoflow::PortDirection::Input,
                     name: Some(#protoflow::prelude::String::from(#port_name_str)),
                     label: None,
-                    r#type: Some(#protoflow::prelude::String::from(#port_type_str)),
+                    r#type: #port_type,
                     id: #protoflow::Port::id(&self.#port_name),
                     state: #protoflow::Port::state(&self.#port_name),
                 }
@@ -123,14 +192,13 @@ pub(crate) fn expand_derive_block_for_struct(
         .map(|(port_name, port_type)| {
             // TODO: mandatory name; implement label
             let port_name_str = port_name.to_string();
-            let mut port_type_str = port_type.to_token_stream().to_string();
-            port_type_str.retain(|c| !c.is_ascii_whitespace());
+            let port_type = port_type_name(port_type);
             quote! {
                 #protoflow::PortDescriptor {
                     direction: #protoflow::PortDirection::Output,
                     name: Some(#protoflow::prelude::String::from(#port_name_str)),
                     label: None,
-                    r#type: Some(#protoflow::prelude::String::from(#port_type_str)),
+                    r#type: #port_type,
                     id: #protoflow::Port::id(&self.#port_name),
                     state: #protoflow::Port::state(&self.#port_name),
                 }
@@ -138,30 +206,7 @@ pub(crate) fn expand_derive_block_for_struct(
         })
         .collect();
 
-    let parameters: Vec<(Ident, Option<Type>)> = fields
-        .iter()
-        .filter(|(.., attr)| matches!(attr, Some(Parameter)))
-        .map(|(name, ty, ..)| (name.clone(), ty.clone()))
-        .collect();
-
-    let parameter_descriptors: Vec<TokenStream> = parameters
-        .iter()
-        .map(|(param_name, param_type)| {
-            // TODO: implement label, default_value
-            let param_name_str = param_name.to_string();
-            let mut param_type_str = param_type.to_token_stream().to_string();
-            param_type_str.retain(|c| !c.is_ascii_whitespace());
-            quote! {
-                #protoflow::ParameterDescriptor {
-                    name: #protoflow::prelude::String::from(#param_name_str),
-                    label: None,
-                    r#type: Some(#protoflow::prelude::String::from(#param_type_str)),
-                    default_value: None,
-                }
-            }
-        })
-        .collect();
-
+    let ident_str = ident.to_string();
     let impl_dogma_traits = quote! {
         #[automatically_derived]
         #[allow(
@@ -170,7 +215,7 @@ pub(crate) fn expand_derive_block_for_struct(
         )]
         impl #impl_generics #protoflow::prelude::MaybeNamed for #ident #ty_generics #where_clause {
             fn name(&self) -> #protoflow::prelude::Option<#protoflow::prelude::Cow<str>> {
-                None // TODO
+                Some(#protoflow::prelude::Cow::Borrowed(#ident_str))
             }
         }
 
@@ -253,6 +298,16 @@ pub(crate) fn expand_derive_block_for_struct(
                 )*
                 Ok(())
             }
+
+            fn state_handles(&self) -> #protoflow::prelude::Vec<(&'static str, #protoflow::prelude::Box<dyn #protoflow::StateHandle>)>
+            where
+                Self: 'static,
+            {
+                #[allow(unused_mut)]
+                let mut state_handles = #protoflow::prelude::Vec::new();
+                #(#state_handles)*
+                state_handles
+            }
         }
     };
 
@@ -263,3 +318,16 @@ pub(crate) fn expand_derive_block_for_struct(
         #impl_dogma_traits
     })
 }
+
+/// Expands to the runtime type name of a port's message type, if known.
+fn port_type_name(port_type: &Option<Type>) -> TokenStream {
+    let protoflow = protoflow_crate();
+    match port_type {
+        Some(port_type) => quote! {
+            Some(#protoflow::prelude::String::from(
+                #protoflow::prelude::type_name::<#port_type>()
+            ))
+        },
+        None => quote! { None },
+    }
+}
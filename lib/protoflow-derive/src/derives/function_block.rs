@@ -1,9 +1,11 @@
 // This is free and unencumbered software released into the public domain.
 
-use crate::util::protoflow_crate;
+use crate::{meta::FunctionBlockFieldAttribute, util::protoflow_crate};
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{self, Data, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUnnamed, Result};
+use syn::{
+    self, Data, DataStruct, DeriveInput, Error, Fields, FieldsNamed, FieldsUnnamed, Member, Result,
+};
 
 pub(crate) fn expand_derive_function_block(input: &DeriveInput) -> Result<TokenStream> {
     let protoflow = protoflow_crate();
@@ -12,25 +14,95 @@ pub(crate) fn expand_derive_function_block(input: &DeriveInput) -> Result<TokenS
     let generics = &input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let _fields = match &input.data {
+    // Tuple structs are `(input, output)` or `(input, output, error)`, whereas
+    // named fields designate their ports with `#[input]`, `#[output]`, and
+    // an optional `#[error]`:
+    let (input_port, output_port, error_port) = match &input.data {
         Data::Struct(DataStruct {
             fields: Fields::Named(FieldsNamed { named: fields, .. }),
             ..
-        }) => fields.into_iter().collect(),
+        }) => {
+            let port = |kind: fn(&FunctionBlockFieldAttribute) -> bool| {
+                fields
+                    .iter()
+                    .find(|field| {
+                        field.attrs.iter().any(|attr| {
+                            FunctionBlockFieldAttribute::try_from(attr)
+                                .is_ok_and(|attr| kind(&attr))
+                        })
+                    })
+                    .and_then(|field| field.ident.clone())
+                    .map(Member::Named)
+            };
+            (
+                port(|attr| matches!(attr, FunctionBlockFieldAttribute::Input)),
+                port(|attr| matches!(attr, FunctionBlockFieldAttribute::Output)),
+                port(|attr| matches!(attr, FunctionBlockFieldAttribute::Error)),
+            )
+        }
         Data::Struct(DataStruct {
             fields:
                 Fields::Unnamed(FieldsUnnamed {
                     unnamed: fields, ..
                 }),
             ..
-        }) => fields.into_iter().collect(),
+        }) => {
+            let port = |index: usize| (index < fields.len()).then(|| Member::from(index));
+            (port(0), port(1), port(2))
+        }
         Data::Struct(DataStruct {
             fields: Fields::Unit,
             ..
-        }) => Vec::new(),
+        }) => (None, None, None),
         _ => panic!("`#[derive(FunctionBlock)]` only supports structs"),
     };
+    let (Some(input_port), Some(output_port)) = (input_port, output_port) else {
+        return Err(Error::new_spanned(
+            ident,
+            "`#[derive(FunctionBlock)]` requires an input port and an output port",
+        ));
+    };
+    let ports: Vec<&Member> = [Some(&input_port), Some(&output_port), error_port.as_ref()]
+        .into_iter()
+        .flatten()
+        .collect();
 
+    let port_descriptor = |port: &Member, name: &str| {
+        let name = match port {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(_) => name.to_string(),
+        };
+        quote! {
+            #protoflow::PortDescriptor {
+                name: Some(#protoflow::prelude::String::from(#name)),
+                ..#protoflow::PortDescriptor::from(&self.#port)
+            }
+        }
+    };
+    let input_port_descriptor = port_descriptor(&input_port, "input");
+    let output_port_descriptors: Vec<TokenStream> = [
+        Some(port_descriptor(&output_port, "output")),
+        error_port
+            .as_ref()
+            .map(|port| port_descriptor(port, "error")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let handle_error = match &error_port {
+        None => quote! { return Err(error) },
+        Some(error_port) => quote! {
+            if #protoflow::Port::is_connected(&self.#error_port) {
+                let error = #protoflow::prelude::ToString::to_string(&error);
+                #protoflow::OutputPort::send(&self.#error_port, &error)?;
+            } else {
+                return Err(error);
+            }
+        },
+    };
+
+    let ident_str = ident.to_string();
     let impl_dogma_traits = quote! {
         #[automatically_derived]
         #[allow(
@@ -39,7 +111,7 @@ pub(crate) fn expand_derive_function_block(input: &DeriveInput) -> Result<TokenS
         )]
         impl #impl_generics #protoflow::prelude::MaybeNamed for #ident #ty_generics #where_clause {
             fn name(&self) -> #protoflow::prelude::Option<#protoflow::prelude::Cow<str>> {
-                None // TODO
+                Some(#protoflow::prelude::Cow::Borrowed(#ident_str))
             }
         }
 
@@ -87,13 +159,13 @@ pub(crate) fn expand_derive_function_block(input: &DeriveInput) -> Result<TokenS
         impl #impl_generics #protoflow::BlockDescriptor for #ident #ty_generics #where_clause {
             fn inputs(&self) -> #protoflow::prelude::Vec<#protoflow::PortDescriptor> {
                 #protoflow::prelude::vec![
-                    #protoflow::PortDescriptor::from(&self.0),
+                    #input_port_descriptor,
                 ]
             }
 
             fn outputs(&self) -> #protoflow::prelude::Vec<#protoflow::PortDescriptor> {
                 #protoflow::prelude::vec![
-                    #protoflow::PortDescriptor::from(&self.1),
+                    #(#output_port_descriptors,)*
                 ]
             }
         }
@@ -111,8 +183,7 @@ pub(crate) fn expand_derive_function_block(input: &DeriveInput) -> Result<TokenS
             }
 
             fn post_execute(&mut self, _runtime: &dyn #protoflow::BlockRuntime) -> #protoflow::BlockResult {
-                self.0.close()?;
-                self.1.close()?;
+                #(self.#ports.close()?;)*
                 Ok(())
             }
         }
@@ -126,12 +197,16 @@ pub(crate) fn expand_derive_function_block(input: &DeriveInput) -> Result<TokenS
         )]
         impl #impl_generics #protoflow::Block for #ident #ty_generics #where_clause {
             fn execute(&mut self, _runtime: &dyn #protoflow::BlockRuntime) -> #protoflow::BlockResult {
-                let input = &self.0;
-                let output = &self.1;
-                while let Some(message) = #protoflow::InputPort::recv(input)? {
-                    if #protoflow::Port::is_connected(output) {
-                        let result = #protoflow::FunctionBlock::compute(self, message)?;
-                        #protoflow::OutputPort::send(output, &result)?;
+                while let Some(message) = #protoflow::InputPort::recv(&self.#input_port)? {
+                    match #protoflow::FunctionBlock::compute(self, message) {
+                        Ok(result) => {
+                            if #protoflow::Port::is_connected(&self.#output_port) {
+                                #protoflow::OutputPort::send(&self.#output_port, &result)?;
+                            }
+                        }
+                        Err(error) => {
+                            #handle_error
+                        }
                     }
                 }
                 Ok(())
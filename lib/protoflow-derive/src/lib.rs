@@ -17,6 +17,34 @@ pub(crate) mod util;
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
+/// Derives `Block` for a struct of ports, parameters, and state.
+///
+/// The block is named after the struct. Fields marked `#[parameter]` are
+/// described with their configured values if their types implement
+/// `ParameterValue`, and without them otherwise.
+///
+/// Fields marked `#[state]` are captured in checkpoints if they are of type
+/// `State<T>`. Marking fields of any other type is deprecated, as they aren't
+/// captured:
+///
+/// ```compile_fail
+/// # #![deny(deprecated)]
+/// # use protoflow_core::{Block, BlockResult, BlockRuntime, InputPort};
+/// # use protoflow_derive::Block;
+/// #[derive(Block, Clone)]
+/// struct Tally {
+///     #[input]
+///     input: InputPort<u64>,
+///
+///     #[state]
+///     total: u64,
+/// }
+/// # impl Block for Tally {
+/// #     fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+/// #         Ok(())
+/// #     }
+/// # }
+/// ```
 #[proc_macro_derive(Block, attributes(input, output, parameter, state))]
 pub fn derive_block(input: TokenStream) -> TokenStream {
     let input: DeriveInput = parse_macro_input!(input);
@@ -25,7 +53,7 @@ pub fn derive_block(input: TokenStream) -> TokenStream {
         .into()
 }
 
-#[proc_macro_derive(FunctionBlock, attributes())]
+#[proc_macro_derive(FunctionBlock, attributes(input, output, error))]
 pub fn derive_function_block(input: TokenStream) -> TokenStream {
     let input: DeriveInput = parse_macro_input!(input);
     derives::expand_derive_function_block(&input)
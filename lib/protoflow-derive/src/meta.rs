@@ -1,6 +1,6 @@
 // This is free and unencumbered software released into the public domain.
 
-use syn::Attribute;
+use syn::{Attribute, LitStr, Meta};
 
 #[derive(Debug, Clone)]
 pub enum BlockFieldAttribute {
@@ -28,3 +28,46 @@ impl TryFrom<&Attribute> for BlockFieldAttribute {
         }
     }
 }
+
+/// Returns the default value declared for a parameter field, as in
+/// `#[parameter(default = "1s")]`, if any.
+pub fn parameter_default(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut default = None;
+    for attr in attrs {
+        if attr.path().is_ident("parameter") && matches!(attr.meta, Meta::List(_)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    default = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported parameter property"))
+                }
+            })?;
+        }
+    }
+    Ok(default)
+}
+
+#[derive(Debug, Clone)]
+pub enum FunctionBlockFieldAttribute {
+    Input,
+    Output,
+    Error,
+}
+
+impl TryFrom<&Attribute> for FunctionBlockFieldAttribute {
+    type Error = ();
+
+    fn try_from(attr: &Attribute) -> Result<Self, ()> {
+        let path = attr.path();
+        if path.is_ident("input") {
+            Ok(FunctionBlockFieldAttribute::Input)
+        } else if path.is_ident("output") {
+            Ok(FunctionBlockFieldAttribute::Output)
+        } else if path.is_ident("error") {
+            Ok(FunctionBlockFieldAttribute::Error)
+        } else {
+            Err(())
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_core::{
+    prelude::{Duration, MaybeNamed},
+    Block, BlockDescriptor, BlockResult, BlockRuntime, BlockTester, InputPort,
+};
+use protoflow_derive::Block;
+
+/// A parameter type that doesn't implement `ParameterValue`.
+#[derive(Clone, Debug)]
+pub struct Window {
+    pub size: usize,
+}
+
+/// A block with parameters of describable and opaque types.
+#[derive(Block, Clone)]
+pub struct Sample {
+    #[input]
+    pub input: InputPort<u64>,
+
+    #[parameter]
+    pub rate: u64,
+
+    #[parameter]
+    pub period: Duration,
+
+    #[parameter]
+    pub window: Window,
+}
+
+impl Block for Sample {
+    fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+        Ok(())
+    }
+}
+
+#[test]
+fn describe_parameter_values() {
+    let tester = BlockTester::new(|s| Sample {
+        input: s.input(),
+        rate: 42,
+        period: Duration::from_millis(1500),
+        window: Window { size: 8 },
+    })
+    .unwrap();
+    let block = tester.block();
+    assert_eq!(block.name().as_deref(), Some("Sample"));
+    let values: Vec<(String, Option<String>)> = block
+        .parameters()
+        .into_iter()
+        .map(|parameter| (parameter.name, parameter.value))
+        .collect();
+    assert_eq!(
+        values,
+        [
+            ("rate".into(), Some("42".into())),
+            ("period".into(), Some("1500ms".into())),
+            ("window".into(), None),
+        ]
+    );
+}
@@ -1,17 +1,84 @@
 // This is free and unencumbered software released into the public domain.
 
-#[test]
-fn define_function_block() {
-    use protoflow_core::{BlockResult, FunctionBlock, InputPort, OutputPort};
-    use protoflow_derive::FunctionBlock;
+use protoflow_core::{
+    BlockDescriptor, BlockError, BlockResult, BlockTester, FunctionBlock, InputPort, OutputPort,
+};
+use protoflow_derive::FunctionBlock;
+
+/// A block that simply echoes inputs to outputs.
+#[derive(FunctionBlock, Clone)]
+pub struct Echo(pub InputPort<i64>, pub OutputPort<i64>);
+
+impl FunctionBlock<i64, i64> for Echo {
+    fn compute(&self, input: i64) -> BlockResult<i64> {
+        Ok(input)
+    }
+}
+
+/// A block that computes square roots, reporting negative inputs.
+#[derive(FunctionBlock, Clone)]
+pub struct SquareRoot {
+    #[input]
+    pub number: InputPort<f64>,
 
-    /// A block that simply echoes inputs to outputs.
-    #[derive(FunctionBlock, Clone)]
-    pub struct Echo(pub InputPort<i64>, pub OutputPort<i64>);
+    #[output]
+    pub root: OutputPort<f64>,
 
-    impl FunctionBlock<i64, i64> for Echo {
-        fn compute(&self, input: i64) -> BlockResult<i64> {
-            Ok(input)
+    #[error]
+    pub error: OutputPort<String>,
+}
+
+impl FunctionBlock<f64, f64> for SquareRoot {
+    fn compute(&self, input: f64) -> BlockResult<f64> {
+        if input < 0.0 {
+            return Err(BlockError::Other(format!("negative input: {}", input)));
         }
+        Ok(input.sqrt())
     }
 }
+
+#[test]
+fn define_function_block() {
+    let tester = BlockTester::new(|s| Echo(s.input(), s.output())).unwrap();
+    let block = tester.block();
+    let names = |ports: Vec<protoflow_core::PortDescriptor>| -> Vec<String> {
+        ports.into_iter().filter_map(|port| port.name).collect()
+    };
+    assert_eq!(names(block.inputs()), ["input"]);
+    assert_eq!(names(block.outputs()), ["output"]);
+
+    let tester = BlockTester::new(|s| SquareRoot {
+        number: s.input(),
+        root: s.output(),
+        error: s.output(),
+    })
+    .unwrap();
+    let block = tester.block();
+    assert_eq!(names(block.inputs()), ["number"]);
+    assert_eq!(names(block.outputs()), ["root", "error"]);
+}
+
+#[test]
+fn execute_function_block() {
+    let mut tester = BlockTester::new(|s| Echo(s.input(), s.output())).unwrap();
+    tester.push("input", &1i64).unwrap();
+    tester.push("input", &2i64).unwrap();
+    tester.run().unwrap();
+    tester.assert_outputs("output", &[1i64, 2]);
+}
+
+#[test]
+fn route_errors_to_error_port() {
+    let mut tester = BlockTester::new(|s| SquareRoot {
+        number: s.input(),
+        root: s.output(),
+        error: s.output(),
+    })
+    .unwrap();
+    tester.push("number", &4.0f64).unwrap();
+    tester.push("number", &-1.0f64).unwrap();
+    tester.push("number", &9.0f64).unwrap();
+    tester.run().unwrap();
+    tester.assert_outputs("root", &[2.0f64, 3.0]);
+    tester.assert_outputs("error", &[String::from("negative input: -1")]);
+}
@@ -6,6 +6,7 @@ extern crate std;
 use crate::prelude::String;
 use displaydoc::Display;
 use error_stack::Result;
+use protoflow_blocks::DefinitionError;
 use sysml_model::QualifiedName;
 
 pub type AnalysisResult<T> = Result<T, AnalysisError>;
@@ -14,6 +15,8 @@ pub type AnalysisResult<T> = Result<T, AnalysisError>;
 pub enum AnalysisError {
     /// Parse failure
     ParseFailure,
+    /// Invalid system definition: {0}
+    InvalidDefinition(DefinitionError),
     /// Invalid import: `{0}`.
     InvalidImport(QualifiedName),
     /// Unknown name: `{0}`.
@@ -12,5 +12,8 @@ pub use analysis_error::*;
 mod codegen;
 pub use codegen::*;
 
+mod sysml_writer;
+pub use sysml_writer::*;
+
 mod system_parser;
 pub use system_parser::*;
@@ -0,0 +1,185 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    prelude::{format, String, ToString, Vec},
+    AnalysisError, AnalysisResult,
+};
+use error_stack::Report;
+use protoflow_blocks::{BlockConnections, SystemDefinition};
+use serde_yml::{Mapping, Value};
+
+/// Writes system definitions in SysML v2 textual notation, which
+/// [`SystemDefinition::from_sysml`] parses back.
+pub trait ToSysml {
+    /// Writes the system definition as a package with the given name.
+    ///
+    /// ```rust
+    /// # use protoflow_blocks::SystemDefinition;
+    /// # use protoflow_syntax::ToSysml;
+    /// let definition = SystemDefinition::from_yaml(r#"
+    ///     greeting: !Const { output: sink, value: "Hello, world!" }
+    ///     sink: !Drop { input: greeting }
+    /// "#).unwrap();
+    /// assert_eq!(definition.to_sysml("Example").unwrap(), r#"package Example {
+    ///     import Protoflow::*;
+    ///
+    ///     block greeting : Const {
+    ///         attribute value = "Hello, world!";
+    ///     }
+    ///
+    ///     block sink : Drop;
+    ///
+    ///     connect greeting.output to sink.input;
+    /// }
+    /// "#);
+    /// ```
+    fn to_sysml(&self, package_name: &str) -> AnalysisResult<String>;
+}
+
+impl ToSysml for SystemDefinition {
+    fn to_sysml(&self, package_name: &str) -> AnalysisResult<String> {
+        let configs = self
+            .canonical_configs()
+            .map_err(|error| Report::new(AnalysisError::InvalidDefinition(error)))?;
+        let mut output = format!("package {} {{\n", name(package_name));
+        output.push_str("    import Protoflow::*;\n");
+        for ((name, config), (_, block)) in configs.iter().zip(&self.blocks) {
+            let ports: Vec<&str> = block
+                .input_connections()
+                .into_iter()
+                .chain(block.output_connections())
+                .map(|(port_name, _)| port_name)
+                .collect();
+            let mut attributes = Mapping::new();
+            if let Value::Mapping(fields) = &config.value {
+                for (field, value) in fields {
+                    if !field.as_str().is_some_and(|field| ports.contains(&field)) {
+                        attributes.insert(field.clone(), value.clone());
+                    }
+                }
+            }
+            output.push_str(&format!(
+                "\n    block {} : {}",
+                self::name(name),
+                config.tag.to_string().trim_start_matches('!')
+            ));
+            write_attributes(&mut output, &attributes, 1)?;
+        }
+        let connections = self.connections();
+        if !connections.is_empty() {
+            output.push('\n');
+        }
+        for ((source_block, source_port), (target_block, target_port)) in connections {
+            output.push_str(&format!(
+                "    connect {}.{} to {}.{};\n",
+                name(&source_block),
+                name(&source_port),
+                name(&target_block),
+                name(&target_port)
+            ));
+        }
+        output.push_str("}\n");
+        Ok(output)
+    }
+}
+
+/// Writes the body of an element with the given attributes: either `;`, or
+/// the attributes within braces.
+fn write_attributes(output: &mut String, attributes: &Mapping, depth: usize) -> AnalysisResult<()> {
+    if attributes.is_empty() {
+        output.push_str(";\n");
+        return Ok(());
+    }
+    output.push_str(" {\n");
+    let indent = "    ".repeat(depth + 1);
+    for (field, value) in attributes {
+        let Some(field) = field.as_str() else {
+            return Err(unsupported("non-string attribute name"));
+        };
+        output.push_str(&format!("{}attribute {}", indent, name(field)));
+        let value = match value {
+            Value::Tagged(tagged) => {
+                let variant = tagged.tag.to_string();
+                output.push_str(&format!(" : {}", name(variant.trim_start_matches('!'))));
+                &tagged.value
+            }
+            value => value,
+        };
+        match value {
+            Value::Null => output.push_str(";\n"),
+            Value::Mapping(attributes) => write_attributes(output, attributes, depth + 1)?,
+            value => output.push_str(&format!(" = {};\n", literal(value)?)),
+        }
+    }
+    output.push_str(&format!("{}}}\n", "    ".repeat(depth)));
+    Ok(())
+}
+
+fn literal(value: &Value) -> AnalysisResult<String> {
+    Ok(match value {
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => format!("\"{}\"", escape(value, '"')),
+        Value::Sequence(values) => format!(
+            "({})",
+            values
+                .iter()
+                .map(literal)
+                .collect::<Result<Vec<_>, _>>()?
+                .join(", ")
+        ),
+        _ => return Err(unsupported("structured value in a sequence")),
+    })
+}
+
+/// Returns the name as is if it's a basic name, or quoted otherwise.
+fn name(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_basic = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_basic && !KEYWORDS.contains(&name) {
+        name.to_string()
+    } else {
+        format!("'{}'", escape(name, '\''))
+    }
+}
+
+fn escape(string: &str, quote: char) -> String {
+    let mut result = String::with_capacity(string.len());
+    for c in string.chars() {
+        match c {
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\0' => result.push_str("\\0"),
+            '\\' => result.push_str("\\\\"),
+            c if c == quote => {
+                result.push('\\');
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// The names that can't be used as basic names.
+const KEYWORDS: &[&str] = &[
+    "attribute",
+    "block",
+    "connect",
+    "false",
+    "import",
+    "package",
+    "to",
+    "true",
+];
+
+fn unsupported(what: &str) -> Report<AnalysisError> {
+    Report::new(AnalysisError::Other(format!(
+        "{} can't be written as SysML",
+        what
+    )))
+}
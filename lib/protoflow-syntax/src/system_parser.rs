@@ -4,11 +4,12 @@
 extern crate std;
 
 use crate::{
-    prelude::{vec, BTreeSet, ToString, Vec},
+    prelude::{vec, BTreeMap, BTreeSet, String, Vec},
     AnalysisError, AnalysisResult,
 };
 use error_stack::ResultExt;
-use protoflow_blocks::BlockTag;
+use protoflow_blocks::{BlockRegistry, System};
+use protoflow_core::{BlockID, SystemDescription};
 use sysml_model::QualifiedName;
 
 pub use sysml_parser::{ParseError, ParsedBlock, ParsedMember, ParsedModel};
@@ -62,8 +63,7 @@ impl SystemParser {
         match member {
             ParsedMember::Import(import) => match import.imported_name.to_tuple3() {
                 (Some("Protoflow"), Some("*") | Some("**"), None) => {
-                    for block_tag in BlockTag::all() {
-                        let block_name = block_tag.to_string();
+                    for block_name in BlockRegistry::global().names() {
                         self.imported_names.insert(QualifiedName::new(vec![
                             "Protoflow".into(),
                             block_name.into(),
@@ -71,10 +71,7 @@ impl SystemParser {
                     }
                 }
                 (Some("Protoflow"), Some(unqualified_name), None) => {
-                    if !BlockTag::all()
-                        .iter()
-                        .any(|block_tag| block_tag.as_str() == unqualified_name)
-                    {
+                    if !BlockRegistry::global().contains(unqualified_name) {
                         return Err(AnalysisError::InvalidImport(import.imported_name.clone()));
                     }
                     self.imported_names.insert(import.imported_name.clone());
@@ -90,7 +87,7 @@ impl SystemParser {
             }
             ParsedMember::BlockUsage(block) => {
                 if let Some(definition_name) = &block.definition {
-                    if !self.imported_names.contains(&definition_name) {
+                    if !self.is_block_type(definition_name) {
                         return Err(AnalysisError::UnknownName(definition_name.clone()));
                     }
                 }
@@ -116,4 +113,71 @@ impl SystemParser {
     ) -> core::result::Result<(), AnalysisError> {
         Ok(()) // TODO
     }
+
+    /// Checks whether the name is the qualified name of a block type, or
+    /// an imported one.
+    fn is_block_type(&self, name: &QualifiedName) -> bool {
+        match name.to_tuple3() {
+            (Some("Protoflow"), Some(block_name), None) => {
+                BlockRegistry::global().contains(block_name)
+            }
+            (Some(block_name), None, None) => {
+                self.imported_names.contains(&QualifiedName::new(vec![
+                    "Protoflow".into(),
+                    block_name.into(),
+                ]))
+            }
+            _ => false,
+        }
+    }
+
+    /// Describes the topology of the checked model, by instantiating each
+    /// of its block usages in a new system.
+    ///
+    /// Block usages are labeled with their declared names. Connections are
+    /// not yet supported by the SysML parser, and hence not described.
+    #[cfg(feature = "std")]
+    pub fn describe(&mut self) -> AnalysisResult<SystemDescription> {
+        let members = self.check()?.members().clone();
+        let mut labels = BTreeMap::new();
+        let system = System::build(|system| {
+            for member in &members {
+                instantiate_member(system, member, &mut labels);
+            }
+        });
+        let mut description = system.describe();
+        for block in &mut description.blocks {
+            if let Some(label) = labels.remove(&block.id) {
+                block.label = label;
+            }
+        }
+        Ok(description)
+    }
+}
+
+fn instantiate_member(
+    system: &mut System,
+    member: &ParsedMember,
+    labels: &mut BTreeMap<BlockID, Option<String>>,
+) {
+    match member {
+        ParsedMember::Package(package) => {
+            for member in package.members() {
+                instantiate_member(system, member, labels);
+            }
+        }
+        ParsedMember::BlockUsage(usage) => {
+            let Some(block) = usage
+                .definition
+                .as_ref()
+                .and_then(|definition| definition.last())
+                .and_then(|block_name| BlockRegistry::global().instantiate(block_name, system))
+            else {
+                return;
+            };
+            let block_id = system.add_block(block);
+            labels.insert(block_id, usage.name.clone());
+        }
+        _ => {}
+    }
 }
@@ -0,0 +1,43 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::SystemDefinition;
+use protoflow_syntax::{AnalysisError, SystemParser, ToSysml};
+
+const PIPELINE: &str = r#"
+random: !Random { output: delay, seed: 42, type: f64 }
+delay: !Delay { input: random, output: counter, delay: !Fixed 1500ms, type: f64 }
+counter: !Count { input: delay, count: sink 2, type: f64 }
+sink: !Drop { input: counter.output, type: f64 }
+'sink 2': !Drop { input: counter.count, type: u64 }
+"#;
+
+#[test]
+fn round_trip_sysml() {
+    let definition = SystemDefinition::from_yaml(PIPELINE).unwrap();
+    let sysml = definition.to_sysml("Pipeline").unwrap();
+    assert!(sysml.contains(
+        "    block delay : Delay {\n        attribute delay : Fixed = \"1500ms\";\n        attribute type = \"f64\";\n    }\n"
+    ));
+    assert!(sysml.contains("    block 'sink 2' : Drop {\n"));
+    assert!(sysml.contains("    connect counter.count to 'sink 2'.input;\n"));
+
+    let reloaded = SystemDefinition::from_sysml(&sysml).unwrap();
+    assert_eq!(reloaded.to_sysml("Pipeline").unwrap(), sysml);
+    assert_eq!(reloaded.to_yaml().unwrap(), definition.to_yaml().unwrap());
+}
+
+#[test]
+fn resolve_block_types() {
+    let mut parser = SystemParser::default()
+        .from_string("package Example { import Protoflow::Drop; block sink : Drop; }")
+        .unwrap();
+    assert!(parser.check().is_ok());
+
+    let mut parser = SystemParser::default()
+        .from_string("package Example { import Protoflow::*; block sink : Dorp; }")
+        .unwrap();
+    let error = parser.check().unwrap_err();
+    assert!(
+        matches!(error.current_context(), AnalysisError::UnknownName(name) if name.to_string() == "Dorp")
+    );
+}
@@ -0,0 +1,62 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow::{
+    blocks::*, derive::Block, prelude::Arc, Block, BlockResult, BlockRuntime, InputPort, OutputPort,
+};
+
+/// A block that converts strings to uppercase.
+#[derive(Block, Clone)]
+pub struct Uppercase {
+    #[input]
+    pub input: InputPort<String>,
+
+    #[output]
+    pub output: OutputPort<String>,
+}
+
+impl Uppercase {
+    pub fn with_system(system: &System) -> Self {
+        Self {
+            input: system.input(),
+            output: system.output(),
+        }
+    }
+}
+
+impl Block for Uppercase {
+    fn execute(&mut self, _runtime: &dyn BlockRuntime) -> BlockResult {
+        while let Some(input) = self.input.recv()? {
+            self.output.send(&input.to_uppercase())?;
+        }
+        Ok(())
+    }
+}
+
+impl StdioSystem for Uppercase {
+    fn build_system(config: StdioConfig) -> Result<System, StdioError> {
+        config.reject_any()?;
+
+        Ok(System::build(|s| {
+            let stdin = config.read_stdin(s);
+            let line_decoder = s.decode_with::<String>(config.encoding);
+            let uppercase = s.block(Uppercase::with_system(s));
+            let line_encoder = s.encode_with::<String>(config.encoding);
+            let stdout = config.write_stdout(s);
+            s.connect(&stdin.output, &line_decoder.input);
+            s.connect(&line_decoder.output, &uppercase.input);
+            s.connect(&uppercase.output, &line_encoder.input);
+            s.connect(&line_encoder.output, &stdout.input);
+        }))
+    }
+}
+
+fn register(registry: &mut BlockRegistry) {
+    registry.register(BlockFactory {
+        build_stdio_system: Some(Arc::new(Uppercase::build_system)),
+        ..BlockFactory::new("Uppercase", |system| {
+            Box::new(Uppercase::with_system(system))
+        })
+    });
+}
+
+protoflow::blocks::export_plugin!(register);
@@ -0,0 +1,664 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{commands::execute::build_system, exit::ExitCode};
+use clientele::crates::clap::{self, Args};
+use protoflow_blocks::{types::Encoding, StdioConfig, StdioEndpoint, System};
+use protoflow_core::{prelude::prost::Message, ConnectionOptions, PortID, SystemExecution};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The latency percentiles to report.
+const PERCENTILES: [(&str, f64); 4] = [("p50", 50.0), ("p90", 90.0), ("p99", 99.0), ("max", 100.0)];
+
+/// Options for the transport of a benchmarked system, and for the
+/// synthetic messages that drive it.
+#[derive(Clone, Debug, Args)]
+pub struct BenchOptions {
+    /// Specify the transport to run the system on (mpsc)
+    #[clap(short = 't', long, value_parser = parse_transport, default_value = "mpsc")]
+    pub transport: BenchTransport,
+
+    /// Specify the number of messages to send
+    #[clap(short = 'n', long, default_value = "10000")]
+    pub messages: u64,
+
+    /// Specify the size of each message's payload, in bytes
+    #[clap(short = 's', long, value_name = "BYTES", default_value = "64")]
+    pub size: usize,
+
+    /// Limit the rate of messages sent per second (unlimited by default)
+    #[clap(short = 'r', long)]
+    pub rate: Option<f64>,
+}
+
+/// Benchmarks a system or block, by driving its input with synthetic
+/// messages and measuring its throughput and latencies, end to end and for
+/// each of its connections.
+pub fn bench(
+    system_uri: PathBuf,
+    system_params: Vec<(String, String)>,
+    stdio_encoding: Encoding,
+    channel_capacity: Option<usize>,
+    options: BenchOptions,
+    json: bool,
+    baseline: Option<PathBuf>,
+) -> Result<(), ExitCode> {
+    if options
+        .rate
+        .is_some_and(|rate| rate <= 0.0 || !rate.is_finite())
+    {
+        return Err(BenchError::InvalidRate.into());
+    }
+    let baseline = match baseline {
+        Some(path) => {
+            let input = std::fs::read_to_string(&path)?;
+            let baseline: Value = serde_json::from_str(&input)
+                .map_err(|error| BenchError::InvalidBaseline(path, error.to_string()))?;
+            Some(baseline)
+        }
+        None => None,
+    };
+
+    // The system reads its input from, and writes its output to, the
+    // driver and the collector, connected over local sockets:
+    let input_listener = TcpListener::bind("127.0.0.1:0")?;
+    let output_listener = TcpListener::bind("127.0.0.1:0")?;
+    let system_config = StdioConfig::new(stdio_encoding, system_params.iter().cloned().collect())
+        .with_input(StdioEndpoint::Tcp(input_listener.local_addr()?.to_string()))
+        .with_output(StdioEndpoint::Tcp(
+            output_listener.local_addr()?.to_string(),
+        ));
+    let connection_options = ConnectionOptions {
+        capacity: channel_capacity,
+        ..Default::default()
+    };
+    let system = options
+        .transport
+        .build_system(&system_uri, system_config, connection_options)?;
+
+    // Name the ports by their blocks, once they've been opened in the
+    // transport, to report the metrics of each connection:
+    system
+        .prepare()
+        .map_err(|error| BenchError::Failed(error.to_string()))?;
+    let port_names = port_names(&system.describe());
+    let transport = system.transport().clone();
+    transport.enable_metrics();
+
+    let is_done = Arc::new(AtomicBool::new(false));
+    let driver = {
+        let is_done = is_done.clone();
+        let options = options.clone();
+        thread::spawn(move || drive(input_listener, &is_done, stdio_encoding, &options))
+    };
+    let collector = {
+        let is_done = is_done.clone();
+        thread::spawn(move || collect(output_listener, &is_done, stdio_encoding))
+    };
+    let started = Instant::now();
+    let result = system
+        .execute()
+        .and_then(|process| process.join())
+        .map_err(|error| BenchError::Failed(error.to_string()));
+    let elapsed = started.elapsed();
+    is_done.store(true, Ordering::SeqCst);
+    let (sent, sent_bytes) = driver.join().unwrap()?;
+    let (received, received_bytes) = collector.join().unwrap()?;
+    result?;
+    let traffic = Traffic {
+        sent,
+        sent_bytes,
+        received,
+        received_bytes,
+    };
+
+    let name = |id: PortID| port_names.get(&id).cloned().unwrap_or(id.to_string());
+    let connections = transport
+        .metrics()
+        .iter()
+        .map(|metrics| {
+            json!({
+                "source": name(metrics.source.into()),
+                "target": name(metrics.target.into()),
+                "messages": metrics.messages,
+                "bytes": metrics.bytes,
+                "latency_us": latency_percentiles(metrics.latency_percentiles(&percentiles())),
+            })
+        })
+        .collect();
+    let results = results(
+        &system_uri,
+        stdio_encoding,
+        channel_capacity,
+        &options,
+        elapsed,
+        &traffic,
+        connections,
+    );
+
+    if json {
+        let output = serde_json::to_string_pretty(&results).map_err(std::io::Error::from)?;
+        std::println!("{}", output);
+    } else {
+        print_results(&results, baseline.as_ref());
+    }
+    Ok(())
+}
+
+/// Sends the synthetic messages to the system once it connects, at the
+/// configured rate, and returns when each message was sent, along with
+/// the total number of bytes sent.
+fn drive(
+    listener: TcpListener,
+    is_done: &AtomicBool,
+    encoding: Encoding,
+    options: &BenchOptions,
+) -> Result<(Vec<Instant>, u64), ExitCode> {
+    let Some(mut stream) = accept(listener, is_done)? else {
+        return Ok((Vec::new(), 0)); // the system doesn't read its input
+    };
+    stream.set_nodelay(true)?;
+    let message = synthetic_message(encoding, options.size);
+    let interval = options.rate.map(|rate| Duration::from_secs_f64(1.0 / rate));
+    let started = Instant::now();
+    let mut sent = Vec::with_capacity(options.messages as usize);
+    for i in 0..options.messages {
+        if let Some(interval) = interval {
+            let scheduled = started + interval.mul_f64(i as f64);
+            thread::sleep(scheduled.saturating_duration_since(Instant::now()));
+        }
+        let sent_at = Instant::now();
+        match stream.write_all(&message) {
+            Ok(()) => sent.push(sent_at),
+            // The system may stop reading its input early:
+            Err(error) if error.kind() == ErrorKind::BrokenPipe => break,
+            Err(error) if error.kind() == ErrorKind::ConnectionReset => break,
+            Err(error) => return Err(error.into()),
+        }
+    }
+    let bytes = sent.len() as u64 * message.len() as u64;
+    Ok((sent, bytes))
+}
+
+/// Receives the output of the system once it connects, and returns when
+/// each message was received, as delimited by the encoding, along with the
+/// total number of bytes received.
+fn collect(
+    listener: TcpListener,
+    is_done: &AtomicBool,
+    encoding: Encoding,
+) -> Result<(Vec<Instant>, u64), ExitCode> {
+    let Some(mut stream) = accept(listener, is_done)? else {
+        return Ok((Vec::new(), 0)); // the system doesn't write its output
+    };
+    let mut received = Vec::new();
+    let mut bytes = 0u64;
+    let mut pending = Vec::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let len = match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        };
+        let now = Instant::now();
+        bytes += len as u64;
+        pending.extend_from_slice(&buffer[..len]);
+        let consumed = split_messages(encoding, &pending, || received.push(now));
+        pending.drain(..consumed);
+    }
+    Ok((received, bytes))
+}
+
+/// Waits for the system to connect to the listener, unless it finishes
+/// without connecting.
+fn accept(listener: TcpListener, is_done: &AtomicBool) -> Result<Option<TcpStream>, ExitCode> {
+    listener.set_nonblocking(true)?;
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                return Ok(Some(stream));
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                if is_done.load(Ordering::SeqCst) {
+                    return Ok(None);
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+            Err(error) => return Err(error.into()),
+        }
+    }
+}
+
+/// Returns a synthetic message with a payload of the given size, encoded
+/// as a string message.
+fn synthetic_message(encoding: Encoding, size: usize) -> Vec<u8> {
+    let payload = "x".repeat(size);
+    match encoding {
+        Encoding::ProtobufWithLengthPrefix => payload.encode_length_delimited_to_vec(),
+        Encoding::ProtobufWithoutLengthPrefix => payload.encode_to_vec(),
+        Encoding::TextWithNewlineSuffix => format!("{}\n", payload).into_bytes(),
+    }
+}
+
+/// Calls `on_message` for every complete message in the buffer, and
+/// returns the number of bytes that they took up. Messages without a
+/// length prefix can't be delimited, so each read counts as a message.
+fn split_messages(encoding: Encoding, buffer: &[u8], mut on_message: impl FnMut()) -> usize {
+    match encoding {
+        Encoding::ProtobufWithLengthPrefix => {
+            let mut consumed = 0;
+            loop {
+                let mut rest = &buffer[consumed..];
+                let Ok(len) = protoflow_core::prelude::prost::encoding::decode_varint(&mut rest)
+                else {
+                    return consumed; // incomplete length prefix
+                };
+                let prefix_len = buffer.len() - consumed - rest.len();
+                if rest.len() < len as usize {
+                    return consumed;
+                }
+                consumed += prefix_len + len as usize;
+                on_message();
+            }
+        }
+        Encoding::ProtobufWithoutLengthPrefix => {
+            on_message();
+            buffer.len()
+        }
+        Encoding::TextWithNewlineSuffix => {
+            let mut consumed = 0;
+            while let Some(pos) = buffer[consumed..].iter().position(|&b| b == b'\n') {
+                consumed += pos + 1;
+                on_message();
+            }
+            consumed
+        }
+    }
+}
+
+/// Returns the names of the ports of the system's blocks, as `block.port`,
+/// by their IDs. Blocks of the same type are numbered in order, as in
+/// `Encode#2`.
+pub(crate) fn port_names(
+    description: &protoflow_core::SystemDescription,
+) -> BTreeMap<PortID, String> {
+    let mut names = BTreeMap::new();
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for block in &description.blocks {
+        let block_name = block
+            .label
+            .clone()
+            .or(block.name.clone())
+            .unwrap_or_else(|| String::from("block"));
+        let count = counts.entry(block_name.clone()).or_default();
+        *count += 1;
+        let block_name = match count {
+            1 => block_name,
+            n => format!("{}#{}", block_name, n),
+        };
+        for port in block.inputs.iter().chain(&block.outputs) {
+            let port_name = port.name.clone().unwrap_or_else(|| port.id.to_string());
+            names.insert(port.id, format!("{}.{}", block_name, port_name));
+        }
+    }
+    names
+}
+
+fn percentiles() -> Vec<f64> {
+    PERCENTILES
+        .iter()
+        .map(|(_, percentile)| *percentile)
+        .collect()
+}
+
+/// Returns the latency percentiles, in microseconds, as a JSON object.
+fn latency_percentiles(latencies: Option<Vec<Duration>>) -> Value {
+    let Some(latencies) = latencies else {
+        return Value::Null;
+    };
+    PERCENTILES
+        .iter()
+        .zip(latencies)
+        .map(|((name, _), latency)| (name.to_string(), json!(micros(latency))))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// The synthetic messages sent to a system, and the messages received
+/// from it, with when each was sent or received.
+struct Traffic {
+    sent: Vec<Instant>,
+    sent_bytes: u64,
+    received: Vec<Instant>,
+    received_bytes: u64,
+}
+
+impl Traffic {
+    /// Returns the end-to-end latency percentiles, which can only be
+    /// matched up when every message sent results in one message received.
+    fn latency_percentiles(&self) -> Option<Vec<Duration>> {
+        if self.sent.is_empty() || self.sent.len() != self.received.len() {
+            return None;
+        }
+        let mut latencies: Vec<Duration> = self
+            .sent
+            .iter()
+            .zip(&self.received)
+            .map(|(sent, received)| received.saturating_duration_since(*sent))
+            .collect();
+        latencies.sort_unstable();
+        let last = latencies.len() - 1;
+        Some(
+            percentiles()
+                .iter()
+                .map(|percentile| latencies[(percentile / 100.0 * last as f64).round() as usize])
+                .collect(),
+        )
+    }
+}
+
+fn results(
+    system_uri: &Path,
+    encoding: Encoding,
+    channel_capacity: Option<usize>,
+    options: &BenchOptions,
+    elapsed: Duration,
+    traffic: &Traffic,
+    connections: Vec<Value>,
+) -> Value {
+    let elapsed = elapsed.as_secs_f64();
+    let per_second = |count: f64| if elapsed > 0.0 { count / elapsed } else { 0.0 };
+    json!({
+        "system": system_uri.to_string_lossy(),
+        "transport": options.transport.to_string(),
+        "encoding": encoding.to_string(),
+        "channel_capacity": channel_capacity,
+        "message_size": options.size,
+        "rate": options.rate,
+        "elapsed_secs": elapsed,
+        "throughput": {
+            "messages_per_sec": per_second(traffic.sent.len() as f64),
+            "bytes_per_sec": per_second(traffic.sent_bytes as f64),
+        },
+        "sent": {
+            "messages": traffic.sent.len(),
+            "bytes": traffic.sent_bytes,
+        },
+        "received": {
+            "messages": traffic.received.len(),
+            "bytes": traffic.received_bytes,
+        },
+        "latency_us": latency_percentiles(traffic.latency_percentiles()),
+        "connections": connections,
+    })
+}
+
+fn print_results(results: &Value, baseline: Option<&Value>) {
+    std::println!(
+        "{} ({} transport, {} encoding, channel capacity {})",
+        results["system"].as_str().unwrap_or_default(),
+        results["transport"].as_str().unwrap_or_default(),
+        results["encoding"].as_str().unwrap_or_default(),
+        results["channel_capacity"]
+            .as_u64()
+            .map_or(String::from("default"), |capacity| capacity.to_string()),
+    );
+    if let Some(baseline) = baseline {
+        std::println!(
+            "compared with {} ({} transport, {} encoding, channel capacity {})",
+            baseline["system"].as_str().unwrap_or_default(),
+            baseline["transport"].as_str().unwrap_or_default(),
+            baseline["encoding"].as_str().unwrap_or_default(),
+            baseline["channel_capacity"]
+                .as_u64()
+                .map_or(String::from("default"), |capacity| capacity.to_string()),
+        );
+        if let Some(warning) = transport_mismatch(results, baseline) {
+            std::println!("warning: {}", warning);
+        }
+    }
+    std::println!();
+    std::println!(
+        "  sent:         {} messages, {} bytes",
+        results["sent"]["messages"],
+        results["sent"]["bytes"]
+    );
+    std::println!(
+        "  received:     {} messages, {} bytes",
+        results["received"]["messages"],
+        results["received"]["bytes"]
+    );
+    let row = |label: &str, path: &[&str], unit: &str| {
+        let label = match label {
+            "" => String::new(),
+            label => format!("{}:", label),
+        };
+        let line = compare(results, baseline, path);
+        std::println!("  {:13} {} {}", label, line, unit);
+    };
+    row("elapsed", &["elapsed_secs"], "s");
+    row(
+        "throughput",
+        &["throughput", "messages_per_sec"],
+        "messages/s",
+    );
+    row("", &["throughput", "bytes_per_sec"], "bytes/s");
+    for (name, _) in PERCENTILES {
+        row(&format!("latency {}", name), &["latency_us", name], "µs");
+    }
+
+    let Some(connections) = results["connections"].as_array() else {
+        return;
+    };
+    if connections.is_empty() {
+        return;
+    }
+    std::println!();
+    std::println!("Connections:");
+    for connection in connections {
+        let (source, target) = (&connection["source"], &connection["target"]);
+        let previous = baseline
+            .and_then(|baseline| baseline["connections"].as_array())
+            .and_then(|previous| {
+                previous
+                    .iter()
+                    .find(|previous| previous["source"] == *source && previous["target"] == *target)
+            });
+        std::println!(
+            "  {} -> {}: {} messages, {} bytes",
+            source.as_str().unwrap_or_default(),
+            target.as_str().unwrap_or_default(),
+            connection["messages"],
+            connection["bytes"],
+        );
+        for (name, _) in PERCENTILES {
+            let line = compare(connection, previous, &["latency_us", name]);
+            std::println!("    {:13} {} µs", format!("latency {}:", name), line);
+        }
+    }
+}
+
+/// Returns a warning if the results were measured on a different transport
+/// than the baseline, which makes them incomparable.
+fn transport_mismatch(results: &Value, baseline: &Value) -> Option<String> {
+    let (transport, previous) = (&results["transport"], &baseline["transport"]);
+    if transport == previous {
+        return None;
+    }
+    Some(format!(
+        "comparing the {} transport with the {} transport of the baseline",
+        transport.as_str().unwrap_or("unknown"),
+        previous.as_str().unwrap_or("unknown"),
+    ))
+}
+
+/// Formats a result, along with its previous value and relative change, if
+/// a baseline is given.
+fn compare(results: &Value, baseline: Option<&Value>, path: &[&str]) -> String {
+    let value = lookup(results, path);
+    match (value, baseline.and_then(|baseline| lookup(baseline, path))) {
+        (Some(value), Some(previous)) if previous != 0.0 => format!(
+            "{} (was {}, {:+.1}%)",
+            format_number(Some(value)),
+            format_number(Some(previous)),
+            (value - previous) / previous * 100.0
+        ),
+        (value, Some(previous)) => {
+            format!(
+                "{} (was {})",
+                format_number(value),
+                format_number(Some(previous))
+            )
+        }
+        (value, None) => format_number(value),
+    }
+}
+
+fn lookup(value: &Value, path: &[&str]) -> Option<f64> {
+    path.iter()
+        .try_fold(value, |value, key| value.get(key))
+        .and_then(Value::as_f64)
+}
+
+fn format_number(value: Option<f64>) -> String {
+    match value {
+        None => String::from("n/a"),
+        Some(value) if value >= 100.0 => format!("{:.0}", value),
+        Some(value) => format!("{:.3}", value),
+    }
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000_000.0
+}
+
+/// A transport that systems can be benchmarked on.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum BenchTransport {
+    /// In-process channels, with a thread for each block.
+    #[default]
+    Mpsc,
+}
+
+impl BenchTransport {
+    /// Builds the system to benchmark, running on this transport.
+    fn build_system(
+        self,
+        system_uri: &Path,
+        system_config: StdioConfig,
+        connection_options: ConnectionOptions,
+    ) -> Result<System, ExitCode> {
+        match self {
+            Self::Mpsc => build_system(system_uri, system_config, connection_options),
+        }
+    }
+}
+
+impl FromStr for BenchTransport {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "mpsc" => Ok(Self::Mpsc),
+            _ => Err(String::from(input)),
+        }
+    }
+}
+
+impl fmt::Display for BenchTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Mpsc => write!(f, "mpsc"),
+        }
+    }
+}
+
+fn parse_transport(input: &str) -> Result<BenchTransport, BenchError> {
+    input
+        .parse()
+        .map_err(|e: String| BenchError::InvalidTransport(e))
+}
+
+#[derive(Clone, Debug)]
+pub enum BenchError {
+    InvalidRate,
+    InvalidTransport(String),
+    InvalidBaseline(PathBuf, String),
+    Failed(String),
+}
+
+impl std::error::Error for BenchError {}
+
+impl std::fmt::Display for BenchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use BenchError::*;
+        match self {
+            InvalidRate => {
+                write!(
+                    f,
+                    "invalid rate: expected a positive number of messages per second"
+                )
+            }
+            InvalidTransport(transport) => {
+                write!(f, "invalid transport: {}", transport)
+            }
+            InvalidBaseline(path, error) => {
+                write!(
+                    f,
+                    "{}: invalid benchmark results: {}",
+                    path.display(),
+                    error
+                )
+            }
+            Failed(error) => {
+                write!(f, "benchmark failed: {}", error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{transport_mismatch, BenchTransport};
+    use serde_json::json;
+
+    #[test]
+    fn parse_transports() {
+        assert_eq!("mpsc".parse(), Ok(BenchTransport::Mpsc));
+        assert_eq!(BenchTransport::default().to_string(), "mpsc");
+        assert!("tcp".parse::<BenchTransport>().is_err());
+    }
+
+    #[test]
+    fn flag_transport_mismatches() {
+        let results = json!({ "transport": "mpsc" });
+        assert_eq!(
+            transport_mismatch(&results, &json!({ "transport": "mpsc" })),
+            None
+        );
+        assert_eq!(
+            transport_mismatch(&results, &json!({ "transport": "flume" })).as_deref(),
+            Some("comparing the mpsc transport with the flume transport of the baseline")
+        );
+        assert_eq!(
+            transport_mismatch(&results, &json!({})).as_deref(),
+            Some("comparing the mpsc transport with the unknown transport of the baseline")
+        );
+    }
+}
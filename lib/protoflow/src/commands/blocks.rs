@@ -0,0 +1,129 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::exit::ExitCode;
+use protoflow_blocks::{BlockCategory, BlockRegistry, BlockTag, MessageTypeRegistry};
+use protoflow_core::PortDescription;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// The category of blocks registered by plugins or other crates.
+const CUSTOM_CATEGORY: &str = "Custom";
+
+pub fn list(json: bool) -> Result<(), ExitCode> {
+    let registry = BlockRegistry::global();
+    let mut groups: Vec<(&str, Vec<&str>)> = BlockCategory::all()
+        .into_iter()
+        .map(|category| (category.as_str(), Vec::new()))
+        .collect();
+    groups.push((CUSTOM_CATEGORY, Vec::new()));
+    for name in registry.names() {
+        let category = BlockTag::from_str(name)
+            .map(|tag| tag.category().as_str())
+            .unwrap_or(CUSTOM_CATEGORY);
+        if let Some((_, names)) = groups.iter_mut().find(|(group, _)| *group == category) {
+            names.push(name);
+        }
+    }
+    groups.retain(|(_, names)| !names.is_empty());
+
+    if json {
+        let groups: Vec<_> = groups
+            .iter()
+            .map(|(category, names)| {
+                let blocks: Vec<_> = names
+                    .iter()
+                    .filter_map(|name| Some(registry.get(name)?.describe()))
+                    .collect();
+                json!({ "category": category, "blocks": blocks })
+            })
+            .collect();
+        print_json(&Value::from(groups))?;
+    } else {
+        for (i, (category, names)) in groups.iter().enumerate() {
+            if i > 0 {
+                std::println!();
+            }
+            std::println!("{}:", category);
+            for name in names {
+                std::println!("  {}", name);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn describe(block_name: String, json: bool) -> Result<(), ExitCode> {
+    let registry = BlockRegistry::global();
+    let Some(factory) = registry.get(&block_name) else {
+        return Err(BlocksError::UnknownBlock(block_name).into());
+    };
+    let description = factory.describe();
+
+    if json {
+        return print_json(&json!(description));
+    }
+
+    let category = BlockTag::from_str(&block_name)
+        .map(|tag| tag.category().as_str())
+        .unwrap_or(CUSTOM_CATEGORY);
+    std::println!("{} ({})", block_name, category);
+    print_ports("Inputs", &description.inputs);
+    print_ports("Outputs", &description.outputs);
+    if !description.parameters.is_empty() {
+        std::println!("\nParameters:");
+        for parameter in &description.parameters {
+            std::print!("  {}", parameter.name);
+            if let Some(r#type) = &parameter.r#type {
+                std::print!(": {}", r#type);
+            }
+            if let Some(default_value) = &parameter.default_value {
+                std::print!(" = {:?}", default_value);
+            }
+            std::println!();
+        }
+    }
+    Ok(())
+}
+
+fn print_ports(heading: &str, ports: &[PortDescription]) {
+    if ports.is_empty() {
+        return;
+    }
+    std::println!("\n{}:", heading);
+    let message_types = MessageTypeRegistry::global();
+    for port in ports {
+        std::print!("  {}", port.name.as_deref().unwrap_or_default());
+        if let Some(r#type) = &port.r#type {
+            // Prefer the names that select message types on the command line:
+            let type_name = message_types
+                .get_by_type_name(r#type)
+                .map_or(r#type.as_str(), |message_type| message_type.name.as_str());
+            std::print!(": {}", type_name);
+        }
+        std::println!();
+    }
+}
+
+fn print_json(value: &Value) -> Result<(), ExitCode> {
+    let json = serde_json::to_string_pretty(value).map_err(std::io::Error::from)?;
+    std::println!("{}", json);
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub enum BlocksError {
+    UnknownBlock(String),
+}
+
+impl std::error::Error for BlocksError {}
+
+impl std::fmt::Display for BlocksError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use BlocksError::*;
+        match self {
+            UnknownBlock(name) => {
+                write!(f, "unknown block: {}", name)
+            }
+        }
+    }
+}
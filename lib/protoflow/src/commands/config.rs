@@ -1,8 +1,90 @@
 // This is free and unencumbered software released into the public domain.
 
-use crate::exit::ExitCode;
+use crate::{exit::ExitCode, settings::Config};
 
-#[allow(dead_code)]
-pub fn config() -> Result<(), ExitCode> {
-    Ok(()) // TODO
+pub fn config(config: &Config) -> Result<(), ExitCode> {
+    let mut rows: Vec<(String, String, String)> = Vec::new();
+    let mut row = |key: &str, value: String, source: &dyn ToString| {
+        rows.push((key.to_string(), value, source.to_string()));
+    };
+
+    row(
+        "encoding",
+        quote(&config.encoding.value),
+        &config.encoding.source,
+    );
+    row(
+        "plugin_dir",
+        match &config.plugin_dir.value {
+            Some(dir) => quote(&dir.display()),
+            None => String::from("(none)"),
+        },
+        &config.plugin_dir.source,
+    );
+    for plugin in &config.plugins {
+        row("plugins", quote(&plugin.value.display()), &plugin.source);
+    }
+    row(
+        "channel_capacity",
+        match config.channel_capacity.value {
+            Some(capacity) => capacity.to_string(),
+            None => String::from("(transport default)"),
+        },
+        &config.channel_capacity.source,
+    );
+    row(
+        "log.level",
+        quote(&config.log_level.value),
+        &config.log_level.source,
+    );
+    for filter in &config.log_filters {
+        row("log.filters", quote(&filter.value), &filter.source);
+    }
+    row(
+        "log.format",
+        quote(&config.log_format.value),
+        &config.log_format.source,
+    );
+    row(
+        "log.file",
+        match &config.log_file.value {
+            Some(path) => quote(&path.display()),
+            None => String::from("(stderr)"),
+        },
+        &config.log_file.source,
+    );
+    for (name, preset) in &config.presets {
+        let params: Vec<String> = preset
+            .value
+            .iter()
+            .map(|(param, value)| format!("{} = {}", param, quote(value)))
+            .collect();
+        row(
+            &format!("presets.{}", name),
+            format!("{{ {} }}", params.join(", ")),
+            &preset.source,
+        );
+    }
+
+    let key_width = rows.iter().map(|(key, ..)| key.len()).max().unwrap_or(0);
+    let value_width = rows
+        .iter()
+        .map(|(_, value, _)| value.len())
+        .max()
+        .unwrap_or(0);
+    for (key, value, source) in rows {
+        std::println!(
+            "{:key_width$} = {:value_width$}  # {}",
+            key,
+            value,
+            source,
+            key_width = key_width,
+            value_width = value_width,
+        );
+    }
+    Ok(())
+}
+
+fn quote(value: &dyn std::fmt::Display) -> String {
+    format!("{:?}", value.to_string())
 }
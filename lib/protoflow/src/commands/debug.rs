@@ -0,0 +1,421 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    commands::{bench::port_names, execute::build_system},
+    exit::ExitCode,
+};
+use protoflow_blocks::{types::Encoding, MessageTypeRegistry, StdioConfig, StdioEndpoint};
+use protoflow_core::{
+    prelude::{prost::Message, Bytes, Rc},
+    transports::MpscTransport,
+    ConnectionOptions, HeldEvent, HeldEventKind, OutputPortID, PortDirection, PortID, Process,
+    SystemDescription, SystemExecution,
+};
+use serde_json::{json, Value};
+use std::{
+    any,
+    collections::BTreeMap,
+    io::{BufRead, Write},
+    path::PathBuf,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long to wait for a message to be sent, when stepping.
+const STEP_TIMEOUT: Duration = Duration::from_secs(1);
+
+const HELP: &str = "\
+Commands:
+  ports               List the ports, with their message types
+  break [PORT]        Set a breakpoint on a port, or list the breakpoints
+  delete PORT         Clear the breakpoint on a port
+  queue [PORT]        Show the held messages, sent to or from a port if given
+  step [COUNT]        Deliver the next held message, or COUNT messages
+  continue            Resume delivery, until a breakpoint is hit
+  inject PORT VALUE   Send a JSON value to an input port, or from an output port
+  drop ID             Discard a held message
+  help                Show this help
+  quit                Exit, abandoning the system
+
+Ports are named as block.port, as listed by `ports`.";
+
+/// Executes a system or block with message delivery paused, and debugs it
+/// with commands read from stdin.
+pub fn debug(
+    system_uri: PathBuf,
+    system_params: Vec<(String, String)>,
+    stdio_encoding: Encoding,
+    stdio_input: StdioEndpoint,
+    stdio_output: StdioEndpoint,
+    channel_capacity: Option<usize>,
+) -> Result<(), ExitCode> {
+    if stdio_input == StdioEndpoint::Stdio {
+        return Err(DebugError::StdinInUse.into());
+    }
+    let connection_options = ConnectionOptions {
+        capacity: channel_capacity,
+        ..Default::default()
+    };
+    let system_config = StdioConfig::new(stdio_encoding, system_params.iter().cloned().collect())
+        .with_input(stdio_input)
+        .with_output(stdio_output);
+    let system = build_system(&system_uri, system_config, connection_options)?;
+
+    // Pause delivery once the ports have been opened, before any block
+    // starts sending:
+    system
+        .prepare()
+        .map_err(|error| DebugError::Failed(error.to_string()))?;
+    let description = system.describe();
+    let transport = system.transport().clone();
+    transport.pause();
+    let process = system
+        .execute()
+        .map_err(|error| DebugError::Failed(error.to_string()))?;
+
+    let debugger = Debugger {
+        port_names: port_names(&description),
+        description,
+        transport,
+        process,
+    };
+    std::println!(
+        "Paused {} before its first message. Type `help` for the commands.",
+        system_uri.display()
+    );
+    debugger.run()
+}
+
+struct Debugger {
+    description: SystemDescription,
+    port_names: BTreeMap<PortID, String>,
+    transport: Arc<MpscTransport>,
+    process: Rc<dyn Process>,
+}
+
+impl Debugger {
+    /// Reads and runs commands until the system finishes or the user quits.
+    fn run(&self) -> Result<(), ExitCode> {
+        let mut lines = std::io::stdin().lock().lines();
+        loop {
+            if !self.process.is_alive() {
+                return match self.process.join() {
+                    Ok(()) => {
+                        std::println!("The system finished.");
+                        Ok(())
+                    }
+                    Err(error) => Err(DebugError::Failed(error.to_string()).into()),
+                };
+            }
+            std::print!("(protoflow) ");
+            std::io::stdout().flush()?;
+            let Some(line) = lines.next() else {
+                std::println!();
+                return Ok(()); // EOF
+            };
+            let line = line?;
+            let (command, args) = match line.trim().split_once(char::is_whitespace) {
+                Some((command, args)) => (command, args.trim()),
+                None => (line.trim(), ""),
+            };
+            let result = match command {
+                "" => Ok(()),
+                "ports" => self.ports(),
+                "break" | "b" => self.set_breakpoint(args),
+                "delete" | "d" => self.clear_breakpoint(args),
+                "queue" => self.queue(args),
+                "step" | "s" => self.step(args),
+                "continue" | "c" => self.resume(),
+                "inject" => self.inject(args),
+                "drop" => self.drop(args),
+                "help" | "h" | "?" => {
+                    std::println!("{}", HELP);
+                    Ok(())
+                }
+                "quit" | "q" => return Ok(()),
+                _ => Err(format!("unknown command: {} (type `help`)", command)),
+            };
+            if let Err(error) = result {
+                std::eprintln!("error: {}", error);
+            }
+        }
+    }
+
+    fn ports(&self) -> Result<(), String> {
+        let breakpoints = self.transport.breakpoints();
+        let width = self.port_names.values().map(String::len).max().unwrap_or(0);
+        for port in self
+            .description
+            .blocks
+            .iter()
+            .flat_map(|block| block.ports())
+        {
+            std::println!(
+                "{} {:width$}  {:6}  {}",
+                if breakpoints.contains(&port.id) {
+                    "*"
+                } else {
+                    " "
+                },
+                self.port_name(port.id),
+                match port.direction {
+                    PortDirection::Input => "input",
+                    PortDirection::Output => "output",
+                },
+                port.r#type.as_deref().unwrap_or("?"),
+                width = width,
+            );
+        }
+        Ok(())
+    }
+
+    fn set_breakpoint(&self, args: &str) -> Result<(), String> {
+        if args.is_empty() {
+            let breakpoints = self.transport.breakpoints();
+            if breakpoints.is_empty() {
+                std::println!("No breakpoints are set.");
+            }
+            for port in breakpoints {
+                std::println!("Breakpoint on {}", self.port_name(port));
+            }
+            return Ok(());
+        }
+        let port = self.parse_port(args)?;
+        self.transport.set_breakpoint(port);
+        std::println!("Breakpoint on {}", self.port_name(port));
+        Ok(())
+    }
+
+    fn clear_breakpoint(&self, args: &str) -> Result<(), String> {
+        let port = self.parse_port(args)?;
+        if !self.transport.clear_breakpoint(port) {
+            return Err(format!("no breakpoint on {}", self.port_name(port)));
+        }
+        std::println!("Deleted the breakpoint on {}", self.port_name(port));
+        Ok(())
+    }
+
+    fn queue(&self, args: &str) -> Result<(), String> {
+        let port = match args {
+            "" => None,
+            _ => Some(self.parse_port(args)?),
+        };
+        let events: Vec<HeldEvent> = self
+            .transport
+            .held_events()
+            .into_iter()
+            .filter(|event| match port {
+                None => true,
+                Some(port) => port == event.source.into() || port == event.target.into(),
+            })
+            .collect();
+        if events.is_empty() {
+            std::println!("No messages are held.");
+        }
+        for event in &events {
+            std::println!("{}", self.format_event(event));
+        }
+        Ok(())
+    }
+
+    fn step(&self, args: &str) -> Result<(), String> {
+        let count: usize = match args {
+            "" => 1,
+            _ => args
+                .parse()
+                .map_err(|_| format!("invalid count: {}", args))?,
+        };
+        for _ in 0..count {
+            // Blocks may still be about to send their next message:
+            let deadline = Instant::now() + STEP_TIMEOUT;
+            let event = loop {
+                if let Some(event) = self.transport.step() {
+                    break event;
+                }
+                if Instant::now() >= deadline || !self.process.is_alive() {
+                    return Err(match self.transport.held_events().is_empty() {
+                        true => "no messages are waiting to be delivered".into(),
+                        false => "no held message can be delivered until its input port's \
+                                  queued messages are received"
+                            .into(),
+                    });
+                }
+                thread::sleep(Duration::from_millis(10));
+            };
+            std::println!("Delivered {}", self.format_event(&event));
+        }
+        Ok(())
+    }
+
+    fn resume(&self) -> Result<(), String> {
+        self.transport.resume();
+        std::println!("Continuing.");
+        loop {
+            if self.transport.wait_held(Duration::from_millis(100)) {
+                break;
+            }
+            if !self.process.is_alive() {
+                return Ok(());
+            }
+        }
+        let event = match self.transport.paused_at() {
+            Some(event) => event,
+            None => self.transport.held_events().remove(0),
+        };
+        std::println!("Paused at {}", self.format_event(&event));
+        Ok(())
+    }
+
+    fn inject(&self, args: &str) -> Result<(), String> {
+        let Some((port, value)) = args.split_once(char::is_whitespace) else {
+            return Err("expected a port and a value to inject".into());
+        };
+        let port = self.parse_port(port)?;
+        let source = match port {
+            PortID::Output(output) => output,
+            PortID::Input(input) => self
+                .description
+                .connections
+                .iter()
+                .find(|connection| connection.target == input)
+                .map(|connection| connection.source)
+                .ok_or_else(|| format!("{} isn't connected", self.port_name(port)))?,
+        };
+
+        // Unquoted strings are accepted for convenience:
+        let value = value.trim();
+        let text = match serde_json::from_str(value) {
+            Ok(Value::String(text)) => text,
+            Ok(value) => value.to_string(),
+            Err(_) => value.to_string(),
+        };
+        let type_name = self.port_type(source.into()).unwrap_or("?");
+        let message = encode(type_name, &text)
+            .ok_or_else(|| format!("can't encode {} as a message of type {}", value, type_name))?;
+        let id = self
+            .transport
+            .inject(source, message)
+            .map_err(|error| error.to_string())?;
+        std::println!("Injected message #{}", id);
+        Ok(())
+    }
+
+    fn drop(&self, args: &str) -> Result<(), String> {
+        let id: u64 = args
+            .trim_start_matches('#')
+            .parse()
+            .map_err(|_| format!("invalid message ID: {}", args))?;
+        let event = self
+            .transport
+            .drop_held(id)
+            .ok_or_else(|| format!("no held message #{}", id))?;
+        std::println!("Dropped {}", self.format_event(&event));
+        Ok(())
+    }
+
+    fn format_event(&self, event: &HeldEvent) -> String {
+        let payload = match &event.kind {
+            HeldEventKind::Message(message) => self.decode(event.source, message).to_string(),
+            HeldEventKind::Barrier(checkpoint) => format!("<barrier {}>", checkpoint),
+            HeldEventKind::Disconnect => String::from("<disconnect>"),
+        };
+        format!(
+            "#{} {} -> {}: {}",
+            event.id,
+            self.port_name(event.source.into()),
+            self.port_name(event.target.into()),
+            payload
+        )
+    }
+
+    fn decode(&self, source: OutputPortID, message: &Bytes) -> Value {
+        decode(self.port_type(source.into()).unwrap_or("?"), message)
+    }
+
+    fn parse_port(&self, input: &str) -> Result<PortID, String> {
+        if input.is_empty() {
+            return Err("expected a port".into());
+        }
+        self.port_names
+            .iter()
+            .find(|(_, name)| *name == input)
+            .map(|(port, _)| *port)
+            .ok_or_else(|| format!("unknown port: {} (type `ports`)", input))
+    }
+
+    fn port_name(&self, port: PortID) -> String {
+        self.port_names
+            .get(&port)
+            .cloned()
+            .unwrap_or(port.to_string())
+    }
+
+    fn port_type(&self, port: PortID) -> Option<&str> {
+        self.description.port(port)?.r#type.as_deref()
+    }
+}
+
+/// Decodes a message of the given type as JSON, or else returns its bytes
+/// in hexadecimal. Raw bytes are decoded as a string if they're UTF-8.
+fn decode(type_name: &str, message: &Bytes) -> Value {
+    if type_name == any::type_name::<Bytes>() {
+        if let Ok(bytes) = Bytes::decode_length_delimited(message.clone()) {
+            if let Ok(text) = std::str::from_utf8(&bytes) {
+                return Value::String(text.to_string());
+            }
+        }
+    }
+    let registry = MessageTypeRegistry::global();
+    let Some((message_type, Ok(text))) = registry
+        .get_by_type_name(type_name)
+        .map(|message_type| (message_type, message_type.format_message(message)))
+    else {
+        let hex: String = message.iter().map(|byte| format!("{:02x}", byte)).collect();
+        return json!({ "bytes": hex });
+    };
+    if message_type.type_name() == any::type_name::<String>() {
+        return Value::String(text);
+    }
+    match serde_json::from_str(&text) {
+        Ok(value @ (Value::Bool(_) | Value::Number(_))) => value,
+        _ => Value::String(text),
+    }
+}
+
+/// Encodes a message of the given type from text. Raw bytes are encoded
+/// as the text's UTF-8 bytes.
+fn encode(type_name: &str, text: &str) -> Option<Bytes> {
+    if type_name == any::type_name::<Bytes>() {
+        let bytes = Bytes::from(text.to_string());
+        return Some(bytes.encode_length_delimited_to_vec().into());
+    }
+    MessageTypeRegistry::global()
+        .get_by_type_name(type_name)
+        .and_then(|message_type| message_type.parse_message(text))
+}
+
+#[derive(Clone, Debug)]
+pub enum DebugError {
+    StdinInUse,
+    Failed(String),
+}
+
+impl std::error::Error for DebugError {}
+
+impl std::fmt::Display for DebugError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use DebugError::*;
+        match self {
+            StdinInUse => {
+                write!(
+                    f,
+                    "the debugger reads commands from stdin, so the input must be redirected with --input"
+                )
+            }
+            Failed(error) => {
+                write!(f, "the system failed: {}", error)
+            }
+        }
+    }
+}
@@ -0,0 +1,17 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{commands::execute::describe_system, exit::ExitCode};
+use protoflow_blocks::{types::Encoding, StdioConfig};
+use std::path::PathBuf;
+
+pub fn describe(
+    system_uri: PathBuf,
+    system_params: Vec<(String, String)>,
+    stdio_encoding: Encoding,
+) -> Result<(), ExitCode> {
+    let system_config = StdioConfig::new(stdio_encoding, system_params.iter().cloned().collect());
+    let description = describe_system(&system_uri, system_config)?;
+    let json = serde_json::to_string_pretty(&description).map_err(std::io::Error::from)?;
+    std::println!("{}", json);
+    Ok(())
+}
@@ -1,25 +1,113 @@
 // This is free and unencumbered software released into the public domain.
 
 use crate::exit::ExitCode;
-use protoflow_blocks::{build_stdio_system, types::Encoding, StdioConfig, StdioError};
-use protoflow_core::SystemExecution;
-use std::path::PathBuf;
+use protoflow_blocks::{
+    build_stdio_system, types::Encoding, DefinitionError, StdioConfig, StdioEndpoint, StdioError,
+    System, SystemDefinition,
+};
+use protoflow_core::{ConnectionOptions, SystemDescription, SystemExecution};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
 
 pub fn execute(
     system_uri: PathBuf,
     system_params: Vec<(String, String)>,
     stdio_encoding: Encoding,
+    stdio_input: StdioEndpoint,
+    stdio_output: StdioEndpoint,
+    channel_capacity: Option<usize>,
 ) -> Result<(), ExitCode> {
-    let system_uri = system_uri.to_string_lossy().to_string();
-    let system_config = StdioConfig {
-        encoding: stdio_encoding,
-        params: system_params.iter().cloned().collect(),
+    let connection_options = ConnectionOptions {
+        capacity: channel_capacity,
+        ..Default::default()
     };
-    let system = build_stdio_system(system_uri, system_config)?;
-    system.execute().unwrap().join().unwrap(); // TODO: improve error handling
+    let system_config = StdioConfig::new(stdio_encoding, system_params.iter().cloned().collect())
+        .with_input(stdio_input)
+        .with_output(stdio_output);
+    let system = build_system(&system_uri, system_config.clone(), connection_options)?;
+    system_config.open()?;
+    let process = system
+        .execute()
+        .map_err(|error| ExecuteError::Failed(error.to_string()))?;
+    process
+        .join()
+        .map_err(|error| ExecuteError::Failed(error.to_string()))?;
     Ok(())
 }
 
+/// Builds a system from a YAML or SysML definition file, or else builds the
+/// named block's stdio system. Redirecting the input or output of a system
+/// that doesn't read or write it fails.
+pub(crate) fn build_system(
+    system_uri: &Path,
+    system_config: StdioConfig,
+    connection_options: ConnectionOptions,
+) -> Result<System, ExitCode> {
+    Ok(match load_definition(system_uri)? {
+        Some(definition) => build_definition(definition, &system_config, connection_options)?,
+        None => {
+            let system_uri = system_uri.to_string_lossy().to_string();
+            build_stdio_system(system_uri, system_config)?
+        }
+    })
+}
+
+/// Describes the system that [`build_system`] builds, with the blocks of
+/// definition files labeled with their names.
+pub(crate) fn describe_system(
+    system_uri: &Path,
+    system_config: StdioConfig,
+) -> Result<SystemDescription, ExitCode> {
+    let connection_options = ConnectionOptions::default();
+    let Some(definition) = load_definition(system_uri)? else {
+        return Ok(build_system(system_uri, system_config, connection_options)?.describe());
+    };
+    let names: Vec<String> = definition
+        .blocks
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect();
+    let system = build_definition(definition, &system_config, connection_options)?;
+    let mut description = system.describe();
+    for (block, name) in description.blocks.iter_mut().zip(names) {
+        block.label = Some(name);
+    }
+    Ok(description)
+}
+
+/// Loads a YAML or SysML definition file, or returns `None` if the system
+/// is named by a block instead.
+fn load_definition(system_uri: &Path) -> Result<Option<SystemDefinition>, ExitCode> {
+    Ok(match system_uri.extension().and_then(OsStr::to_str) {
+        Some("yaml" | "yml") => {
+            let input = std::fs::read_to_string(system_uri)?;
+            Some(SystemDefinition::from_yaml(&input)?)
+        }
+        #[cfg(feature = "sysml")]
+        Some("sysml") => {
+            let input = std::fs::read_to_string(system_uri)?;
+            Some(SystemDefinition::from_sysml(&input)?)
+        }
+        _ => None,
+    })
+}
+
+/// Builds a system definition, with its block parameters overridden by the
+/// given `block.parameter=value` parameters, and its stdin and stdout
+/// redirected to the given endpoints.
+pub(crate) fn build_definition(
+    mut definition: SystemDefinition,
+    system_config: &StdioConfig,
+    connection_options: ConnectionOptions,
+) -> Result<System, DefinitionError> {
+    for (path, value) in &system_config.params {
+        definition.set_parameter(path, value)?;
+    }
+    definition.build_with_stdio(connection_options, system_config)
+}
+
 #[derive(Clone, Debug)]
 pub enum ExecuteError {
     UnknownSystem(String),
@@ -27,6 +115,11 @@ pub enum ExecuteError {
     MissingParameter(&'static str),
     InvalidParameter(&'static str),
     InvalidEncoding(String),
+    InvalidEndpoint(String),
+    UnsupportedRedirection,
+    UnavailableInput(StdioEndpoint, String),
+    UnavailableOutput(StdioEndpoint, String),
+    Failed(String),
 }
 
 impl std::error::Error for ExecuteError {}
@@ -50,6 +143,21 @@ impl std::fmt::Display for ExecuteError {
             InvalidEncoding(encoding) => {
                 write!(f, "invalid encoding: {}", encoding)
             }
+            InvalidEndpoint(endpoint) => {
+                write!(f, "invalid endpoint: {}", endpoint)
+            }
+            UnsupportedRedirection => {
+                write!(f, "input and output can't be redirected for this system")
+            }
+            UnavailableInput(endpoint, error) => {
+                write!(f, "can't read from {}: {}", endpoint, error)
+            }
+            UnavailableOutput(endpoint, error) => {
+                write!(f, "can't write to {}: {}", endpoint, error)
+            }
+            Failed(error) => {
+                write!(f, "execution failed: {}", error)
+            }
         }
     }
 }
@@ -62,6 +170,9 @@ impl From<StdioError> for ExecuteError {
             UnknownParameter(parameter) => Self::UnknownParameter(parameter),
             MissingParameter(parameter) => Self::MissingParameter(parameter),
             InvalidParameter(parameter) => Self::InvalidParameter(parameter),
+            UnsupportedRedirection => Self::UnsupportedRedirection,
+            UnavailableInput(endpoint, error) => Self::UnavailableInput(endpoint, error),
+            UnavailableOutput(endpoint, error) => Self::UnavailableOutput(endpoint, error),
         }
     }
 }
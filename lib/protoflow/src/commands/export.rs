@@ -0,0 +1,70 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::exit::ExitCode;
+use protoflow_blocks::SystemDefinition;
+use protoflow_syntax::ToSysml;
+use std::{ffi::OsStr, path::PathBuf, str::FromStr};
+
+pub fn export(system_path: PathBuf, export_format: ExportFormat) -> Result<(), ExitCode> {
+    let input = std::fs::read_to_string(&system_path)?;
+    let definition = match system_path.extension().and_then(OsStr::to_str) {
+        Some("yaml" | "yml") => SystemDefinition::from_yaml(&input)?,
+        Some("sysml") => SystemDefinition::from_sysml(&input)?,
+        _ => {
+            let system_path = system_path.to_string_lossy().to_string();
+            return Err(ExportError::UnknownFileType(system_path).into());
+        }
+    };
+    let output = match export_format {
+        ExportFormat::Yaml => definition.to_yaml()?,
+        ExportFormat::Sysml => {
+            let package_name = system_path.file_stem().unwrap_or_default();
+            definition.to_sysml(&package_name.to_string_lossy())?
+        }
+    };
+    std::print!("{}", output);
+    Ok(())
+}
+
+/// The format of an exported system definition.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ExportFormat {
+    #[default]
+    Yaml,
+    Sysml,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        use ExportFormat::*;
+        Ok(match input {
+            "yaml" | "yml" => Yaml,
+            "sysml" => Sysml,
+            _ => return Err(String::from(input)),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ExportError {
+    InvalidFormat(String),
+    UnknownFileType(String),
+}
+
+impl std::error::Error for ExportError {}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use ExportError::*;
+        match self {
+            InvalidFormat(format) => {
+                write!(f, "invalid export format: {}", format)
+            }
+            UnknownFileType(path) => {
+                write!(f, "unknown system file type: {}", path)
+            }
+        }
+    }
+}
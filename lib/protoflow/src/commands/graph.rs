@@ -0,0 +1,73 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{commands::execute::describe_system, exit::ExitCode};
+use protoflow_blocks::{types::Encoding, StdioConfig};
+use protoflow_core::GraphFormat;
+use std::path::{Path, PathBuf};
+
+pub fn graph(
+    system_uri: PathBuf,
+    system_params: Vec<(String, String)>,
+    stdio_encoding: Encoding,
+    graph_format: GraphFormat,
+) -> Result<(), ExitCode> {
+    let system_config = StdioConfig::new(stdio_encoding, system_params.iter().cloned().collect());
+    std::print!("{}", render(&system_uri, system_config, graph_format)?);
+    Ok(())
+}
+
+/// Renders the diagram of a system definition file or of a block's stdio
+/// system.
+fn render(
+    system_uri: &Path,
+    system_config: StdioConfig,
+    graph_format: GraphFormat,
+) -> Result<String, ExitCode> {
+    let description = describe_system(system_uri, system_config)?;
+    Ok(description.to_graph(graph_format))
+}
+
+#[derive(Clone, Debug)]
+pub enum GraphError {
+    InvalidFormat(String),
+}
+
+impl std::error::Error for GraphError {}
+
+impl std::fmt::Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use GraphError::*;
+        match self {
+            InvalidFormat(format) => {
+                write!(f, "invalid graph format: {}", format)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_yaml_definitions() {
+        let dir = tempfile::tempdir().unwrap();
+        let system_uri = dir.path().join("hello.yaml");
+        std::fs::write(
+            &system_uri,
+            "greeting: !Const { output: sink, value: Hello }\nsink: !Drop { input: greeting }\n",
+        )
+        .unwrap();
+        let system_config = StdioConfig::new(
+            Default::default(),
+            [("greeting.value".into(), "Hi".into())].into(),
+        );
+        let graph = render(&system_uri, system_config, GraphFormat::Mermaid).unwrap();
+        assert!(graph.contains("greeting: Const"), "{}", graph);
+        assert!(graph.contains("sink: Drop"), "{}", graph);
+        assert!(graph.contains("output → input"), "{}", graph);
+
+        let unknown = dir.path().join("missing.yaml");
+        assert!(render(&unknown, StdioConfig::default(), GraphFormat::Dot).is_err());
+    }
+}
@@ -0,0 +1,594 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{commands::execute::build_definition, exit::ExitCode};
+use protoflow_blocks::{
+    build_stdio_system, types::Encoding, DefinitionError, StdioConfig, StdioEndpoint, StdioError,
+    System, SystemDefinition,
+};
+use protoflow_core::{ConnectionOptions, SystemExecution};
+use std::{
+    ffi::OsStr,
+    fmt,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use toml::{Table, Value};
+
+/// The suffix of the names of test case files.
+const CASE_SUFFIX: &str = ".test.toml";
+
+/// Runs the test cases in the given files and directories, comparing the
+/// output of each system with its expected output fixture, or with `bless`,
+/// updating the fixtures with the actual output.
+pub fn test(
+    paths: Vec<PathBuf>,
+    default_encoding: Encoding,
+    channel_capacity: Option<usize>,
+    bless: bool,
+) -> Result<(), ExitCode> {
+    let mut case_paths = Vec::new();
+    for path in &paths {
+        discover(path, &mut case_paths)?;
+    }
+    if case_paths.is_empty() {
+        return Err(TestError::NoTestCases.into());
+    }
+    let cases = case_paths
+        .iter()
+        .map(|path| TestCase::load(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    std::println!("running {} test case(s)", cases.len());
+    let started = Instant::now();
+    let (mut passed, mut failed, mut blessed) = (0, 0, 0);
+    let mut failures = Vec::new();
+    for (index, case) in cases.iter().enumerate() {
+        let case_started = Instant::now();
+        let result = case.run(index, default_encoding, channel_capacity);
+        let elapsed = case_started.elapsed();
+        let status = match result {
+            Err(error) => {
+                failed += 1;
+                failures.push((case, error));
+                TestStatus::Error
+            }
+            Ok(actual) => match std::fs::read(&case.expected) {
+                Ok(expected) if expected == actual => {
+                    passed += 1;
+                    TestStatus::Ok
+                }
+                _ if bless => {
+                    std::fs::write(&case.expected, &actual)?;
+                    blessed += 1;
+                    TestStatus::Blessed
+                }
+                Ok(expected) => {
+                    failed += 1;
+                    failures.push((case, diff(&expected, &actual)));
+                    TestStatus::Failed
+                }
+                Err(error) => {
+                    failed += 1;
+                    let message = format!("{}: {}", case.expected.display(), error);
+                    failures.push((case, message));
+                    TestStatus::Failed
+                }
+            },
+        };
+        std::println!(
+            "test {} ... {} ({})",
+            case.name,
+            status,
+            format_duration(elapsed)
+        );
+    }
+
+    for (case, message) in &failures {
+        std::println!("\n---- {} ({}) ----", case.name, case.path.display());
+        std::println!("{}", message.trim_end());
+    }
+    std::println!(
+        "\ntest result: {}. {} passed; {} failed; {} blessed; finished in {}",
+        if failed > 0 { "FAILED" } else { "ok" },
+        passed,
+        failed,
+        blessed,
+        format_duration(started.elapsed()),
+    );
+    if failed > 0 {
+        return Err(TestError::Failed(failed).into());
+    }
+    Ok(())
+}
+
+/// Adds the test case files at the given path to `cases`, searching
+/// directories recursively, except for hidden and `target` directories.
+fn discover(path: &Path, cases: &mut Vec<PathBuf>) -> Result<(), ExitCode> {
+    if !path.is_dir() {
+        if !path.exists() {
+            return Err(TestError::NotFound(path.to_path_buf()).into());
+        }
+        cases.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for entry in entries {
+        let file_name = entry
+            .file_name()
+            .and_then(OsStr::to_str)
+            .unwrap_or_default();
+        if entry.is_dir() {
+            if !file_name.starts_with('.') && file_name != "target" {
+                discover(&entry, cases)?;
+            }
+        } else if file_name.ends_with(CASE_SUFFIX) {
+            cases.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// A test case, as loaded from a `NAME.test.toml` file:
+///
+/// ```toml
+/// system = "SplitString"       # a block name, or a .yaml, .yml, or .sysml file
+/// params = { delimiter = "," } # optional block parameters
+/// encoding = "text"            # optional, defaults to the configured encoding
+/// input = "NAME.in"            # optional, defaults to NAME.in if it exists
+/// expected = "NAME.out"        # optional, defaults to NAME.out
+/// ```
+///
+/// Relative paths are relative to the test case file.
+#[derive(Clone, Debug)]
+struct TestCase {
+    name: String,
+    path: PathBuf,
+    system: String,
+    params: Vec<(String, String)>,
+    encoding: Option<Encoding>,
+    input: Option<PathBuf>,
+    expected: PathBuf,
+}
+
+impl TestCase {
+    fn load(path: &Path) -> Result<Self, TestError> {
+        let file_name = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+        let name = file_name
+            .strip_suffix(CASE_SUFFIX)
+            .unwrap_or(file_name)
+            .to_string();
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let invalid = |key: &str, message: &str| {
+            TestError::InvalidCase(path.to_path_buf(), format!("{}: {}", key, message))
+        };
+
+        let input = std::fs::read_to_string(path)
+            .map_err(|error| TestError::InvalidCase(path.to_path_buf(), error.to_string()))?;
+        let table: Table = input.parse().map_err(|error: toml::de::Error| {
+            TestError::InvalidCase(path.to_path_buf(), error.to_string())
+        })?;
+
+        let mut case = Self {
+            path: path.to_path_buf(),
+            system: String::new(),
+            params: Vec::new(),
+            encoding: None,
+            input: Some(dir.join(format!("{}.in", name))).filter(|input| input.exists()),
+            expected: dir.join(format!("{}.out", name)),
+            name,
+        };
+        for (key, value) in &table {
+            match (key.as_str(), value) {
+                ("system", Value::String(system)) => case.system = system.clone(),
+                ("params", Value::Table(params)) => {
+                    for (param, value) in params {
+                        let value = match value {
+                            Value::String(value) => value.clone(),
+                            Value::Integer(_) | Value::Float(_) | Value::Boolean(_) => {
+                                value.to_string()
+                            }
+                            _ => return Err(invalid(key, "expected parameter values")),
+                        };
+                        case.params.push((param.clone(), value));
+                    }
+                }
+                ("encoding", Value::String(encoding)) => {
+                    let encoding = encoding
+                        .parse()
+                        .map_err(|_| invalid(key, "unknown encoding"))?;
+                    case.encoding = Some(encoding);
+                }
+                ("input", Value::String(input)) => case.input = Some(dir.join(input)),
+                ("expected", Value::String(expected)) => case.expected = dir.join(expected),
+                ("system" | "params" | "encoding" | "input" | "expected", _) => {
+                    return Err(invalid(key, "invalid value type"));
+                }
+                _ => return Err(invalid(key, "unknown key")),
+            }
+        }
+        if case.system.is_empty() {
+            return Err(invalid("system", "missing system"));
+        }
+        Ok(case)
+    }
+
+    /// Runs the system of the test case, with its input fixture, if any,
+    /// and returns its output.
+    fn run(
+        &self,
+        index: usize,
+        default_encoding: Encoding,
+        channel_capacity: Option<usize>,
+    ) -> Result<Vec<u8>, String> {
+        let scratch =
+            std::env::temp_dir().join(format!("protoflow-test-{}-{}", std::process::id(), index));
+        let input = match &self.input {
+            Some(input) => input.clone(),
+            None => {
+                let input = scratch.with_extension("in");
+                std::fs::write(&input, []).map_err(|error| error.to_string())?;
+                input
+            }
+        };
+        let output = scratch.with_extension("out");
+        let system_config = StdioConfig::new(
+            self.encoding.unwrap_or(default_encoding),
+            self.params.iter().cloned().collect(),
+        )
+        .with_input(StdioEndpoint::File(input))
+        .with_output(StdioEndpoint::File(output.clone()));
+        let connection_options = ConnectionOptions {
+            capacity: channel_capacity,
+            ..Default::default()
+        };
+
+        let result = self
+            .build(system_config, connection_options)
+            .and_then(|system| {
+                let process = system.execute().map_err(|error| error.to_string())?;
+                process.join().map_err(|error| error.to_string())
+            })
+            .and_then(|_| std::fs::read(&output).map_err(|error| error.to_string()));
+        let _ = std::fs::remove_file(scratch.with_extension("in"));
+        let _ = std::fs::remove_file(&output);
+        result
+    }
+
+    /// Builds the system of the test case. Without an input fixture, systems
+    /// that don't read their input are built without redirecting it.
+    fn build(
+        &self,
+        system_config: StdioConfig,
+        connection_options: ConnectionOptions,
+    ) -> Result<System, String> {
+        let unredirected = system_config.clone().with_input(StdioEndpoint::Stdio);
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        let system_path = dir.join(&self.system);
+        let definition = match system_path.extension().and_then(OsStr::to_str) {
+            Some("yaml" | "yml") => {
+                let input = std::fs::read_to_string(&system_path)
+                    .map_err(|error| format!("{}: {}", system_path.display(), error))?;
+                SystemDefinition::from_yaml(&input).map_err(|error| error.to_string())?
+            }
+            #[cfg(feature = "sysml")]
+            Some("sysml") => {
+                let input = std::fs::read_to_string(&system_path)
+                    .map_err(|error| format!("{}: {}", system_path.display(), error))?;
+                SystemDefinition::from_sysml(&input).map_err(|error| error.to_string())?
+            }
+            _ => {
+                return match build_stdio_system(self.system.clone(), system_config) {
+                    Err(StdioError::UnsupportedRedirection) if self.input.is_none() => {
+                        build_stdio_system(self.system.clone(), unredirected)
+                    }
+                    result => result,
+                }
+                .map_err(|error| error.to_string())
+            }
+        };
+        match build_definition(definition.clone(), &system_config, connection_options) {
+            Err(DefinitionError::Stdio(StdioError::UnsupportedRedirection))
+                if self.input.is_none() =>
+            {
+                build_definition(definition, &unredirected, connection_options)
+            }
+            result => result,
+        }
+        .map_err(|error| error.to_string())
+    }
+}
+
+/// The outcome of running a test case.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TestStatus {
+    Ok,
+    Failed,
+    Error,
+    Blessed,
+}
+
+impl fmt::Display for TestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TestStatus::*;
+        match self {
+            Ok => write!(f, "ok"),
+            Failed => write!(f, "FAILED"),
+            Error => write!(f, "ERROR"),
+            Blessed => write!(f, "blessed"),
+        }
+    }
+}
+
+/// Returns a line diff of the expected and actual output, with removed
+/// lines prefixed by `-` and added lines by `+`.
+fn diff(expected: &[u8], actual: &[u8]) -> String {
+    let expected = String::from_utf8_lossy(expected);
+    let actual = String::from_utf8_lossy(actual);
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    let mut edits = Vec::new();
+    diff_lines(&expected, &actual, &mut edits);
+
+    let mut output = String::from("--- expected\n+++ actual\n");
+    let mut changed = false;
+    for (prefix, line) in edits {
+        output += &format!("{}{}\n", prefix, line);
+        changed |= prefix != ' ';
+    }
+    if !changed {
+        output += "(the output differs only in line endings or in invalid UTF-8)\n";
+    }
+    output
+}
+
+/// Appends a shortest edit script from `a` to `b` to `edits`, as lines
+/// prefixed by ` `, `-`, or `+`. This is Myers' O((N+M)D) algorithm in its
+/// linear-space variant, which recursively splits both sequences at the
+/// middle snake of a shortest edit path.
+fn diff_lines<'a>(a: &[&'a str], b: &[&'a str], edits: &mut Vec<(char, &'a str)>) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    edits.extend(a[..prefix].iter().map(|line| (' ', *line)));
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a, b, common) = (
+        &a[..a.len() - suffix],
+        &b[..b.len() - suffix],
+        &a[a.len() - suffix..],
+    );
+
+    if a.is_empty() {
+        edits.extend(b.iter().map(|line| ('+', *line)));
+    } else if b.is_empty() {
+        edits.extend(a.iter().map(|line| ('-', *line)));
+    } else {
+        let (x, y, u, v) = middle_snake(a, b);
+        diff_lines(&a[..x], &b[..y], edits);
+        edits.extend(a[x..u].iter().map(|line| (' ', *line)));
+        diff_lines(&a[u..], &b[v..], edits);
+    }
+    edits.extend(common.iter().map(|line| (' ', *line)));
+}
+
+/// Returns the start `(x, y)` and end `(u, v)` of the middle snake of a
+/// shortest edit path from `a` to `b`, by searching for furthest-reaching
+/// paths forward from the start and backward from the end until they
+/// overlap.
+fn middle_snake(a: &[&str], b: &[&str]) -> (usize, usize, usize, usize) {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    let index = |k: isize| (offset + k) as usize;
+
+    // The furthest x reached on each diagonal k = x - y, forward from the
+    // start, and backward from the end (as a distance from the end):
+    let mut forward = vec![0isize; 2 * offset as usize + 1];
+    let mut backward = vec![0isize; 2 * offset as usize + 1];
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[index(k - 1)] < forward[index(k + 1)]) {
+                forward[index(k + 1)]
+            } else {
+                forward[index(k - 1)] + 1
+            };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                (x, y) = (x + 1, y + 1);
+            }
+            forward[index(k)] = x;
+            let c = delta - k;
+            if odd && (-(d - 1)..=d - 1).contains(&c) && x + backward[index(c)] >= n {
+                return (x0 as usize, y0 as usize, x as usize, y as usize);
+            }
+        }
+        for c in (-d..=d).step_by(2) {
+            let mut x = if c == -d || (c != d && backward[index(c - 1)] < backward[index(c + 1)]) {
+                backward[index(c + 1)]
+            } else {
+                backward[index(c - 1)] + 1
+            };
+            let (x0, y0) = (x, x - c);
+            let mut y = y0;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                (x, y) = (x + 1, y + 1);
+            }
+            backward[index(c)] = x;
+            let k = delta - c;
+            if !odd && (-d..=d).contains(&k) && forward[index(k)] + x >= n {
+                return (
+                    (n - x) as usize,
+                    (m - y) as usize,
+                    (n - x0) as usize,
+                    (m - y0) as usize,
+                );
+            }
+        }
+    }
+    unreachable!("the forward and backward paths always overlap")
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}s", duration.as_secs_f64())
+}
+
+#[derive(Clone, Debug)]
+pub enum TestError {
+    NotFound(PathBuf),
+    NoTestCases,
+    InvalidCase(PathBuf, String),
+    Failed(usize),
+}
+
+impl std::error::Error for TestError {}
+
+impl fmt::Display for TestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TestError::*;
+        match self {
+            NotFound(path) => {
+                write!(f, "no such file or directory: {}", path.display())
+            }
+            NoTestCases => {
+                write!(f, "no test cases found")
+            }
+            InvalidCase(path, message) => {
+                write!(f, "{}: {}", path.display(), message.trim_end())
+            }
+            Failed(count) => {
+                write!(f, "{} test case(s) failed", count)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, diff_lines, discover, test};
+    use protoflow_blocks::types::Encoding;
+    use std::path::Path;
+
+    fn write_file(dir: &Path, name: &str, contents: &str) {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn discover_test_cases() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "b.test.toml",
+            "a.test.toml",
+            "a.out",
+            "nested/c.test.toml",
+            ".hidden/d.test.toml",
+            "target/e.test.toml",
+        ] {
+            write_file(dir.path(), name, "");
+        }
+        let mut cases = Vec::new();
+        discover(dir.path(), &mut cases).unwrap();
+        let cases: Vec<_> = cases
+            .iter()
+            .map(|path| path.strip_prefix(dir.path()).unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(cases, ["a.test.toml", "b.test.toml", "nested/c.test.toml"]);
+
+        // Files given explicitly are run whatever their names:
+        let mut cases = Vec::new();
+        discover(&dir.path().join("a.out"), &mut cases).unwrap();
+        assert_eq!(cases, [dir.path().join("a.out")]);
+        assert!(discover(&dir.path().join("missing"), &mut cases).is_err());
+    }
+
+    #[test]
+    fn diff_lines_minimally() {
+        assert_eq!(
+            diff(b"a\nb\nc\nd\n", b"a\nc\nd\ne\n"),
+            "--- expected\n+++ actual\n a\n-b\n c\n d\n+e\n"
+        );
+        assert!(diff(b"a\r\n", b"a\n")
+            .ends_with("(the output differs only in line endings or in invalid UTF-8)\n"));
+
+        // Compare the lengths of the edit scripts of pseudo-random inputs
+        // with those given by their longest common subsequences:
+        let mut seed = 0x2545_f491_u32;
+        let mut random = |bound: u32| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed % bound
+        };
+        const LINES: [&str; 3] = ["x", "y", "z"];
+        for _ in 0..500 {
+            let a: Vec<&str> = (0..random(12)).map(|_| LINES[random(3) as usize]).collect();
+            let b: Vec<&str> = (0..random(12)).map(|_| LINES[random(3) as usize]).collect();
+            let mut edits = Vec::new();
+            diff_lines(&a, &b, &mut edits);
+
+            let removed: Vec<&str> = edits
+                .iter()
+                .filter(|(op, _)| *op != '+')
+                .map(|(_, line)| *line)
+                .collect();
+            let added: Vec<&str> = edits
+                .iter()
+                .filter(|(op, _)| *op != '-')
+                .map(|(_, line)| *line)
+                .collect();
+            assert_eq!((&removed, &added), (&a, &b));
+
+            let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+            for i in (0..a.len()).rev() {
+                for j in (0..b.len()).rev() {
+                    lengths[i][j] = if a[i] == b[j] {
+                        lengths[i + 1][j + 1] + 1
+                    } else {
+                        lengths[i + 1][j].max(lengths[i][j + 1])
+                    };
+                }
+            }
+            let changes = edits.iter().filter(|(op, _)| *op != ' ').count();
+            assert_eq!(
+                changes,
+                a.len() + b.len() - 2 * lengths[0][0],
+                "{:?} → {:?}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn bless_expected_output() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(
+            dir.path(),
+            "greeting.test.toml",
+            "system = \"Const\"\nparams = { value = \"Hello\" }\n",
+        );
+        let paths = vec![dir.path().to_path_buf()];
+        let expected = dir.path().join("greeting.out");
+
+        assert!(test(paths.clone(), Encoding::TextWithNewlineSuffix, None, false).is_err());
+        assert!(test(paths.clone(), Encoding::TextWithNewlineSuffix, None, true).is_ok());
+        assert_eq!(std::fs::read_to_string(&expected).unwrap(), "Hello\n");
+        assert!(test(paths.clone(), Encoding::TextWithNewlineSuffix, None, false).is_ok());
+
+        std::fs::write(&expected, "Hi\n").unwrap();
+        assert!(test(paths.clone(), Encoding::TextWithNewlineSuffix, None, false).is_err());
+        assert!(test(paths, Encoding::TextWithNewlineSuffix, None, true).is_ok());
+        assert_eq!(std::fs::read_to_string(&expected).unwrap(), "Hello\n");
+    }
+}
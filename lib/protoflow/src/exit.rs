@@ -42,6 +42,7 @@ impl From<error_stack::Report<protoflow_syntax::AnalysisError>> for ExitCode {
         std::eprintln!("{}: {:?}", "protoflow", error); // TODO: pretty print it
         match error.current_context() {
             ParseFailure => Self(SysexitsError::EX_NOINPUT),
+            InvalidDefinition(_) => Self(SysexitsError::EX_DATAERR),
             InvalidImport(_) => Self(SysexitsError::EX_DATAERR),
             UnknownName(_) => Self(SysexitsError::EX_DATAERR),
             Other(_) => Self(SysexitsError::EX_SOFTWARE),
@@ -58,6 +59,71 @@ impl From<protoflow_blocks::StdioError> for ExitCode {
             UnknownParameter(_) | MissingParameter(_) | InvalidParameter(_) => {
                 Self(SysexitsError::EX_USAGE)
             }
+            UnsupportedRedirection => Self(SysexitsError::EX_USAGE),
+            UnavailableInput(..) => Self(SysexitsError::EX_NOINPUT),
+            UnavailableOutput(..) => Self(SysexitsError::EX_CANTCREAT),
+        }
+    }
+}
+
+impl From<protoflow_blocks::DefinitionError> for ExitCode {
+    fn from(error: protoflow_blocks::DefinitionError) -> Self {
+        use protoflow_blocks::DefinitionError::*;
+        match error {
+            Stdio(error) => Self::from(error),
+            error => {
+                std::eprintln!("{}: {}", "protoflow", error);
+                Self(SysexitsError::EX_DATAERR)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<crate::commands::blocks::BlocksError> for ExitCode {
+    fn from(error: crate::commands::blocks::BlocksError) -> Self {
+        use crate::commands::blocks::BlocksError::*;
+        std::eprintln!("{}: {}", "protoflow", error);
+        match error {
+            UnknownBlock(_) => Self(SysexitsError::EX_UNAVAILABLE),
+        }
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "sysml"))]
+impl From<crate::commands::export::ExportError> for ExitCode {
+    fn from(error: crate::commands::export::ExportError) -> Self {
+        use crate::commands::export::ExportError::*;
+        std::eprintln!("{}: {}", "protoflow", error);
+        match error {
+            InvalidFormat(_) => Self(SysexitsError::EX_USAGE),
+            UnknownFileType(_) => Self(SysexitsError::EX_DATAERR),
+        }
+    }
+}
+
+impl From<crate::settings::ConfigError> for ExitCode {
+    fn from(error: crate::settings::ConfigError) -> Self {
+        use crate::settings::ConfigError::*;
+        std::eprintln!("{}: {}", "protoflow", error);
+        match error {
+            Io(..) => Self(SysexitsError::EX_NOINPUT),
+            Parse(..) | UnknownKey(..) | InvalidValue(..) => Self(SysexitsError::EX_CONFIG),
+            UnknownPreset(_) => Self(SysexitsError::EX_USAGE),
+        }
+    }
+}
+
+impl From<protoflow_blocks::PluginError> for ExitCode {
+    fn from(error: protoflow_blocks::PluginError) -> Self {
+        use protoflow_blocks::PluginError::*;
+        std::eprintln!("{}: {}", "protoflow", error);
+        match error {
+            Io(..) => Self(SysexitsError::EX_NOINPUT),
+            Load(..) | MissingDeclaration(_) => Self(SysexitsError::EX_UNAVAILABLE),
+            IncompatibleAbi(..) | IncompatibleVersion(..) | IncompatibleFeatures(..) => {
+                Self(SysexitsError::EX_CONFIG)
+            }
         }
     }
 }
@@ -89,7 +155,45 @@ impl From<crate::commands::execute::ExecuteError> for ExitCode {
             UnknownParameter(_) | MissingParameter(_) | InvalidParameter(_) => {
                 Self(SysexitsError::EX_USAGE)
             }
-            InvalidEncoding(_) => Self(SysexitsError::EX_USAGE),
+            InvalidEncoding(_) | InvalidEndpoint(_) => Self(SysexitsError::EX_USAGE),
+            UnsupportedRedirection => Self(SysexitsError::EX_USAGE),
+            UnavailableInput(..) => Self(SysexitsError::EX_NOINPUT),
+            UnavailableOutput(..) => Self(SysexitsError::EX_CANTCREAT),
+            Failed(_) => Self(SysexitsError::EX_SOFTWARE),
+        }
+    }
+}
+
+impl From<crate::commands::bench::BenchError> for ExitCode {
+    fn from(error: crate::commands::bench::BenchError) -> Self {
+        use crate::commands::bench::BenchError::*;
+        std::eprintln!("{}: {}", "protoflow", error);
+        match error {
+            InvalidRate | InvalidTransport(_) => Self(SysexitsError::EX_USAGE),
+            InvalidBaseline(..) => Self(SysexitsError::EX_DATAERR),
+            Failed(_) => Self(SysexitsError::EX_SOFTWARE),
+        }
+    }
+}
+
+impl From<crate::commands::debug::DebugError> for ExitCode {
+    fn from(error: crate::commands::debug::DebugError) -> Self {
+        use crate::commands::debug::DebugError::*;
+        std::eprintln!("{}: {}", "protoflow", error);
+        match error {
+            StdinInUse => Self(SysexitsError::EX_USAGE),
+            Failed(_) => Self(SysexitsError::EX_SOFTWARE),
+        }
+    }
+}
+
+impl From<crate::commands::test::TestError> for ExitCode {
+    fn from(error: crate::commands::test::TestError) -> Self {
+        use crate::commands::test::TestError::*;
+        std::eprintln!("{}: {}", "protoflow", error);
+        match error {
+            NotFound(_) | NoTestCases => Self(SysexitsError::EX_NOINPUT),
+            InvalidCase(..) | Failed(_) => Self(SysexitsError::EX_DATAERR),
         }
     }
 }
@@ -0,0 +1,285 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::settings::Config;
+use clientele::crates::clap::{self, Args};
+use std::{fmt, fs::File, path::PathBuf, str::FromStr, sync::Mutex, thread};
+use tracing_subscriber::{
+    filter::{filter_fn, LevelFilter},
+    fmt::writer::BoxMakeWriter,
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+};
+
+/// Options for the log output of blocks and of the CLI itself, which
+/// override the log settings in the configuration.
+#[derive(Clone, Debug, Default, Args)]
+pub struct LogOptions {
+    /// Set the log level, overall or for a block or module, as in `debug`,
+    /// `Count=debug`, or `protoflow_blocks=trace` (may be repeated)
+    #[clap(long = "log", value_name = "FILTER", global = true)]
+    pub directives: Vec<LogDirective>,
+
+    /// Specify the log output format (text or json)
+    #[clap(long, value_parser = parse_log_format, global = true)]
+    pub log_format: Option<LogFormat>,
+
+    /// Write the log output to a file, instead of to stderr
+    #[clap(long, value_name = "PATH", global = true)]
+    pub log_file: Option<PathBuf>,
+}
+
+/// Installs the global log subscriber, as configured by the log settings.
+///
+/// Log lines include the name of the thread they were logged on, which for
+/// a block is the name of the block.
+pub fn init_logging(config: &Config) -> std::io::Result<()> {
+    let default_level = config.log_level.value;
+    let named_levels: Vec<(String, LevelFilter)> = config
+        .log_filters
+        .iter()
+        .filter_map(|filter| Some((filter.value.name.clone()?, filter.value.level)))
+        .collect();
+    let max_level = named_levels
+        .iter()
+        .map(|(_, level)| *level)
+        .fold(default_level, LevelFilter::max);
+
+    let filter = filter_fn(move |metadata| {
+        let thread = thread::current();
+        let level = level_for(
+            &named_levels,
+            default_level,
+            thread.name(),
+            metadata.target(),
+        );
+        *metadata.level() <= level
+    });
+
+    let (writer, ansi) = match &config.log_file.value {
+        Some(path) => (BoxMakeWriter::new(Mutex::new(File::create(path)?)), false),
+        None => (BoxMakeWriter::new(std::io::stderr), true),
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(max_level)
+        .with_thread_names(true)
+        .with_writer(writer);
+
+    // A subscriber may already have been installed, such as in tests:
+    let _ = match config.log_format.value {
+        LogFormat::Text => builder.with_ansi(ansi).finish().with(filter).try_init(),
+        LogFormat::Json => builder.json().finish().with(filter).try_init(),
+    };
+    Ok(())
+}
+
+/// Returns the level for a log event, given the block that logged it by the
+/// name of the current thread, and the module that logged it by its target.
+/// Block names take precedence over the longest matching module path, which
+/// takes precedence over the default level.
+fn level_for(
+    named_levels: &[(String, LevelFilter)],
+    default_level: LevelFilter,
+    thread_name: Option<&str>,
+    target: &str,
+) -> LevelFilter {
+    if let Some(thread_name) = thread_name {
+        let block_level = named_levels
+            .iter()
+            .rev()
+            .find(|(name, _)| name == thread_name);
+        if let Some((_, level)) = block_level {
+            return *level;
+        }
+    }
+    named_levels
+        .iter()
+        .filter(|(name, _)| {
+            target == name
+                || target
+                    .strip_prefix(name.as_str())
+                    .is_some_and(|rest| rest.starts_with("::"))
+        })
+        .max_by_key(|(name, _)| name.len())
+        .map_or(default_level, |(_, level)| *level)
+}
+
+/// A log level for all log events, or for those of a block or module.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogDirective {
+    pub name: Option<String>,
+    pub level: LevelFilter,
+}
+
+impl FromStr for LogDirective {
+    type Err = LogError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (name, level) = match input.split_once('=') {
+            Some((name, level)) if !name.is_empty() => (Some(name.to_string()), level),
+            Some(_) => return Err(LogError::InvalidDirective(input.to_string())),
+            None => (None, input),
+        };
+        // Empty levels would otherwise parse as `error`:
+        let level = Some(level)
+            .filter(|level| !level.is_empty())
+            .and_then(|level| level.parse().ok())
+            .ok_or_else(|| LogError::InvalidDirective(input.to_string()))?;
+        Ok(Self { name, level })
+    }
+}
+
+impl fmt::Display for LogDirective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}={}", name, self.level),
+            None => write!(f, "{}", self.level),
+        }
+    }
+}
+
+/// The format of log output.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        use LogFormat::*;
+        Ok(match input {
+            "text" => Text,
+            "json" => Json,
+            _ => return Err(String::from(input)),
+        })
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use LogFormat::*;
+        match self {
+            Text => write!(f, "text"),
+            Json => write!(f, "json"),
+        }
+    }
+}
+
+fn parse_log_format(input: &str) -> Result<LogFormat, LogError> {
+    input.parse().map_err(LogError::InvalidFormat)
+}
+
+#[derive(Clone, Debug)]
+pub enum LogError {
+    InvalidDirective(String),
+    InvalidFormat(String),
+}
+
+impl std::error::Error for LogError {}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use LogError::*;
+        match self {
+            InvalidDirective(directive) => {
+                write!(f, "invalid log filter: {}", directive)
+            }
+            InvalidFormat(format) => {
+                write!(f, "invalid log format: {}", format)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{level_for, LogDirective, LogError};
+    use tracing_subscriber::filter::LevelFilter;
+
+    fn directive(input: &str) -> Result<LogDirective, LogError> {
+        input.parse()
+    }
+
+    #[test]
+    fn parse_directives() {
+        let debug = directive("debug").unwrap();
+        assert_eq!(debug.name, None);
+        assert_eq!(debug.level, LevelFilter::DEBUG);
+
+        let count = directive("Count=trace").unwrap();
+        assert_eq!(count.name.as_deref(), Some("Count"));
+        assert_eq!(count.level, LevelFilter::TRACE);
+        assert_eq!(count.to_string(), "Count=trace");
+
+        let module = directive("protoflow_blocks::count=off").unwrap();
+        assert_eq!(module.name.as_deref(), Some("protoflow_blocks::count"));
+        assert_eq!(module.level, LevelFilter::OFF);
+    }
+
+    #[test]
+    fn reject_malformed_directives() {
+        for input in [
+            "",
+            "loud",
+            "=debug",
+            "Count=",
+            "Count=loud",
+            "Count=debug=trace",
+        ] {
+            assert!(
+                matches!(directive(input), Err(LogError::InvalidDirective(d)) if d == input),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn prefer_blocks_over_modules() {
+        let named_levels = [
+            ("protoflow_blocks".to_string(), LevelFilter::INFO),
+            ("protoflow_blocks::count".to_string(), LevelFilter::TRACE),
+            ("Count".to_string(), LevelFilter::ERROR),
+            ("Count".to_string(), LevelFilter::DEBUG),
+        ];
+        let level =
+            |thread_name, target| level_for(&named_levels, LevelFilter::WARN, thread_name, target);
+
+        // The last level given for a block wins, whatever the module:
+        assert_eq!(
+            level(Some("Count"), "protoflow_blocks::count"),
+            LevelFilter::DEBUG
+        );
+        assert_eq!(level(Some("Count"), "other"), LevelFilter::DEBUG);
+
+        // Otherwise, the longest matching module path wins:
+        assert_eq!(
+            level(Some("Drop"), "protoflow_blocks::count"),
+            LevelFilter::TRACE
+        );
+        assert_eq!(
+            level(None, "protoflow_blocks::count::inner"),
+            LevelFilter::TRACE
+        );
+        assert_eq!(level(None, "protoflow_blocks::drop"), LevelFilter::INFO);
+
+        // Module paths match whole segments only:
+        assert_eq!(level(None, "protoflow_blocks_extra"), LevelFilter::WARN);
+        assert_eq!(level(None, "protoflow"), LevelFilter::WARN);
+    }
+
+    #[test]
+    fn default_to_overall_level() {
+        assert_eq!(
+            level_for(&[], LevelFilter::WARN, None, "protoflow"),
+            LevelFilter::WARN
+        );
+        assert_eq!(
+            level_for(&[], LevelFilter::OFF, Some("Count"), "protoflow_blocks"),
+            LevelFilter::OFF
+        );
+    }
+}
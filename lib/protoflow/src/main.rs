@@ -4,24 +4,40 @@
 #![allow(unused)]
 
 mod commands {
+    pub mod bench;
+    #[cfg(feature = "serde")]
+    pub mod blocks;
     #[cfg(feature = "beta")]
     pub mod check;
-    #[cfg(feature = "beta")]
     pub mod config;
+    pub mod debug;
+    #[cfg(feature = "serde")]
+    pub mod describe;
     pub mod execute;
+    #[cfg(all(feature = "serde", feature = "sysml"))]
+    pub mod export;
     #[cfg(feature = "beta")]
     pub mod generate;
+    pub mod graph;
+    pub mod test;
 }
 use commands::*;
 
 mod exit;
 
+mod logging;
+
+mod settings;
+
+mod plugins;
+
 use crate::exit::ExitCode;
 use clientele::{
     crates::clap::{Args, Parser, Subcommand},
     StandardOptions,
 };
-use protoflow_blocks::types::Encoding;
+use protoflow_blocks::{types::Encoding, StdioEndpoint};
+use protoflow_core::GraphFormat;
 use std::{error::Error, path::PathBuf, str::FromStr};
 
 /// Protoflow Command-Line Interface (CLI)
@@ -32,14 +48,52 @@ struct Options {
     #[clap(flatten)]
     flags: StandardOptions,
 
+    #[clap(flatten)]
+    config: settings::ConfigOptions,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
 
 #[derive(Debug, Subcommand)]
 enum Command {
-    /// Show the current configuration
-    #[cfg(feature = "beta")]
+    /// List and describe the available blocks
+    #[cfg(feature = "serde")]
+    Blocks {
+        #[command(subcommand)]
+        command: BlocksCommand,
+    },
+
+    /// Benchmark the throughput and latencies of a Protoflow system or block
+    Bench {
+        /// Pathname of the Protoflow system or block
+        block: PathBuf,
+
+        #[clap(flatten)]
+        options: bench::BenchOptions,
+
+        /// Specify the message encoding to use on stdin/stdout
+        #[clap(short = 'e', long, value_parser = parse_encoding)]
+        encoding: Option<Encoding>,
+
+        /// Apply a named set of block parameters from the configuration
+        #[clap(long = "preset", value_name = "NAME")]
+        presets: Vec<String>,
+
+        /// Output the results as JSON
+        #[clap(long)]
+        json: bool,
+
+        /// Compare the results with those of a previous run, as output with --json
+        #[clap(long, value_name = "PATH", conflicts_with = "json")]
+        compare: Option<PathBuf>,
+
+        /// Specify block parameters in key=value format
+        #[clap(value_parser = parse_kv_param::<String, String>)]
+        params: Vec<(String, String)>,
+    },
+
+    /// Show the effective configuration and where each setting came from
     Config {},
 
     /// Check the syntax of a Protoflow system
@@ -50,26 +104,152 @@ enum Command {
         paths: Vec<PathBuf>,
     },
 
+    /// Debug a Protoflow system or block, stepping through its message deliveries
+    Debug {
+        /// Pathname of the Protoflow system or block
+        block: PathBuf,
+
+        /// Read the input from a file, named pipe, or tcp:// or unix:// socket, as stdin is used for commands
+        #[clap(short = 'i', long, value_name = "ENDPOINT", value_parser = parse_endpoint, default_value = "/dev/null")]
+        input: StdioEndpoint,
+
+        /// Write the output to a file, named pipe, or tcp:// or unix:// socket, instead of stdout
+        #[clap(short = 'o', long, value_name = "ENDPOINT", value_parser = parse_endpoint, default_value = "-")]
+        output: StdioEndpoint,
+
+        /// Specify the message encoding to use on stdin/stdout
+        #[clap(short = 'e', long, value_parser = parse_encoding)]
+        encoding: Option<Encoding>,
+
+        /// Apply a named set of block parameters from the configuration
+        #[clap(long = "preset", value_name = "NAME")]
+        presets: Vec<String>,
+
+        /// Specify block parameters in key=value format
+        #[clap(value_parser = parse_kv_param::<String, String>)]
+        params: Vec<(String, String)>,
+    },
+
+    /// Describe the topology of a Protoflow system or block as JSON
+    #[cfg(feature = "serde")]
+    Describe {
+        /// Pathname of the Protoflow system or block
+        block: PathBuf,
+
+        /// Specify the message encoding to use on stdin/stdout
+        #[clap(short = 'e', long, value_parser = parse_encoding)]
+        encoding: Option<Encoding>,
+
+        /// Apply a named set of block parameters from the configuration
+        #[clap(long = "preset", value_name = "NAME")]
+        presets: Vec<String>,
+
+        /// Specify block parameters in key=value format
+        #[clap(value_parser = parse_kv_param::<String, String>)]
+        params: Vec<(String, String)>,
+    },
+
     /// Execute a Protoflow system or block
     Execute {
         /// Pathname of the Protoflow system or block
         block: PathBuf,
 
+        /// Read the input from a file, named pipe, or tcp:// or unix:// socket, instead of stdin
+        #[clap(short = 'i', long, value_name = "ENDPOINT", value_parser = parse_endpoint, default_value = "-")]
+        input: StdioEndpoint,
+
+        /// Write the output to a file, named pipe, or tcp:// or unix:// socket, instead of stdout
+        #[clap(short = 'o', long, value_name = "ENDPOINT", value_parser = parse_endpoint, default_value = "-")]
+        output: StdioEndpoint,
+
         /// Specify the message encoding to use on stdin/stdout
-        #[clap(short = 'e', long, value_parser = parse_encoding, default_value = "text")]
-        encoding: Encoding,
+        #[clap(short = 'e', long, value_parser = parse_encoding)]
+        encoding: Option<Encoding>,
+
+        /// Apply a named set of block parameters from the configuration
+        #[clap(long = "preset", value_name = "NAME")]
+        presets: Vec<String>,
 
         /// Specify block parameters in key=value format
         #[clap(value_parser = parse_kv_param::<String, String>)]
         params: Vec<(String, String)>,
     },
 
+    /// Export a Protoflow system as a canonical YAML or SysML definition
+    #[cfg(all(feature = "serde", feature = "sysml"))]
+    Export {
+        /// Pathname of the Protoflow system (.yaml, .yml, or .sysml)
+        path: PathBuf,
+
+        /// Specify the definition format to write (yaml or sysml)
+        #[clap(short = 'f', long, value_parser = parse_export_format, default_value = "yaml")]
+        format: export::ExportFormat,
+    },
+
     /// Generate code from a Protoflow system
     #[cfg(feature = "beta")]
     Generate {
         /// Pathname of the Protoflow file
         path: PathBuf,
     },
+
+    /// Render a Protoflow system or block as a Mermaid or DOT diagram
+    Graph {
+        /// Pathname of the Protoflow system or block
+        block: PathBuf,
+
+        /// Specify the diagram format to render (mermaid or dot)
+        #[clap(short = 'f', long, value_parser = parse_graph_format, default_value = "mermaid")]
+        format: GraphFormat,
+
+        /// Specify the message encoding to use on stdin/stdout
+        #[clap(short = 'e', long, value_parser = parse_encoding)]
+        encoding: Option<Encoding>,
+
+        /// Apply a named set of block parameters from the configuration
+        #[clap(long = "preset", value_name = "NAME")]
+        presets: Vec<String>,
+
+        /// Specify block parameters in key=value format
+        #[clap(value_parser = parse_kv_param::<String, String>)]
+        params: Vec<(String, String)>,
+    },
+
+    /// Run the golden-file test cases of Protoflow systems and blocks
+    Test {
+        /// Pathnames of test case files (*.test.toml), or of directories to search
+        #[clap(default_value = ".")]
+        paths: Vec<PathBuf>,
+
+        /// Specify the message encoding of test cases that don't specify one
+        #[clap(short = 'e', long, value_parser = parse_encoding)]
+        encoding: Option<Encoding>,
+
+        /// Update the expected output of the test cases with their actual output
+        #[clap(long)]
+        bless: bool,
+    },
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Subcommand)]
+enum BlocksCommand {
+    /// List the available blocks, grouped by category
+    List {
+        /// Output the block descriptions as JSON
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Describe the ports and parameters of a block
+    Describe {
+        /// Name of the block, such as Delay
+        block: String,
+
+        /// Output the block description as JSON
+        #[clap(long)]
+        json: bool,
+    },
 }
 
 pub fn main() -> Result<(), ExitCode> {
@@ -92,23 +272,100 @@ pub fn main() -> Result<(), ExitCode> {
         return Ok(());
     }
 
+    // Load the configuration files, environment variables, and flags:
+    let config =
+        settings::Config::load(&options.config, options.flags.verbose, options.flags.debug)?;
+
     // Configure verbose/debug output:
-    if options.flags.verbose > 0 || options.flags.debug {
-        // TODO: configure tracing
-    }
+    logging::init_logging(&config)?;
+
+    // Load block plugins:
+    plugins::load_plugins(&config.plugin_paths())?;
 
     match options.command.unwrap() {
-        #[cfg(feature = "beta")]
-        Command::Config {} => config::config(),
+        #[cfg(feature = "serde")]
+        Command::Blocks { command } => match command {
+            BlocksCommand::List { json } => blocks::list(json),
+            BlocksCommand::Describe { block, json } => blocks::describe(block, json),
+        },
+        Command::Bench {
+            block,
+            options,
+            encoding,
+            presets,
+            json,
+            compare,
+            params,
+        } => {
+            let params = [config.preset_params(&presets)?, params].concat();
+            let encoding = encoding.unwrap_or(config.encoding.value);
+            let capacity = config.channel_capacity.value;
+            bench::bench(block, params, encoding, capacity, options, json, compare)
+        }
+        Command::Config {} => config::config(&config),
         #[cfg(feature = "beta")]
         Command::Check { paths } => check::check(paths),
+        Command::Debug {
+            block,
+            input,
+            output,
+            encoding,
+            presets,
+            params,
+        } => {
+            let params = [config.preset_params(&presets)?, params].concat();
+            let encoding = encoding.unwrap_or(config.encoding.value);
+            let capacity = config.channel_capacity.value;
+            debug::debug(block, params, encoding, input, output, capacity)
+        }
+        #[cfg(feature = "serde")]
+        Command::Describe {
+            block,
+            encoding,
+            presets,
+            params,
+        } => {
+            let params = [config.preset_params(&presets)?, params].concat();
+            let encoding = encoding.unwrap_or(config.encoding.value);
+            describe::describe(block, params, encoding)
+        }
         Command::Execute {
             block,
+            input,
+            output,
             encoding,
+            presets,
             params,
-        } => execute::execute(block, params, encoding),
+        } => {
+            let params = [config.preset_params(&presets)?, params].concat();
+            let encoding = encoding.unwrap_or(config.encoding.value);
+            let capacity = config.channel_capacity.value;
+            execute::execute(block, params, encoding, input, output, capacity)
+        }
+        #[cfg(all(feature = "serde", feature = "sysml"))]
+        Command::Export { path, format } => export::export(path, format),
         #[cfg(feature = "beta")]
         Command::Generate { path } => generate::generate(path),
+        Command::Graph {
+            block,
+            format,
+            encoding,
+            presets,
+            params,
+        } => {
+            let params = [config.preset_params(&presets)?, params].concat();
+            let encoding = encoding.unwrap_or(config.encoding.value);
+            graph::graph(block, params, encoding, format)
+        }
+        Command::Test {
+            paths,
+            encoding,
+            bless,
+        } => {
+            let encoding = encoding.unwrap_or(config.encoding.value);
+            let capacity = config.channel_capacity.value;
+            test::test(paths, encoding, capacity, bless)
+        }
     }
 }
 
@@ -118,6 +375,25 @@ fn parse_encoding(input: &str) -> Result<Encoding, execute::ExecuteError> {
         .map_err(|e: String| execute::ExecuteError::InvalidEncoding(e))
 }
 
+fn parse_endpoint(input: &str) -> Result<StdioEndpoint, execute::ExecuteError> {
+    input
+        .parse()
+        .map_err(|e: String| execute::ExecuteError::InvalidEndpoint(e))
+}
+
+fn parse_graph_format(input: &str) -> Result<GraphFormat, graph::GraphError> {
+    input
+        .parse()
+        .map_err(|e: String| graph::GraphError::InvalidFormat(e))
+}
+
+#[cfg(all(feature = "serde", feature = "sysml"))]
+fn parse_export_format(input: &str) -> Result<export::ExportFormat, export::ExportError> {
+    input
+        .parse()
+        .map_err(|e: String| export::ExportError::InvalidFormat(e))
+}
+
 fn parse_kv_param<K, V>(input: &str) -> Result<(K, V), Box<dyn Error + Send + Sync + 'static>>
 where
     K: FromStr,
@@ -0,0 +1,18 @@
+// This is free and unencumbered software released into the public domain.
+
+use protoflow_blocks::{BlockRegistry, PluginError};
+use std::path::PathBuf;
+
+/// Loads the given block plugin libraries, or all those in the given
+/// directories, into the global block registry.
+pub fn load_plugins(paths: &[PathBuf]) -> Result<(), PluginError> {
+    let mut registry = BlockRegistry::global_mut();
+    for path in paths {
+        if path.is_dir() {
+            registry.load_plugins(path)?;
+        } else {
+            registry.load_plugin(path)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,594 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::logging::{LogDirective, LogFormat, LogOptions};
+use clientele::crates::clap::{self, Args};
+use protoflow_blocks::types::Encoding;
+use std::{
+    collections::BTreeMap,
+    env, fmt,
+    path::{Path, PathBuf},
+};
+use toml::{Table, Value};
+use tracing_subscriber::filter::LevelFilter;
+
+/// The name of the project-local configuration file, which is looked up in
+/// the current directory and its ancestors.
+pub const PROJECT_CONFIG_FILE: &str = "protoflow.toml";
+
+/// Options that override the configuration files and environment variables.
+#[derive(Clone, Debug, Default, Args)]
+pub struct ConfigOptions {
+    /// Load blocks from a plugin library, or from all plugin libraries in a directory
+    #[clap(long = "plugin", value_name = "PATH", global = true)]
+    pub plugins: Vec<PathBuf>,
+
+    /// Set the maximum number of messages buffered on each connection
+    #[clap(long, value_name = "COUNT", global = true)]
+    pub channel_capacity: Option<usize>,
+
+    #[clap(flatten)]
+    pub log: LogOptions,
+}
+
+/// The effective configuration of the CLI, along with the source of each
+/// setting.
+///
+/// Settings are layered, with each layer overriding the ones before it:
+/// the defaults, the user's `~/.config/protoflow/config.toml`, the nearest
+/// project-local `protoflow.toml`, `PROTOFLOW_*` environment variables, and
+/// finally command-line flags. Plugin paths, log filters, and presets are
+/// instead accumulated across layers.
+///
+/// Values are validated the same way in every layer.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The message encoding to use on stdin/stdout.
+    pub encoding: Setting<Encoding>,
+
+    /// The directory of block plugin libraries to load, if it exists.
+    pub plugin_dir: Setting<Option<PathBuf>>,
+
+    /// The block plugin libraries, or directories of them, to load.
+    pub plugins: Vec<Setting<PathBuf>>,
+
+    /// The maximum number of messages buffered on each connection, if not
+    /// the transport's default.
+    pub channel_capacity: Setting<Option<usize>>,
+
+    /// The log level for events not matched by any log filter.
+    pub log_level: Setting<LevelFilter>,
+
+    /// The log levels for blocks and modules.
+    pub log_filters: Vec<Setting<LogDirective>>,
+
+    /// The format of the log output.
+    pub log_format: Setting<LogFormat>,
+
+    /// The file to write the log output to, instead of to stderr.
+    pub log_file: Setting<Option<PathBuf>>,
+
+    /// The named sets of block parameters, selected with `--preset`.
+    pub presets: BTreeMap<String, Setting<Vec<(String, String)>>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            encoding: Setting::default(Encoding::TextWithNewlineSuffix),
+            plugin_dir: Setting::default(
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".protoflow/plugins")),
+            ),
+            plugins: Vec::new(),
+            channel_capacity: Setting::default(None),
+            log_level: Setting::default(LevelFilter::WARN),
+            log_filters: Vec::new(),
+            log_format: Setting::default(LogFormat::Text),
+            log_file: Setting::default(None),
+            presets: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the configuration from the configuration files, environment
+    /// variables, and the given command-line flags.
+    pub fn load(options: &ConfigOptions, verbose: u8, debug: bool) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        for path in [user_config_file(), project_config_file()]
+            .into_iter()
+            .flatten()
+        {
+            if path.is_file() {
+                config.load_file(&path)?;
+            }
+        }
+        config.load_env()?;
+        config.apply_flags(options, verbose, debug)?;
+        Ok(config)
+    }
+
+    /// Loads a configuration file, overriding the settings it contains.
+    pub fn load_file(&mut self, path: &Path) -> Result<(), ConfigError> {
+        let input = std::fs::read_to_string(path)
+            .map_err(|error| ConfigError::Io(path.to_path_buf(), error.to_string()))?;
+        let table: Table = input.parse().map_err(|error: toml::de::Error| {
+            ConfigError::Parse(path.to_path_buf(), error.to_string())
+        })?;
+        // Relative paths are relative to the directory of the file:
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let source = ConfigSource::File(path.to_path_buf());
+        let invalid = |key: &str, message: &str| {
+            ConfigError::InvalidValue(source.clone(), key.to_string(), message.to_string())
+        };
+
+        for (key, value) in &table {
+            match (key.as_str(), value) {
+                ("encoding", Value::String(encoding)) => {
+                    let encoding = encoding
+                        .parse()
+                        .map_err(|_| invalid(key, "unknown encoding"))?;
+                    self.encoding = Setting::new(encoding, source.clone());
+                }
+                ("plugin_dir", Value::String(path)) => {
+                    self.plugin_dir = Setting::new(Some(dir.join(path)), source.clone());
+                }
+                ("plugins", Value::Array(paths)) => {
+                    for path in paths {
+                        let Value::String(path) = path else {
+                            return Err(invalid(key, "expected an array of paths"));
+                        };
+                        self.plugins
+                            .push(Setting::new(dir.join(path), source.clone()));
+                    }
+                }
+                ("channel_capacity", Value::Integer(capacity)) => {
+                    let capacity = usize::try_from(*capacity)
+                        .ok()
+                        .and_then(|capacity| validate_capacity(capacity).ok())
+                        .ok_or_else(|| invalid(key, "expected a positive integer"))?;
+                    self.channel_capacity = Setting::new(Some(capacity), source.clone());
+                }
+                ("log", Value::Table(log)) => {
+                    for (log_key, value) in log {
+                        let key = format!("log.{}", log_key);
+                        match (log_key.as_str(), value) {
+                            ("level", Value::String(level)) => {
+                                let level =
+                                    level.parse().map_err(|_| invalid(&key, "unknown level"))?;
+                                self.log_level = Setting::new(level, source.clone());
+                            }
+                            ("filters", Value::Array(filters)) => {
+                                for filter in filters {
+                                    let directive = filter
+                                        .as_str()
+                                        .and_then(|filter| filter.parse().ok())
+                                        .ok_or_else(|| invalid(&key, "invalid log filter"))?;
+                                    self.add_log_directive(directive, source.clone());
+                                }
+                            }
+                            ("format", Value::String(format)) => {
+                                let format = format
+                                    .parse()
+                                    .map_err(|_| invalid(&key, "unknown format"))?;
+                                self.log_format = Setting::new(format, source.clone());
+                            }
+                            ("file", Value::String(file)) => {
+                                self.log_file = Setting::new(Some(dir.join(file)), source.clone());
+                            }
+                            ("level" | "filters" | "format" | "file", _) => {
+                                return Err(invalid(&key, "invalid value type"));
+                            }
+                            _ => return Err(ConfigError::UnknownKey(source, key)),
+                        }
+                    }
+                }
+                ("presets", Value::Table(presets)) => {
+                    for (name, preset) in presets {
+                        let key = format!("presets.{}", name);
+                        let Value::Table(preset) = preset else {
+                            return Err(invalid(&key, "expected a table of parameters"));
+                        };
+                        let params = preset
+                            .iter()
+                            .map(|(param, value)| match value {
+                                Value::String(value) => Ok((param.clone(), value.clone())),
+                                Value::Integer(_) | Value::Float(_) | Value::Boolean(_) => {
+                                    Ok((param.clone(), value.to_string()))
+                                }
+                                _ => Err(invalid(&key, "expected parameter values")),
+                            })
+                            .collect::<Result<_, _>>()?;
+                        self.presets
+                            .insert(name.clone(), Setting::new(params, source.clone()));
+                    }
+                }
+                (
+                    "encoding" | "plugin_dir" | "plugins" | "channel_capacity" | "log" | "presets",
+                    _,
+                ) => {
+                    return Err(invalid(key, "invalid value type"));
+                }
+                _ => return Err(ConfigError::UnknownKey(source, key.clone())),
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the `PROTOFLOW_*` environment variables, overriding the
+    /// settings they contain.
+    pub fn load_env(&mut self) -> Result<(), ConfigError> {
+        self.load_vars(|name| env::var(name).ok())
+    }
+
+    /// Loads the `PROTOFLOW_*` variables looked up with the given function.
+    /// See [`Config::load_env`].
+    fn load_vars(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let var = |name: &'static str| {
+            let value = lookup(name).filter(|value| !value.is_empty())?;
+            Some((value, ConfigSource::Env(name)))
+        };
+        let invalid = |source: &ConfigSource, value: &str| {
+            ConfigError::InvalidValue(source.clone(), value.to_string(), "invalid value".into())
+        };
+
+        if let Some((encoding, source)) = var("PROTOFLOW_ENCODING") {
+            let value = encoding.parse().map_err(|_| invalid(&source, &encoding))?;
+            self.encoding = Setting::new(value, source);
+        }
+        if let Some((dir, source)) = var("PROTOFLOW_PLUGIN_DIR") {
+            self.plugin_dir = Setting::new(Some(PathBuf::from(dir)), source);
+        }
+        if let Some((paths, source)) = var("PROTOFLOW_PLUGINS") {
+            for path in env::split_paths(&paths) {
+                self.plugins.push(Setting::new(path, source.clone()));
+            }
+        }
+        if let Some((capacity, source)) = var("PROTOFLOW_CHANNEL_CAPACITY") {
+            let value = capacity
+                .parse()
+                .ok()
+                .and_then(|capacity| validate_capacity(capacity).ok())
+                .ok_or_else(|| invalid(&source, &capacity))?;
+            self.channel_capacity = Setting::new(Some(value), source);
+        }
+        if let Some((filters, source)) = var("PROTOFLOW_LOG") {
+            for filter in filters.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+                let directive = filter.parse().map_err(|_| invalid(&source, filter))?;
+                self.add_log_directive(directive, source.clone());
+            }
+        }
+        if let Some((format, source)) = var("PROTOFLOW_LOG_FORMAT") {
+            let value = format.parse().map_err(|_| invalid(&source, &format))?;
+            self.log_format = Setting::new(value, source);
+        }
+        if let Some((file, source)) = var("PROTOFLOW_LOG_FILE") {
+            self.log_file = Setting::new(Some(PathBuf::from(file)), source);
+        }
+        Ok(())
+    }
+
+    /// Applies the command-line flags, overriding all other settings.
+    pub fn apply_flags(
+        &mut self,
+        options: &ConfigOptions,
+        verbose: u8,
+        debug: bool,
+    ) -> Result<(), ConfigError> {
+        for path in &options.plugins {
+            self.plugins
+                .push(Setting::new(path.clone(), ConfigSource::Flag("--plugin")));
+        }
+        if let Some(capacity) = options.channel_capacity {
+            let source = ConfigSource::Flag("--channel-capacity");
+            let capacity = validate_capacity(capacity).map_err(|message| {
+                ConfigError::InvalidValue(source.clone(), capacity.to_string(), message.into())
+            })?;
+            self.channel_capacity = Setting::new(Some(capacity), source);
+        }
+        if verbose > 0 || debug {
+            let verbosity = if debug { verbose.max(2) } else { verbose };
+            let level = match verbosity {
+                1 => LevelFilter::INFO,
+                2 => LevelFilter::DEBUG,
+                _ => LevelFilter::TRACE,
+            };
+            let flag = if debug { "--debug" } else { "--verbose" };
+            self.log_level = Setting::new(level, ConfigSource::Flag(flag));
+        }
+        for directive in &options.log.directives {
+            self.add_log_directive(directive.clone(), ConfigSource::Flag("--log"));
+        }
+        if let Some(format) = options.log.log_format {
+            self.log_format = Setting::new(format, ConfigSource::Flag("--log-format"));
+        }
+        if let Some(file) = &options.log.log_file {
+            self.log_file = Setting::new(Some(file.clone()), ConfigSource::Flag("--log-file"));
+        }
+        Ok(())
+    }
+
+    /// Returns the parameters of the given presets, in order.
+    pub fn preset_params(&self, names: &[String]) -> Result<Vec<(String, String)>, ConfigError> {
+        let mut params = Vec::new();
+        for name in names {
+            let Some(preset) = self.presets.get(name) else {
+                return Err(ConfigError::UnknownPreset(name.clone()));
+            };
+            params.extend(preset.value.iter().cloned());
+        }
+        Ok(params)
+    }
+
+    /// Returns the paths of the block plugins to load: the plugin directory,
+    /// if it exists, followed by the other plugin paths.
+    pub fn plugin_paths(&self) -> Vec<PathBuf> {
+        let plugin_dir = self.plugin_dir.value.iter().filter(|dir| dir.is_dir());
+        plugin_dir
+            .chain(self.plugins.iter().map(|path| &path.value))
+            .cloned()
+            .collect()
+    }
+
+    /// A log directive without a name sets the overall log level.
+    fn add_log_directive(&mut self, directive: LogDirective, source: ConfigSource) {
+        match directive.name {
+            None => self.log_level = Setting::new(directive.level, source),
+            Some(_) => self.log_filters.push(Setting::new(directive, source)),
+        }
+    }
+}
+
+/// Checks that a channel capacity is positive, as connections can't buffer
+/// zero messages.
+fn validate_capacity(capacity: usize) -> Result<usize, &'static str> {
+    match capacity {
+        0 => Err("expected a positive integer"),
+        capacity => Ok(capacity),
+    }
+}
+
+/// Returns the path of the user's configuration file, if the home directory
+/// is known.
+pub fn user_config_file() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("protoflow").join("config.toml"))
+}
+
+/// Returns the path of the nearest project-local configuration file, if any.
+pub fn project_config_file() -> Option<PathBuf> {
+    let current_dir = env::current_dir().ok()?;
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// A configuration setting, along with where its value came from.
+#[derive(Clone, Debug)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: ConfigSource,
+}
+
+impl<T> Setting<T> {
+    pub fn new(value: T, source: ConfigSource) -> Self {
+        Self { value, source }
+    }
+
+    pub fn default(value: T) -> Self {
+        Self::new(value, ConfigSource::Default)
+    }
+}
+
+/// Where the value of a configuration setting came from.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum ConfigSource {
+    #[default]
+    Default,
+    File(PathBuf),
+    Env(&'static str),
+    Flag(&'static str),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ConfigSource::*;
+        match self {
+            Default => write!(f, "default"),
+            File(path) => write!(f, "{}", path.display()),
+            Env(name) => write!(f, "${}", name),
+            Flag(flag) => write!(f, "{}", flag),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ConfigError {
+    Io(PathBuf, String),
+    Parse(PathBuf, String),
+    UnknownKey(ConfigSource, String),
+    InvalidValue(ConfigSource, String, String),
+    UnknownPreset(String),
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ConfigError::*;
+        match self {
+            Io(path, error) => {
+                write!(f, "{}: {}", path.display(), error)
+            }
+            Parse(path, error) => {
+                write!(f, "{}: {}", path.display(), error.trim_end())
+            }
+            UnknownKey(source, key) => {
+                write!(f, "{}: unknown configuration key: {}", source, key)
+            }
+            InvalidValue(source, key, message) => {
+                write!(f, "{}: {}: {}", source, key, message)
+            }
+            UnknownPreset(name) => {
+                write!(f, "unknown preset: {}", name)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, ConfigError, ConfigOptions, ConfigSource};
+    use protoflow_blocks::types::Encoding;
+    use std::path::{Path, PathBuf};
+    use tracing_subscriber::filter::LevelFilter;
+
+    fn write_file(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn vars<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn override_layers_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let user_file = write_file(
+            dir.path(),
+            "config.toml",
+            "encoding = \"protobuf\"\nchannel_capacity = 8\n[log]\nlevel = \"info\"\n",
+        );
+        let project_file = write_file(
+            dir.path(),
+            "protoflow.toml",
+            "channel_capacity = 4\nplugin_dir = \"plugins\"\nplugins = [\"a.so\"]\n",
+        );
+        let mut config = Config::default();
+        config.load_file(&user_file).unwrap();
+        config.load_file(&project_file).unwrap();
+        config
+            .load_vars(vars(&[
+                ("PROTOFLOW_CHANNEL_CAPACITY", "2"),
+                ("PROTOFLOW_PLUGINS", "b.so"),
+                ("PROTOFLOW_LOG", "Count=debug"),
+            ]))
+            .unwrap();
+        let options = ConfigOptions {
+            plugins: vec![PathBuf::from("c.so")],
+            channel_capacity: Some(1),
+            ..Default::default()
+        };
+        config.apply_flags(&options, 0, false).unwrap();
+
+        assert_eq!(config.encoding.value, Encoding::ProtobufWithLengthPrefix);
+        assert_eq!(
+            config.encoding.source,
+            ConfigSource::File(user_file.clone())
+        );
+        assert_eq!(config.log_level.value, LevelFilter::INFO);
+        assert_eq!(config.log_level.source, ConfigSource::File(user_file));
+        assert_eq!(config.channel_capacity.value, Some(1));
+        assert_eq!(
+            config.channel_capacity.source,
+            ConfigSource::Flag("--channel-capacity")
+        );
+        assert_eq!(config.log_filters[0].value.to_string(), "Count=debug");
+        assert_eq!(
+            config.log_filters[0].source,
+            ConfigSource::Env("PROTOFLOW_LOG")
+        );
+
+        // Relative paths are relative to the file they're given in:
+        assert_eq!(config.plugin_dir.value, Some(dir.path().join("plugins")));
+        assert_eq!(
+            config.plugin_dir.source,
+            ConfigSource::File(project_file.clone())
+        );
+
+        // Plugin paths accumulate across layers:
+        let plugins: Vec<_> = config
+            .plugins
+            .iter()
+            .map(|plugin| (plugin.value.clone(), plugin.source.clone()))
+            .collect();
+        assert_eq!(
+            plugins,
+            [
+                (dir.path().join("a.so"), ConfigSource::File(project_file)),
+                (
+                    PathBuf::from("b.so"),
+                    ConfigSource::Env("PROTOFLOW_PLUGINS")
+                ),
+                (PathBuf::from("c.so"), ConfigSource::Flag("--plugin")),
+            ]
+        );
+    }
+
+    #[test]
+    fn load_plugin_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        assert_eq!(config.plugin_dir.source, ConfigSource::Default);
+
+        config
+            .load_vars(vars(&[(
+                "PROTOFLOW_PLUGIN_DIR",
+                &dir.path().to_string_lossy(),
+            )]))
+            .unwrap();
+        assert_eq!(config.plugin_dir.value.as_deref(), Some(dir.path()));
+        assert_eq!(
+            config.plugin_dir.source,
+            ConfigSource::Env("PROTOFLOW_PLUGIN_DIR")
+        );
+        config
+            .plugins
+            .push(super::Setting::default(PathBuf::from("a.so")));
+        assert_eq!(
+            config.plugin_paths(),
+            [dir.path().to_path_buf(), PathBuf::from("a.so")]
+        );
+
+        // Missing plugin directories are skipped:
+        config.plugin_dir.value = Some(dir.path().join("missing"));
+        assert_eq!(config.plugin_paths(), [PathBuf::from("a.so")]);
+    }
+
+    #[test]
+    fn reject_zero_capacity() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write_file(dir.path(), "protoflow.toml", "channel_capacity = 0\n");
+        assert!(matches!(
+            Config::default().load_file(&file),
+            Err(ConfigError::InvalidValue(ConfigSource::File(_), key, _)) if key == "channel_capacity"
+        ));
+        assert!(matches!(
+            Config::default().load_vars(vars(&[("PROTOFLOW_CHANNEL_CAPACITY", "0")])),
+            Err(ConfigError::InvalidValue(
+                ConfigSource::Env("PROTOFLOW_CHANNEL_CAPACITY"),
+                ..
+            ))
+        ));
+        let options = ConfigOptions {
+            channel_capacity: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Config::default().apply_flags(&options, 0, false),
+            Err(ConfigError::InvalidValue(
+                ConfigSource::Flag("--channel-capacity"),
+                ..
+            ))
+        ));
+    }
+}
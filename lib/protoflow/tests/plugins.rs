@@ -0,0 +1,72 @@
+// This is free and unencumbered software released into the public domain.
+
+#![cfg(feature = "cli")]
+
+use protoflow::blocks::{BlockRegistry, PluginDeclaration, PluginError};
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    path::PathBuf,
+};
+
+/// Returns the path of the `block_plugin` example, which Cargo builds
+/// alongside the tests.
+fn example_plugin() -> Option<PathBuf> {
+    let target_dir = std::env::current_exe()
+        .ok()?
+        .parent()?
+        .parent()?
+        .to_path_buf();
+    let path = target_dir
+        .join("examples")
+        .join(format!("{}block_plugin{}", DLL_PREFIX, DLL_SUFFIX));
+    path.exists().then_some(path)
+}
+
+#[test]
+fn load_plugin() {
+    let Some(path) = example_plugin() else {
+        eprintln!("skipped: the block_plugin example hasn't been built");
+        return;
+    };
+    let mut registry = BlockRegistry::new();
+    registry.load_plugin(&path).unwrap();
+    assert!(registry.contains("Uppercase"));
+
+    let factory = registry.get("Uppercase").unwrap();
+    assert!(factory.build_stdio_system.is_some());
+    let description = factory.describe();
+    assert_eq!(description.name.as_deref(), Some("Uppercase"));
+    assert_eq!(description.inputs.len(), 1);
+    assert_eq!(description.outputs.len(), 1);
+}
+
+#[test]
+fn reject_non_plugins() {
+    let mut registry = BlockRegistry::new();
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    assert!(matches!(
+        registry.load_plugin(manifest),
+        Err(PluginError::Load(..))
+    ));
+    assert!(matches!(
+        registry.load_plugin("nonexistent.so"),
+        Err(PluginError::Io(..))
+    ));
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn reject_incompatible_declarations() {
+    let host = PluginDeclaration::new(|_| {});
+    assert!(host.is_compatible());
+    assert!(!PluginDeclaration {
+        blocks_version: "0.0.0",
+        ..host
+    }
+    .is_compatible());
+    assert!(!PluginDeclaration {
+        features: "unknown",
+        ..host
+    }
+    .is_compatible());
+}